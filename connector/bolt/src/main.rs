@@ -3,8 +3,8 @@ use chrono::NaiveDate;
 use clap::{arg, Parser};
 use futures::future::join_all;
 use lute_bolt_connector::client::lute::{
-  event::Event, event_service_client::EventServiceClient, parsed_file_data::Data, EventStreamItem,
-  EventStreamRequest, ParsedAlbum,
+  event::Event, event_service_client::EventServiceClient, parsed_file_data::Data,
+  EventStreamFilter, EventStreamItem, EventStreamRequest, PageType, ParsedAlbum,
 };
 use neo4rs::{query, ConfigBuilder, Graph};
 use std::sync::Arc;
@@ -20,6 +20,11 @@ fn event_stream_request(
     subscriber_id: subscriber_id.to_string(),
     cursor,
     max_batch_size: Some(100),
+    filter: Some(EventStreamFilter {
+      event_kinds: vec!["FileParsed".to_string()],
+      page_types: vec![PageType::AlbumPage.into()],
+      correlation_id_prefix: None,
+    }),
   }
 }
 
@@ -6,7 +6,7 @@ use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use lute_postgres_connector::{
   client::lute::{
     event::Event, event_service_client::EventServiceClient, parsed_file_data::Data,
-    EventStreamItem, EventStreamRequest,
+    EventStreamFilter, EventStreamItem, EventStreamRequest, PageType,
   },
   models::*,
 };
@@ -297,6 +297,11 @@ fn event_stream_request(
     subscriber_id: subscriber_id.to_string(),
     cursor,
     max_batch_size: Some(100),
+    filter: Some(EventStreamFilter {
+      event_kinds: vec!["FileParsed".to_string()],
+      page_types: vec![PageType::AlbumPage.into()],
+      correlation_id_prefix: None,
+    }),
   }
 }
 
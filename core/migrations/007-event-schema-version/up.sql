@@ -0,0 +1,4 @@
+ALTER TABLE
+  events
+ADD
+  COLUMN schema_version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS external_event_subscriber_streams;
+
+DROP TABLE IF EXISTS external_event_subscribers;
@@ -0,0 +1,14 @@
+CREATE TABLE external_event_subscribers (
+  id TEXT PRIMARY KEY,
+  owner TEXT,
+  description TEXT,
+  created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_seen_at DATETIME
+);
+
+CREATE TABLE external_event_subscriber_streams (
+  subscriber_id TEXT NOT NULL,
+  stream TEXT NOT NULL,
+  PRIMARY KEY (subscriber_id, stream),
+  FOREIGN KEY (subscriber_id) REFERENCES external_event_subscribers(id) ON DELETE CASCADE
+);
@@ -0,0 +1,9 @@
+CREATE TABLE webhooks (
+  id TEXT PRIMARY KEY,
+  url TEXT NOT NULL,
+  secret TEXT,
+  event_kinds TEXT NOT NULL,
+  created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_delivered_at DATETIME,
+  last_error TEXT
+);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS parsed_file_versions_page_type_parser_version;
+DROP TABLE IF EXISTS parsed_file_versions;
@@ -0,0 +1,9 @@
+CREATE TABLE parsed_file_versions (
+  file_name TEXT PRIMARY KEY,
+  page_type TEXT NOT NULL,
+  parser_version INTEGER NOT NULL,
+  failed_parser_version INTEGER,
+  updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX parsed_file_versions_page_type_parser_version ON parsed_file_versions (page_type, parser_version);
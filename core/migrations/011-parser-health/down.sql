@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS parser_degradations;
+DROP TABLE IF EXISTS parse_outcomes;
@@ -0,0 +1,17 @@
+CREATE TABLE parse_outcomes (
+  bucket INTEGER NOT NULL,
+  page_type TEXT NOT NULL,
+  error TEXT NOT NULL,
+  count INTEGER NOT NULL,
+  PRIMARY KEY (bucket, page_type, error)
+);
+
+CREATE TABLE parser_degradations (
+  page_type TEXT NOT NULL,
+  error TEXT NOT NULL,
+  failure_rate REAL NOT NULL,
+  baseline_failure_rate REAL NOT NULL,
+  sample_count INTEGER NOT NULL,
+  detected_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (page_type, error)
+);
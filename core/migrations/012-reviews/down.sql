@@ -0,0 +1,3 @@
+DROP INDEX idx_reviews_album_id;
+
+DROP TABLE reviews;
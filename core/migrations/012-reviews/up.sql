@@ -0,0 +1,11 @@
+CREATE TABLE reviews (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  album_id INTEGER NOT NULL,
+  author TEXT NOT NULL,
+  rating REAL,
+  date DATE,
+  text TEXT NOT NULL,
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_reviews_album_id ON reviews (album_id);
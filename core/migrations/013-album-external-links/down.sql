@@ -0,0 +1,3 @@
+DROP INDEX idx_album_external_links_source_external_id;
+
+DROP TABLE album_external_links;
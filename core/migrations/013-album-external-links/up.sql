@@ -0,0 +1,10 @@
+CREATE TABLE album_external_links (
+  album_id INTEGER NOT NULL,
+  source TEXT NOT NULL,
+  external_id TEXT NOT NULL,
+  url TEXT NOT NULL,
+  PRIMARY KEY (album_id, source, external_id),
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_album_external_links_source_external_id ON album_external_links (source, external_id);
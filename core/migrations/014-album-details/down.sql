@@ -0,0 +1,15 @@
+DROP INDEX idx_track_credits_artist_id;
+
+DROP INDEX idx_album_labels_label_id;
+
+DROP TABLE track_credits;
+
+DROP TABLE album_chart_positions;
+
+DROP TABLE album_labels;
+
+DROP TABLE labels;
+
+ALTER TABLE albums DROP COLUMN total_duration_seconds;
+
+ALTER TABLE albums DROP COLUMN release_type;
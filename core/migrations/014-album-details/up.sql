@@ -0,0 +1,39 @@
+ALTER TABLE albums ADD COLUMN release_type TEXT;
+
+ALTER TABLE albums ADD COLUMN total_duration_seconds INTEGER;
+
+CREATE TABLE labels (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE album_labels (
+  album_id INTEGER NOT NULL,
+  label_id INTEGER NOT NULL,
+  catalog_number TEXT,
+  PRIMARY KEY (album_id, label_id),
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE,
+  FOREIGN KEY (label_id) REFERENCES labels(id) ON DELETE CASCADE
+);
+
+CREATE TABLE album_chart_positions (
+  album_id INTEGER NOT NULL,
+  chart TEXT NOT NULL,
+  position INTEGER NOT NULL,
+  PRIMARY KEY (album_id, chart),
+  FOREIGN KEY (album_id) REFERENCES albums(id) ON DELETE CASCADE
+);
+
+CREATE TABLE track_credits (
+  track_id INTEGER NOT NULL,
+  artist_id INTEGER NOT NULL,
+  role_id INTEGER NOT NULL,
+  PRIMARY KEY (track_id, artist_id, role_id),
+  FOREIGN KEY (track_id) REFERENCES tracks(id) ON DELETE CASCADE,
+  FOREIGN KEY (artist_id) REFERENCES artists(id) ON DELETE CASCADE,
+  FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_album_labels_label_id ON album_labels (label_id);
+
+CREATE INDEX idx_track_credits_artist_id ON track_credits (artist_id);
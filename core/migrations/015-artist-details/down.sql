@@ -0,0 +1,15 @@
+DROP INDEX idx_artist_relationships_related_artist_id;
+
+DROP INDEX idx_artist_genres_genre_id;
+
+DROP TABLE artist_relationships;
+
+DROP TABLE artist_genres;
+
+ALTER TABLE artists DROP COLUMN country;
+
+ALTER TABLE artists DROP COLUMN location;
+
+ALTER TABLE artists DROP COLUMN disbanded_date;
+
+ALTER TABLE artists DROP COLUMN formed_date;
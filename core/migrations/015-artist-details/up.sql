@@ -0,0 +1,28 @@
+ALTER TABLE artists ADD COLUMN formed_date DATE;
+
+ALTER TABLE artists ADD COLUMN disbanded_date DATE;
+
+ALTER TABLE artists ADD COLUMN location TEXT;
+
+ALTER TABLE artists ADD COLUMN country TEXT;
+
+CREATE TABLE artist_genres (
+  artist_id INTEGER NOT NULL,
+  genre_id INTEGER NOT NULL,
+  PRIMARY KEY (artist_id, genre_id),
+  FOREIGN KEY (artist_id) REFERENCES artists(id) ON DELETE CASCADE,
+  FOREIGN KEY (genre_id) REFERENCES genres(id) ON DELETE CASCADE
+);
+
+CREATE TABLE artist_relationships (
+  artist_id INTEGER NOT NULL,
+  related_artist_id INTEGER NOT NULL,
+  relationship TEXT NOT NULL,
+  PRIMARY KEY (artist_id, related_artist_id, relationship),
+  FOREIGN KEY (artist_id) REFERENCES artists(id) ON DELETE CASCADE,
+  FOREIGN KEY (related_artist_id) REFERENCES artists(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_artist_genres_genre_id ON artist_genres (genre_id);
+
+CREATE INDEX idx_artist_relationships_related_artist_id ON artist_relationships (related_artist_id);
@@ -0,0 +1,7 @@
+DROP INDEX idx_chart_albums_file_name;
+
+DROP INDEX idx_chart_albums_chart_id_page_number;
+
+DROP TABLE chart_albums;
+
+DROP TABLE charts;
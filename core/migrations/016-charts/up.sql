@@ -0,0 +1,21 @@
+CREATE TABLE charts (
+  id INTEGER PRIMARY KEY,
+  file_name TEXT NOT NULL UNIQUE,
+  total_pages INTEGER
+);
+
+CREATE TABLE chart_albums (
+  chart_id INTEGER NOT NULL,
+  page_number INTEGER NOT NULL,
+  position INTEGER,
+  file_name TEXT NOT NULL,
+  name TEXT NOT NULL,
+  rating REAL NOT NULL,
+  rating_count INTEGER NOT NULL,
+  PRIMARY KEY (chart_id, file_name),
+  FOREIGN KEY (chart_id) REFERENCES charts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_chart_albums_chart_id_page_number ON chart_albums (chart_id, page_number);
+
+CREATE INDEX idx_chart_albums_file_name ON chart_albums (file_name);
@@ -0,0 +1,5 @@
+DROP INDEX idx_album_embeddings_key;
+
+DROP TABLE album_embeddings;
+
+DROP TABLE album_search;
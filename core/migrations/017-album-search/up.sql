@@ -0,0 +1,15 @@
+CREATE VIRTUAL TABLE album_search USING fts5(
+  file_name UNINDEXED,
+  name,
+  artist_names,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TABLE album_embeddings (
+  file_name TEXT NOT NULL,
+  key TEXT NOT NULL,
+  embedding BLOB NOT NULL,
+  PRIMARY KEY (file_name, key)
+);
+
+CREATE INDEX idx_album_embeddings_key ON album_embeddings (key);
@@ -0,0 +1,3 @@
+DROP INDEX idx_genre_parents_parent_genre;
+
+DROP TABLE genre_parents;
@@ -0,0 +1,7 @@
+CREATE TABLE genre_parents (
+  genre TEXT NOT NULL,
+  parent_genre TEXT NOT NULL,
+  PRIMARY KEY (genre, parent_genre)
+);
+
+CREATE INDEX idx_genre_parents_parent_genre ON genre_parents (parent_genre);
@@ -0,0 +1,5 @@
+DROP TABLE album_distinct_overrides;
+
+DROP INDEX idx_album_duplicate_overrides_duplicate_of;
+
+DROP TABLE album_duplicate_overrides;
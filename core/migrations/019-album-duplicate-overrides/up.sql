@@ -0,0 +1,12 @@
+CREATE TABLE album_duplicate_overrides (
+  file_name TEXT NOT NULL PRIMARY KEY,
+  duplicate_of TEXT NOT NULL
+);
+
+CREATE INDEX idx_album_duplicate_overrides_duplicate_of ON album_duplicate_overrides (duplicate_of);
+
+CREATE TABLE album_distinct_overrides (
+  file_name TEXT NOT NULL,
+  other_file_name TEXT NOT NULL,
+  PRIMARY KEY (file_name, other_file_name)
+);
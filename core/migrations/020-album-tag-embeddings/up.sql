@@ -0,0 +1,5 @@
+CREATE TABLE album_tag_embeddings (
+  tag TEXT NOT NULL PRIMARY KEY,
+  idf REAL NOT NULL,
+  vector TEXT NOT NULL
+);
@@ -0,0 +1,24 @@
+UPDATE
+  event_subscribers
+SET
+  cursor = MIN(
+    cursor,
+    COALESCE(
+      (
+        SELECT
+          cursor
+        FROM
+          event_subscribers
+        WHERE
+          id = 'delete_album_read_models'
+      ),
+      cursor
+    )
+  )
+WHERE
+  id = 'update_album_read_models';
+
+DELETE FROM
+  event_subscribers
+WHERE
+  id = 'delete_album_read_models';
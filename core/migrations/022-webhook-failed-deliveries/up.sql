@@ -0,0 +1,14 @@
+CREATE TABLE webhook_failed_deliveries (
+  webhook_id TEXT NOT NULL,
+  entry_id TEXT NOT NULL,
+  event_kind TEXT NOT NULL,
+  body TEXT NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 1,
+  last_error TEXT NOT NULL,
+  next_attempt_at DATETIME NOT NULL,
+  created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (webhook_id, entry_id),
+  FOREIGN KEY (webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_failed_deliveries_next_attempt_at ON webhook_failed_deliveries(next_attempt_at);
@@ -0,0 +1,3 @@
+ALTER TABLE parsed_file_versions DROP COLUMN enqueued_at;
+
+ALTER TABLE parsed_file_versions DROP COLUMN enqueued_parser_version;
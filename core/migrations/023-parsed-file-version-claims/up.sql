@@ -0,0 +1,3 @@
+ALTER TABLE parsed_file_versions ADD COLUMN enqueued_parser_version INTEGER;
+
+ALTER TABLE parsed_file_versions ADD COLUMN enqueued_at DATETIME;
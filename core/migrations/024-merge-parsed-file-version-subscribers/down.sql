@@ -0,0 +1,10 @@
+INSERT INTO
+  event_subscribers (id, cursor, status)
+SELECT
+  'delete_parsed_file_versions',
+  cursor,
+  status
+FROM
+  event_subscribers
+WHERE
+  id = 'update_parsed_file_versions' ON CONFLICT (id) DO NOTHING;
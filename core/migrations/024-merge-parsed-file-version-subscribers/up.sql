@@ -0,0 +1,24 @@
+UPDATE
+  event_subscribers
+SET
+  cursor = MIN(
+    cursor,
+    COALESCE(
+      (
+        SELECT
+          cursor
+        FROM
+          event_subscribers
+        WHERE
+          id = 'delete_parsed_file_versions'
+      ),
+      cursor
+    )
+  )
+WHERE
+  id = 'update_parsed_file_versions';
+
+DELETE FROM
+  event_subscribers
+WHERE
+  id = 'delete_parsed_file_versions';
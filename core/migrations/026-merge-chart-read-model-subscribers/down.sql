@@ -0,0 +1,10 @@
+INSERT INTO
+  event_subscribers (id, cursor, status)
+SELECT
+  'delete_chart_read_models',
+  cursor,
+  status
+FROM
+  event_subscribers
+WHERE
+  id = 'update_chart_read_models' ON CONFLICT (id) DO NOTHING;
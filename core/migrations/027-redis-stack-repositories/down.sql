@@ -0,0 +1,19 @@
+DROP INDEX idx_spotify_import_lookup_subscriptions_profile_id;
+
+DROP TABLE spotify_import_lookup_subscriptions;
+
+DROP TABLE profile_albums;
+
+DROP TABLE profiles;
+
+DROP INDEX idx_album_search_lookups_album_file_name;
+
+DROP INDEX idx_album_search_lookups_status;
+
+DROP TABLE album_search_lookups;
+
+DROP INDEX idx_failed_parse_files_page_type;
+
+DROP INDEX idx_failed_parse_files_error;
+
+DROP TABLE failed_parse_files;
@@ -0,0 +1,46 @@
+CREATE TABLE failed_parse_files (
+  file_name TEXT PRIMARY KEY,
+  page_type TEXT NOT NULL,
+  error TEXT NOT NULL,
+  error_details TEXT NOT NULL,
+  last_attempted_at DATETIME NOT NULL
+);
+
+CREATE INDEX idx_failed_parse_files_error ON failed_parse_files(error);
+
+CREATE INDEX idx_failed_parse_files_page_type ON failed_parse_files(page_type);
+
+CREATE TABLE album_search_lookups (
+  encoded_query TEXT PRIMARY KEY,
+  status TEXT NOT NULL,
+  album_file_name TEXT,
+  lookup TEXT NOT NULL
+);
+
+CREATE INDEX idx_album_search_lookups_status ON album_search_lookups(status);
+
+CREATE INDEX idx_album_search_lookups_album_file_name ON album_search_lookups(album_file_name);
+
+CREATE TABLE profiles (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  last_updated_at DATETIME NOT NULL
+);
+
+CREATE TABLE profile_albums (
+  profile_id TEXT NOT NULL,
+  file_name TEXT NOT NULL,
+  factor INTEGER NOT NULL,
+  PRIMARY KEY (profile_id, file_name),
+  FOREIGN KEY (profile_id) REFERENCES profiles(id) ON DELETE CASCADE
+);
+
+CREATE TABLE spotify_import_lookup_subscriptions (
+  album_search_lookup_encoded_query TEXT NOT NULL,
+  profile_id TEXT NOT NULL,
+  album_search_lookup_query TEXT NOT NULL,
+  factor INTEGER NOT NULL,
+  PRIMARY KEY (album_search_lookup_encoded_query, profile_id)
+);
+
+CREATE INDEX idx_spotify_import_lookup_subscriptions_profile_id ON spotify_import_lookup_subscriptions(profile_id);
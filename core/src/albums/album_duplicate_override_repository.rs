@@ -0,0 +1,202 @@
+use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
+use anyhow::{anyhow, Result};
+use rusqlite::{params, types::Value};
+use std::{
+  collections::{HashMap, HashSet},
+  rc::Rc,
+  sync::Arc,
+};
+use tracing::{error, instrument};
+
+/**
+ * Duplicates marked or unmarked by hand. They are keyed by file name rather than album id, so
+ * that they survive re-parses and read model resets.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct AlbumDuplicateOverrides {
+  pub duplicate_of: HashMap<FileName, FileName>,
+  pub distinct: HashSet<(FileName, FileName)>,
+}
+
+impl AlbumDuplicateOverrides {
+  pub fn manual_duplicates(&self, original: &FileName) -> Vec<FileName> {
+    let mut duplicates = self
+      .duplicate_of
+      .iter()
+      .filter(|(_, duplicate_of)| *duplicate_of == original)
+      .map(|(file_name, _)| file_name.clone())
+      .collect::<Vec<_>>();
+    duplicates.sort();
+    duplicates
+  }
+
+  pub fn are_distinct(&self, a: &FileName, b: &FileName) -> bool {
+    self.distinct.contains(&(a.clone(), b.clone()))
+  }
+}
+
+fn to_file_name(value: String) -> rusqlite::Result<FileName> {
+  FileName::try_from(value).map_err(|e| {
+    error!(message = e.to_string(), "Failed to parse album file name");
+    rusqlite::Error::ExecuteReturnedResults
+  })
+}
+
+#[derive(Debug, Clone)]
+pub struct AlbumDuplicateOverrideRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl AlbumDuplicateOverrideRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  /**
+   * Finds the overrides involving any of the given albums, on either side.
+   */
+  #[instrument(skip_all, fields(count = file_names.len()))]
+  pub async fn find(&self, file_names: Vec<FileName>) -> Result<AlbumDuplicateOverrides> {
+    let overrides = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let file_names = Rc::new(
+          file_names
+            .into_iter()
+            .map(|file_name| Value::from(file_name.to_string()))
+            .collect::<Vec<Value>>(),
+        );
+        let mut overrides = AlbumDuplicateOverrides::default();
+        let mut statement = conn.prepare(
+          "
+          SELECT file_name, duplicate_of FROM album_duplicate_overrides
+          WHERE file_name IN rarray(?1) OR duplicate_of IN rarray(?1)
+          ",
+        )?;
+        let rows = statement.query_map(params![file_names], |row| {
+          Ok((
+            to_file_name(row.get::<_, String>(0)?)?,
+            to_file_name(row.get::<_, String>(1)?)?,
+          ))
+        })?;
+        for row in rows {
+          let (file_name, duplicate_of) = row?;
+          overrides.duplicate_of.insert(file_name, duplicate_of);
+        }
+        let mut statement = conn.prepare(
+          "
+          SELECT file_name, other_file_name FROM album_distinct_overrides
+          WHERE file_name IN rarray(?1)
+          ",
+        )?;
+        let rows = statement.query_map(params![file_names], |row| {
+          Ok((
+            to_file_name(row.get::<_, String>(0)?)?,
+            to_file_name(row.get::<_, String>(1)?)?,
+          ))
+        })?;
+        for row in rows {
+          let (file_name, other_file_name) = row?;
+          overrides
+            .distinct
+            .insert((other_file_name.clone(), file_name.clone()));
+          overrides.distinct.insert((file_name, other_file_name));
+        }
+        Ok::<_, rusqlite::Error>(overrides)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find album duplicate overrides"
+        );
+        anyhow!("Failed to find album duplicate overrides")
+      })??;
+    Ok(overrides)
+  }
+
+  /**
+   * Marks an album as a duplicate of another, replacing any override between them.
+   */
+  #[instrument(skip(self))]
+  pub async fn put_duplicate_of(
+    &self,
+    file_name: &FileName,
+    duplicate_of: &FileName,
+  ) -> Result<()> {
+    let file_name = file_name.to_string();
+    let duplicate_of = duplicate_of.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+          "
+          DELETE FROM album_distinct_overrides
+          WHERE (file_name = ?1 AND other_file_name = ?2) OR (file_name = ?2 AND other_file_name = ?1)
+          ",
+          params![file_name, duplicate_of],
+        )?;
+        tx.execute(
+          "
+          INSERT INTO album_duplicate_overrides (file_name, duplicate_of)
+          VALUES (?, ?)
+          ON CONFLICT (file_name) DO UPDATE SET duplicate_of = excluded.duplicate_of
+          ",
+          params![file_name, duplicate_of],
+        )?;
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put album duplicate override");
+        anyhow!("Failed to put album duplicate override")
+      })?
+  }
+
+  /**
+   * Marks two albums as distinct, removing a duplicate override between them.
+   */
+  #[instrument(skip(self))]
+  pub async fn put_distinct(&self, file_name: &FileName, other_file_name: &FileName) -> Result<()> {
+    let file_name = file_name.to_string();
+    let other_file_name = other_file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+          "
+          DELETE FROM album_duplicate_overrides
+          WHERE (file_name = ?1 AND duplicate_of = ?2) OR (file_name = ?2 AND duplicate_of = ?1)
+          ",
+          params![file_name, other_file_name],
+        )?;
+        tx.execute(
+          "
+          INSERT INTO album_distinct_overrides (file_name, other_file_name)
+          VALUES (?, ?)
+          ON CONFLICT (file_name, other_file_name) DO NOTHING
+          ",
+          params![file_name, other_file_name],
+        )?;
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to put album distinct override"
+        );
+        anyhow!("Failed to put album distinct override")
+      })?
+  }
+}
@@ -0,0 +1,87 @@
+use super::album_read_model::AlbumReadModel;
+use crate::settings::{AlbumDuplicateSettings, AlbumNameNormalization};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashSet;
+use unidecode::unidecode;
+
+lazy_static! {
+  static ref EDITION_SUFFIX: Regex = Regex::new(
+    r"(?i)\s*[\(\[][^\)\]]*\b(edition|version|remaster(ed)?|reissue|deluxe|expanded|anniversary|bonus)\b[^\)\]]*[\)\]]"
+  )
+  .unwrap();
+}
+
+/**
+ * Decides whether two albums are duplicates of each other, before manual overrides are applied.
+ */
+#[derive(Debug, Clone)]
+pub struct AlbumDuplicateRules {
+  settings: AlbumDuplicateSettings,
+}
+
+impl AlbumDuplicateRules {
+  pub fn new(settings: AlbumDuplicateSettings) -> Self {
+    Self { settings }
+  }
+
+  pub fn normalize_name(&self, album: &AlbumReadModel) -> String {
+    let name = match self.settings.name_normalization {
+      AlbumNameNormalization::IgnoreEditions => {
+        EDITION_SUFFIX.replace_all(&album.name, "").to_string()
+      }
+      _ => album.name.clone(),
+    };
+    let ascii_name = unidecode(&name).to_lowercase();
+    match self.settings.name_normalization {
+      AlbumNameNormalization::Ascii => ascii_name,
+      _ => ascii_name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect(),
+    }
+  }
+
+  /**
+   * Shared artists over the artists of either album, in percent.
+   */
+  fn artist_overlap_percent(a: &AlbumReadModel, b: &AlbumReadModel) -> u32 {
+    let a_artists = a
+      .artists
+      .iter()
+      .map(|artist| &artist.file_name)
+      .collect::<HashSet<_>>();
+    let b_artists = b
+      .artists
+      .iter()
+      .map(|artist| &artist.file_name)
+      .collect::<HashSet<_>>();
+    let union = a_artists.union(&b_artists).count();
+    if union == 0 {
+      return 0;
+    }
+    (a_artists.intersection(&b_artists).count() * 100 / union) as u32
+  }
+
+  pub fn is_match(&self, a: &AlbumReadModel, b: &AlbumReadModel) -> bool {
+    if a.file_name == b.file_name {
+      return true;
+    }
+    let artist_overlap_percent = Self::artist_overlap_percent(a, b);
+    if artist_overlap_percent == 0
+      || artist_overlap_percent < self.settings.min_artist_overlap_percent
+    {
+      return false;
+    }
+    if let (Some(window), Some(a_date), Some(b_date)) = (
+      self.settings.release_date_window_days,
+      a.release_date,
+      b.release_date,
+    ) {
+      if (a_date - b_date).num_days().unsigned_abs() > window as u64 {
+        return false;
+      }
+    }
+    self.normalize_name(a) == self.normalize_name(b)
+  }
+}
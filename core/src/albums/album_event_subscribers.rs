@@ -1,11 +1,15 @@
 use super::{
   album_interactor::AlbumInteractor,
   album_read_model::{
-    AlbumReadModel, AlbumReadModelArtist, AlbumReadModelCredit, AlbumReadModelTrack,
+    AlbumReadModel, AlbumReadModelArtist, AlbumReadModelChartPosition, AlbumReadModelCredit,
+    AlbumReadModelExternalLink, AlbumReadModelLabel, AlbumReadModelReview, AlbumReadModelTrack,
+  },
+  album_repository::AlbumRepository,
+  album_search_index::{build_album_search_index, AlbumEmbedding},
+  embedding_provider::{
+    AlbumEmbeddingProvider, LocalAlbumEmbeddingProvider, OpenAIAlbumEmbeddingProvider,
+    TagAlbumEmbeddingProvider,
   },
-  album_search_index::{AlbumEmbedding, AlbumSearchIndex},
-  embedding_provider::{AlbumEmbeddingProvider, OpenAIAlbumEmbeddingProvider},
-  redis_album_search_index::RedisAlbumSearchIndex,
   sqlite_album_repository::SqliteAlbumRepository,
 };
 use crate::{
@@ -19,14 +23,16 @@ use crate::{
   },
   files::file_metadata::{file_name::FileName, page_type::PageType},
   parser::parsed_file_data::{
-    ParsedAlbum, ParsedArtistReference, ParsedCredit, ParsedFileData, ParsedTrack,
+    ParsedAlbum, ParsedArtistReference, ParsedChartPosition, ParsedCredit, ParsedExternalLink,
+    ParsedFileData, ParsedLabel, ParsedReview, ParsedTrack,
   },
   settings::Settings,
   sqlite::SqliteConnection,
 };
 use anyhow::Result;
 use rustis::{bb8::Pool, client::PooledClientManager};
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
+use tracing::info;
 
 impl From<&ParsedTrack> for AlbumReadModelTrack {
   fn from(parsed_track: &ParsedTrack) -> Self {
@@ -35,6 +41,11 @@ impl From<&ParsedTrack> for AlbumReadModelTrack {
       duration_seconds: parsed_track.duration_seconds,
       rating: parsed_track.rating,
       position: parsed_track.position.clone(),
+      credits: parsed_track
+        .credits
+        .iter()
+        .map(AlbumReadModelCredit::from)
+        .collect(),
     }
   }
 }
@@ -57,6 +68,45 @@ impl From<&ParsedCredit> for AlbumReadModelCredit {
   }
 }
 
+impl From<&ParsedLabel> for AlbumReadModelLabel {
+  fn from(parsed_label: &ParsedLabel) -> Self {
+    Self {
+      name: parsed_label.name.clone(),
+      catalog_number: parsed_label.catalog_number.clone(),
+    }
+  }
+}
+
+impl From<&ParsedChartPosition> for AlbumReadModelChartPosition {
+  fn from(parsed_chart_position: &ParsedChartPosition) -> Self {
+    Self {
+      chart: parsed_chart_position.chart.clone(),
+      position: parsed_chart_position.position,
+    }
+  }
+}
+
+impl From<&ParsedReview> for AlbumReadModelReview {
+  fn from(parsed_review: &ParsedReview) -> Self {
+    Self {
+      author: parsed_review.author.clone(),
+      rating: parsed_review.rating,
+      date: parsed_review.date,
+      text: parsed_review.text.clone(),
+    }
+  }
+}
+
+impl From<&ParsedExternalLink> for AlbumReadModelExternalLink {
+  fn from(parsed_link: &ParsedExternalLink) -> Self {
+    Self {
+      source: parsed_link.source.clone(),
+      id: parsed_link.id.clone(),
+      url: parsed_link.url.clone(),
+    }
+  }
+}
+
 impl AlbumReadModel {
   pub fn from_parsed_album(file_name: &FileName, parsed_album: ParsedAlbum) -> Self {
     Self {
@@ -87,35 +137,109 @@ impl AlbumReadModel {
       duplicates: vec![],
       duplicate_of: None,
       cover_image_url: parsed_album.cover_image_url,
+      external_links: parsed_album
+        .external_links
+        .iter()
+        .map(AlbumReadModelExternalLink::from)
+        .collect::<Vec<AlbumReadModelExternalLink>>(),
+      release_type: parsed_album.release_type,
+      labels: parsed_album
+        .labels
+        .iter()
+        .map(AlbumReadModelLabel::from)
+        .collect::<Vec<AlbumReadModelLabel>>(),
+      chart_positions: parsed_album
+        .chart_positions
+        .iter()
+        .map(AlbumReadModelChartPosition::from)
+        .collect::<Vec<AlbumReadModelChartPosition>>(),
+      total_duration_seconds: parsed_album.total_duration_seconds,
     }
   }
 }
 
+fn is_album_deletion(event: &Event) -> bool {
+  match event {
+    Event::FileDeleted { file_name, .. } => file_name.page_type() == PageType::Album,
+    _ => false,
+  }
+}
+
+/**
+ * Projects both parsed and deleted album files, so that a replay applies deletions in order with
+ * the parses they follow.
+ */
 async fn update_album_read_models(context: SubscriberContext) -> Result<()> {
-  if let Event::FileParsed {
-    file_id: _,
-    file_name,
-    data: ParsedFileData::Album(parsed_album),
-  } = context.payload.event
-  {
-    let album_read_model = AlbumReadModel::from_parsed_album(&file_name, parsed_album);
-    let album_repository = SqliteAlbumRepository::new(Arc::clone(&context.sqlite_connection));
-    let album_search_index = RedisAlbumSearchIndex::new(Arc::clone(&context.redis_connection_pool));
-    let album_interactor =
-      AlbumInteractor::new(Arc::new(album_repository), Arc::new(album_search_index));
-    album_interactor.put(album_read_model).await?;
+  let album_repository: Arc<dyn AlbumRepository + Send + Sync> = Arc::new(
+    SqliteAlbumRepository::new(Arc::clone(&context.sqlite_connection)),
+  );
+  let album_search_index = build_album_search_index(
+    &context.settings,
+    Arc::clone(&context.redis_connection_pool),
+    Arc::clone(&context.sqlite_connection),
+  );
+  let album_interactor = AlbumInteractor::new(
+    &context.settings,
+    Arc::clone(&context.sqlite_connection),
+    Arc::clone(&album_repository),
+    album_search_index,
+  );
+  match context.payload.event {
+    Event::FileParsed {
+      file_name,
+      data: ParsedFileData::Album(parsed_album),
+      ..
+    } => {
+      let reviews = parsed_album
+        .reviews
+        .iter()
+        .map(AlbumReadModelReview::from)
+        .collect::<Vec<AlbumReadModelReview>>();
+      let album_read_model = AlbumReadModel::from_parsed_album(&file_name, parsed_album);
+      album_interactor.put(album_read_model).await?;
+      album_repository.put_reviews(&file_name, reviews).await?;
+    }
+    Event::FileDeleted { file_name, .. } if file_name.page_type() == PageType::Album => {
+      album_interactor.delete(&file_name).await?;
+    }
+    _ => {}
   }
   Ok(())
 }
 
-async fn delete_album_read_models(context: SubscriberContext) -> Result<()> {
-  if let Event::FileDeleted { file_name, .. } = context.payload.event {
-    let album_repository = SqliteAlbumRepository::new(Arc::clone(&context.sqlite_connection));
-    let album_search_index = RedisAlbumSearchIndex::new(Arc::clone(&context.redis_connection_pool));
-    let album_interactor =
-      AlbumInteractor::new(Arc::new(album_repository), Arc::new(album_search_index));
-    album_interactor.delete(&file_name).await?;
+/**
+ * Search index documents are left in place and overwritten during the replay, so that album
+ * embeddings survive. Documents the replay did not recreate are pruned once it completes.
+ */
+async fn reset_album_read_models(sqlite_connection: Arc<SqliteConnection>) -> Result<()> {
+  let album_repository = SqliteAlbumRepository::new(sqlite_connection);
+  album_repository.delete_all().await
+}
+
+/**
+ * Deletes the search index documents of albums missing from the replayed read models.
+ */
+async fn prune_album_search_index(
+  settings: Arc<Settings>,
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
+) -> Result<()> {
+  let album_repository = SqliteAlbumRepository::new(Arc::clone(&sqlite_connection));
+  let album_search_index =
+    build_album_search_index(&settings, redis_connection_pool, sqlite_connection);
+  let file_names = album_repository
+    .get_file_names()
+    .await?
+    .into_iter()
+    .collect::<HashSet<FileName>>();
+  let mut pruned = 0;
+  for file_name in album_search_index.get_file_names().await? {
+    if !file_names.contains(&file_name) {
+      album_search_index.delete(&file_name).await?;
+      pruned += 1;
+    }
   }
+  info!(count = pruned, "Pruned album search index");
   Ok(())
 }
 
@@ -136,13 +260,13 @@ async fn crawl_chart_albums(
   crawler_interactor: Arc<CrawlerInteractor>,
 ) -> Result<()> {
   if let Event::FileParsed {
-    file_id: _,
     file_name,
-    data: ParsedFileData::Chart(albums),
+    data: ParsedFileData::Chart(chart),
+    ..
   } = context.payload.event
   {
     let priority = get_crawl_priority(context.payload.correlation_id);
-    for album in albums {
+    for album in chart.albums {
       crawler_interactor
         .enqueue_if_stale(QueuePushParameters {
           file_name: album.file_name,
@@ -152,6 +276,23 @@ async fn crawl_chart_albums(
         })
         .await?;
     }
+
+    let chart_settings = &context.settings.crawler.chart;
+    let page_number = file_name.chart_page_number().unwrap_or(chart.page_number);
+    let last_page = chart
+      .total_pages
+      .map(|total_pages| total_pages.min(chart_settings.max_page))
+      .unwrap_or(chart_settings.max_page);
+    if chart_settings.follow_pages && page_number < last_page {
+      crawler_interactor
+        .enqueue_if_stale(QueuePushParameters {
+          file_name: file_name.chart_page(page_number + 1)?,
+          priority: Some(priority),
+          correlation_id: Some(format!("crawl_chart_pages:{}", file_name.to_string())),
+          ..Default::default()
+        })
+        .await?;
+    }
   }
   Ok(())
 }
@@ -161,9 +302,9 @@ async fn crawl_artist_albums(
   crawler_interactor: Arc<CrawlerInteractor>,
 ) -> Result<()> {
   if let Event::FileParsed {
-    file_id: _,
     file_name,
     data: ParsedFileData::Artist(parsed_artist),
+    ..
   } = context.payload.event
   {
     let priority = get_crawl_priority(context.payload.correlation_id);
@@ -186,14 +327,23 @@ async fn update_album_embedding(
   context: SubscriberContext,
 ) -> Result<()> {
   if let Event::FileParsed {
-    file_id: _,
     file_name,
     data: ParsedFileData::Album(parsed_album),
+    ..
   } = context.payload.event
   {
-    let album_search_index = RedisAlbumSearchIndex::new(Arc::clone(&context.redis_connection_pool));
+    let album_search_index = build_album_search_index(
+      &context.settings,
+      Arc::clone(&context.redis_connection_pool),
+      Arc::clone(&context.sqlite_connection),
+    );
+    let reviews = parsed_album
+      .reviews
+      .iter()
+      .map(AlbumReadModelReview::from)
+      .collect::<Vec<AlbumReadModelReview>>();
     let album_read_model = AlbumReadModel::from_parsed_album(&file_name, parsed_album);
-    let embeddings = provider.generate(&album_read_model).await?;
+    let embeddings = provider.generate(&album_read_model, &reviews).await?;
     for (key, embedding) in embeddings {
       album_search_index
         .put_embedding(&AlbumEmbedding {
@@ -212,12 +362,23 @@ fn build_embedding_provider_event_subscribers(
   sqlite_connection: Arc<SqliteConnection>,
   settings: Arc<Settings>,
 ) -> Result<Vec<EventSubscriber>> {
-  let mut providers = vec![];
+  let mut providers: Vec<Arc<dyn AlbumEmbeddingProvider + Send + Sync + 'static>> = vec![];
   if settings.openai.is_some() {
     providers.push(Arc::new(OpenAIAlbumEmbeddingProvider::new(Arc::clone(
       &settings,
     ))?));
   }
+  if settings.local_embedding.is_some() {
+    providers.push(Arc::new(LocalAlbumEmbeddingProvider::new(Arc::clone(
+      &settings,
+    ))?));
+  }
+  if settings.tag_embedding.enabled {
+    providers.push(Arc::new(TagAlbumEmbeddingProvider::new(
+      Arc::clone(&settings),
+      Arc::clone(&sqlite_connection),
+    )));
+  }
   let subscribers = providers
     .into_iter()
     .filter_map(|provider| {
@@ -239,18 +400,23 @@ fn build_embedding_provider_event_subscribers(
   Ok(subscribers)
 }
 
-pub fn build_album_event_subscribers(
+/**
+ * Projects album files into the read models and search index.
+ */
+pub fn build_album_read_model_event_subscriber(
   redis_connection_pool: Arc<Pool<PooledClientManager>>,
   sqlite_connection: Arc<SqliteConnection>,
   settings: Arc<Settings>,
-  crawler_interactor: Arc<CrawlerInteractor>,
-) -> Result<Vec<EventSubscriber>> {
-  let album_crawler_interactor = Arc::clone(&crawler_interactor);
-  let artist_crawler_interactor = Arc::clone(&crawler_interactor);
-  let mut subscribers = vec![
+) -> Result<EventSubscriber> {
+  let reset_sqlite_connection = Arc::clone(&sqlite_connection);
+  let prune_settings = Arc::clone(&settings);
+  let prune_redis_connection_pool = Arc::clone(&redis_connection_pool);
+  let prune_sqlite_connection = Arc::clone(&sqlite_connection);
+  Ok(
     EventSubscriberBuilder::default()
       .id("update_album_read_models")
       .stream(Stream::Parser)
+      .stream(Stream::File)
       .batch_size(250)
       .redis_connection_pool(Arc::clone(&redis_connection_pool))
       .sqlite_connection(Arc::clone(&sqlite_connection))
@@ -262,28 +428,40 @@ pub fn build_album_event_subscribers(
         } => Some(album.ascii_name()), // Ensure potential duplicates are processed sequentially
         _ => None,
       }))
+      .is_barrier(Arc::new(|row| is_album_deletion(&row.payload.event)))
       .handle(Arc::new(|context| {
         Box::pin(async move { update_album_read_models(context).await })
       }))
-      .build()?,
-    EventSubscriberBuilder::default()
-      .id("delete_album_read_models")
-      .stream(Stream::File)
-      .batch_size(250)
-      .redis_connection_pool(Arc::clone(&redis_connection_pool))
-      .sqlite_connection(Arc::clone(&sqlite_connection))
-      .settings(Arc::clone(&settings))
-      .generate_ordered_processing_group_id(Arc::new(|row| match &row.payload.event {
-        Event::FileDeleted { file_name, .. } => match file_name.page_type() {
-          PageType::Album => Some(file_name.to_string()),
-          _ => None,
-        },
-        _ => None,
+      .reset(Arc::new(move || {
+        let sqlite_connection = Arc::clone(&reset_sqlite_connection);
+        Box::pin(async move { reset_album_read_models(sqlite_connection).await })
       }))
-      .handle(Arc::new(|context| {
-        Box::pin(async move { delete_album_read_models(context).await })
+      .finish_replay(Arc::new(move || {
+        let settings = Arc::clone(&prune_settings);
+        let redis_connection_pool = Arc::clone(&prune_redis_connection_pool);
+        let sqlite_connection = Arc::clone(&prune_sqlite_connection);
+        Box::pin(async move {
+          prune_album_search_index(settings, redis_connection_pool, sqlite_connection).await
+        })
       }))
       .build()?,
+  )
+}
+
+pub fn build_album_event_subscribers(
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
+  settings: Arc<Settings>,
+  crawler_interactor: Arc<CrawlerInteractor>,
+) -> Result<Vec<EventSubscriber>> {
+  let album_crawler_interactor = Arc::clone(&crawler_interactor);
+  let artist_crawler_interactor = Arc::clone(&crawler_interactor);
+  let mut subscribers = vec![
+    build_album_read_model_event_subscriber(
+      Arc::clone(&redis_connection_pool),
+      Arc::clone(&sqlite_connection),
+      Arc::clone(&settings),
+    )?,
     EventSubscriberBuilder::default()
       .id("crawl_chart_albums")
       .stream(Stream::Parser)
@@ -1,12 +1,16 @@
 use super::{
+  album_duplicate_override_repository::AlbumDuplicateOverrideRepository,
+  album_duplicate_rules::AlbumDuplicateRules,
   album_read_model::AlbumReadModel,
   album_repository::{AlbumRepository, GenreAggregate, ItemAndCount},
   album_search_index::AlbumSearchIndex,
 };
-use crate::files::file_metadata::file_name::FileName;
+use crate::{
+  files::file_metadata::file_name::FileName, settings::Settings, sqlite::SqliteConnection,
+};
 use anyhow::Result;
 use iter_tools::Itertools;
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 use tokio::try_join;
 use tracing::{error, instrument};
 
@@ -26,16 +30,22 @@ pub struct AlbumMonitor {
 pub struct AlbumInteractor {
   album_repository: Arc<dyn AlbumRepository + Send + Sync + 'static>,
   album_search_index: Arc<dyn AlbumSearchIndex + Send + Sync + 'static>,
+  duplicate_override_repository: AlbumDuplicateOverrideRepository,
+  duplicate_rules: AlbumDuplicateRules,
 }
 
 impl AlbumInteractor {
   pub fn new(
+    settings: &Settings,
+    sqlite_connection: Arc<SqliteConnection>,
     album_repository: Arc<dyn AlbumRepository + Send + Sync + 'static>,
     album_search_index: Arc<dyn AlbumSearchIndex + Send + Sync + 'static>,
   ) -> Self {
     Self {
       album_repository,
       album_search_index,
+      duplicate_override_repository: AlbumDuplicateOverrideRepository::new(sqlite_connection),
+      duplicate_rules: AlbumDuplicateRules::new(settings.album_duplicates.clone()),
     }
   }
 
@@ -78,12 +88,31 @@ impl AlbumInteractor {
     })
   }
 
-  #[instrument(skip(self))]
-  async fn process_duplicates(&self, album: &AlbumReadModel) -> Result<()> {
-    let potential_duplicates = self
+  /**
+   * Finds the duplicate group of an album: the original and its duplicates. Automatic matches come
+   * from the duplicate rules, and manual overrides win over them.
+   */
+  async fn find_duplicate_group(
+    &self,
+    album: &AlbumReadModel,
+  ) -> Result<(AlbumReadModel, Vec<AlbumReadModel>)> {
+    let album_overrides = self
+      .duplicate_override_repository
+      .find(vec![album.file_name.clone()])
+      .await?;
+    let pivot = match album_overrides.duplicate_of.get(&album.file_name) {
+      Some(duplicate_of) => self
+        .album_repository
+        .find(duplicate_of)
+        .await?
+        .unwrap_or_else(|| album.clone()),
+      None => album.clone(),
+    };
+
+    let mut candidates = self
       .album_repository
       .find_artist_albums(
-        album
+        pivot
           .artists
           .iter()
           .map(|artist| artist.file_name.clone())
@@ -91,62 +120,153 @@ impl AlbumInteractor {
       )
       .await?
       .into_iter()
-      .filter(|potential_duplicate| {
-        potential_duplicate
-          .ascii_name()
-          .eq_ignore_ascii_case(album.ascii_name().as_str())
-      })
+      .filter(|candidate| self.duplicate_rules.is_match(&pivot, candidate))
       .collect::<Vec<_>>();
+    if !candidates.iter().any(|c| c.file_name == pivot.file_name) {
+      candidates.push(pivot.clone());
+    }
 
-    if potential_duplicates.len() <= 1 {
-      return Ok(());
+    let overrides = self
+      .duplicate_override_repository
+      .find(candidates.iter().map(|c| c.file_name.clone()).collect())
+      .await?;
+    let candidate_file_names = candidates
+      .iter()
+      .map(|c| c.file_name.clone())
+      .collect::<HashSet<_>>();
+    let mut members = candidates
+      .into_iter()
+      .filter(|candidate| {
+        let group = candidate
+          .duplicate_of
+          .as_ref()
+          .unwrap_or(&candidate.file_name);
+        candidate.file_name == pivot.file_name
+          || (!overrides.are_distinct(&pivot.file_name, &candidate.file_name)
+            && !overrides.are_distinct(&pivot.file_name, group)
+            && overrides
+              .duplicate_of
+              .get(&candidate.file_name)
+              .map(|duplicate_of| candidate_file_names.contains(duplicate_of))
+              .unwrap_or(true))
+      })
+      .collect::<Vec<_>>();
+    let manual_duplicates = overrides
+      .duplicate_of
+      .iter()
+      .filter(|(file_name, duplicate_of)| {
+        members.iter().any(|m| &m.file_name == *duplicate_of)
+          && !members.iter().any(|m| &m.file_name == *file_name)
+      })
+      .map(|(file_name, _)| file_name.clone())
+      .collect::<Vec<_>>();
+    if !manual_duplicates.is_empty() {
+      members.extend(self.album_repository.find_many(manual_duplicates).await?);
     }
 
-    let mut duplicate_albums = potential_duplicates
+    // Albums with manual duplicates are originals by choice. Otherwise the current original is
+    // kept, so that groups stay stable, and new groups go to the most rated album.
+    let mut members = members
       .into_iter()
       .sorted_by(|a, b| {
-        b.rating_count
-          .partial_cmp(&a.rating_count)
-          .unwrap_or(std::cmp::Ordering::Equal)
+        let a_is_manual = !overrides.manual_duplicates(&a.file_name).is_empty();
+        let b_is_manual = !overrides.manual_duplicates(&b.file_name).is_empty();
+        b_is_manual
+          .cmp(&a_is_manual)
+          .then_with(|| (!b.duplicates.is_empty()).cmp(&!a.duplicates.is_empty()))
+          .then_with(|| {
+            b.rating_count
+              .partial_cmp(&a.rating_count)
+              .unwrap_or(std::cmp::Ordering::Equal)
+          })
       })
       .collect::<Vec<_>>();
-    let mut original_album = duplicate_albums.remove(0);
+    let original = members.remove(0);
+    let duplicates = members
+      .into_iter()
+      .filter(|duplicate| {
+        overrides.duplicate_of.get(&duplicate.file_name) == Some(&original.file_name)
+          || !overrides.are_distinct(&original.file_name, &duplicate.file_name)
+      })
+      .collect::<Vec<_>>();
+    Ok((original, duplicates))
+  }
 
-    let mut duplicates = duplicate_albums
+  /**
+   * Writes a duplicate group, detaching its members from previous groups, and reindexes every
+   * album whose duplicates changed.
+   */
+  async fn apply_duplicate_group(
+    &self,
+    original: AlbumReadModel,
+    duplicates: Vec<AlbumReadModel>,
+  ) -> Result<()> {
+    let mut duplicate_file_names = duplicates
       .iter()
       .map(|album| album.file_name.clone())
       .collect::<Vec<FileName>>();
-    duplicates.sort();
+    duplicate_file_names.sort();
+    if original.duplicate_of.is_none()
+      && original.duplicates == duplicate_file_names
+      && duplicates
+        .iter()
+        .all(|d| d.duplicate_of.as_ref() == Some(&original.file_name) && d.duplicates.is_empty())
+    {
+      return Ok(());
+    }
 
-    let original_album_file_name = original_album.file_name.clone();
-    if original_album.duplicates != duplicates {
+    let mut changed = HashSet::<FileName>::new();
+    changed.insert(original.file_name.clone());
+    changed.extend(original.duplicates.iter().cloned());
+    if let Some(previous_original) = &original.duplicate_of {
+      let previous_original = self.album_repository.get(previous_original).await?;
       self
         .album_repository
-        .set_duplicates(&original_album.file_name, duplicates.clone())
+        .set_duplicates(
+          &previous_original.file_name,
+          previous_original
+            .duplicates
+            .iter()
+            .filter(|d| *d != &original.file_name)
+            .cloned()
+            .collect(),
+        )
         .await?;
-      original_album.duplicates = duplicates;
-      self.album_search_index.put(original_album).await?;
+      changed.insert(previous_original.file_name.clone());
+      changed.extend(previous_original.duplicates);
     }
-
-    for mut duplicate_album in duplicate_albums.into_iter() {
-      if duplicate_album
-        .duplicate_of
-        .as_ref()
-        .map(|d| d != &original_album_file_name)
-        .unwrap_or(true)
-      {
+    for duplicate in duplicates.iter() {
+      changed.extend(duplicate.duplicate_of.iter().cloned());
+      if !duplicate.duplicates.is_empty() {
         self
           .album_repository
-          .set_duplicate_of(&duplicate_album.file_name, &original_album_file_name)
+          .set_duplicates(&duplicate.file_name, vec![])
           .await?;
-        duplicate_album.duplicate_of = Some(original_album_file_name.clone());
-        self.album_search_index.put(duplicate_album).await?;
+        changed.extend(duplicate.duplicates.iter().cloned());
       }
     }
+    self
+      .album_repository
+      .set_duplicates(&original.file_name, duplicate_file_names.clone())
+      .await?;
+    changed.extend(duplicate_file_names);
 
+    for album in self
+      .album_repository
+      .find_many(changed.into_iter().collect())
+      .await?
+    {
+      self.album_search_index.put(album).await?;
+    }
     Ok(())
   }
 
+  #[instrument(skip(self))]
+  async fn process_duplicates(&self, album: &AlbumReadModel) -> Result<()> {
+    let (original, duplicates) = self.find_duplicate_group(album).await?;
+    self.apply_duplicate_group(original, duplicates).await
+  }
+
   #[instrument(skip(self), name = "AlbumInteractor::put")]
   pub async fn put(&self, album: AlbumReadModel) -> Result<()> {
     let file_name = album.file_name.clone();
@@ -171,13 +291,17 @@ impl AlbumInteractor {
   }
 
   pub async fn delete(&self, file_name: &FileName) -> Result<()> {
-    let album = self.album_repository.get(file_name).await?;
+    let album = match self.album_repository.find(file_name).await? {
+      Some(album) => album,
+      // Files that never parsed into an album, e.g. while replaying, only need their index document removed.
+      None => return self.album_search_index.delete(file_name).await,
+    };
     self.album_repository.delete(file_name).await?;
     self.album_search_index.delete(file_name).await?;
     // If this album is a duplicate, we need to re-process the original album.
     // If this album has duplicates, we need to re-process them. It is enough to only re-process the first duplicate, as that will cascade to the rest.
     if let Some(duplicate_of) = &album.duplicate_of.as_ref().or(album.duplicates.first()) {
-      if let Err(err) = self.process_duplicates_by_file_name(file_name).await {
+      if let Err(err) = self.process_duplicates_by_file_name(duplicate_of).await {
         error!(
           "Failed to process duplicates for {}: {}",
           duplicate_of.to_string(),
@@ -187,4 +311,99 @@ impl AlbumInteractor {
     }
     Ok(())
   }
+
+  /**
+   * Manually marks an album as a duplicate of another. The override persists across re-parses.
+   */
+  #[instrument(skip(self))]
+  pub async fn mark_duplicate(&self, file_name: &FileName, duplicate_of: &FileName) -> Result<()> {
+    if file_name == duplicate_of {
+      anyhow::bail!("An album cannot be a duplicate of itself");
+    }
+    let album = self.album_repository.get(file_name).await?;
+    let original = self.album_repository.get(duplicate_of).await?;
+    let original_file_name = original
+      .duplicate_of
+      .clone()
+      .unwrap_or_else(|| original.file_name.clone());
+    if original_file_name == album.file_name {
+      anyhow::bail!("An album cannot be a duplicate of its own duplicate");
+    }
+    self
+      .duplicate_override_repository
+      .put_duplicate_of(&album.file_name, &original_file_name)
+      .await?;
+    self.process_duplicates(&album).await
+  }
+
+  /**
+   * Manually merges albums into one duplicate group. Existing duplicates of the merged albums move
+   * along with them.
+   */
+  #[instrument(skip(self))]
+  pub async fn merge_duplicates(
+    &self,
+    original_file_name: &FileName,
+    file_names: Vec<FileName>,
+  ) -> Result<()> {
+    let original = self.album_repository.get(original_file_name).await?;
+    let albums = self.album_repository.find_many(file_names.clone()).await?;
+    if let Some(file_name) = file_names
+      .iter()
+      .find(|file_name| !albums.iter().any(|album| &album.file_name == *file_name))
+    {
+      anyhow::bail!("Album {} does not exist", file_name.to_string());
+    }
+    let mut merged = albums
+      .iter()
+      .flat_map(|album| {
+        std::iter::once(album.file_name.clone()).chain(album.duplicates.iter().cloned())
+      })
+      .collect::<HashSet<_>>();
+    merged.extend(original.duplicate_of.iter().cloned());
+    merged.remove(&original.file_name);
+    for file_name in merged.iter() {
+      self
+        .duplicate_override_repository
+        .put_duplicate_of(file_name, &original.file_name)
+        .await?;
+    }
+    let original = self.album_repository.get(original_file_name).await?;
+    self.process_duplicates(&original).await
+  }
+
+  /**
+   * Manually separates an album from its duplicate group, so that it is not detected as a
+   * duplicate of its current original again.
+   */
+  #[instrument(skip(self))]
+  pub async fn unmark_duplicate(&self, file_name: &FileName) -> Result<()> {
+    let album = self.album_repository.get(file_name).await?;
+    let original_file_name = match &album.duplicate_of {
+      Some(duplicate_of) => duplicate_of.clone(),
+      None => anyhow::bail!("Album is not a duplicate"),
+    };
+    self
+      .duplicate_override_repository
+      .put_distinct(&album.file_name, &original_file_name)
+      .await?;
+    let original = self.album_repository.get(&original_file_name).await?;
+    self
+      .album_repository
+      .set_duplicates(
+        &original.file_name,
+        original
+          .duplicates
+          .iter()
+          .filter(|d| *d != &album.file_name)
+          .cloned()
+          .collect(),
+      )
+      .await?;
+    let album = self.album_repository.get(file_name).await?;
+    self.album_search_index.put(album.clone()).await?;
+    let original = self.album_repository.get(&original_file_name).await?;
+    self.album_search_index.put(original).await?;
+    self.process_duplicates(&album).await
+  }
 }
@@ -1,4 +1,6 @@
-use crate::{files::file_metadata::file_name::FileName, proto};
+use crate::{
+  files::file_metadata::file_name::FileName, parser::parsed_file_data::ExternalLinkSource, proto,
+};
 use anyhow::Result;
 use chrono::NaiveDate;
 use data_encoding::BASE64;
@@ -7,6 +9,13 @@ use serde_derive::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use unidecode::unidecode;
 
+/**
+ * The Bayesian-weighted rating pulls the ratings of albums with few ratings towards a prior mean,
+ * as if every album had this many extra ratings of that mean.
+ */
+pub const BAYESIAN_RATING_PRIOR_MEAN: f32 = 3.0;
+pub const BAYESIAN_RATING_PRIOR_COUNT: u32 = 100;
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
 pub struct AlbumReadModelArtist {
   pub name: String,
@@ -19,6 +28,8 @@ pub struct AlbumReadModelTrack {
   pub duration_seconds: Option<u32>,
   pub rating: Option<f32>,
   pub position: Option<String>,
+  #[serde(default)]
+  pub credits: Vec<AlbumReadModelCredit>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
@@ -27,6 +38,33 @@ pub struct AlbumReadModelCredit {
   pub roles: Vec<String>,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct AlbumReadModelLabel {
+  pub name: String,
+  pub catalog_number: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct AlbumReadModelChartPosition {
+  pub chart: String,
+  pub position: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct AlbumReadModelReview {
+  pub author: String,
+  pub rating: Option<f32>,
+  pub date: Option<NaiveDate>,
+  pub text: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct AlbumReadModelExternalLink {
+  pub source: ExternalLinkSource,
+  pub id: String,
+  pub url: String,
+}
+
 #[derive(Debug, PartialEq, Builder, Serialize, Deserialize, Clone, Default)]
 #[builder(default)]
 pub struct AlbumReadModel {
@@ -45,6 +83,11 @@ pub struct AlbumReadModel {
   pub duplicate_of: Option<FileName>,
   pub duplicates: Vec<FileName>,
   pub cover_image_url: Option<String>,
+  pub external_links: Vec<AlbumReadModelExternalLink>,
+  pub release_type: Option<String>,
+  pub labels: Vec<AlbumReadModelLabel>,
+  pub chart_positions: Vec<AlbumReadModelChartPosition>,
+  pub total_duration_seconds: Option<u32>,
 }
 
 impl AlbumReadModel {
@@ -72,6 +115,12 @@ impl AlbumReadModel {
   pub fn ascii_name(&self) -> String {
     unidecode(&self.name)
   }
+
+  pub fn bayesian_rating(&self) -> f32 {
+    (self.rating * self.rating_count as f32
+      + BAYESIAN_RATING_PRIOR_MEAN * BAYESIAN_RATING_PRIOR_COUNT as f32)
+      / (self.rating_count + BAYESIAN_RATING_PRIOR_COUNT) as f32
+  }
 }
 
 impl From<AlbumReadModelTrack> for proto::Track {
@@ -81,6 +130,59 @@ impl From<AlbumReadModelTrack> for proto::Track {
       duration_seconds: val.duration_seconds,
       rating: val.rating,
       position: val.position,
+      credits: val
+        .credits
+        .into_iter()
+        .map(|credit| credit.into())
+        .collect(),
+    }
+  }
+}
+
+impl From<AlbumReadModelCredit> for proto::AlbumCredit {
+  fn from(val: AlbumReadModelCredit) -> Self {
+    proto::AlbumCredit {
+      artist: Some(val.artist.into()),
+      roles: val.roles,
+    }
+  }
+}
+
+impl From<AlbumReadModelLabel> for proto::AlbumLabel {
+  fn from(val: AlbumReadModelLabel) -> Self {
+    proto::AlbumLabel {
+      name: val.name,
+      catalog_number: val.catalog_number,
+    }
+  }
+}
+
+impl From<AlbumReadModelChartPosition> for proto::AlbumChartPosition {
+  fn from(val: AlbumReadModelChartPosition) -> Self {
+    proto::AlbumChartPosition {
+      chart: val.chart,
+      position: val.position,
+    }
+  }
+}
+
+impl From<AlbumReadModelReview> for proto::AlbumReview {
+  fn from(val: AlbumReadModelReview) -> Self {
+    proto::AlbumReview {
+      author: val.author,
+      rating: val.rating,
+      date: val.date.map(|date| date.to_string()),
+      text: val.text,
+    }
+  }
+}
+
+impl From<AlbumReadModelExternalLink> for proto::AlbumExternalLink {
+  fn from(val: AlbumReadModelExternalLink) -> Self {
+    proto::AlbumExternalLink {
+      source: val.source.to_string(),
+      id: val.id,
+      url: val.url,
     }
   }
 }
@@ -119,6 +221,19 @@ impl From<AlbumReadModel> for proto::Album {
         .into_iter()
         .map(|file_name| file_name.to_string())
         .collect(),
+      external_links: val
+        .external_links
+        .into_iter()
+        .map(|link| link.into())
+        .collect(),
+      release_type: val.release_type,
+      labels: val.labels.into_iter().map(|label| label.into()).collect(),
+      chart_positions: val
+        .chart_positions
+        .into_iter()
+        .map(|chart_position| chart_position.into())
+        .collect(),
+      total_duration_seconds: val.total_duration_seconds,
     }
   }
 }
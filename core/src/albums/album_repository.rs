@@ -1,5 +1,7 @@
-use super::album_read_model::AlbumReadModel;
-use crate::files::file_metadata::file_name::FileName;
+use super::album_read_model::{AlbumReadModel, AlbumReadModelReview};
+use crate::{
+  files::file_metadata::file_name::FileName, parser::parsed_file_data::ExternalLinkSource,
+};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -11,6 +13,7 @@ pub struct GenreAggregate {
   pub secondary_genre_count: u32,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct ItemAndCount {
   pub name: String,
   pub count: u32,
@@ -22,12 +25,32 @@ pub trait AlbumRepository {
   async fn set_duplicates(&self, file_name: &FileName, duplicates: Vec<FileName>) -> Result<()>;
   async fn set_duplicate_of(&self, file_name: &FileName, duplicate_of: &FileName) -> Result<()>;
   async fn delete(&self, file_name: &FileName) -> Result<()>;
+  async fn delete_all(&self) -> Result<()>;
+  /**
+   * Replaces the reviews of an album. The album must already exist.
+   */
+  async fn put_reviews(
+    &self,
+    file_name: &FileName,
+    reviews: Vec<AlbumReadModelReview>,
+  ) -> Result<()>;
+  async fn find_reviews(&self, file_name: &FileName) -> Result<Vec<AlbumReadModelReview>>;
   async fn find(&self, file_name: &FileName) -> Result<Option<AlbumReadModel>>;
   async fn find_artist_albums(
     &self,
     artist_file_names: Vec<FileName>,
   ) -> Result<Vec<AlbumReadModel>>;
   async fn find_many(&self, file_names: Vec<FileName>) -> Result<Vec<AlbumReadModel>>;
+  async fn get_file_names(&self) -> Result<Vec<FileName>>;
+  /**
+   * Maps external IDs of the given source to the file names of the albums that link to them.
+   * IDs without a matching album are omitted.
+   */
+  async fn find_file_names_by_external_ids(
+    &self,
+    source: ExternalLinkSource,
+    ids: Vec<String>,
+  ) -> Result<HashMap<String, FileName>>;
   async fn get_aggregated_genres(&self, limit: Option<u32>) -> Result<Vec<GenreAggregate>>;
   async fn get_aggregated_descriptors(&self, limit: Option<u32>) -> Result<Vec<ItemAndCount>>;
   async fn get_aggregated_languages(&self, limit: Option<u32>) -> Result<Vec<ItemAndCount>>;
@@ -0,0 +1,496 @@
+use crate::genres::genre_hierarchy::GenreHierarchy;
+use std::fmt;
+
+/**
+ * Boolean query language over album attributes, for example
+ * `genre:"Shoegaze" AND (descriptor:melancholic OR descriptor:atmospheric) year:1990..1999 rating>3.5`.
+ *
+ * Terms next to each other are combined with AND, which binds tighter than OR. Terms are negated
+ * with NOT or a leading `-`, and words without a field match the album text.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlbumSearchExpression {
+  Text(String),
+  Tag {
+    field: AlbumSearchTagField,
+    value: String,
+  },
+  Range {
+    field: AlbumSearchNumericField,
+    min: Option<NumericBound>,
+    max: Option<NumericBound>,
+  },
+  Not(Box<AlbumSearchExpression>),
+  And(Vec<AlbumSearchExpression>),
+  Or(Vec<AlbumSearchExpression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlbumSearchTagField {
+  /**
+   * Either a primary or a secondary genre.
+   */
+  Genre,
+  PrimaryGenre,
+  SecondaryGenre,
+  Descriptor,
+  Language,
+  /**
+   * Artist file name, as in `AlbumSearchQuery::include_artists`.
+   */
+  Artist,
+  Label,
+  ReleaseType,
+  CreditedArtist,
+  CreditRole,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlbumSearchNumericField {
+  Year,
+  Rating,
+  RatingCount,
+  TrackCount,
+  DurationSeconds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericBound {
+  pub value: f64,
+  pub inclusive: bool,
+}
+
+impl NumericBound {
+  fn inclusive(value: f64) -> Self {
+    Self {
+      value,
+      inclusive: true,
+    }
+  }
+
+  fn exclusive(value: f64) -> Self {
+    Self {
+      value,
+      inclusive: false,
+    }
+  }
+}
+
+/**
+ * Position is the 1-based character offset in the expression.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlbumSearchExpressionError {
+  pub position: usize,
+  pub message: String,
+}
+
+impl fmt::Display for AlbumSearchExpressionError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "Syntax error at position {}: {}",
+      self.position, self.message
+    )
+  }
+}
+
+impl std::error::Error for AlbumSearchExpressionError {}
+
+type ExpressionResult<T> = Result<T, AlbumSearchExpressionError>;
+
+fn syntax_error<T>(position: usize, message: String) -> ExpressionResult<T> {
+  Err(AlbumSearchExpressionError {
+    position: position + 1,
+    message,
+  })
+}
+
+fn get_tag_field(name: &str) -> Option<AlbumSearchTagField> {
+  match name {
+    "genre" => Some(AlbumSearchTagField::Genre),
+    "primary_genre" => Some(AlbumSearchTagField::PrimaryGenre),
+    "secondary_genre" => Some(AlbumSearchTagField::SecondaryGenre),
+    "descriptor" => Some(AlbumSearchTagField::Descriptor),
+    "language" => Some(AlbumSearchTagField::Language),
+    "artist" => Some(AlbumSearchTagField::Artist),
+    "label" => Some(AlbumSearchTagField::Label),
+    "type" => Some(AlbumSearchTagField::ReleaseType),
+    "credit" => Some(AlbumSearchTagField::CreditedArtist),
+    "role" => Some(AlbumSearchTagField::CreditRole),
+    _ => None,
+  }
+}
+
+fn get_numeric_field(name: &str) -> Option<AlbumSearchNumericField> {
+  match name {
+    "year" => Some(AlbumSearchNumericField::Year),
+    "rating" => Some(AlbumSearchNumericField::Rating),
+    "rating_count" => Some(AlbumSearchNumericField::RatingCount),
+    "track_count" => Some(AlbumSearchNumericField::TrackCount),
+    "duration" => Some(AlbumSearchNumericField::DurationSeconds),
+    _ => None,
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+  LeftParen,
+  RightParen,
+  Minus,
+  Word(String),
+  Quoted(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+  kind: TokenKind,
+  position: usize,
+  /**
+   * Whether the token directly follows the previous one, as the value of `genre:"Shoegaze"` does.
+   */
+  attached: bool,
+}
+
+fn tokenize(expression: &str) -> ExpressionResult<Vec<Token>> {
+  let chars = expression.chars().collect::<Vec<char>>();
+  let mut tokens = vec![];
+  let mut i = 0;
+  let mut attached = false;
+  while i < chars.len() {
+    let position = i;
+    match chars[i] {
+      c if c.is_whitespace() => {
+        i += 1;
+        attached = false;
+        continue;
+      }
+      '(' => {
+        tokens.push(Token {
+          kind: TokenKind::LeftParen,
+          position,
+          attached,
+        });
+        i += 1;
+      }
+      ')' => {
+        tokens.push(Token {
+          kind: TokenKind::RightParen,
+          position,
+          attached,
+        });
+        i += 1;
+      }
+      '-' if !attached => {
+        tokens.push(Token {
+          kind: TokenKind::Minus,
+          position,
+          attached,
+        });
+        i += 1;
+      }
+      '"' => {
+        let end = chars[i + 1..].iter().position(|c| *c == '"');
+        match end {
+          Some(end) => {
+            tokens.push(Token {
+              kind: TokenKind::Quoted(chars[i + 1..i + 1 + end].iter().collect()),
+              position,
+              attached,
+            });
+            i += end + 2;
+          }
+          None => return syntax_error(position, "unterminated quote".to_string()),
+        }
+      }
+      _ => {
+        let start = i;
+        while i < chars.len() && !chars[i].is_whitespace() && !['(', ')', '"'].contains(&chars[i]) {
+          i += 1;
+        }
+        tokens.push(Token {
+          kind: TokenKind::Word(chars[start..i].iter().collect()),
+          position,
+          attached,
+        });
+      }
+    }
+    attached = true;
+  }
+  Ok(tokens)
+}
+
+fn parse_number(value: &str, position: usize) -> ExpressionResult<f64> {
+  value
+    .parse::<f64>()
+    .or_else(|_| syntax_error(position, format!("invalid number '{}'", value)))
+}
+
+fn parse_range(
+  field: AlbumSearchNumericField,
+  operator: &str,
+  value: &str,
+  position: usize,
+) -> ExpressionResult<AlbumSearchExpression> {
+  let (min, max) = match operator {
+    ">" => (
+      Some(NumericBound::exclusive(parse_number(value, position)?)),
+      None,
+    ),
+    ">=" => (
+      Some(NumericBound::inclusive(parse_number(value, position)?)),
+      None,
+    ),
+    "<" => (
+      None,
+      Some(NumericBound::exclusive(parse_number(value, position)?)),
+    ),
+    "<=" => (
+      None,
+      Some(NumericBound::inclusive(parse_number(value, position)?)),
+    ),
+    _ => match value.split_once("..") {
+      Some((min, max)) => {
+        let min = if min.is_empty() {
+          None
+        } else {
+          Some(NumericBound::inclusive(parse_number(min, position)?))
+        };
+        let max = if max.is_empty() {
+          None
+        } else {
+          Some(NumericBound::inclusive(parse_number(max, position)?))
+        };
+        if min.is_none() && max.is_none() {
+          return syntax_error(position, "range needs at least one bound".to_string());
+        }
+        (min, max)
+      }
+      None => {
+        let value = parse_number(value, position)?;
+        (
+          Some(NumericBound::inclusive(value)),
+          Some(NumericBound::inclusive(value)),
+        )
+      }
+    },
+  };
+  Ok(AlbumSearchExpression::Range { field, min, max })
+}
+
+struct ExpressionParser {
+  tokens: Vec<Token>,
+  index: usize,
+  end: usize,
+}
+
+impl ExpressionParser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.index)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.index).cloned();
+    self.index += 1;
+    token
+  }
+
+  fn peek_keyword(&self, keyword: &str) -> bool {
+    self
+      .peek()
+      .is_some_and(|token| token.kind == TokenKind::Word(keyword.to_string()))
+  }
+
+  fn parse_or(&mut self) -> ExpressionResult<AlbumSearchExpression> {
+    let mut operands = vec![self.parse_and()?];
+    while self.peek_keyword("OR") {
+      self.next();
+      operands.push(self.parse_and()?);
+    }
+    Ok(if operands.len() == 1 {
+      operands.remove(0)
+    } else {
+      AlbumSearchExpression::Or(operands)
+    })
+  }
+
+  fn parse_and(&mut self) -> ExpressionResult<AlbumSearchExpression> {
+    let mut operands = vec![self.parse_unary()?];
+    loop {
+      if self.peek_keyword("AND") {
+        self.next();
+      } else if self.peek().is_none()
+        || self.peek_keyword("OR")
+        || self
+          .peek()
+          .is_some_and(|token| token.kind == TokenKind::RightParen)
+      {
+        break;
+      }
+      operands.push(self.parse_unary()?);
+    }
+    Ok(if operands.len() == 1 {
+      operands.remove(0)
+    } else {
+      AlbumSearchExpression::And(operands)
+    })
+  }
+
+  fn parse_unary(&mut self) -> ExpressionResult<AlbumSearchExpression> {
+    if self.peek_keyword("NOT")
+      || self
+        .peek()
+        .is_some_and(|token| token.kind == TokenKind::Minus)
+    {
+      self.next();
+      return Ok(AlbumSearchExpression::Not(Box::new(self.parse_unary()?)));
+    }
+    self.parse_primary()
+  }
+
+  fn parse_primary(&mut self) -> ExpressionResult<AlbumSearchExpression> {
+    let token = match self.next() {
+      Some(token) => token,
+      None => return syntax_error(self.end, "unexpected end of expression".to_string()),
+    };
+    match token.kind {
+      TokenKind::LeftParen => {
+        let expression = self.parse_or()?;
+        match self.next() {
+          Some(Token {
+            kind: TokenKind::RightParen,
+            ..
+          }) => Ok(expression),
+          Some(token) => syntax_error(token.position, "expected ')'".to_string()),
+          None => syntax_error(self.end, "expected ')'".to_string()),
+        }
+      }
+      TokenKind::RightParen => syntax_error(token.position, "unexpected ')'".to_string()),
+      TokenKind::Minus => syntax_error(token.position, "unexpected '-'".to_string()),
+      TokenKind::Quoted(text) => Ok(AlbumSearchExpression::Text(text)),
+      TokenKind::Word(word) if ["AND", "OR"].contains(&word.as_str()) => {
+        syntax_error(token.position, format!("expected a term before {}", word))
+      }
+      TokenKind::Word(word) => self.parse_word(word, token.position),
+    }
+  }
+
+  fn parse_word(
+    &mut self,
+    word: String,
+    position: usize,
+  ) -> ExpressionResult<AlbumSearchExpression> {
+    let operator_start = match word.find(|c| [':', '<', '>', '='].contains(&c)) {
+      Some(operator_start) => operator_start,
+      None => return Ok(AlbumSearchExpression::Text(word)),
+    };
+    let name = &word[..operator_start];
+    let rest = &word[operator_start..];
+    let operator = [">=", "<=", ":", "=", ">", "<"]
+      .into_iter()
+      .find(|operator| rest.starts_with(operator))
+      .unwrap();
+    let mut value = rest[operator.len()..].to_string();
+    let value_position = position + name.chars().count() + operator.len();
+    if name.is_empty() {
+      return syntax_error(
+        position,
+        format!("expected a field name before '{}'", operator),
+      );
+    }
+    if value.is_empty() {
+      match self.peek() {
+        Some(Token {
+          kind: TokenKind::Quoted(quoted),
+          attached: true,
+          ..
+        }) => {
+          value = quoted.clone();
+          self.next();
+        }
+        _ => {
+          return syntax_error(
+            value_position,
+            format!("expected a value for field '{}'", name),
+          )
+        }
+      }
+    }
+    if let Some(field) = get_tag_field(name) {
+      if operator != ":" && operator != "=" {
+        return syntax_error(
+          position,
+          format!(
+            "operator '{}' is not supported by field '{}'",
+            operator, name
+          ),
+        );
+      }
+      Ok(AlbumSearchExpression::Tag { field, value })
+    } else if let Some(field) = get_numeric_field(name) {
+      parse_range(field, operator, &value, value_position)
+    } else {
+      syntax_error(position, format!("unknown field '{}'", name))
+    }
+  }
+}
+
+impl AlbumSearchExpression {
+  pub fn with_genre_descendants(self, hierarchy: &GenreHierarchy) -> Self {
+    match self {
+      AlbumSearchExpression::Tag { field, value }
+        if matches!(
+          field,
+          AlbumSearchTagField::Genre
+            | AlbumSearchTagField::PrimaryGenre
+            | AlbumSearchTagField::SecondaryGenre
+        ) =>
+      {
+        let genres = hierarchy.with_descendants(std::slice::from_ref(&value));
+        if genres.len() == 1 {
+          AlbumSearchExpression::Tag { field, value }
+        } else {
+          AlbumSearchExpression::Or(
+            genres
+              .into_iter()
+              .map(|value| AlbumSearchExpression::Tag { field, value })
+              .collect(),
+          )
+        }
+      }
+      AlbumSearchExpression::Not(expression) => {
+        AlbumSearchExpression::Not(Box::new(expression.with_genre_descendants(hierarchy)))
+      }
+      AlbumSearchExpression::And(expressions) => AlbumSearchExpression::And(
+        expressions
+          .into_iter()
+          .map(|expression| expression.with_genre_descendants(hierarchy))
+          .collect(),
+      ),
+      AlbumSearchExpression::Or(expressions) => AlbumSearchExpression::Or(
+        expressions
+          .into_iter()
+          .map(|expression| expression.with_genre_descendants(hierarchy))
+          .collect(),
+      ),
+      expression => expression,
+    }
+  }
+
+  pub fn parse(expression: &str) -> Result<Self, AlbumSearchExpressionError> {
+    let tokens = tokenize(expression)?;
+    if tokens.is_empty() {
+      return syntax_error(0, "empty expression".to_string());
+    }
+    let mut parser = ExpressionParser {
+      tokens,
+      index: 0,
+      end: expression.chars().count(),
+    };
+    let expression = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+      return syntax_error(token.position, "unexpected ')'".to_string());
+    }
+    Ok(expression)
+  }
+}
@@ -1,10 +1,41 @@
-use crate::files::file_metadata::file_name::FileName;
+use crate::{
+  files::file_metadata::file_name::FileName,
+  genres::genre_hierarchy::GenreHierarchy,
+  settings::{AlbumSearchBackend, Settings},
+  sqlite::SqliteConnection,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use derive_builder::Builder;
+use rustis::{bb8::Pool, client::PooledClientManager};
 use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
 
-use super::album_read_model::AlbumReadModel;
+use super::{
+  album_read_model::AlbumReadModel, album_repository::ItemAndCount,
+  album_search_expression::AlbumSearchExpression, redis_album_search_index::RedisAlbumSearchIndex,
+  sqlite_album_repository::SqliteAlbumRepository,
+  sqlite_album_search_index::SqliteAlbumSearchIndex,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlbumSearchSortKey {
+  Rating,
+  RatingCount,
+  ReleaseDate,
+  Name,
+  DescriptorCount,
+  /**
+   * See `AlbumReadModel::bayesian_rating`.
+   */
+  BayesianRating,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlbumSearchSort {
+  pub key: AlbumSearchSortKey,
+  pub descending: bool,
+}
 
 #[derive(Default, Builder, Debug)]
 #[builder(setter(into), default)]
@@ -22,12 +53,58 @@ pub struct AlbumSearchQuery {
   pub include_languages: Vec<String>,
   pub exclude_languages: Vec<String>,
   pub include_descriptors: Vec<String>,
+  pub exclude_descriptors: Vec<String>,
   pub min_primary_genre_count: Option<usize>,
   pub min_secondary_genre_count: Option<usize>,
   pub min_descriptor_count: Option<usize>,
   pub min_release_year: Option<u32>,
   pub max_release_year: Option<u32>,
   pub include_duplicates: Option<bool>,
+  pub include_release_types: Vec<String>,
+  pub exclude_release_types: Vec<String>,
+  pub include_labels: Vec<String>,
+  pub exclude_labels: Vec<String>,
+  /**
+   * Combined with the other filters, which are all required to match.
+   */
+  pub expression: Option<AlbumSearchExpression>,
+  pub min_rating: Option<f32>,
+  pub max_rating: Option<f32>,
+  pub min_rating_count: Option<u32>,
+  /**
+   * Artist file names of album credits, in any role.
+   */
+  pub include_credited_artists: Vec<String>,
+  pub include_credit_roles: Vec<String>,
+  pub min_track_count: Option<u32>,
+  pub max_track_count: Option<u32>,
+  pub min_duration_seconds: Option<u32>,
+  pub max_duration_seconds: Option<u32>,
+  /**
+   * Results are in relevance order when there is no sort, or in index order without text.
+   */
+  pub sort: Option<AlbumSearchSort>,
+  /**
+   * Whether genre filters also match descendant genres, see `AlbumSearchQuery::with_genre_descendants`.
+   */
+  pub include_genre_descendants: Option<bool>,
+}
+
+impl AlbumSearchQuery {
+  /**
+   * Expands the genre filters, including those of the expression, to the descendants of their
+   * genres. Search indexes only match genres by name, so the expansion happens before searching.
+   */
+  pub fn with_genre_descendants(mut self, hierarchy: &GenreHierarchy) -> Self {
+    self.include_primary_genres = hierarchy.with_descendants(&self.include_primary_genres);
+    self.exclude_primary_genres = hierarchy.with_descendants(&self.exclude_primary_genres);
+    self.include_secondary_genres = hierarchy.with_descendants(&self.include_secondary_genres);
+    self.exclude_secondary_genres = hierarchy.with_descendants(&self.exclude_secondary_genres);
+    self.expression = self
+      .expression
+      .map(|expression| expression.with_genre_descendants(hierarchy));
+    self
+  }
 }
 
 #[derive(Debug)]
@@ -42,6 +119,46 @@ pub struct AlbumSearchResult {
   pub total: usize,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlbumSearchFacet {
+  PrimaryGenre,
+  SecondaryGenre,
+  Descriptor,
+  Language,
+  /**
+   * Release decades, named after their first year.
+   */
+  Decade,
+  /**
+   * Artists, named after their file name so that values can be used as artist filters.
+   */
+  Artist,
+}
+
+pub const DEFAULT_FACET_LIMIT: usize = 20;
+
+#[derive(Debug, Clone)]
+pub struct AlbumSearchFacetQuery {
+  pub facet: AlbumSearchFacet,
+  pub limit: usize,
+}
+
+#[derive(Debug)]
+pub struct AlbumSearchFacetResult {
+  pub facet: AlbumSearchFacet,
+  pub values: Vec<ItemAndCount>,
+}
+
+/**
+ * Orders facet values by descending count, then by name, and keeps the top `limit` values.
+ */
+pub fn rank_facet_values(mut values: Vec<ItemAndCount>, limit: usize) -> Vec<ItemAndCount> {
+  values.retain(|value| !value.name.is_empty());
+  values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+  values.truncate(limit);
+  values
+}
+
 #[derive(Debug)]
 pub struct AlbumEmbeddingSimilarirtySearchQuery {
   pub embedding: Vec<f32>,
@@ -50,6 +167,11 @@ pub struct AlbumEmbeddingSimilarirtySearchQuery {
   pub limit: usize,
 }
 
+/**
+ * Size of the indexed embedding vectors, which is the size of OpenAI embeddings.
+ */
+pub const EMBEDDING_DIMENSIONS: usize = 1536;
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
 pub struct AlbumEmbedding {
   pub file_name: FileName,
@@ -64,6 +186,13 @@ pub fn embedding_to_bytes(embedding: &Vec<f32>) -> Vec<u8> {
     .collect()
 }
 
+pub fn embedding_from_bytes(bytes: &[u8]) -> Vec<f32> {
+  bytes
+    .chunks_exact(4)
+    .map(|chunk| f32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+    .collect()
+}
+
 impl AlbumEmbedding {
   pub fn embedding_bytes(&self) -> Vec<u8> {
     embedding_to_bytes(&self.embedding)
@@ -80,6 +209,14 @@ pub trait AlbumSearchIndex {
     query: &AlbumSearchQuery,
     pagination: Option<&SearchPagination>,
   ) -> Result<AlbumSearchResult>;
+  /**
+   * Counts the values of each facet over the albums matching the query.
+   */
+  async fn get_facets(
+    &self,
+    query: &AlbumSearchQuery,
+    facets: &[AlbumSearchFacetQuery],
+  ) -> Result<Vec<AlbumSearchFacetResult>>;
   async fn get_embeddings(&self, file_name: &FileName) -> Result<Vec<AlbumEmbedding>>;
   async fn find_many_embeddings(
     &self,
@@ -95,4 +232,53 @@ pub trait AlbumSearchIndex {
     query: &AlbumEmbeddingSimilarirtySearchQuery,
   ) -> Result<Vec<(AlbumReadModel, f32)>>;
   async fn get_embedding_keys(&self) -> Result<Vec<String>>;
+  async fn get_file_names(&self) -> Result<Vec<FileName>>;
+}
+
+/**
+ * Builds the album search index for the configured backend.
+ */
+pub fn build_album_search_index(
+  settings: &Settings,
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
+) -> Arc<dyn AlbumSearchIndex + Send + Sync + 'static> {
+  match settings.album_search.backend {
+    AlbumSearchBackend::Redis => Arc::new(RedisAlbumSearchIndex::new(redis_connection_pool)),
+    AlbumSearchBackend::Sqlite => Arc::new(SqliteAlbumSearchIndex::new(sqlite_connection)),
+  }
+}
+
+/**
+ * Prepares the album search index of the configured backend, and fills it from the album read
+ * models when it is new or behind them. The SQLite index also starts with the embeddings stored in
+ * the Redis index when it has none, so switching backends does not lose them.
+ */
+pub async fn setup_album_search_index(
+  settings: &Settings,
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
+) -> Result<()> {
+  match settings.album_search.backend {
+    AlbumSearchBackend::Redis => {
+      let album_search_index = RedisAlbumSearchIndex::new(redis_connection_pool);
+      if album_search_index.setup_index().await? {
+        album_search_index
+          .reindex(&SqliteAlbumRepository::new(sqlite_connection))
+          .await?;
+      }
+    }
+    AlbumSearchBackend::Sqlite => {
+      let album_search_index = SqliteAlbumSearchIndex::new(sqlite_connection);
+      if album_search_index.setup_index().await? {
+        album_search_index.reindex().await?;
+      }
+      if album_search_index.get_embedding_keys().await?.is_empty() {
+        album_search_index
+          .backfill_embeddings(&RedisAlbumSearchIndex::new(redis_connection_pool))
+          .await?;
+      }
+    }
+  }
+  Ok(())
 }
@@ -1,12 +1,24 @@
 use super::{
   album_interactor::{AlbumInteractor, AlbumMonitor},
   album_repository::{AlbumRepository, GenreAggregate, ItemAndCount},
-  album_search_index::{AlbumSearchIndex, AlbumSearchQuery, SearchPagination},
+  album_search_expression::AlbumSearchExpression,
+  album_search_index::{
+    AlbumEmbedding, AlbumSearchFacet, AlbumSearchFacetQuery, AlbumSearchFacetResult,
+    AlbumSearchIndex, AlbumSearchQuery, AlbumSearchSort, AlbumSearchSortKey, SearchPagination,
+    DEFAULT_FACET_LIMIT,
+  },
+  embedding_provider::{TagAlbumEmbeddingProvider, TAG_EMBEDDING_KEY},
+};
+use crate::{
+  files::file_metadata::file_name::FileName,
+  genres::genre_hierarchy_repository::GenreHierarchyRepository, proto, settings::Settings,
+  sqlite::SqliteConnection,
 };
-use crate::{files::file_metadata::file_name::FileName, proto};
 use anyhow::{Error, Result};
 use std::sync::Arc;
+use tokio::spawn;
 use tonic::{async_trait, Request, Response, Status};
+use tracing::error;
 
 impl From<GenreAggregate> for proto::GenreAggregate {
   fn from(val: GenreAggregate) -> Self {
@@ -82,12 +94,59 @@ impl TryFrom<proto::AlbumSearchQuery> for AlbumSearchQuery {
       include_languages: value.include_languages,
       exclude_languages: value.exclude_languages,
       include_descriptors: value.include_descriptors,
+      exclude_descriptors: value.exclude_descriptors,
       min_primary_genre_count: value.min_primary_genre_count.map(|i| i as usize),
       min_secondary_genre_count: value.min_secondary_genre_count.map(|i| i as usize),
       min_descriptor_count: value.min_descriptor_count.map(|i| i as usize),
       min_release_year: value.min_release_year.map(|i| i as u32),
       max_release_year: value.max_release_year.map(|i| i as u32),
       include_duplicates: value.include_duplicates,
+      include_release_types: value.include_release_types,
+      exclude_release_types: value.exclude_release_types,
+      include_labels: value.include_labels,
+      exclude_labels: value.exclude_labels,
+      expression: value
+        .expression
+        .map(|expression| AlbumSearchExpression::parse(&expression))
+        .transpose()?,
+      min_rating: value.min_rating,
+      max_rating: value.max_rating,
+      min_rating_count: value.min_rating_count,
+      include_credited_artists: value.include_credited_artists,
+      include_credit_roles: value.include_credit_roles,
+      min_track_count: value.min_track_count,
+      max_track_count: value.max_track_count,
+      min_duration_seconds: value.min_duration_seconds,
+      max_duration_seconds: value.max_duration_seconds,
+      include_genre_descendants: value.include_genre_descendants,
+      sort: value.sort.map(|sort| sort.try_into()).transpose()?,
+    })
+  }
+}
+
+impl TryFrom<i32> for AlbumSearchSortKey {
+  type Error = anyhow::Error;
+
+  fn try_from(value: i32) -> Result<Self> {
+    match value {
+      0 => Ok(Self::Rating),
+      1 => Ok(Self::RatingCount),
+      2 => Ok(Self::ReleaseDate),
+      3 => Ok(Self::Name),
+      4 => Ok(Self::DescriptorCount),
+      5 => Ok(Self::BayesianRating),
+      _ => Err(anyhow::anyhow!("Unknown sort key value {}", value)),
+    }
+  }
+}
+
+impl TryFrom<proto::AlbumSearchSort> for AlbumSearchSort {
+  type Error = anyhow::Error;
+
+  fn try_from(value: proto::AlbumSearchSort) -> Result<Self> {
+    Ok(AlbumSearchSort {
+      key: value.key.try_into()?,
+      descending: value.descending,
     })
   }
 }
@@ -103,23 +162,102 @@ impl TryFrom<proto::SearchPagination> for SearchPagination {
   }
 }
 
+impl TryFrom<i32> for AlbumSearchFacet {
+  type Error = anyhow::Error;
+
+  fn try_from(value: i32) -> Result<Self> {
+    match value {
+      0 => Ok(Self::PrimaryGenre),
+      1 => Ok(Self::SecondaryGenre),
+      2 => Ok(Self::Descriptor),
+      3 => Ok(Self::Language),
+      4 => Ok(Self::Decade),
+      5 => Ok(Self::Artist),
+      _ => Err(anyhow::anyhow!("Unknown facet value {}", value)),
+    }
+  }
+}
+
+impl From<AlbumSearchFacet> for proto::AlbumSearchFacet {
+  fn from(val: AlbumSearchFacet) -> Self {
+    match val {
+      AlbumSearchFacet::PrimaryGenre => proto::AlbumSearchFacet::PrimaryGenreFacet,
+      AlbumSearchFacet::SecondaryGenre => proto::AlbumSearchFacet::SecondaryGenreFacet,
+      AlbumSearchFacet::Descriptor => proto::AlbumSearchFacet::DescriptorFacet,
+      AlbumSearchFacet::Language => proto::AlbumSearchFacet::LanguageFacet,
+      AlbumSearchFacet::Decade => proto::AlbumSearchFacet::DecadeFacet,
+      AlbumSearchFacet::Artist => proto::AlbumSearchFacet::ArtistFacet,
+    }
+  }
+}
+
+impl TryFrom<proto::AlbumSearchFacetRequest> for AlbumSearchFacetQuery {
+  type Error = anyhow::Error;
+
+  fn try_from(value: proto::AlbumSearchFacetRequest) -> Result<Self> {
+    Ok(AlbumSearchFacetQuery {
+      facet: value.facet.try_into()?,
+      limit: value
+        .limit
+        .map(|limit| limit as usize)
+        .unwrap_or(DEFAULT_FACET_LIMIT),
+    })
+  }
+}
+
+impl From<AlbumSearchFacetResult> for proto::AlbumSearchFacetResult {
+  fn from(val: AlbumSearchFacetResult) -> Self {
+    proto::AlbumSearchFacetResult {
+      facet: proto::AlbumSearchFacet::from(val.facet).into(),
+      values: val.values.into_iter().map(|value| value.into()).collect(),
+    }
+  }
+}
+
 pub struct AlbumService {
   album_interactor: Arc<AlbumInteractor>,
   album_repository: Arc<dyn AlbumRepository + Send + Sync + 'static>,
   album_search_index: Arc<dyn AlbumSearchIndex + Send + Sync + 'static>,
+  genre_hierarchy_repository: GenreHierarchyRepository,
+  tag_embedding_provider: Option<Arc<TagAlbumEmbeddingProvider>>,
 }
 
 impl AlbumService {
   pub fn new(
+    settings: Arc<Settings>,
+    sqlite_connection: Arc<SqliteConnection>,
     album_repository: Arc<dyn AlbumRepository + Send + Sync + 'static>,
     album_search_index: Arc<dyn AlbumSearchIndex + Send + Sync + 'static>,
   ) -> Self {
     Self {
+      genre_hierarchy_repository: GenreHierarchyRepository::new(Arc::clone(&sqlite_connection)),
+      tag_embedding_provider: settings.tag_embedding.enabled.then(|| {
+        Arc::new(TagAlbumEmbeddingProvider::new(
+          Arc::clone(&settings),
+          Arc::clone(&sqlite_connection),
+        ))
+      }),
       album_repository: Arc::clone(&album_repository),
       album_search_index: Arc::clone(&album_search_index),
-      album_interactor: Arc::new(AlbumInteractor::new(album_repository, album_search_index)),
+      album_interactor: Arc::new(AlbumInteractor::new(
+        &settings,
+        sqlite_connection,
+        album_repository,
+        album_search_index,
+      )),
     }
   }
+
+  async fn get_album_reply(&self, file_name: &FileName) -> Result<proto::GetAlbumReply, Status> {
+    let album = self
+      .album_repository
+      .get(file_name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(proto::GetAlbumReply {
+      album: Some(album.into()),
+    })
+  }
 }
 
 #[async_trait]
@@ -156,6 +294,23 @@ impl proto::AlbumService for AlbumService {
     Ok(Response::new(reply))
   }
 
+  async fn get_album_reviews(
+    &self,
+    request: Request<proto::GetAlbumReviewsRequest>,
+  ) -> Result<Response<proto::GetAlbumReviewsReply>, Status> {
+    let file_name = FileName::try_from(request.into_inner().file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let reviews = self
+      .album_repository
+      .find_reviews(&file_name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    let reply = proto::GetAlbumReviewsReply {
+      reviews: reviews.into_iter().map(|review| review.into()).collect(),
+    };
+    Ok(Response::new(reply))
+  }
+
   async fn get_many_albums(
     &self,
     request: Request<proto::GetManyAlbumsRequest>,
@@ -183,12 +338,20 @@ impl proto::AlbumService for AlbumService {
     request: Request<proto::SearchAlbumsRequest>,
   ) -> Result<Response<proto::SearchAlbumsReply>, Status> {
     let request = request.into_inner();
-    let query: AlbumSearchQuery = request
+    let mut query: AlbumSearchQuery = request
       .query
       .map(|q| q.try_into())
       .transpose()
       .map_err(|e: Error| Status::invalid_argument(format!("Invalid query: {}", e.to_string())))?
       .unwrap_or_default();
+    if query.include_genre_descendants.is_some_and(|b| b) {
+      let hierarchy = self
+        .genre_hierarchy_repository
+        .get()
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+      query = query.with_genre_descendants(&hierarchy);
+    }
     let pagination = request
       .pagination
       .map(|p| p.try_into())
@@ -196,11 +359,26 @@ impl proto::AlbumService for AlbumService {
       .map_err(|e: Error| {
         Status::invalid_argument(format!("Invalid pagination: {}", e.to_string()))
       })?;
+    let facet_queries = request
+      .facets
+      .into_iter()
+      .map(|facet| facet.try_into())
+      .collect::<Result<Vec<AlbumSearchFacetQuery>>>()
+      .map_err(|e| Status::invalid_argument(format!("Invalid facet: {}", e.to_string())))?;
     let results = self
       .album_search_index
       .search(&query, pagination.as_ref())
       .await
       .map_err(|e| Status::internal(e.to_string()))?;
+    let facets = if facet_queries.is_empty() {
+      vec![]
+    } else {
+      self
+        .album_search_index
+        .get_facets(&query, &facet_queries)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+    };
     let reply = proto::SearchAlbumsReply {
       albums: results
         .albums
@@ -208,6 +386,7 @@ impl proto::AlbumService for AlbumService {
         .map(|album| album.into())
         .collect::<Vec<proto::Album>>(),
       total: results.total as u32,
+      facets: facets.into_iter().map(|facet| facet.into()).collect(),
     };
     Ok(Response::new(reply))
   }
@@ -225,4 +404,110 @@ impl proto::AlbumService for AlbumService {
     };
     Ok(Response::new(reply))
   }
+
+  async fn retrain_tag_embeddings(
+    &self,
+    _request: Request<()>,
+  ) -> Result<Response<proto::RetrainTagEmbeddingsReply>, Status> {
+    let tag_embedding_provider =
+      self
+        .tag_embedding_provider
+        .as_ref()
+        .ok_or(Status::failed_precondition(
+          "Tag embeddings are not enabled",
+        ))?;
+    let retraining = tag_embedding_provider
+      .retrain()
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    let album_count = retraining.embeddings.len() as u32;
+
+    // Embeddings of the previous model are not comparable, so every album is updated.
+    let album_search_index = Arc::clone(&self.album_search_index);
+    spawn(async move {
+      for (file_name, embedding) in retraining.embeddings {
+        let result = match embedding {
+          Some(embedding) => {
+            album_search_index
+              .put_embedding(&AlbumEmbedding {
+                file_name: file_name.clone(),
+                key: TAG_EMBEDDING_KEY.to_string(),
+                embedding,
+              })
+              .await
+          }
+          None => {
+            album_search_index
+              .delete_embedding(&file_name, TAG_EMBEDDING_KEY)
+              .await
+          }
+        };
+        if let Err(e) = result {
+          error!(
+            "Failed to update tag embedding for {}: {:?}",
+            file_name.to_string(),
+            e
+          );
+        }
+      }
+    });
+    Ok(Response::new(proto::RetrainTagEmbeddingsReply {
+      tag_count: retraining.tag_count as u32,
+      album_count,
+    }))
+  }
+
+  async fn mark_album_duplicate(
+    &self,
+    request: Request<proto::MarkAlbumDuplicateRequest>,
+  ) -> Result<Response<proto::GetAlbumReply>, Status> {
+    let request = request.into_inner();
+    let file_name =
+      FileName::try_from(request.file_name).map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let duplicate_of = FileName::try_from(request.duplicate_of)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    self
+      .album_interactor
+      .mark_duplicate(&file_name, &duplicate_of)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(self.get_album_reply(&file_name).await?))
+  }
+
+  async fn merge_album_duplicates(
+    &self,
+    request: Request<proto::MergeAlbumDuplicatesRequest>,
+  ) -> Result<Response<proto::GetAlbumReply>, Status> {
+    let request = request.into_inner();
+    let original_file_name = FileName::try_from(request.original_file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let duplicate_file_names = request
+      .duplicate_file_names
+      .into_iter()
+      .map(FileName::try_from)
+      .collect::<Result<Vec<FileName>, Error>>()
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    self
+      .album_interactor
+      .merge_duplicates(&original_file_name, duplicate_file_names)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(
+      self.get_album_reply(&original_file_name).await?,
+    ))
+  }
+
+  async fn unmark_album_duplicate(
+    &self,
+    request: Request<proto::UnmarkAlbumDuplicateRequest>,
+  ) -> Result<Response<proto::GetAlbumReply>, Status> {
+    let file_name = FileName::try_from(request.into_inner().file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    self
+      .album_interactor
+      .unmark_duplicate(&file_name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(self.get_album_reply(&file_name).await?))
+  }
 }
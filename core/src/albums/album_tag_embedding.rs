@@ -0,0 +1,283 @@
+use super::album_read_model::AlbumReadModel;
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+/**
+ * Secondary genres describe an album less than its primary genres.
+ */
+const SECONDARY_GENRE_WEIGHT: f32 = 0.5;
+const SVD_ITERATIONS: usize = 30;
+const SVD_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/**
+ * The tags an album is embedded from.
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AlbumTags {
+  pub primary_genres: Vec<String>,
+  pub secondary_genres: Vec<String>,
+  pub descriptors: Vec<String>,
+  /**
+   * Credited artist file names and roles, formatted as in `AlbumReadModel::credit_tags`.
+   */
+  pub credit_tags: Vec<String>,
+  pub release_date: Option<NaiveDate>,
+}
+
+impl From<&AlbumReadModel> for AlbumTags {
+  fn from(album: &AlbumReadModel) -> Self {
+    Self {
+      primary_genres: album.primary_genres.clone(),
+      secondary_genres: album.secondary_genres.clone(),
+      descriptors: album.descriptors.clone(),
+      credit_tags: album.credit_tags(),
+      release_date: album.release_date,
+    }
+  }
+}
+
+impl AlbumTags {
+  /**
+   * Distinct tags with their term weights, e.g. ("genre:shoegaze", 1.0) or ("decade:1990", 1.0).
+   */
+  pub fn weighted_tags(&self) -> Vec<(String, f32)> {
+    let mut tags = BTreeMap::<String, f32>::new();
+    let mut add = |tag: String, weight: f32| {
+      let entry = tags.entry(tag).or_insert(0.0);
+      *entry = entry.max(weight);
+    };
+    for genre in &self.primary_genres {
+      add(format!("genre:{}", genre.to_lowercase()), 1.0);
+    }
+    for genre in &self.secondary_genres {
+      add(
+        format!("genre:{}", genre.to_lowercase()),
+        SECONDARY_GENRE_WEIGHT,
+      );
+    }
+    for descriptor in &self.descriptors {
+      add(format!("descriptor:{}", descriptor.to_lowercase()), 1.0);
+    }
+    for credit_tag in &self.credit_tags {
+      add(format!("credit:{}", credit_tag), 1.0);
+    }
+    if let Some(release_date) = self.release_date {
+      add(format!("decade:{}", release_date.year() / 10 * 10), 1.0);
+    }
+    tags.into_iter().collect()
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumTagEmbeddingTraining {
+  pub dimensions: usize,
+  /**
+   * Only the most frequent tags are embedded, which bounds the size of the co-occurrence matrix.
+   */
+  pub max_tags: usize,
+  pub min_tag_count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumTagVector {
+  pub idf: f32,
+  pub vector: Vec<f32>,
+}
+
+/**
+ * Tag vectors learned from the album-tag matrix: the positive pointwise mutual information of tag
+ * co-occurrences, factorized by truncated SVD. Albums are embedded as the sum of their tag
+ * vectors, weighted by TF-IDF. Training and embedding are deterministic.
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AlbumTagEmbeddingModel {
+  pub tags: HashMap<String, AlbumTagVector>,
+}
+
+/**
+ * Xorshift generator, so that the SVD starts from the same vectors on every training.
+ */
+fn next_random(state: &mut u64) -> f32 {
+  *state ^= *state << 13;
+  *state ^= *state >> 7;
+  *state ^= *state << 17;
+  (*state >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+  a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn normalize(vector: &mut [f32]) -> bool {
+  let norm = dot(vector, vector).sqrt();
+  if norm <= f32::EPSILON {
+    vector.iter_mut().for_each(|value| *value = 0.0);
+    return false;
+  }
+  vector.iter_mut().for_each(|value| *value /= norm);
+  true
+}
+
+/**
+ * Top eigenvectors of a sparse symmetric matrix by orthogonal iteration, scaled by the square root
+ * of their eigenvalue magnitudes. For a symmetric matrix this is its truncated SVD.
+ */
+fn factorize(rows: &[Vec<(usize, f32)>], dimensions: usize) -> Vec<Vec<f32>> {
+  let size = rows.len();
+  let multiply = |column: &[f32]| -> Vec<f32> {
+    rows
+      .iter()
+      .map(|row| row.iter().map(|(j, value)| value * column[*j]).sum())
+      .collect()
+  };
+  let mut state = SVD_SEED;
+  let mut columns = (0..dimensions)
+    .map(|_| {
+      (0..size)
+        .map(|_| next_random(&mut state))
+        .collect::<Vec<f32>>()
+    })
+    .collect::<Vec<_>>();
+  for _ in 0..SVD_ITERATIONS {
+    let mut next = columns
+      .par_iter()
+      .map(|column| multiply(column))
+      .collect::<Vec<_>>();
+    for i in 0..next.len() {
+      let (previous, rest) = next.split_at_mut(i);
+      let column = &mut rest[0];
+      for other in previous.iter() {
+        let projection = dot(column, other);
+        column
+          .iter_mut()
+          .zip(other)
+          .for_each(|(value, other)| *value -= projection * other);
+      }
+      normalize(column);
+    }
+    columns = next;
+  }
+  let scales = columns
+    .iter()
+    .map(|column| dot(column, &multiply(column)).abs().sqrt())
+    .collect::<Vec<_>>();
+  (0..size)
+    .map(|i| {
+      columns
+        .iter()
+        .zip(&scales)
+        .map(|(column, scale)| column[i] * scale)
+        .collect()
+    })
+    .collect()
+}
+
+impl AlbumTagEmbeddingModel {
+  pub fn new(tags: HashMap<String, AlbumTagVector>) -> Self {
+    Self { tags }
+  }
+
+  pub fn train(albums: &[AlbumTags], training: &AlbumTagEmbeddingTraining) -> Result<Self> {
+    if training.dimensions == 0 {
+      anyhow::bail!("Tag embeddings need at least one dimension");
+    }
+    let album_tags = albums
+      .iter()
+      .map(|album| album.weighted_tags())
+      .collect::<Vec<_>>();
+
+    let mut counts = HashMap::<&str, u32>::new();
+    for tags in &album_tags {
+      for (tag, _) in tags {
+        *counts.entry(tag.as_str()).or_default() += 1;
+      }
+    }
+    let mut vocabulary = counts
+      .into_iter()
+      .filter(|(_, count)| *count >= training.min_tag_count.max(1))
+      .collect::<Vec<_>>();
+    vocabulary.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    vocabulary.truncate(training.max_tags);
+    let indices = vocabulary
+      .iter()
+      .enumerate()
+      .map(|(i, (tag, _))| (*tag, i))
+      .collect::<HashMap<_, _>>();
+
+    let mut co_occurrences = HashMap::<(usize, usize), u32>::new();
+    let mut album_count = 0;
+    for tags in &album_tags {
+      let tag_indices = tags
+        .iter()
+        .filter_map(|(tag, _)| indices.get(tag.as_str()).copied())
+        .collect::<Vec<_>>();
+      if tag_indices.is_empty() {
+        continue;
+      }
+      album_count += 1;
+      for (n, i) in tag_indices.iter().enumerate() {
+        for j in tag_indices.iter().skip(n + 1) {
+          *co_occurrences.entry((*i.min(j), *i.max(j))).or_default() += 1;
+        }
+      }
+    }
+
+    let mut row_sums = vec![0.0f64; vocabulary.len()];
+    for ((i, j), count) in &co_occurrences {
+      row_sums[*i] += *count as f64;
+      row_sums[*j] += *count as f64;
+    }
+    let total = row_sums.iter().sum::<f64>();
+    let mut rows = vec![Vec::<(usize, f32)>::new(); vocabulary.len()];
+    for ((i, j), count) in &co_occurrences {
+      let pmi = (*count as f64 * total / (row_sums[*i] * row_sums[*j])).ln();
+      if pmi > 0.0 {
+        rows[*i].push((*j, pmi as f32));
+        rows[*j].push((*i, pmi as f32));
+      }
+    }
+    rows.iter_mut().for_each(|row| row.sort_by_key(|(j, _)| *j));
+
+    let vectors = factorize(&rows, training.dimensions.min(vocabulary.len()));
+    Ok(Self {
+      tags: vocabulary
+        .into_iter()
+        .zip(vectors)
+        .map(|((tag, count), mut vector)| {
+          vector.resize(training.dimensions, 0.0);
+          (
+            tag.to_string(),
+            AlbumTagVector {
+              idf: (album_count as f32 / count as f32).ln(),
+              vector,
+            },
+          )
+        })
+        .collect(),
+    })
+  }
+
+  /**
+   * The normalized TF-IDF weighted sum of the album's tag vectors, or none if the album has no
+   * known tags.
+   */
+  pub fn embed(&self, tags: &AlbumTags) -> Option<Vec<f32>> {
+    let mut embedding: Option<Vec<f32>> = None;
+    for (tag, weight) in tags.weighted_tags() {
+      if let Some(tag_vector) = self.tags.get(&tag) {
+        let embedding = embedding.get_or_insert_with(|| vec![0.0; tag_vector.vector.len()]);
+        for (value, tag_value) in embedding.iter_mut().zip(&tag_vector.vector) {
+          *value += weight * tag_vector.idf * tag_value;
+        }
+      }
+    }
+    let mut embedding = embedding?;
+    if normalize(&mut embedding) {
+      Some(embedding)
+    } else {
+      None
+    }
+  }
+}
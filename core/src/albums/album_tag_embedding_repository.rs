@@ -0,0 +1,237 @@
+use super::album_tag_embedding::{AlbumTagEmbeddingModel, AlbumTagVector, AlbumTags};
+use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use rusqlite::{params, types::Value};
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+use tracing::{error, instrument};
+
+#[derive(Debug, Clone)]
+pub struct TaggedAlbum {
+  pub file_name: FileName,
+  pub is_duplicate: bool,
+  pub tags: AlbumTags,
+}
+
+fn to_file_name(value: String) -> rusqlite::Result<FileName> {
+  FileName::try_from(value).map_err(|e| {
+    error!(message = e.to_string(), "Failed to parse album file name");
+    rusqlite::Error::ExecuteReturnedResults
+  })
+}
+
+#[derive(Debug, Clone)]
+pub struct AlbumTagEmbeddingRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl AlbumTagEmbeddingRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  /**
+   * Reads the tags of every album at once, which is cheaper than loading full read models for
+   * training.
+   */
+  #[instrument(skip(self))]
+  pub async fn find_all_tagged_albums(&self) -> Result<Vec<TaggedAlbum>> {
+    let albums = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut albums = HashMap::<i64, TaggedAlbum>::new();
+        let mut stmt = conn.prepare(
+          "
+          SELECT
+            albums.id,
+            albums.file_name,
+            albums.release_date,
+            album_duplicates.original_album_id IS NOT NULL
+          FROM albums
+          LEFT JOIN album_duplicates ON album_duplicates.duplicate_album_id = albums.id
+          ",
+        )?;
+        let rows = stmt.query_map([], |row| {
+          Ok((
+            row.get::<_, i64>(0)?,
+            TaggedAlbum {
+              file_name: to_file_name(row.get::<_, String>(1)?)?,
+              is_duplicate: row.get::<_, bool>(3)?,
+              tags: AlbumTags {
+                release_date: row.get::<_, Option<NaiveDate>>(2)?,
+                ..Default::default()
+              },
+            },
+          ))
+        })?;
+        for row in rows {
+          let (id, album) = row?;
+          albums.insert(id, album);
+        }
+
+        let mut stmt = conn.prepare(
+          "
+          SELECT album_genres.album_id, genres.name, album_genres.is_primary
+          FROM album_genres
+          JOIN genres ON genres.id = album_genres.genre_id
+          ",
+        )?;
+        let rows = stmt.query_map([], |row| {
+          Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, bool>(2)?,
+          ))
+        })?;
+        for row in rows {
+          let (id, genre, is_primary) = row?;
+          if let Some(album) = albums.get_mut(&id) {
+            if is_primary {
+              album.tags.primary_genres.push(genre);
+            } else {
+              album.tags.secondary_genres.push(genre);
+            }
+          }
+        }
+
+        let mut stmt = conn.prepare(
+          "
+          SELECT album_descriptors.album_id, descriptors.name
+          FROM album_descriptors
+          JOIN descriptors ON descriptors.id = album_descriptors.descriptor_id
+          ",
+        )?;
+        let rows = stmt.query_map([], |row| {
+          Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+          let (id, descriptor) = row?;
+          if let Some(album) = albums.get_mut(&id) {
+            album.tags.descriptors.push(descriptor);
+          }
+        }
+
+        // Formatted as AlbumReadModel::credit_tags.
+        let mut stmt = conn.prepare(
+          "
+          SELECT credits.album_id, artists.file_name || ':' || replace(lower(roles.name), ' ', '_')
+          FROM credits
+          JOIN artists ON artists.id = credits.artist_id
+          JOIN credit_roles ON credit_roles.credit_id = credits.id
+          JOIN roles ON roles.id = credit_roles.role_id
+          ",
+        )?;
+        let rows = stmt.query_map([], |row| {
+          Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+          let (id, credit_tag) = row?;
+          if let Some(album) = albums.get_mut(&id) {
+            album.tags.credit_tags.push(credit_tag);
+          }
+        }
+
+        let mut albums = albums.into_values().collect::<Vec<_>>();
+        albums.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        Ok::<_, rusqlite::Error>(albums)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find tagged albums");
+        anyhow!("Failed to find tagged albums")
+      })??;
+    Ok(albums)
+  }
+
+  /**
+   * Replaces the whole model, as tag vectors of different trainings are not comparable.
+   */
+  #[instrument(skip_all, fields(count = model.tags.len()))]
+  pub async fn replace(&self, model: &AlbumTagEmbeddingModel) -> Result<()> {
+    let tags = model
+      .tags
+      .iter()
+      .map(|(tag, tag_vector)| {
+        Ok((
+          tag.clone(),
+          tag_vector.idf,
+          serde_json::to_string(&tag_vector.vector)?,
+        ))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM album_tag_embeddings", [])?;
+        {
+          let mut statement =
+            tx.prepare("INSERT INTO album_tag_embeddings (tag, idf, vector) VALUES (?, ?, ?)")?;
+          for (tag, idf, vector) in tags {
+            statement.execute(params![tag, idf as f64, vector])?;
+          }
+        }
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to replace album tag embeddings"
+        );
+        anyhow!("Failed to replace album tag embeddings")
+      })?
+  }
+
+  /**
+   * Loads the part of the model covering the given tags.
+   */
+  #[instrument(skip_all, fields(count = tags.len()))]
+  pub async fn find(&self, tags: Vec<String>) -> Result<AlbumTagEmbeddingModel> {
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let tags = Rc::new(tags.into_iter().map(Value::from).collect::<Vec<Value>>());
+        let mut stmt = conn
+          .prepare("SELECT tag, idf, vector FROM album_tag_embeddings WHERE tag IN rarray(?)")?;
+        let rows = stmt
+          .query_map(params![tags], |row| {
+            Ok((
+              row.get::<_, String>(0)?,
+              row.get::<_, f64>(1)?,
+              row.get::<_, String>(2)?,
+            ))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find album tag embeddings"
+        );
+        anyhow!("Failed to find album tag embeddings")
+      })??;
+    let tags = rows
+      .into_iter()
+      .map(|(tag, idf, vector)| {
+        Ok((
+          tag,
+          AlbumTagVector {
+            idf: idf as f32,
+            vector: serde_json::from_str(&vector)?,
+          },
+        ))
+      })
+      .collect::<Result<HashMap<_, _>>>()?;
+    Ok(AlbumTagEmbeddingModel::new(tags))
+  }
+}
@@ -1,17 +1,75 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
-use crate::{albums::album_read_model::AlbumReadModel, settings::Settings};
+use crate::{
+  albums::{
+    album_read_model::{AlbumReadModel, AlbumReadModelReview},
+    album_search_index::EMBEDDING_DIMENSIONS,
+    album_tag_embedding::{AlbumTagEmbeddingModel, AlbumTagEmbeddingTraining, AlbumTags},
+    album_tag_embedding_repository::AlbumTagEmbeddingRepository,
+  },
+  files::file_metadata::file_name::FileName,
+  settings::{Settings, TagEmbeddingSettings},
+  sqlite::SqliteConnection,
+};
 use anyhow::Result;
 use async_openai::{config::OpenAIConfig, types::CreateEmbeddingRequestArgs, Client};
 use async_trait::async_trait;
+use candle_core::{Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use serde_derive::Deserialize;
+use tokenizers::{Tokenizer, TruncationParams};
 
 #[async_trait]
 pub trait AlbumEmbeddingProvider {
   fn name(&self) -> &str;
   /**
-   * Returns a mapping of embedding keys to embeddings for the given album.
+   * Returns a mapping of embedding keys to embeddings for the given album and its reviews.
    */
-  async fn generate(&self, album: &AlbumReadModel) -> Result<Vec<(&str, Vec<f32>)>>;
+  async fn generate(
+    &self,
+    album: &AlbumReadModel,
+    reviews: &[AlbumReadModelReview],
+  ) -> Result<Vec<(&str, Vec<f32>)>>;
+}
+
+/**
+ * Caps the review text appended to the embedding input, to stay within the model's token limit.
+ */
+const MAX_REVIEW_INPUT_CHARS: usize = 12000;
+
+/**
+ * The text embedded for an album, shared by the text embedding providers.
+ */
+fn get_input(album: &AlbumReadModel, reviews: &[AlbumReadModelReview]) -> String {
+  let mut corpus = vec![];
+  corpus.push(album.rating.to_string());
+  corpus.push(album.rating_count.to_string());
+  if let Some(release_date) = album.release_date {
+    corpus.push(release_date.to_string());
+  }
+  corpus.extend(album.artists.clone().into_iter().map(|artist| artist.name));
+  corpus.extend(album.primary_genres.clone());
+  corpus.extend(album.secondary_genres.clone());
+  corpus.extend(album.descriptors.clone());
+  corpus.extend(album.languages.clone());
+  corpus.extend(album.credits.clone().into_iter().map(|c| c.artist.name));
+  let mut input = corpus.join(", ");
+  let mut remaining_review_chars = MAX_REVIEW_INPUT_CHARS;
+  for review in reviews {
+    if remaining_review_chars == 0 {
+      break;
+    }
+    let text = review
+      .text
+      .chars()
+      .take(remaining_review_chars)
+      .collect::<String>();
+    remaining_review_chars -= text.chars().count();
+    input.push('\n');
+    input.push_str(&text);
+  }
+  input
 }
 
 pub struct OpenAIAlbumEmbeddingProvider {
@@ -27,22 +85,6 @@ impl OpenAIAlbumEmbeddingProvider {
       None => Err(anyhow::anyhow!("OpenAI settings not found")),
     }
   }
-
-  fn get_input(&self, album: &AlbumReadModel) -> String {
-    let mut corpus = vec![];
-    corpus.push(album.rating.to_string());
-    corpus.push(album.rating_count.to_string());
-    if let Some(release_date) = album.release_date {
-      corpus.push(release_date.to_string());
-    }
-    corpus.extend(album.artists.clone().into_iter().map(|artist| artist.name));
-    corpus.extend(album.primary_genres.clone());
-    corpus.extend(album.secondary_genres.clone());
-    corpus.extend(album.descriptors.clone());
-    corpus.extend(album.languages.clone());
-    corpus.extend(album.credits.clone().into_iter().map(|c| c.artist.name));
-    corpus.join(", ")
-  }
 }
 
 #[async_trait]
@@ -51,11 +93,15 @@ impl AlbumEmbeddingProvider for OpenAIAlbumEmbeddingProvider {
     "openai"
   }
 
-  #[tracing::instrument(name = "OpenAIAlbumEmbeddingProvider::generate", skip(self))]
-  async fn generate(&self, album: &AlbumReadModel) -> Result<Vec<(&str, Vec<f32>)>> {
+  #[tracing::instrument(name = "OpenAIAlbumEmbeddingProvider::generate", skip(self, reviews))]
+  async fn generate(
+    &self,
+    album: &AlbumReadModel,
+    reviews: &[AlbumReadModelReview],
+  ) -> Result<Vec<(&str, Vec<f32>)>> {
     let request = CreateEmbeddingRequestArgs::default()
       .model("text-embedding-ada-002")
-      .input([self.get_input(album)])
+      .input([get_input(album, reviews)])
       .build()?;
     let mut response = self.client.embeddings().create(request).await?;
     let mut result = Vec::new();
@@ -70,3 +116,235 @@ impl AlbumEmbeddingProvider for OpenAIAlbumEmbeddingProvider {
     Ok(result)
   }
 }
+
+/**
+ * The parts of the model's config.json needed before loading it.
+ */
+#[derive(Deserialize)]
+pub struct LocalEmbeddingModelDimensions {
+  pub hidden_size: usize,
+  pub max_position_embeddings: usize,
+}
+
+impl LocalEmbeddingModelDimensions {
+  /**
+   * Reads the dimensions from the model's config.json, rejecting models whose embeddings don't
+   * fit the indexed embedding size.
+   */
+  pub fn from_config(config_json: &str) -> Result<Self> {
+    let dimensions: Self = serde_json::from_str(config_json)?;
+    if dimensions.hidden_size > EMBEDDING_DIMENSIONS {
+      return Err(anyhow::anyhow!(
+        "Local embedding model has {} dimensions, more than the supported {}",
+        dimensions.hidden_size,
+        EMBEDDING_DIMENSIONS
+      ));
+    }
+    Ok(dimensions)
+  }
+}
+
+/**
+ * Mean-pools the token embeddings of a single input, of shape (1, tokens, hidden size), and
+ * L2-normalizes the result. It is zero-padded to the indexed embedding size, which leaves cosine
+ * similarity unchanged.
+ */
+pub fn pool_embedding(token_embeddings: &Tensor) -> Result<Vec<f32>> {
+  let (_, token_count, _) = token_embeddings.dims3()?;
+  let pooled = (token_embeddings.sum(1)? / token_count as f64)?;
+  let normalized = pooled.broadcast_div(&pooled.sqr()?.sum_keepdim(1)?.sqrt()?)?;
+  let mut embedding = normalized.squeeze(0)?.to_vec1::<f32>()?;
+  embedding.resize(EMBEDDING_DIMENSIONS, 0.0);
+  Ok(embedding)
+}
+
+struct LocalEmbeddingModel {
+  model: BertModel,
+  tokenizer: Tokenizer,
+  device: Device,
+}
+
+impl LocalEmbeddingModel {
+  /**
+   * Sentence embedding of the input, see `pool_embedding`.
+   */
+  fn embed(&self, input: &str) -> Result<Vec<f32>> {
+    let encoding = self
+      .tokenizer
+      .encode(input, true)
+      .map_err(anyhow::Error::msg)?;
+    let token_ids = Tensor::new(encoding.get_ids(), &self.device)?.unsqueeze(0)?;
+    let token_type_ids = token_ids.zeros_like()?;
+    let output = self.model.forward(&token_ids, &token_type_ids)?;
+    pool_embedding(&output)
+  }
+}
+
+/**
+ * Runs a BERT sentence-embedding model on the CPU, so that embeddings need no network access.
+ */
+pub struct LocalAlbumEmbeddingProvider {
+  model: Arc<LocalEmbeddingModel>,
+}
+
+impl LocalAlbumEmbeddingProvider {
+  pub fn new(settings: Arc<Settings>) -> Result<Self> {
+    let model_dir = match &settings.local_embedding {
+      Some(local_embedding) => Path::new(&local_embedding.model_dir),
+      None => return Err(anyhow::anyhow!("Local embedding settings not found")),
+    };
+    let config_json = std::fs::read_to_string(model_dir.join("config.json"))?;
+    let config: Config = serde_json::from_str(&config_json)?;
+    let dimensions = LocalEmbeddingModelDimensions::from_config(&config_json)?;
+    let mut tokenizer =
+      Tokenizer::from_file(model_dir.join("tokenizer.json")).map_err(anyhow::Error::msg)?;
+    tokenizer
+      .with_padding(None)
+      .with_truncation(Some(TruncationParams {
+        max_length: dimensions.max_position_embeddings,
+        ..Default::default()
+      }))
+      .map_err(anyhow::Error::msg)?;
+    let device = Device::Cpu;
+    // Safety: the weights file is not expected to change while it is mapped.
+    let vb = unsafe {
+      VarBuilder::from_mmaped_safetensors(&[model_dir.join("model.safetensors")], DTYPE, &device)?
+    };
+    let model = BertModel::load(vb, &config)?;
+    Ok(Self {
+      model: Arc::new(LocalEmbeddingModel {
+        model,
+        tokenizer,
+        device,
+      }),
+    })
+  }
+}
+
+#[async_trait]
+impl AlbumEmbeddingProvider for LocalAlbumEmbeddingProvider {
+  fn name(&self) -> &str {
+    "local"
+  }
+
+  #[tracing::instrument(name = "LocalAlbumEmbeddingProvider::generate", skip(self, reviews))]
+  async fn generate(
+    &self,
+    album: &AlbumReadModel,
+    reviews: &[AlbumReadModelReview],
+  ) -> Result<Vec<(&str, Vec<f32>)>> {
+    let input = get_input(album, reviews);
+    let model = Arc::clone(&self.model);
+    let embedding = tokio::task::spawn_blocking(move || model.embed(&input)).await??;
+    Ok(vec![("local-default", embedding)])
+  }
+}
+
+pub const TAG_EMBEDDING_KEY: &str = "tags-default";
+
+pub struct AlbumTagEmbeddingRetraining {
+  pub tag_count: usize,
+  /**
+   * New embeddings of every album, or none for albums without known tags.
+   */
+  pub embeddings: Vec<(FileName, Option<Vec<f32>>)>,
+}
+
+/**
+ * Embeds albums from their genres, descriptors, credits and decade with a model trained over all
+ * albums (see `AlbumTagEmbeddingModel`). Albums get no embedding until the model is trained.
+ */
+pub struct TagAlbumEmbeddingProvider {
+  settings: TagEmbeddingSettings,
+  album_tag_embedding_repository: AlbumTagEmbeddingRepository,
+}
+
+impl TagAlbumEmbeddingProvider {
+  pub fn new(settings: Arc<Settings>, sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self {
+      settings: settings.tag_embedding.clone(),
+      album_tag_embedding_repository: AlbumTagEmbeddingRepository::new(sqlite_connection),
+    }
+  }
+
+  /**
+   * Retrains the model over all albums except duplicates, and embeds every album with it.
+   */
+  #[tracing::instrument(name = "TagAlbumEmbeddingProvider::retrain", skip(self))]
+  pub async fn retrain(&self) -> Result<AlbumTagEmbeddingRetraining> {
+    if self.settings.dimensions > EMBEDDING_DIMENSIONS {
+      return Err(anyhow::anyhow!(
+        "Tag embeddings have {} dimensions, more than the supported {}",
+        self.settings.dimensions,
+        EMBEDDING_DIMENSIONS
+      ));
+    }
+    let training = AlbumTagEmbeddingTraining {
+      dimensions: self.settings.dimensions,
+      max_tags: self.settings.max_tags,
+      min_tag_count: self.settings.min_tag_count,
+    };
+    let albums = self
+      .album_tag_embedding_repository
+      .find_all_tagged_albums()
+      .await?;
+    let (model, embeddings) = tokio::task::spawn_blocking(move || {
+      let training_albums = albums
+        .iter()
+        .filter(|album| !album.is_duplicate)
+        .map(|album| album.tags.clone())
+        .collect::<Vec<_>>();
+      let model = AlbumTagEmbeddingModel::train(&training_albums, &training)?;
+      let embeddings = albums
+        .into_iter()
+        .map(|album| {
+          let embedding = model.embed(&album.tags).map(|mut embedding| {
+            embedding.resize(EMBEDDING_DIMENSIONS, 0.0);
+            embedding
+          });
+          (album.file_name, embedding)
+        })
+        .collect::<Vec<_>>();
+      Ok::<_, anyhow::Error>((model, embeddings))
+    })
+    .await??;
+    self.album_tag_embedding_repository.replace(&model).await?;
+    Ok(AlbumTagEmbeddingRetraining {
+      tag_count: model.tags.len(),
+      embeddings,
+    })
+  }
+}
+
+#[async_trait]
+impl AlbumEmbeddingProvider for TagAlbumEmbeddingProvider {
+  fn name(&self) -> &str {
+    "tags"
+  }
+
+  #[tracing::instrument(name = "TagAlbumEmbeddingProvider::generate", skip(self, _reviews))]
+  async fn generate(
+    &self,
+    album: &AlbumReadModel,
+    _reviews: &[AlbumReadModelReview],
+  ) -> Result<Vec<(&str, Vec<f32>)>> {
+    let tags = AlbumTags::from(album);
+    let model = self
+      .album_tag_embedding_repository
+      .find(
+        tags
+          .weighted_tags()
+          .into_iter()
+          .map(|(tag, _)| tag)
+          .collect(),
+      )
+      .await?;
+    Ok(match model.embed(&tags) {
+      Some(mut embedding) => {
+        embedding.resize(EMBEDDING_DIMENSIONS, 0.0);
+        vec![(TAG_EMBEDDING_KEY, embedding)]
+      }
+      None => vec![],
+    })
+  }
+}
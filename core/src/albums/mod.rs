@@ -1,9 +1,15 @@
+pub mod album_duplicate_override_repository;
+pub mod album_duplicate_rules;
 pub mod album_event_subscribers;
 pub mod album_interactor;
 pub mod album_read_model;
 pub mod album_repository;
+pub mod album_search_expression;
 pub mod album_search_index;
 pub mod album_service;
-mod embedding_provider;
+pub mod album_tag_embedding;
+pub mod album_tag_embedding_repository;
+pub mod embedding_provider;
 pub mod redis_album_search_index;
 pub mod sqlite_album_repository;
+pub mod sqlite_album_search_index;
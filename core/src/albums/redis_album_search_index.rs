@@ -1,12 +1,17 @@
 use super::{
   album_read_model::{
-    AlbumReadModel, AlbumReadModelArtist, AlbumReadModelBuilder, AlbumReadModelCredit,
-    AlbumReadModelTrack,
+    AlbumReadModel, AlbumReadModelArtist, AlbumReadModelBuilder, AlbumReadModelChartPosition,
+    AlbumReadModelCredit, AlbumReadModelExternalLink, AlbumReadModelLabel, AlbumReadModelTrack,
+  },
+  album_repository::{AlbumRepository, ItemAndCount},
+  album_search_expression::{
+    AlbumSearchExpression, AlbumSearchNumericField, AlbumSearchTagField, NumericBound,
   },
-  album_repository::ItemAndCount,
   album_search_index::{
-    embedding_to_bytes, AlbumEmbedding, AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchIndex,
-    AlbumSearchQuery, AlbumSearchResult, SearchPagination,
+    embedding_to_bytes, rank_facet_values, AlbumEmbedding, AlbumEmbeddingSimilarirtySearchQuery,
+    AlbumSearchFacet, AlbumSearchFacetQuery, AlbumSearchFacetResult, AlbumSearchIndex,
+    AlbumSearchQuery, AlbumSearchResult, AlbumSearchSortKey, SearchPagination,
+    EMBEDDING_DIMENSIONS,
   },
 };
 use crate::{
@@ -20,14 +25,14 @@ use rustis::{
   bb8::Pool,
   client::PooledClientManager,
   commands::{
-    FtCreateOptions, FtFieldSchema, FtFieldType, FtFlatVectorFieldAttributes, FtIndexDataType,
-    FtSearchOptions, FtSearchReturnAttribute, FtVectorDistanceMetric, FtVectorFieldAlgorithm,
-    FtVectorType, GenericCommands, JsonCommands, JsonGetOptions, SearchCommands, SetCondition,
-    SortOrder,
+    FtAggregateOptions, FtCreateOptions, FtFieldSchema, FtFieldType, FtFlatVectorFieldAttributes,
+    FtIndexDataType, FtReducer, FtSearchOptions, FtSearchReturnAttribute, FtVectorDistanceMetric,
+    FtVectorFieldAlgorithm, FtVectorType, GenericCommands, JsonCommands, JsonGetOptions,
+    SearchCommands, SetCondition, SortOrder,
   },
 };
 use serde_derive::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{collections::HashMap, fmt::Display, sync::Arc};
 use tracing::{info, instrument};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
@@ -58,6 +63,10 @@ pub struct RedisAlbumReadModel {
   #[serde(default)]
   pub credit_tag_count: u32,
   #[serde(default)]
+  pub credit_roles: Vec<String>,
+  #[serde(default)]
+  pub track_count: u32,
+  #[serde(default)]
   pub duplicate_of: Option<FileName>,
   #[serde(default)]
   pub is_duplicate: u8,
@@ -67,6 +76,20 @@ pub struct RedisAlbumReadModel {
   pub name_tag: String, // redisearch doesn't support exact matching on text fields, so we need to store a tag for exact matching
   #[serde(default)]
   pub cover_image_url: Option<String>,
+  #[serde(default)]
+  pub external_links: Vec<AlbumReadModelExternalLink>,
+  #[serde(default)]
+  pub release_type: Option<String>,
+  #[serde(default)]
+  pub labels: Vec<AlbumReadModelLabel>,
+  #[serde(default)]
+  pub chart_positions: Vec<AlbumReadModelChartPosition>,
+  #[serde(default)]
+  pub total_duration_seconds: Option<u32>,
+  #[serde(default)]
+  pub release_date_ordinal: Option<i32>, // days since the common era, as release dates are stored as strings
+  #[serde(default)]
+  pub bayesian_rating: f32,
 }
 
 impl Into<AlbumReadModel> for RedisAlbumReadModel {
@@ -87,6 +110,11 @@ impl Into<AlbumReadModel> for RedisAlbumReadModel {
       duplicate_of: self.duplicate_of,
       duplicates: self.duplicates,
       cover_image_url: self.cover_image_url,
+      external_links: self.external_links,
+      release_type: self.release_type,
+      labels: self.labels,
+      chart_positions: self.chart_positions,
+      total_duration_seconds: self.total_duration_seconds,
     }
   }
 }
@@ -100,8 +128,18 @@ impl Into<RedisAlbumReadModel> for AlbumReadModel {
     let language_count = self.languages.len() as u32;
     let credit_tags = self.credit_tags();
     let credit_tag_count = credit_tags.len() as u32;
+    let mut credit_roles = self
+      .credits
+      .iter()
+      .flat_map(|credit| credit.roles.clone())
+      .collect::<Vec<String>>();
+    credit_roles.sort();
+    credit_roles.dedup();
+    let track_count = self.tracks.len() as u32;
     let release_year = self.release_date.map(|d| d.year() as u32);
     let is_duplicate = if self.duplicate_of.is_some() { 1 } else { 0 };
+    let release_date_ordinal = self.release_date.map(|d| d.num_days_from_ce());
+    let bayesian_rating = self.bayesian_rating();
 
     RedisAlbumReadModel {
       name_tag: self.name.clone(),
@@ -125,10 +163,19 @@ impl Into<RedisAlbumReadModel> for AlbumReadModel {
       credits: self.credits,
       credit_tags,
       credit_tag_count,
+      credit_roles,
+      track_count,
       duplicate_of: self.duplicate_of,
       duplicates: self.duplicates,
       is_duplicate,
       cover_image_url: self.cover_image_url,
+      external_links: self.external_links,
+      release_type: self.release_type,
+      labels: self.labels,
+      chart_positions: self.chart_positions,
+      total_duration_seconds: self.total_duration_seconds,
+      release_date_ordinal,
+      bayesian_rating,
     }
   }
 }
@@ -189,7 +236,7 @@ fn get_min_num_query(tag: &str, min: Option<usize>) -> String {
   }
 }
 
-fn get_num_range_query(tag: &str, min: Option<u32>, max: Option<u32>) -> String {
+fn get_num_range_query<T: Display>(tag: &str, min: Option<T>, max: Option<T>) -> String {
   match (min, max) {
     (Some(min), Some(max)) => format!("{}:[{}, {}] ", tag, min, max),
     (Some(min), None) => format!("{}:[{}, +inf] ", tag, min),
@@ -198,6 +245,86 @@ fn get_num_range_query(tag: &str, min: Option<u32>, max: Option<u32>) -> String
   }
 }
 
+fn get_tag_field_attributes(field: AlbumSearchTagField) -> &'static [&'static str] {
+  match field {
+    AlbumSearchTagField::Genre => &["@primary_genre", "@secondary_genre"],
+    AlbumSearchTagField::PrimaryGenre => &["@primary_genre"],
+    AlbumSearchTagField::SecondaryGenre => &["@secondary_genre"],
+    AlbumSearchTagField::Descriptor => &["@descriptor"],
+    AlbumSearchTagField::Language => &["@language"],
+    AlbumSearchTagField::Artist => &["@artist_file_name"],
+    AlbumSearchTagField::Label => &["@label"],
+    AlbumSearchTagField::ReleaseType => &["@release_type"],
+    AlbumSearchTagField::CreditedArtist => &["@credit_artist_file_name"],
+    AlbumSearchTagField::CreditRole => &["@credit_role"],
+  }
+}
+
+fn get_numeric_field_attribute(field: AlbumSearchNumericField) -> &'static str {
+  match field {
+    AlbumSearchNumericField::Year => "@release_year",
+    AlbumSearchNumericField::Rating => "@rating",
+    AlbumSearchNumericField::RatingCount => "@rating_count",
+    AlbumSearchNumericField::TrackCount => "@track_count",
+    AlbumSearchNumericField::DurationSeconds => "@total_duration_seconds",
+  }
+}
+
+fn get_bound_query(bound: Option<NumericBound>, unbounded: &str) -> String {
+  match bound {
+    Some(NumericBound {
+      value,
+      inclusive: true,
+    }) => value.to_string(),
+    Some(NumericBound {
+      value,
+      inclusive: false,
+    }) => format!("({}", value),
+    None => unbounded.to_string(),
+  }
+}
+
+impl AlbumSearchExpression {
+  pub fn to_ft_search_query(&self) -> String {
+    match self {
+      AlbumSearchExpression::Text(text) => format!("({})", escape_search_query_text(text)),
+      AlbumSearchExpression::Tag { field, value } => format!(
+        "({})",
+        get_tag_field_attributes(*field)
+          .iter()
+          .map(|attribute| get_tag_query(attribute, &vec![value]).trim().to_string())
+          .collect::<Vec<String>>()
+          .join(" | ")
+      ),
+      AlbumSearchExpression::Range { field, min, max } => format!(
+        "{}:[{} {}]",
+        get_numeric_field_attribute(*field),
+        get_bound_query(*min, "-inf"),
+        get_bound_query(*max, "+inf")
+      ),
+      AlbumSearchExpression::Not(expression) => {
+        format!("-({})", expression.to_ft_search_query())
+      }
+      AlbumSearchExpression::And(expressions) => format!(
+        "({})",
+        expressions
+          .iter()
+          .map(|expression| expression.to_ft_search_query())
+          .collect::<Vec<String>>()
+          .join(" ")
+      ),
+      AlbumSearchExpression::Or(expressions) => format!(
+        "({})",
+        expressions
+          .iter()
+          .map(|expression| expression.to_ft_search_query())
+          .collect::<Vec<String>>()
+          .join(" | ")
+      ),
+    }
+  }
+}
+
 impl AlbumSearchQuery {
   pub fn to_ft_search_query(&self) -> String {
     let mut ft_search_query = String::from("");
@@ -207,6 +334,9 @@ impl AlbumSearchQuery {
     if let Some(exact_name) = &self.exact_name {
       ft_search_query.push_str(&get_tag_query("@name_tag", &vec![exact_name]));
     }
+    if let Some(expression) = &self.expression {
+      ft_search_query.push_str(&format!("{} ", expression.to_ft_search_query()));
+    }
     if !self.include_duplicates.is_some_and(|b| b == true) {
       ft_search_query.push_str(&get_num_range_query("@is_duplicate", Some(0), Some(0)));
     }
@@ -239,6 +369,33 @@ impl AlbumSearchQuery {
     ));
     ft_search_query.push_str(&get_tag_query("@language", &self.include_languages));
     ft_search_query.push_str(&get_tag_query("@descriptor", &self.include_descriptors));
+    ft_search_query.push_str(&get_tag_query("@release_type", &self.include_release_types));
+    ft_search_query.push_str(&get_tag_query("@label", &self.include_labels));
+    ft_search_query.push_str(&get_num_range_query(
+      "@rating",
+      self.min_rating,
+      self.max_rating,
+    ));
+    ft_search_query.push_str(&get_num_range_query(
+      "@rating_count",
+      self.min_rating_count,
+      None,
+    ));
+    ft_search_query.push_str(&get_tag_query(
+      "@credit_artist_file_name",
+      &self.include_credited_artists,
+    ));
+    ft_search_query.push_str(&get_tag_query("@credit_role", &self.include_credit_roles));
+    ft_search_query.push_str(&get_num_range_query(
+      "@track_count",
+      self.min_track_count,
+      self.max_track_count,
+    ));
+    ft_search_query.push_str(&get_num_range_query(
+      "@total_duration_seconds",
+      self.min_duration_seconds,
+      self.max_duration_seconds,
+    ));
     ft_search_query.push_str(&get_tag_query("-@artist_file_name", &self.exclude_artists));
     ft_search_query.push_str(&get_tag_query("-@file_name", &self.exclude_file_names));
     ft_search_query.push_str(&get_tag_query(
@@ -250,6 +407,12 @@ impl AlbumSearchQuery {
       &self.exclude_secondary_genres,
     ));
     ft_search_query.push_str(&get_tag_query("-@language", &self.exclude_languages));
+    ft_search_query.push_str(&get_tag_query(
+      "-@release_type",
+      &self.exclude_release_types,
+    ));
+    ft_search_query.push_str(&get_tag_query("-@label", &self.exclude_labels));
+    ft_search_query.push_str(&get_tag_query("-@descriptor", &self.exclude_descriptors));
     return ft_search_query.trim().to_string();
   }
 }
@@ -265,6 +428,44 @@ impl AlbumEmbeddingSimilarirtySearchQuery {
   }
 }
 
+fn get_facet_attribute(facet: AlbumSearchFacet) -> &'static str {
+  match facet {
+    AlbumSearchFacet::PrimaryGenre => "@primary_genre",
+    AlbumSearchFacet::SecondaryGenre => "@secondary_genre",
+    AlbumSearchFacet::Descriptor => "@descriptor",
+    AlbumSearchFacet::Language => "@language",
+    AlbumSearchFacet::Decade => "@release_year",
+    AlbumSearchFacet::Artist => "@artist_file_name",
+  }
+}
+
+/**
+ * Decades are not indexed, so release years are grouped into decades after aggregation.
+ */
+fn group_years_by_decade(years: Vec<ItemAndCount>) -> Vec<ItemAndCount> {
+  let mut decades = HashMap::<String, u32>::new();
+  for item in years {
+    if let Ok(year) = item.name.parse::<u32>() {
+      *decades.entry((year / 10 * 10).to_string()).or_default() += item.count;
+    }
+  }
+  decades
+    .into_iter()
+    .map(|(name, count)| ItemAndCount { name, count })
+    .collect()
+}
+
+fn get_sort_attribute(key: AlbumSearchSortKey) -> &'static str {
+  match key {
+    AlbumSearchSortKey::Rating => "rating",
+    AlbumSearchSortKey::RatingCount => "rating_count",
+    AlbumSearchSortKey::ReleaseDate => "release_date_ordinal",
+    AlbumSearchSortKey::Name => "name",
+    AlbumSearchSortKey::DescriptorCount => "descriptor_count",
+    AlbumSearchSortKey::BayesianRating => "bayesian_rating",
+  }
+}
+
 pub struct RedisAlbumRepository {
   pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
 }
@@ -274,7 +475,14 @@ pub struct RedisAlbumSearchIndex {
 }
 
 const NAMESPACE: &str = "album";
-const INDEX_NAME: &str = "album_idx";
+/**
+ * RediSearch can't add attributes to an existing index, so the index name is versioned. Bump it
+ * whenever the schema in `setup_index` changes, and move the previous name to
+ * `PREVIOUS_INDEX_NAMES`.
+ */
+const INDEX_NAME: &str = "album_idx_v2";
+const PREVIOUS_INDEX_NAMES: [&str; 1] = ["album_idx"];
+const REINDEX_BATCH_SIZE: usize = 500;
 
 fn redis_key(file_name: &FileName) -> String {
   format!("{}:{}", NAMESPACE, file_name.to_string())
@@ -287,83 +495,133 @@ impl RedisAlbumSearchIndex {
     }
   }
 
-  pub async fn setup_index(&self) -> Result<()> {
+  /**
+   * Creates the index if it does not exist, dropping the indexes of previous schema versions.
+   * Returns whether the index was created, in which case the albums have to be re-indexed.
+   */
+  pub async fn setup_index(&self) -> Result<bool> {
     let connection = self.redis_connection_pool.get().await?;
-    if !does_ft_index_exist(&connection, INDEX_NAME).await {
-      info!("Creating index {}", INDEX_NAME);
-      connection
-        .ft_create(
-          INDEX_NAME,
-          FtCreateOptions::default()
-            .on(FtIndexDataType::Json)
-            .prefix(format!("{}:", NAMESPACE)),
-          [
-            FtFieldSchema::identifier("$.name")
-              .as_attribute("name")
-              .field_type(FtFieldType::Text),
-            FtFieldSchema::identifier("$.file_name")
-              .as_attribute("file_name")
-              .field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("$.artists[*].name")
-              .as_attribute("artist_name")
-              .field_type(FtFieldType::Text),
-            FtFieldSchema::identifier("$.artists[*].file_name")
-              .as_attribute("artist_file_name")
-              .field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("$.rating")
-              .as_attribute("rating")
-              .field_type(FtFieldType::Numeric),
-            FtFieldSchema::identifier("$.rating_count")
-              .as_attribute("rating_count")
-              .field_type(FtFieldType::Numeric),
-            FtFieldSchema::identifier("$.primary_genres.*")
-              .as_attribute("primary_genre")
-              .field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("$.primary_genre_count")
-              .as_attribute("primary_genre_count")
-              .field_type(FtFieldType::Numeric),
-            FtFieldSchema::identifier("$.secondary_genres.*")
-              .as_attribute("secondary_genre")
-              .field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("$.secondary_genre_count")
-              .as_attribute("secondary_genre_count")
-              .field_type(FtFieldType::Numeric),
-            FtFieldSchema::identifier("$.descriptors.*")
-              .as_attribute("descriptor")
-              .field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("$.descriptor_count")
-              .as_attribute("descriptor_count")
-              .field_type(FtFieldType::Numeric),
-            FtFieldSchema::identifier("$.release_year")
-              .as_attribute("release_year")
-              .field_type(FtFieldType::Numeric),
-            FtFieldSchema::identifier("$.languages.*")
-              .as_attribute("language")
-              .field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("$.language_count")
-              .as_attribute("language_count")
-              .field_type(FtFieldType::Numeric),
-            FtFieldSchema::identifier("$.embeddings..key")
-              .as_attribute("embedding_key")
-              .field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("$.embeddings..embedding")
-              .as_attribute("embedding")
-              .field_type(FtFieldType::Vector(Some(FtVectorFieldAlgorithm::Flat(
-                FtFlatVectorFieldAttributes::new(
-                  FtVectorType::Float32,
-                  1536,
-                  FtVectorDistanceMetric::Cosine,
-                ),
-              )))),
-            FtFieldSchema::identifier("$.is_duplicate")
-              .as_attribute("is_duplicate")
-              .field_type(FtFieldType::Numeric),
-            FtFieldSchema::identifier("$.name_tag")
-              .as_attribute("name_tag")
-              .field_type(FtFieldType::Tag),
-          ],
-        )
-        .await?;
+    for previous_index_name in PREVIOUS_INDEX_NAMES {
+      if does_ft_index_exist(&connection, previous_index_name).await {
+        info!("Dropping index {}", previous_index_name);
+        connection.ft_dropindex(previous_index_name, false).await?;
+      }
+    }
+    if does_ft_index_exist(&connection, INDEX_NAME).await {
+      return Ok(false);
+    }
+    info!("Creating index {}", INDEX_NAME);
+    connection
+      .ft_create(
+        INDEX_NAME,
+        FtCreateOptions::default()
+          .on(FtIndexDataType::Json)
+          .prefix(format!("{}:", NAMESPACE)),
+        [
+          FtFieldSchema::identifier("$.name")
+            .as_attribute("name")
+            .field_type(FtFieldType::Text),
+          FtFieldSchema::identifier("$.file_name")
+            .as_attribute("file_name")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.artists[*].name")
+            .as_attribute("artist_name")
+            .field_type(FtFieldType::Text),
+          FtFieldSchema::identifier("$.artists[*].file_name")
+            .as_attribute("artist_file_name")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.rating")
+            .as_attribute("rating")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.rating_count")
+            .as_attribute("rating_count")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.primary_genres.*")
+            .as_attribute("primary_genre")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.primary_genre_count")
+            .as_attribute("primary_genre_count")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.secondary_genres.*")
+            .as_attribute("secondary_genre")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.secondary_genre_count")
+            .as_attribute("secondary_genre_count")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.descriptors.*")
+            .as_attribute("descriptor")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.descriptor_count")
+            .as_attribute("descriptor_count")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.release_year")
+            .as_attribute("release_year")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.languages.*")
+            .as_attribute("language")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.language_count")
+            .as_attribute("language_count")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.embeddings..key")
+            .as_attribute("embedding_key")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.embeddings..embedding")
+            .as_attribute("embedding")
+            .field_type(FtFieldType::Vector(Some(FtVectorFieldAlgorithm::Flat(
+              FtFlatVectorFieldAttributes::new(
+                FtVectorType::Float32,
+                EMBEDDING_DIMENSIONS,
+                FtVectorDistanceMetric::Cosine,
+              ),
+            )))),
+          FtFieldSchema::identifier("$.is_duplicate")
+            .as_attribute("is_duplicate")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.name_tag")
+            .as_attribute("name_tag")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.release_type")
+            .as_attribute("release_type")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.labels[*].name")
+            .as_attribute("label")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.release_date_ordinal")
+            .as_attribute("release_date_ordinal")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.bayesian_rating")
+            .as_attribute("bayesian_rating")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.credits[*].artist.file_name")
+            .as_attribute("credit_artist_file_name")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.credit_roles.*")
+            .as_attribute("credit_role")
+            .field_type(FtFieldType::Tag),
+          FtFieldSchema::identifier("$.track_count")
+            .as_attribute("track_count")
+            .field_type(FtFieldType::Numeric),
+          FtFieldSchema::identifier("$.total_duration_seconds")
+            .as_attribute("total_duration_seconds")
+            .field_type(FtFieldType::Numeric),
+        ],
+      )
+      .await?;
+    Ok(true)
+  }
+
+  /**
+   * Puts every album of the repository, so that documents written before an attribute was added
+   * to the schema get a value for it.
+   */
+  pub async fn reindex(&self, album_repository: &dyn AlbumRepository) -> Result<()> {
+    let file_names = album_repository.get_file_names().await?;
+    info!(count = file_names.len(), "Re-indexing albums");
+    for chunk in file_names.chunks(REINDEX_BATCH_SIZE) {
+      for album in album_repository.find_many(chunk.to_vec()).await? {
+        self.put(album).await?;
+      }
     }
     Ok(())
   }
@@ -455,12 +713,24 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
     let limit = pagination.and_then(|p| p.limit).unwrap_or_else(|| 100000);
     let offset = pagination.and_then(|p| p.offset).unwrap_or_else(|| 0);
 
+    let mut options = FtSearchOptions::default().limit(offset, limit);
+    if let Some(sort) = &query.sort {
+      options = options.sortby(
+        get_sort_attribute(sort.key),
+        if sort.descending {
+          SortOrder::Desc
+        } else {
+          SortOrder::Asc
+        },
+      );
+    }
+
     let connection = self.redis_connection_pool.get().await?;
     let result = connection
       .ft_search(
         INDEX_NAME,
         query.to_ft_search_query(),
-        FtSearchOptions::default().limit(offset, limit)._return([
+        options._return([
           FtSearchReturnAttribute::identifier("$.name"),
           FtSearchReturnAttribute::identifier("$.file_name"),
           FtSearchReturnAttribute::identifier("$.rating"),
@@ -476,6 +746,11 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
           FtSearchReturnAttribute::identifier("$.duplicate_of"),
           FtSearchReturnAttribute::identifier("$.duplicates"),
           FtSearchReturnAttribute::identifier("$.cover_image_url"),
+          FtSearchReturnAttribute::identifier("$.external_links"),
+          FtSearchReturnAttribute::identifier("$.release_type"),
+          FtSearchReturnAttribute::identifier("$.labels"),
+          FtSearchReturnAttribute::identifier("$.chart_positions"),
+          FtSearchReturnAttribute::identifier("$.total_duration_seconds"),
         ]),
       )
       .await?;
@@ -540,6 +815,27 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
               _ => album_builder.cover_image_url(Some(value)),
             };
           }
+          "$.external_links" => {
+            album_builder.external_links(serde_json::from_str(value.as_str())?);
+          }
+          "$.release_type" => {
+            match value.as_str() {
+              "" => album_builder.release_type(None),
+              _ => album_builder.release_type(Some(value)),
+            };
+          }
+          "$.labels" => {
+            album_builder.labels(serde_json::from_str(value.as_str())?);
+          }
+          "$.chart_positions" => {
+            album_builder.chart_positions(serde_json::from_str(value.as_str())?);
+          }
+          "$.total_duration_seconds" => {
+            match value.as_str() {
+              "" => album_builder.total_duration_seconds(None),
+              _ => album_builder.total_duration_seconds(Some(value.parse()?)),
+            };
+          }
           _ => {}
         };
       }
@@ -552,6 +848,46 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
     })
   }
 
+  #[instrument(skip(self))]
+  async fn get_facets(
+    &self,
+    query: &AlbumSearchQuery,
+    facets: &[AlbumSearchFacetQuery],
+  ) -> Result<Vec<AlbumSearchFacetResult>> {
+    let connection = self.redis_connection_pool.get().await?;
+    let ft_search_query = match query.to_ft_search_query() {
+      ft_search_query if ft_search_query.is_empty() => String::from("*"),
+      ft_search_query => ft_search_query,
+    };
+    let mut results = Vec::with_capacity(facets.len());
+    for facet_query in facets {
+      let result = connection
+        .ft_aggregate(
+          INDEX_NAME,
+          ft_search_query.clone(),
+          FtAggregateOptions::default().groupby(
+            get_facet_attribute(facet_query.facet),
+            FtReducer::count().as_name("count"),
+          ),
+        )
+        .await?;
+      let values = result
+        .results
+        .iter()
+        .map(ItemAndCount::try_from)
+        .collect::<Result<Vec<ItemAndCount>>>()?;
+      let values = match facet_query.facet {
+        AlbumSearchFacet::Decade => group_years_by_decade(values),
+        _ => values,
+      };
+      results.push(AlbumSearchFacetResult {
+        facet: facet_query.facet,
+        values: rank_facet_values(values, facet_query.limit),
+      });
+    }
+    Ok(results)
+  }
+
   #[instrument(skip(self))]
   async fn put_embedding(&self, embedding: &AlbumEmbedding) -> Result<()> {
     self.ensure_embeddings_field(&embedding.file_name).await?;
@@ -681,4 +1017,17 @@ impl AlbumSearchIndex for RedisAlbumSearchIndex {
     let result: Vec<String> = connection.ft_tagvals(INDEX_NAME, "embedding_key").await?;
     Ok(result)
   }
+
+  async fn get_file_names(&self) -> Result<Vec<FileName>> {
+    let connection = self.redis_connection_pool.get().await?;
+    let prefix = format!("{}:", NAMESPACE);
+    let keys: Vec<String> = connection.keys(format!("{}*", prefix)).await?;
+    keys
+      .into_iter()
+      .map(|key| {
+        let file_name = key.strip_prefix(&prefix).unwrap_or(&key).to_string();
+        FileName::try_from(file_name)
+      })
+      .collect()
+  }
 }
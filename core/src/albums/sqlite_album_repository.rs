@@ -1,10 +1,14 @@
 use super::{
   album_read_model::{
-    AlbumReadModel, AlbumReadModelArtist, AlbumReadModelCredit, AlbumReadModelTrack,
+    AlbumReadModel, AlbumReadModelArtist, AlbumReadModelChartPosition, AlbumReadModelCredit,
+    AlbumReadModelExternalLink, AlbumReadModelLabel, AlbumReadModelReview, AlbumReadModelTrack,
   },
   album_repository::{AlbumRepository, GenreAggregate, ItemAndCount},
 };
-use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
+use crate::{
+  files::file_metadata::file_name::FileName, parser::parsed_file_data::ExternalLinkSource,
+  sqlite::SqliteConnection,
+};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use chrono::NaiveDate;
@@ -34,6 +38,8 @@ struct AlbumEntity {
   pub rating_count: u32,
   pub release_date: Option<NaiveDate>,
   pub cover_image_url: Option<String>,
+  pub release_type: Option<String>,
+  pub total_duration_seconds: Option<u32>,
 }
 
 impl SqliteAlbumRepository {
@@ -65,7 +71,9 @@ impl SqliteAlbumRepository {
             rating,
             rating_count,
             release_date,
-            cover_image_url
+            cover_image_url,
+            release_type,
+            total_duration_seconds
           FROM albums
           WHERE file_name IN rarray(?)
           ",
@@ -79,11 +87,23 @@ impl SqliteAlbumRepository {
             row.get::<_, u32>(4)?,
             row.get::<_, Option<String>>(5)?,
             row.get::<_, Option<String>>(6)?,
+            row.get::<_, Option<String>>(7)?,
+            row.get::<_, Option<u32>>(8)?,
           ))
         })?;
         let mut result = HashMap::<FileName, AlbumEntity>::new();
         while let Some(Ok(row)) = rows.next() {
-          let (id, file_name, name, rating, rating_count, release_date, cover_image_url) = row;
+          let (
+            id,
+            file_name,
+            name,
+            rating,
+            rating_count,
+            release_date,
+            cover_image_url,
+            release_type,
+            total_duration_seconds,
+          ) = row;
           let file_name = FileName::try_from(file_name.clone()).map_err(|e| {
             error!(message = e.to_string(), "Failed to parse album file name");
             rusqlite::Error::ExecuteReturnedResults
@@ -99,6 +119,8 @@ impl SqliteAlbumRepository {
               release_date: release_date
                 .map(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").unwrap()),
               cover_image_url,
+              release_type,
+              total_duration_seconds,
             },
           );
         }
@@ -314,6 +336,55 @@ impl SqliteAlbumRepository {
       .read()
       .await?
       .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT
+            track_credits.track_id,
+            artists.file_name,
+            artists.name,
+            roles.name
+          FROM track_credits
+          JOIN tracks ON track_credits.track_id = tracks.id
+          JOIN artists ON track_credits.artist_id = artists.id
+          JOIN roles ON track_credits.role_id = roles.id
+          WHERE tracks.album_id IN rarray(?)
+          ",
+        )?;
+        let mut rows = stmt.query_map([Rc::new(album_id_params.clone())], |row| {
+          Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+          ))
+        })?;
+        let mut track_credits = HashMap::<i64, Vec<AlbumReadModelCredit>>::new();
+        while let Some(Ok(row)) = rows.next() {
+          let (track_id, artist_file_name, artist_name, role) = row;
+          let track_entry = track_credits.entry(track_id).or_insert_with(|| Vec::new());
+          let artist_file_name = FileName::try_from(artist_file_name.clone()).map_err(|e| {
+            error!(message = e.to_string(), "Failed to parse artist file name");
+            rusqlite::Error::ExecuteReturnedResults
+          })?;
+          match track_entry
+            .iter_mut()
+            .find(|credit| credit.artist.file_name == artist_file_name)
+          {
+            Some(credit_entry) => {
+              credit_entry.roles.push(role);
+            }
+            None => {
+              track_entry.push(AlbumReadModelCredit {
+                artist: AlbumReadModelArtist {
+                  file_name: artist_file_name,
+                  name: artist_name,
+                },
+                roles: vec![role],
+              });
+            }
+          }
+        }
+
         let mut stmt = conn.prepare(
           "
           SELECT
@@ -321,9 +392,11 @@ impl SqliteAlbumRepository {
             tracks.name,
             tracks.duration_seconds,
             tracks.rating,
-            tracks.position
+            tracks.position,
+            tracks.id
           FROM tracks
           WHERE tracks.album_id IN rarray(?)
+          ORDER BY tracks.id
           ",
         )?;
         let mut rows = stmt.query_map([Rc::new(album_id_params)], |row| {
@@ -333,17 +406,28 @@ impl SqliteAlbumRepository {
             row.get::<_, Option<u32>>(2)?,
             row.get::<_, Option<f32>>(3)?,
             row.get::<_, Option<String>>(4)?,
+            row.get::<_, i64>(5)?,
           ))
         })?;
         let mut result = HashMap::<i64, Vec<AlbumReadModelTrack>>::new();
         while let Some(Ok(row)) = rows.next() {
-          let (album_id, track_name, track_duration_seconds, track_rating, track_position) = row;
+          let (
+            album_id,
+            track_name,
+            track_duration_seconds,
+            track_rating,
+            track_position,
+            track_id,
+          ) = row;
           let album_entry = result.entry(album_id).or_insert_with(|| Vec::new());
           album_entry.push(AlbumReadModelTrack {
             name: track_name,
             duration_seconds: track_duration_seconds,
             rating: track_rating,
             position: track_position,
+            credits: track_credits
+              .remove(&track_id)
+              .unwrap_or_else(|| Vec::new()),
           });
         }
         Ok(result)
@@ -355,6 +439,166 @@ impl SqliteAlbumRepository {
       })?
   }
 
+  #[instrument(skip_all, fields(count = album_ids.len()))]
+  async fn find_album_external_links(
+    &self,
+    album_ids: Vec<i64>,
+  ) -> Result<HashMap<i64, Vec<AlbumReadModelExternalLink>>> {
+    let album_id_params = album_ids
+      .into_iter()
+      .map(|f| Value::from(f))
+      .collect::<Vec<Value>>();
+
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT
+            album_external_links.album_id,
+            album_external_links.source,
+            album_external_links.external_id,
+            album_external_links.url
+          FROM album_external_links
+          WHERE album_external_links.album_id IN rarray(?)
+          ORDER BY album_external_links.source, album_external_links.external_id
+          ",
+        )?;
+        let mut rows = stmt.query_map([Rc::new(album_id_params)], |row| {
+          Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+          ))
+        })?;
+        let mut result = HashMap::<i64, Vec<AlbumReadModelExternalLink>>::new();
+        while let Some(Ok(row)) = rows.next() {
+          let (album_id, source, id, url) = row;
+          match source.parse::<ExternalLinkSource>() {
+            Ok(source) => {
+              let album_entry = result.entry(album_id).or_insert_with(|| Vec::new());
+              album_entry.push(AlbumReadModelExternalLink { source, id, url });
+            }
+            Err(e) => {
+              warn!(
+                source = source.as_str(),
+                error = e.to_string(),
+                "Skipping album external link with unknown source"
+              );
+            }
+          }
+        }
+        Ok(result)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find album external links"
+        );
+        anyhow!("Failed to find album external links")
+      })?
+  }
+
+  #[instrument(skip_all, fields(count = album_ids.len()))]
+  async fn find_album_labels(
+    &self,
+    album_ids: Vec<i64>,
+  ) -> Result<HashMap<i64, Vec<AlbumReadModelLabel>>> {
+    let album_id_params = album_ids
+      .into_iter()
+      .map(|f| Value::from(f))
+      .collect::<Vec<Value>>();
+
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT album_labels.album_id, labels.name, album_labels.catalog_number
+          FROM album_labels
+          JOIN labels ON album_labels.label_id = labels.id
+          WHERE album_labels.album_id IN rarray(?)
+          ",
+        )?;
+        let mut rows = stmt.query_map([Rc::new(album_id_params)], |row| {
+          Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+          ))
+        })?;
+        let mut result = HashMap::<i64, Vec<AlbumReadModelLabel>>::new();
+        while let Some(Ok(row)) = rows.next() {
+          let (album_id, name, catalog_number) = row;
+          let album_entry = result.entry(album_id).or_insert_with(|| Vec::new());
+          album_entry.push(AlbumReadModelLabel {
+            name,
+            catalog_number,
+          });
+        }
+        Ok(result)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find album labels");
+        anyhow!("Failed to find album labels")
+      })?
+  }
+
+  #[instrument(skip_all, fields(count = album_ids.len()))]
+  async fn find_album_chart_positions(
+    &self,
+    album_ids: Vec<i64>,
+  ) -> Result<HashMap<i64, Vec<AlbumReadModelChartPosition>>> {
+    let album_id_params = album_ids
+      .into_iter()
+      .map(|f| Value::from(f))
+      .collect::<Vec<Value>>();
+
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT album_id, chart, position
+          FROM album_chart_positions
+          WHERE album_id IN rarray(?)
+          ORDER BY position
+          ",
+        )?;
+        let mut rows = stmt.query_map([Rc::new(album_id_params)], |row| {
+          Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, u32>(2)?,
+          ))
+        })?;
+        let mut result = HashMap::<i64, Vec<AlbumReadModelChartPosition>>::new();
+        while let Some(Ok(row)) = rows.next() {
+          let (album_id, chart, position) = row;
+          let album_entry = result.entry(album_id).or_insert_with(|| Vec::new());
+          album_entry.push(AlbumReadModelChartPosition { chart, position });
+        }
+        Ok(result)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find album chart positions"
+        );
+        anyhow!("Failed to find album chart positions")
+      })?
+  }
+
   #[instrument(skip_all, fields(count = album_ids.len()))]
   async fn find_album_credits(
     &self,
@@ -520,14 +764,16 @@ impl AlbumRepository for SqliteAlbumRepository {
         let tx = conn.transaction()?;
         tx.execute(
           "
-          INSERT INTO albums (file_name, name, rating, rating_count, release_date, cover_image_url)
-          VALUES (?, ?, ?, ?, ?, ?)
+          INSERT INTO albums (file_name, name, rating, rating_count, release_date, cover_image_url, release_type, total_duration_seconds)
+          VALUES (?, ?, ?, ?, ?, ?, ?, ?)
           ON CONFLICT (file_name) DO UPDATE SET
             name = excluded.name,
             rating = excluded.rating,
             rating_count = excluded.rating_count,
             release_date = excluded.release_date,
-            cover_image_url = excluded.cover_image_url
+            cover_image_url = excluded.cover_image_url,
+            release_type = excluded.release_type,
+            total_duration_seconds = excluded.total_duration_seconds
           ",
           params![
             album.file_name.to_string(),
@@ -535,7 +781,9 @@ impl AlbumRepository for SqliteAlbumRepository {
             album.rating,
             album.rating_count,
             album.release_date,
-            album.cover_image_url
+            album.cover_image_url,
+            album.release_type,
+            album.total_duration_seconds
           ],
         )?;
         let album_id: i64 = tx.query_row(
@@ -674,10 +922,11 @@ impl AlbumRepository for SqliteAlbumRepository {
           params![album_id],
         )?;
         for track in album.tracks {
-          tx.execute(
+          let track_id: i64 = tx.query_row(
             "
             INSERT INTO tracks (album_id, name, duration_seconds, rating, position)
             VALUES (?, ?, ?, ?, ?)
+            RETURNING id
             ",
             params![
               album_id,
@@ -686,6 +935,85 @@ impl AlbumRepository for SqliteAlbumRepository {
               track.rating,
               track.position,
             ],
+            |row| row.get(0),
+          )?;
+          for credit in track.credits {
+            let artist_id: i64 = tx.query_row(
+              "
+              INSERT INTO artists (file_name, name) 
+              VALUES (?, ?) 
+              ON CONFLICT(file_name) DO UPDATE SET name = excluded.name
+              RETURNING id
+              ",
+              params![credit.artist.file_name.to_string(), credit.artist.name],
+              |row| row.get(0),
+            )?;
+            for role in credit.roles {
+              let role_id: i64 = tx.query_row(
+                "
+                INSERT INTO roles (name) 
+                VALUES (?) 
+                ON CONFLICT(name) DO UPDATE SET name = excluded.name
+                RETURNING id
+                ",
+                params![role],
+                |row| row.get(0),
+              )?;
+              tx.execute(
+                "
+                INSERT INTO track_credits (track_id, artist_id, role_id)
+                VALUES (?, ?, ?)
+                ON CONFLICT DO NOTHING
+                ",
+                params![track_id, artist_id, role_id],
+              )?;
+            }
+          }
+        }
+
+        // labels
+        tx.execute(
+          "
+          DELETE FROM album_labels WHERE album_id = ?
+          ",
+          params![album_id],
+        )?;
+        for label in album.labels {
+          let label_id: i64 = tx.query_row(
+            "
+            INSERT INTO labels (name)
+            VALUES (?)
+            ON CONFLICT(name) DO UPDATE SET name = excluded.name
+            RETURNING id
+            ",
+            params![label.name],
+            |row| row.get(0),
+          )?;
+          tx.execute(
+            "
+            INSERT INTO album_labels (album_id, label_id, catalog_number)
+            VALUES (?, ?, ?)
+            ON CONFLICT (album_id, label_id) DO UPDATE SET catalog_number = excluded.catalog_number
+            ",
+            params![album_id, label_id, label.catalog_number],
+          )?;
+        }
+
+        // chart positions
+        tx.execute(
+          "
+          DELETE FROM album_chart_positions WHERE album_id = ?
+          ",
+          params![album_id],
+        )?;
+        for chart_position in album.chart_positions {
+          tx.execute(
+            "
+            INSERT INTO album_chart_positions (album_id, chart, position)
+            VALUES (?, ?, ?)
+            ON CONFLICT (album_id, chart) DO UPDATE SET position = excluded.position
+            ",
+            params![album_id, chart_position.chart, chart_position.position],
           )?;
         }
 
@@ -725,6 +1053,24 @@ impl AlbumRepository for SqliteAlbumRepository {
           )?;
         }
 
+        // external links
+        tx.execute(
+          "
+          DELETE FROM album_external_links WHERE album_id = ?
+          ",
+          params![album_id],
+        )?;
+        for link in album.external_links {
+          tx.execute(
+            "
+            INSERT INTO album_external_links (album_id, source, external_id, url)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (album_id, source, external_id) DO UPDATE SET url = excluded.url
+            ",
+            params![album_id, link.source.to_string(), link.id, link.url],
+          )?;
+        }
+
         // credits
         tx.execute(
           "
@@ -812,6 +1158,7 @@ impl AlbumRepository for SqliteAlbumRepository {
                 "
                 INSERT INTO album_duplicates (original_album_id, duplicate_album_id)
                 VALUES (?, ?)
+                ON CONFLICT (duplicate_album_id) DO UPDATE SET original_album_id = excluded.original_album_id
                 ",
                 params![album_id, duplicate_id],
               )?;
@@ -890,6 +1237,102 @@ impl AlbumRepository for SqliteAlbumRepository {
       })?
   }
 
+  async fn delete_all(&self) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(|conn| {
+        conn.execute("DELETE FROM albums", [])?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete all albums");
+        anyhow!("Failed to delete all albums")
+      })?
+  }
+
+  #[instrument(skip_all, fields(file_name = file_name.to_string(), count = reviews.len()))]
+  async fn put_reviews(
+    &self,
+    file_name: &FileName,
+    reviews: Vec<AlbumReadModelReview>,
+  ) -> Result<()> {
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        let album_id: i64 = tx.query_row(
+          "SELECT id FROM albums WHERE file_name = ?",
+          params![file_name],
+          |row| row.get(0),
+        )?;
+        tx.execute("DELETE FROM reviews WHERE album_id = ?", params![album_id])?;
+        for review in reviews {
+          tx.execute(
+            "
+            INSERT INTO reviews (album_id, author, rating, date, text)
+            VALUES (?, ?, ?, ?, ?)
+            ",
+            params![
+              album_id,
+              review.author,
+              review.rating,
+              review.date,
+              review.text
+            ],
+          )?;
+        }
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put album reviews");
+        anyhow!("Failed to put album reviews")
+      })?
+  }
+
+  #[instrument(skip_all, fields(file_name))]
+  async fn find_reviews(&self, file_name: &FileName) -> Result<Vec<AlbumReadModelReview>> {
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT reviews.author, reviews.rating, reviews.date, reviews.text
+          FROM reviews
+          JOIN albums ON albums.id = reviews.album_id
+          WHERE albums.file_name = ?
+          ORDER BY reviews.id ASC
+          ",
+        )?;
+        let reviews = stmt
+          .query_map([file_name], |row| {
+            Ok(AlbumReadModelReview {
+              author: row.get::<_, String>(0)?,
+              rating: row.get::<_, Option<f32>>(1)?,
+              date: row.get::<_, Option<NaiveDate>>(2)?,
+              text: row.get::<_, String>(3)?,
+            })
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(reviews)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find album reviews");
+        anyhow!("Failed to find album reviews")
+      })?
+  }
+
   #[instrument(skip_all, fields(count = file_names.len()))]
   async fn find_many(&self, file_names: Vec<FileName>) -> Result<Vec<AlbumReadModel>> {
     let album_entities = self.find_album_entities(file_names.clone()).await?;
@@ -905,6 +1348,9 @@ impl AlbumRepository for SqliteAlbumRepository {
       mut album_tracks,
       mut album_credits,
       mut album_duplicates,
+      mut album_external_links,
+      mut album_labels,
+      mut album_chart_positions,
     ) = try_join!(
       self.find_album_artists(album_ids.clone()),
       self.find_album_genres(album_ids.clone()),
@@ -913,6 +1359,9 @@ impl AlbumRepository for SqliteAlbumRepository {
       self.find_album_tracks(album_ids.clone()),
       self.find_album_credits(album_ids.clone()),
       self.find_album_duplication(album_ids.clone()),
+      self.find_album_external_links(album_ids.clone()),
+      self.find_album_labels(album_ids.clone()),
+      self.find_album_chart_positions(album_ids.clone()),
     )?;
     let mut result = Vec::<AlbumReadModel>::new();
     for file_name in file_names {
@@ -934,6 +1383,13 @@ impl AlbumRepository for SqliteAlbumRepository {
         let credits = album_credits
           .remove(&album_id)
           .unwrap_or_else(|| Vec::new());
+        let external_links = album_external_links
+          .remove(&album_id)
+          .unwrap_or_else(|| Vec::new());
+        let labels = album_labels.remove(&album_id).unwrap_or_else(|| Vec::new());
+        let chart_positions = album_chart_positions
+          .remove(&album_id)
+          .unwrap_or_else(|| Vec::new());
         let (duplicate_of, duplicates) = match album_duplicates
           .remove(&album_id)
           .unwrap_or_else(|| AlbumDuplication::Duplicates(Vec::new()))
@@ -948,6 +1404,8 @@ impl AlbumRepository for SqliteAlbumRepository {
           rating_count: album_entity.rating_count,
           release_date: album_entity.release_date,
           cover_image_url: album_entity.cover_image_url.clone(),
+          release_type: album_entity.release_type.clone(),
+          total_duration_seconds: album_entity.total_duration_seconds,
           duplicate_of,
           duplicates,
           artists,
@@ -957,12 +1415,66 @@ impl AlbumRepository for SqliteAlbumRepository {
           languages,
           tracks,
           credits,
+          external_links,
+          labels,
+          chart_positions,
         });
       }
     }
     Ok(result)
   }
 
+  #[instrument(skip_all, fields(source = source.to_string(), count = ids.len()))]
+  async fn find_file_names_by_external_ids(
+    &self,
+    source: ExternalLinkSource,
+    ids: Vec<String>,
+  ) -> Result<HashMap<String, FileName>> {
+    let source = source.to_string();
+    let id_params = ids
+      .into_iter()
+      .map(|id| Value::from(id))
+      .collect::<Vec<Value>>();
+
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT album_external_links.external_id, albums.file_name
+          FROM album_external_links
+          JOIN albums ON albums.id = album_external_links.album_id
+          WHERE album_external_links.source = ? AND album_external_links.external_id IN rarray(?)
+          ",
+        )?;
+        let mut rows = stmt.query_map(params![source, Rc::new(id_params)], |row| {
+          Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut result = HashMap::<String, FileName>::new();
+        while let Some(Ok((id, file_name))) = rows.next() {
+          match FileName::try_from(file_name) {
+            Ok(file_name) => {
+              result.entry(id).or_insert(file_name);
+            }
+            Err(e) => {
+              warn!(error = e.to_string(), "Invalid album file name");
+            }
+          }
+        }
+        Ok(result)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find albums by external IDs"
+        );
+        anyhow!("Failed to find albums by external IDs")
+      })?
+  }
+
   #[instrument(skip_all, fields(count = artist_file_name.len()))]
   async fn find_artist_albums(
     &self,
@@ -1167,6 +1679,28 @@ impl AlbumRepository for SqliteAlbumRepository {
       })?
   }
 
+  async fn get_file_names(&self) -> Result<Vec<FileName>> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut stmt = conn.prepare("SELECT file_name FROM albums")?;
+        let file_names = stmt
+          .query_map([], |row| row.get::<_, String>(0))?
+          .collect::<Result<Vec<String>, _>>()?
+          .into_iter()
+          .map(FileName::try_from)
+          .collect::<Result<Vec<FileName>>>()?;
+        Ok(file_names)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get album file names");
+        anyhow!("Failed to get album file names")
+      })?
+  }
+
   #[instrument(skip_all)]
   async fn get_album_count(&self) -> Result<u32> {
     self
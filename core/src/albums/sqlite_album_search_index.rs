@@ -0,0 +1,1023 @@
+use super::{
+  album_read_model::{AlbumReadModel, BAYESIAN_RATING_PRIOR_COUNT, BAYESIAN_RATING_PRIOR_MEAN},
+  album_repository::{AlbumRepository, ItemAndCount},
+  album_search_expression::{
+    AlbumSearchExpression, AlbumSearchNumericField, AlbumSearchTagField, NumericBound,
+  },
+  album_search_index::{
+    embedding_from_bytes, embedding_to_bytes, AlbumEmbedding, AlbumEmbeddingSimilarirtySearchQuery,
+    AlbumSearchFacet, AlbumSearchFacetQuery, AlbumSearchFacetResult, AlbumSearchIndex,
+    AlbumSearchQuery, AlbumSearchResult, AlbumSearchSort, AlbumSearchSortKey, SearchPagination,
+  },
+  sqlite_album_repository::SqliteAlbumRepository,
+};
+use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rusqlite::{params, params_from_iter, types::Value, ToSql};
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+use tracing::{error, info, instrument};
+use unidecode::unidecode;
+
+const REINDEX_BATCH_SIZE: usize = 500;
+
+/**
+ * Query parameters are built outside of the connection, and only turned into `rarray` values
+ * (which are not `Send`) once the query runs.
+ */
+enum SqlParam {
+  Value(Value),
+  Array(Vec<Value>),
+}
+
+impl SqlParam {
+  fn into_boxed(self) -> Box<dyn ToSql> {
+    match self {
+      SqlParam::Value(value) => Box::new(value),
+      SqlParam::Array(values) => Box::new(Rc::new(values)),
+    }
+  }
+}
+
+fn to_values<T: ToString>(items: &[T]) -> Vec<Value> {
+  items
+    .iter()
+    .map(|item| Value::from(item.to_string()))
+    .collect()
+}
+
+/**
+ * Turns free text into an FTS5 query matching all of its words, in any of the indexed columns.
+ */
+fn to_match_query(text: &str) -> Option<String> {
+  let terms = unidecode(text)
+    .split(|c: char| !c.is_ascii_alphanumeric())
+    .filter(|term| !term.is_empty())
+    .map(|term| format!("\"{}\"", term))
+    .collect::<Vec<String>>();
+  if terms.is_empty() {
+    None
+  } else {
+    Some(terms.join(" "))
+  }
+}
+
+const FILE_NAME_FILTER: &str = "albums.file_name IN rarray(?)";
+const ARTIST_FILTER: &str = "
+  albums.id IN (
+    SELECT album_artists.album_id
+    FROM album_artists
+    JOIN artists ON artists.id = album_artists.artist_id
+    WHERE artists.file_name IN rarray(?)
+  )";
+const PRIMARY_GENRE_FILTER: &str = "
+  albums.id IN (
+    SELECT album_genres.album_id
+    FROM album_genres
+    JOIN genres ON genres.id = album_genres.genre_id
+    WHERE album_genres.is_primary = 1 AND genres.name IN rarray(?)
+  )";
+const SECONDARY_GENRE_FILTER: &str = "
+  albums.id IN (
+    SELECT album_genres.album_id
+    FROM album_genres
+    JOIN genres ON genres.id = album_genres.genre_id
+    WHERE album_genres.is_primary = 0 AND genres.name IN rarray(?)
+  )";
+const LANGUAGE_FILTER: &str = "
+  albums.id IN (
+    SELECT album_languages.album_id
+    FROM album_languages
+    JOIN languages ON languages.id = album_languages.language_id
+    WHERE languages.name IN rarray(?)
+  )";
+const DESCRIPTOR_FILTER: &str = "
+  albums.id IN (
+    SELECT album_descriptors.album_id
+    FROM album_descriptors
+    JOIN descriptors ON descriptors.id = album_descriptors.descriptor_id
+    WHERE descriptors.name IN rarray(?)
+  )";
+const LABEL_FILTER: &str = "
+  albums.id IN (
+    SELECT album_labels.album_id
+    FROM album_labels
+    JOIN labels ON labels.id = album_labels.label_id
+    WHERE labels.name IN rarray(?)
+  )";
+const CREDITED_ARTIST_FILTER: &str = "
+  albums.id IN (
+    SELECT credits.album_id
+    FROM credits
+    JOIN artists ON artists.id = credits.artist_id
+    WHERE artists.file_name IN rarray(?)
+  )";
+const CREDIT_ROLE_FILTER: &str = "
+  albums.id IN (
+    SELECT credits.album_id
+    FROM credits
+    JOIN credit_roles ON credit_roles.credit_id = credits.id
+    JOIN roles ON roles.id = credit_roles.role_id
+    WHERE roles.name COLLATE NOCASE IN rarray(?)
+  )";
+const TRACK_COUNT: &str = "(SELECT COUNT(*) FROM tracks WHERE tracks.album_id = albums.id)";
+const RELEASE_TYPE_FILTER: &str = "COALESCE(albums.release_type, '') IN rarray(?)";
+
+fn get_tag_field_filters(field: AlbumSearchTagField) -> &'static [&'static str] {
+  match field {
+    AlbumSearchTagField::Genre => &[PRIMARY_GENRE_FILTER, SECONDARY_GENRE_FILTER],
+    AlbumSearchTagField::PrimaryGenre => &[PRIMARY_GENRE_FILTER],
+    AlbumSearchTagField::SecondaryGenre => &[SECONDARY_GENRE_FILTER],
+    AlbumSearchTagField::Descriptor => &[DESCRIPTOR_FILTER],
+    AlbumSearchTagField::Language => &[LANGUAGE_FILTER],
+    AlbumSearchTagField::Artist => &[ARTIST_FILTER],
+    AlbumSearchTagField::Label => &[LABEL_FILTER],
+    AlbumSearchTagField::ReleaseType => &[RELEASE_TYPE_FILTER],
+    AlbumSearchTagField::CreditedArtist => &[CREDITED_ARTIST_FILTER],
+    AlbumSearchTagField::CreditRole => &[CREDIT_ROLE_FILTER],
+  }
+}
+
+fn get_numeric_field_sql(field: AlbumSearchNumericField) -> &'static str {
+  match field {
+    AlbumSearchNumericField::Year => "CAST(substr(albums.release_date, 1, 4) AS INTEGER)",
+    AlbumSearchNumericField::Rating => "albums.rating",
+    AlbumSearchNumericField::RatingCount => "albums.rating_count",
+    AlbumSearchNumericField::TrackCount => TRACK_COUNT,
+    AlbumSearchNumericField::DurationSeconds => "albums.total_duration_seconds",
+  }
+}
+
+fn get_bound_sql(
+  expression: &str,
+  bound: &NumericBound,
+  inclusive_operator: &str,
+  exclusive_operator: &str,
+  params: &mut Vec<SqlParam>,
+) -> String {
+  params.push(SqlParam::Value(Value::from(bound.value)));
+  let operator = if bound.inclusive {
+    inclusive_operator
+  } else {
+    exclusive_operator
+  };
+  format!("{} {} ?", expression, operator)
+}
+
+fn get_expression_sql(expression: &AlbumSearchExpression, params: &mut Vec<SqlParam>) -> String {
+  match expression {
+    AlbumSearchExpression::Text(text) => match to_match_query(text) {
+      Some(match_query) => {
+        params.push(SqlParam::Value(Value::from(match_query)));
+        "albums.file_name IN (SELECT file_name FROM album_search WHERE album_search MATCH ?)"
+          .to_string()
+      }
+      None => "1".to_string(),
+    },
+    AlbumSearchExpression::Tag { field, value } => {
+      let filters = get_tag_field_filters(*field);
+      for _ in filters {
+        params.push(SqlParam::Array(vec![Value::from(value.clone())]));
+      }
+      format!("({})", filters.join(" OR "))
+    }
+    AlbumSearchExpression::Range { field, min, max } => {
+      let column = get_numeric_field_sql(*field);
+      let mut clauses = vec![];
+      if let Some(min) = min {
+        clauses.push(get_bound_sql(column, min, ">=", ">", params));
+      }
+      if let Some(max) = max {
+        clauses.push(get_bound_sql(column, max, "<=", "<", params));
+      }
+      format!("({})", clauses.join(" AND "))
+    }
+    AlbumSearchExpression::Not(expression) => {
+      format!("NOT ({})", get_expression_sql(expression, params))
+    }
+    AlbumSearchExpression::And(expressions) => format!(
+      "({})",
+      expressions
+        .iter()
+        .map(|expression| get_expression_sql(expression, params))
+        .collect::<Vec<String>>()
+        .join(" AND ")
+    ),
+    AlbumSearchExpression::Or(expressions) => format!(
+      "({})",
+      expressions
+        .iter()
+        .map(|expression| get_expression_sql(expression, params))
+        .collect::<Vec<String>>()
+        .join(" OR ")
+    ),
+  }
+}
+
+/**
+ * Albums without a release date are sorted last in either direction, and ties are broken by id
+ * so that pages are stable.
+ */
+fn get_sort_sql(sort: &AlbumSearchSort) -> String {
+  let direction = if sort.descending { "DESC" } else { "ASC" };
+  let expression = match sort.key {
+    AlbumSearchSortKey::Rating => "albums.rating".to_string(),
+    AlbumSearchSortKey::RatingCount => "albums.rating_count".to_string(),
+    AlbumSearchSortKey::ReleaseDate => {
+      return format!(
+        "albums.release_date IS NULL, albums.release_date {}, albums.id",
+        direction
+      );
+    }
+    AlbumSearchSortKey::Name => "albums.name COLLATE NOCASE".to_string(),
+    AlbumSearchSortKey::DescriptorCount => {
+      "(SELECT COUNT(*) FROM album_descriptors WHERE album_descriptors.album_id = albums.id)"
+        .to_string()
+    }
+    AlbumSearchSortKey::BayesianRating => format!(
+      "(albums.rating * albums.rating_count + {:.1} * {}) / (albums.rating_count + {})",
+      BAYESIAN_RATING_PRIOR_MEAN, BAYESIAN_RATING_PRIOR_COUNT, BAYESIAN_RATING_PRIOR_COUNT
+    ),
+  };
+  format!("{} {}, albums.id", expression, direction)
+}
+
+/**
+ * SQL equivalent of an `AlbumSearchQuery`, filtering the `albums` table through the normalized
+ * album tables, and through the full text index when the query has text.
+ */
+struct SqlAlbumFilter {
+  from: String,
+  order_by: String,
+  clauses: Vec<String>,
+  params: Vec<SqlParam>,
+}
+
+impl SqlAlbumFilter {
+  fn new() -> Self {
+    Self {
+      from: "albums".to_string(),
+      order_by: "albums.id".to_string(),
+      clauses: vec![],
+      params: vec![],
+    }
+  }
+
+  fn push(&mut self, clause: &str, params: Vec<SqlParam>) {
+    self.clauses.push(clause.to_string());
+    self.params.extend(params);
+  }
+
+  fn push_tags<T: ToString>(&mut self, clause: &str, include: &[T], exclude: &[T]) {
+    if !include.is_empty() {
+      self.push(clause, vec![SqlParam::Array(to_values(include))]);
+    }
+    if !exclude.is_empty() {
+      self.push(
+        &format!("NOT ({})", clause),
+        vec![SqlParam::Array(to_values(exclude))],
+      );
+    }
+  }
+
+  fn push_range<T: Into<Value> + Copy>(
+    &mut self,
+    expression: &str,
+    min: Option<T>,
+    max: Option<T>,
+  ) {
+    if let Some(min) = min {
+      self.push(
+        &format!("{} >= ?", expression),
+        vec![SqlParam::Value(min.into())],
+      );
+    }
+    if let Some(max) = max {
+      self.push(
+        &format!("{} <= ?", expression),
+        vec![SqlParam::Value(max.into())],
+      );
+    }
+  }
+
+  fn push_query(&mut self, query: &AlbumSearchQuery) {
+    if let Some(match_query) = query.text.as_ref().and_then(|text| to_match_query(text)) {
+      self.from =
+        "albums JOIN album_search ON album_search.file_name = albums.file_name".to_string();
+      self.order_by = "album_search.rank".to_string();
+      self.push(
+        "album_search MATCH ?",
+        vec![SqlParam::Value(Value::from(match_query))],
+      );
+    }
+    if let Some(exact_name) = &query.exact_name {
+      self.push(
+        "albums.name = ? COLLATE NOCASE",
+        vec![SqlParam::Value(Value::from(exact_name.clone()))],
+      );
+    }
+    if let Some(expression) = &query.expression {
+      let mut params = vec![];
+      let clause = get_expression_sql(expression, &mut params);
+      self.push(&clause, params);
+    }
+    if !query.include_duplicates.is_some_and(|b| b) {
+      self.push(
+        "albums.id NOT IN (SELECT duplicate_album_id FROM album_duplicates)",
+        vec![],
+      );
+    }
+    if let Some(min) = query.min_primary_genre_count {
+      self.push(
+        "
+        (
+          SELECT COUNT(*) FROM album_genres
+          WHERE album_genres.album_id = albums.id AND album_genres.is_primary = 1
+        ) >= ?",
+        vec![SqlParam::Value(Value::from(min as i64))],
+      );
+    }
+    if let Some(min) = query.min_secondary_genre_count {
+      self.push(
+        "
+        (
+          SELECT COUNT(*) FROM album_genres
+          WHERE album_genres.album_id = albums.id AND album_genres.is_primary = 0
+        ) >= ?",
+        vec![SqlParam::Value(Value::from(min as i64))],
+      );
+    }
+    if let Some(min) = query.min_descriptor_count {
+      self.push(
+        "
+        (
+          SELECT COUNT(*) FROM album_descriptors
+          WHERE album_descriptors.album_id = albums.id
+        ) >= ?",
+        vec![SqlParam::Value(Value::from(min as i64))],
+      );
+    }
+    if let Some(min) = query.min_release_year {
+      self.push(
+        "CAST(substr(albums.release_date, 1, 4) AS INTEGER) >= ?",
+        vec![SqlParam::Value(Value::from(min))],
+      );
+    }
+    if let Some(max) = query.max_release_year {
+      self.push(
+        "CAST(substr(albums.release_date, 1, 4) AS INTEGER) <= ?",
+        vec![SqlParam::Value(Value::from(max))],
+      );
+    }
+    self.push_tags(
+      FILE_NAME_FILTER,
+      &query.include_file_names,
+      &query.exclude_file_names,
+    );
+    self.push_tags(
+      ARTIST_FILTER,
+      &query.include_artists,
+      &query.exclude_artists,
+    );
+    self.push_tags(
+      PRIMARY_GENRE_FILTER,
+      &query.include_primary_genres,
+      &query.exclude_primary_genres,
+    );
+    self.push_tags(
+      SECONDARY_GENRE_FILTER,
+      &query.include_secondary_genres,
+      &query.exclude_secondary_genres,
+    );
+    self.push_tags(
+      LANGUAGE_FILTER,
+      &query.include_languages,
+      &query.exclude_languages,
+    );
+    self.push_tags(
+      DESCRIPTOR_FILTER,
+      &query.include_descriptors,
+      &query.exclude_descriptors,
+    );
+    self.push_tags(
+      RELEASE_TYPE_FILTER,
+      &query.include_release_types,
+      &query.exclude_release_types,
+    );
+    self.push_tags(LABEL_FILTER, &query.include_labels, &query.exclude_labels);
+    self.push_range(
+      "albums.rating",
+      query.min_rating.map(f64::from),
+      query.max_rating.map(f64::from),
+    );
+    self.push_range("albums.rating_count", query.min_rating_count, None);
+    self.push_tags(CREDITED_ARTIST_FILTER, &query.include_credited_artists, &[]);
+    self.push_tags(CREDIT_ROLE_FILTER, &query.include_credit_roles, &[]);
+    self.push_range(TRACK_COUNT, query.min_track_count, query.max_track_count);
+    self.push_range(
+      "albums.total_duration_seconds",
+      query.min_duration_seconds,
+      query.max_duration_seconds,
+    );
+    if let Some(sort) = &query.sort {
+      self.order_by = get_sort_sql(sort);
+    }
+  }
+
+  fn where_clause(&self) -> String {
+    if self.clauses.is_empty() {
+      String::from("")
+    } else {
+      format!("WHERE {}", self.clauses.join(" AND "))
+    }
+  }
+
+  fn into_params(self) -> Vec<Box<dyn ToSql>> {
+    self
+      .params
+      .into_iter()
+      .map(|param| param.into_boxed())
+      .collect()
+  }
+}
+
+/**
+ * Counts facet values over the `filtered` albums, which hold the album id and release date of
+ * the albums matching the search query.
+ */
+fn get_facet_sql(facet: AlbumSearchFacet) -> &'static str {
+  match facet {
+    AlbumSearchFacet::PrimaryGenre => {
+      "
+      SELECT genres.name, COUNT(*) AS count
+      FROM filtered
+      JOIN album_genres ON album_genres.album_id = filtered.id AND album_genres.is_primary = 1
+      JOIN genres ON genres.id = album_genres.genre_id
+      GROUP BY genres.name
+      "
+    }
+    AlbumSearchFacet::SecondaryGenre => {
+      "
+      SELECT genres.name, COUNT(*) AS count
+      FROM filtered
+      JOIN album_genres ON album_genres.album_id = filtered.id AND album_genres.is_primary = 0
+      JOIN genres ON genres.id = album_genres.genre_id
+      GROUP BY genres.name
+      "
+    }
+    AlbumSearchFacet::Descriptor => {
+      "
+      SELECT descriptors.name, COUNT(*) AS count
+      FROM filtered
+      JOIN album_descriptors ON album_descriptors.album_id = filtered.id
+      JOIN descriptors ON descriptors.id = album_descriptors.descriptor_id
+      GROUP BY descriptors.name
+      "
+    }
+    AlbumSearchFacet::Language => {
+      "
+      SELECT languages.name, COUNT(*) AS count
+      FROM filtered
+      JOIN album_languages ON album_languages.album_id = filtered.id
+      JOIN languages ON languages.id = album_languages.language_id
+      GROUP BY languages.name
+      "
+    }
+    AlbumSearchFacet::Decade => {
+      "
+      SELECT
+        CAST(CAST(substr(filtered.release_date, 1, 4) AS INTEGER) / 10 * 10 AS TEXT) AS decade,
+        COUNT(*) AS count
+      FROM filtered
+      WHERE filtered.release_date IS NOT NULL
+      GROUP BY decade
+      "
+    }
+    AlbumSearchFacet::Artist => {
+      "
+      SELECT artists.file_name, COUNT(*) AS count
+      FROM filtered
+      JOIN album_artists ON album_artists.album_id = filtered.id
+      JOIN artists ON artists.id = album_artists.artist_id
+      GROUP BY artists.file_name
+      "
+    }
+  }
+}
+
+/**
+ * Cosine distance, as reported by the Redis vector index: 0 for identical directions, up to 2
+ * for opposite ones.
+ */
+fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+  let dot = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum::<f32>();
+  let norm_a = a.iter().map(|a| a * a).sum::<f32>().sqrt();
+  let norm_b = b.iter().map(|b| b * b).sum::<f32>().sqrt();
+  if norm_a == 0.0 || norm_b == 0.0 {
+    return 1.0;
+  }
+  1.0 - dot / (norm_a * norm_b)
+}
+
+/**
+ * Album search index backed by SQLite: FTS5 for text search, the normalized album tables for
+ * filters, and embeddings stored as blobs and compared by brute force. Album documents are read
+ * from the album repository, so only the full text rows and the embeddings are written here.
+ * Embeddings are keyed by file name rather than album id, so that they survive album read model
+ * resets like the Redis documents do.
+ */
+pub struct SqliteAlbumSearchIndex {
+  sqlite_connection: Arc<SqliteConnection>,
+  album_repository: SqliteAlbumRepository,
+}
+
+impl SqliteAlbumSearchIndex {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self {
+      album_repository: SqliteAlbumRepository::new(Arc::clone(&sqlite_connection)),
+      sqlite_connection,
+    }
+  }
+
+  /**
+   * The tables are created by migrations, so unlike the Redis index there is nothing to create.
+   * Returns whether the full text index has fewer rows than there are albums, as it does when
+   * switching from the Redis backend, in which case the albums have to be re-indexed.
+   */
+  pub async fn setup_index(&self) -> Result<bool> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let is_behind = conn.query_row(
+          "SELECT (SELECT COUNT(*) FROM album_search) < (SELECT COUNT(*) FROM albums)",
+          [],
+          |row| row.get::<_, bool>(0),
+        )?;
+        Ok(is_behind)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to compare album search index to albums"
+        );
+        anyhow!("Failed to compare album search index to albums")
+      })?
+  }
+
+  pub async fn reindex(&self) -> Result<()> {
+    let file_names = self.album_repository.get_file_names().await?;
+    info!(count = file_names.len(), "Re-indexing albums");
+    for chunk in file_names.chunks(REINDEX_BATCH_SIZE) {
+      for album in self.album_repository.find_many(chunk.to_vec()).await? {
+        self.put(album).await?;
+      }
+    }
+    Ok(())
+  }
+
+  /**
+   * Copies the embeddings of every album in another index. Embeddings are only stored in the
+   * album search index, and generating them again can be expensive, so this carries them over
+   * when switching backends.
+   */
+  pub async fn backfill_embeddings(&self, source: &dyn AlbumSearchIndex) -> Result<()> {
+    let file_names = source.get_file_names().await?;
+    info!(count = file_names.len(), "Backfilling album embeddings");
+    for file_name in file_names {
+      for embedding in source.get_embeddings(&file_name).await? {
+        self.put_embedding(&embedding).await?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl AlbumSearchIndex for SqliteAlbumSearchIndex {
+  #[instrument(skip_all, fields(file_name = album.file_name.to_string()))]
+  async fn put(&self, album: AlbumReadModel) -> Result<()> {
+    let file_name = album.file_name.to_string();
+    let artist_names = album
+      .artists
+      .iter()
+      .map(|artist| artist.name.clone())
+      .collect::<Vec<String>>()
+      .join(" ");
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+          "DELETE FROM album_search WHERE file_name = ?",
+          params![file_name],
+        )?;
+        tx.execute(
+          "INSERT INTO album_search (file_name, name, artist_names) VALUES (?, ?, ?)",
+          params![file_name, album.name, artist_names],
+        )?;
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to put album search document"
+        );
+        anyhow!("Failed to put album search document")
+      })?
+  }
+
+  async fn delete(&self, file_name: &FileName) -> Result<()> {
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+          "DELETE FROM album_search WHERE file_name = ?",
+          params![file_name],
+        )?;
+        tx.execute(
+          "DELETE FROM album_embeddings WHERE file_name = ?",
+          params![file_name],
+        )?;
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to delete album search document"
+        );
+        anyhow!("Failed to delete album search document")
+      })?
+  }
+
+  async fn find(&self, file_name: &FileName) -> Result<Option<AlbumReadModel>> {
+    self.album_repository.find(file_name).await
+  }
+
+  #[instrument(skip(self))]
+  async fn search(
+    &self,
+    query: &AlbumSearchQuery,
+    pagination: Option<&SearchPagination>,
+  ) -> Result<AlbumSearchResult> {
+    let limit = pagination.and_then(|p| p.limit).unwrap_or(100000);
+    let offset = pagination.and_then(|p| p.offset).unwrap_or(0);
+    let mut filter = SqlAlbumFilter::new();
+    filter.push_query(query);
+
+    let (file_names, total) = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let where_clause = filter.where_clause();
+        let count_sql = format!("SELECT COUNT(*) FROM {} {}", filter.from, where_clause);
+        let search_sql = format!(
+          "SELECT albums.file_name FROM {} {} ORDER BY {} LIMIT {} OFFSET {}",
+          filter.from, where_clause, filter.order_by, limit, offset
+        );
+        let params = filter.into_params();
+
+        let total = conn.query_row(&count_sql, params_from_iter(params.iter()), |row| {
+          row.get::<_, i64>(0)
+        })?;
+        let mut stmt = conn.prepare(&search_sql)?;
+        let file_names = stmt
+          .query_map(params_from_iter(params.iter()), |row| {
+            row.get::<_, String>(0)
+          })?
+          .collect::<Result<Vec<String>, _>>()?
+          .into_iter()
+          .map(FileName::try_from)
+          .collect::<Result<Vec<FileName>>>()?;
+        Ok::<_, anyhow::Error>((file_names, total as usize))
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to search albums");
+        anyhow!("Failed to search albums")
+      })??;
+
+    Ok(AlbumSearchResult {
+      albums: self.album_repository.find_many(file_names).await?,
+      total,
+    })
+  }
+
+  #[instrument(skip(self))]
+  async fn get_facets(
+    &self,
+    query: &AlbumSearchQuery,
+    facets: &[AlbumSearchFacetQuery],
+  ) -> Result<Vec<AlbumSearchFacetResult>> {
+    let mut filter = SqlAlbumFilter::new();
+    filter.push_query(query);
+    let facets = facets.to_vec();
+
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let filtered_sql = format!(
+          "SELECT albums.id, albums.release_date FROM {} {}",
+          filter.from,
+          filter.where_clause()
+        );
+        let params = filter.into_params();
+        let mut results = Vec::with_capacity(facets.len());
+        for facet_query in facets {
+          let sql = format!(
+            "WITH filtered AS ({}) {} ORDER BY count DESC, 1 LIMIT {}",
+            filtered_sql,
+            get_facet_sql(facet_query.facet),
+            facet_query.limit
+          );
+          let mut stmt = conn.prepare(&sql)?;
+          let values = stmt
+            .query_map(params_from_iter(params.iter()), |row| {
+              Ok(ItemAndCount {
+                name: row.get(0)?,
+                count: row.get(1)?,
+              })
+            })?
+            .collect::<Result<Vec<ItemAndCount>, _>>()?;
+          results.push(AlbumSearchFacetResult {
+            facet: facet_query.facet,
+            values,
+          });
+        }
+        Ok(results)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get album search facets");
+        anyhow!("Failed to get album search facets")
+      })?
+  }
+
+  async fn get_embeddings(&self, file_name: &FileName) -> Result<Vec<AlbumEmbedding>> {
+    let file_name = file_name.clone();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt =
+          conn.prepare("SELECT key, embedding FROM album_embeddings WHERE file_name = ?")?;
+        let embeddings = stmt
+          .query_map(params![file_name.to_string()], |row| {
+            Ok(AlbumEmbedding {
+              file_name: file_name.clone(),
+              key: row.get::<_, String>(0)?,
+              embedding: embedding_from_bytes(&row.get::<_, Vec<u8>>(1)?),
+            })
+          })?
+          .collect::<Result<Vec<AlbumEmbedding>, _>>()?;
+        Ok(embeddings)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get album embeddings");
+        anyhow!("Failed to get album embeddings")
+      })?
+  }
+
+  async fn find_many_embeddings(
+    &self,
+    file_names: Vec<FileName>,
+    key: &str,
+  ) -> Result<Vec<AlbumEmbedding>> {
+    let file_name_params = to_values(&file_names);
+    let key = key.to_string();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT file_name, embedding
+          FROM album_embeddings
+          WHERE key = ? AND file_name IN rarray(?)
+          ",
+        )?;
+        let rows = stmt
+          .query_map(params![key, Rc::new(file_name_params)], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+          })?
+          .collect::<Result<Vec<(String, Vec<u8>)>, _>>()?;
+        let embeddings = rows
+          .into_iter()
+          .map(|(file_name, embedding)| {
+            Ok(AlbumEmbedding {
+              file_name: FileName::try_from(file_name)?,
+              key: key.clone(),
+              embedding: embedding_from_bytes(&embedding),
+            })
+          })
+          .collect::<Result<Vec<AlbumEmbedding>>>()?;
+        Ok(embeddings)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find album embeddings");
+        anyhow!("Failed to find album embeddings")
+      })?
+  }
+
+  async fn find_embedding(
+    &self,
+    file_name: &FileName,
+    key: &str,
+  ) -> Result<Option<AlbumEmbedding>> {
+    Ok(
+      self
+        .find_many_embeddings(vec![file_name.clone()], key)
+        .await?
+        .into_iter()
+        .next(),
+    )
+  }
+
+  #[instrument(skip_all, fields(file_name = embedding.file_name.to_string(), key = embedding.key.as_str()))]
+  async fn put_embedding(&self, embedding: &AlbumEmbedding) -> Result<()> {
+    let file_name = embedding.file_name.to_string();
+    let key = embedding.key.clone();
+    let bytes = embedding_to_bytes(&embedding.embedding);
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO album_embeddings (file_name, key, embedding)
+          VALUES (?, ?, ?)
+          ON CONFLICT (file_name, key) DO UPDATE SET embedding = excluded.embedding
+          ",
+          params![file_name, key, bytes],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put album embedding");
+        anyhow!("Failed to put album embedding")
+      })?
+  }
+
+  async fn delete_embedding(&self, file_name: &FileName, key: &str) -> Result<()> {
+    let file_name = file_name.to_string();
+    let key = key.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM album_embeddings WHERE file_name = ? AND key = ?",
+          params![file_name, key],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete album embedding");
+        anyhow!("Failed to delete album embedding")
+      })?
+  }
+
+  #[instrument(skip_all, fields(key = query.embedding_key.as_str(), limit = query.limit))]
+  async fn embedding_similarity_search(
+    &self,
+    query: &AlbumEmbeddingSimilarirtySearchQuery,
+  ) -> Result<Vec<(AlbumReadModel, f32)>> {
+    let mut filter = SqlAlbumFilter::new();
+    filter.push(
+      "album_embeddings.key = ?",
+      vec![SqlParam::Value(Value::from(query.embedding_key.clone()))],
+    );
+    filter.push_query(&query.filters);
+    let target = query.embedding.clone();
+    let limit = query.limit;
+
+    let mut distances = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let sql = format!(
+          "
+          SELECT albums.file_name, album_embeddings.embedding
+          FROM {}
+          JOIN album_embeddings ON album_embeddings.file_name = albums.file_name
+          {}
+          ",
+          filter.from,
+          filter.where_clause()
+        );
+        let params = filter.into_params();
+        let mut stmt = conn.prepare(&sql)?;
+        let mut rows = stmt.query(params_from_iter(params.iter()))?;
+        let mut distances = Vec::<(String, f32)>::new();
+        while let Some(row) = rows.next()? {
+          let embedding = embedding_from_bytes(&row.get::<_, Vec<u8>>(1)?);
+          distances.push((row.get(0)?, cosine_distance(&target, &embedding)));
+        }
+        Ok::<_, rusqlite::Error>(distances)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to search album embeddings");
+        anyhow!("Failed to search album embeddings")
+      })??;
+
+    distances.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    distances.truncate(limit);
+    let distances = distances
+      .into_iter()
+      .map(|(file_name, distance)| Ok((FileName::try_from(file_name)?, distance)))
+      .collect::<Result<Vec<(FileName, f32)>>>()?;
+    let distance_by_file_name = distances
+      .iter()
+      .cloned()
+      .collect::<HashMap<FileName, f32>>();
+    let albums = self
+      .album_repository
+      .find_many(
+        distances
+          .into_iter()
+          .map(|(file_name, _)| file_name)
+          .collect(),
+      )
+      .await?
+      .into_iter()
+      .filter_map(|album| {
+        let distance = *distance_by_file_name.get(&album.file_name)?;
+        Some((album, distance))
+      })
+      .collect();
+
+    Ok(albums)
+  }
+
+  async fn get_embedding_keys(&self) -> Result<Vec<String>> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut stmt = conn.prepare("SELECT DISTINCT key FROM album_embeddings ORDER BY key")?;
+        let keys = stmt
+          .query_map([], |row| row.get::<_, String>(0))?
+          .collect::<Result<Vec<String>, _>>()?;
+        Ok(keys)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to get album embedding keys"
+        );
+        anyhow!("Failed to get album embedding keys")
+      })?
+  }
+
+  async fn get_file_names(&self) -> Result<Vec<FileName>> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut stmt = conn.prepare("SELECT file_name FROM album_search")?;
+        let file_names = stmt
+          .query_map([], |row| row.get::<_, String>(0))?
+          .collect::<Result<Vec<String>, _>>()?
+          .into_iter()
+          .map(FileName::try_from)
+          .collect::<Result<Vec<FileName>>>()?;
+        Ok(file_names)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to get album search file names"
+        );
+        anyhow!("Failed to get album search file names")
+      })?
+  }
+}
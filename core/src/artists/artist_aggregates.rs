@@ -0,0 +1,147 @@
+use super::artist_read_model::ArtistReadModelReference;
+use crate::{
+  albums::{album_read_model::AlbumReadModel, album_repository::ItemAndCount},
+  files::file_metadata::file_name::FileName,
+  proto,
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+pub const DEFAULT_ARTIST_AGGREGATE_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtistCollaborator {
+  pub artist: ArtistReadModelReference,
+  pub album_count: u32,
+  pub roles: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ArtistAggregates {
+  pub album_count: u32,
+  /**
+   * Mean of the ratings of the albums that have any.
+   */
+  pub average_rating: Option<f32>,
+  pub top_genres: Vec<ItemAndCount>,
+  pub top_descriptors: Vec<ItemAndCount>,
+  /**
+   * Other artists credited on the albums, by number of albums.
+   */
+  pub collaborators: Vec<ArtistCollaborator>,
+}
+
+fn top_items<'a>(items: impl Iterator<Item = &'a String>, limit: usize) -> Vec<ItemAndCount> {
+  let mut counts = HashMap::<&String, u32>::new();
+  for item in items {
+    *counts.entry(item).or_default() += 1;
+  }
+  let mut items = counts
+    .into_iter()
+    .map(|(name, count)| ItemAndCount {
+      name: name.clone(),
+      count,
+    })
+    .collect::<Vec<_>>();
+  items.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+  items.truncate(limit);
+  items
+}
+
+impl ArtistAggregates {
+  /**
+   * Aggregates the albums of an artist. Duplicate albums are skipped, so that reissues are only
+   * counted once.
+   */
+  pub fn from_albums(artist_file_name: &FileName, albums: &[AlbumReadModel], limit: usize) -> Self {
+    let albums = albums
+      .iter()
+      .filter(|album| album.duplicate_of.is_none())
+      .collect::<Vec<_>>();
+
+    let ratings = albums
+      .iter()
+      .filter(|album| album.rating_count > 0)
+      .map(|album| album.rating)
+      .collect::<Vec<_>>();
+    let average_rating = if ratings.is_empty() {
+      None
+    } else {
+      Some(ratings.iter().sum::<f32>() / ratings.len() as f32)
+    };
+
+    let mut collaborators = HashMap::<FileName, (String, u32, BTreeSet<String>)>::new();
+    for album in albums.iter() {
+      let mut seen = HashSet::new();
+      for credit in album
+        .credits
+        .iter()
+        .chain(album.tracks.iter().flat_map(|track| track.credits.iter()))
+        .filter(|credit| &credit.artist.file_name != artist_file_name)
+      {
+        let (_, album_count, roles) = collaborators
+          .entry(credit.artist.file_name.clone())
+          .or_insert_with(|| (credit.artist.name.clone(), 0, BTreeSet::new()));
+        if seen.insert(&credit.artist.file_name) {
+          *album_count += 1;
+        }
+        roles.extend(credit.roles.iter().cloned());
+      }
+    }
+    let mut collaborators = collaborators
+      .into_iter()
+      .map(
+        |(file_name, (name, album_count, roles))| ArtistCollaborator {
+          artist: ArtistReadModelReference { name, file_name },
+          album_count,
+          roles: roles.into_iter().collect(),
+        },
+      )
+      .collect::<Vec<_>>();
+    collaborators.sort_by(|a, b| {
+      b.album_count
+        .cmp(&a.album_count)
+        .then_with(|| a.artist.name.cmp(&b.artist.name))
+    });
+    collaborators.truncate(limit);
+
+    Self {
+      album_count: albums.len() as u32,
+      average_rating,
+      top_genres: top_items(
+        albums.iter().flat_map(|album| album.primary_genres.iter()),
+        limit,
+      ),
+      top_descriptors: top_items(
+        albums.iter().flat_map(|album| album.descriptors.iter()),
+        limit,
+      ),
+      collaborators,
+    }
+  }
+}
+
+impl From<ArtistCollaborator> for proto::ArtistCollaborator {
+  fn from(val: ArtistCollaborator) -> Self {
+    proto::ArtistCollaborator {
+      artist: Some(val.artist.into()),
+      album_count: val.album_count,
+      roles: val.roles,
+    }
+  }
+}
+
+impl From<ArtistAggregates> for proto::ArtistAggregates {
+  fn from(val: ArtistAggregates) -> Self {
+    proto::ArtistAggregates {
+      album_count: val.album_count,
+      average_rating: val.average_rating,
+      top_genres: val.top_genres.into_iter().map(|i| i.into()).collect(),
+      top_descriptors: val.top_descriptors.into_iter().map(|i| i.into()).collect(),
+      collaborators: val
+        .collaborators
+        .into_iter()
+        .map(|collaborator| collaborator.into())
+        .collect(),
+    }
+  }
+}
@@ -0,0 +1,86 @@
+use super::{artist_read_model::ArtistReadModel, artist_repository::ArtistRepository};
+use crate::{
+  events::{
+    event::{Event, Stream},
+    event_subscriber::{EventSubscriber, EventSubscriberBuilder, SubscriberContext},
+  },
+  files::file_metadata::page_type::PageType,
+  parser::parsed_file_data::ParsedFileData,
+  settings::Settings,
+  sqlite::SqliteConnection,
+};
+use anyhow::Result;
+use rustis::{bb8::Pool, client::PooledClientManager};
+use std::sync::Arc;
+
+fn is_artist_deletion(event: &Event) -> bool {
+  match event {
+    Event::FileDeleted { file_name, .. } => file_name.page_type() == PageType::Artist,
+    _ => false,
+  }
+}
+
+/**
+ * Projects both parsed and deleted artist files, so that a replay applies deletions in order with
+ * the parses they follow.
+ */
+async fn update_artist_read_models(context: SubscriberContext) -> Result<()> {
+  let artist_repository = ArtistRepository::new(Arc::clone(&context.sqlite_connection));
+  match context.payload.event {
+    Event::FileParsed {
+      file_name,
+      data: ParsedFileData::Artist(parsed_artist),
+      ..
+    } => {
+      artist_repository
+        .put(ArtistReadModel::from_parsed_artist(
+          &file_name,
+          parsed_artist,
+        ))
+        .await?;
+    }
+    Event::FileDeleted { file_name, .. } if file_name.page_type() == PageType::Artist => {
+      artist_repository.delete(&file_name).await?;
+    }
+    _ => {}
+  }
+  Ok(())
+}
+
+async fn reset_artist_read_models(sqlite_connection: Arc<SqliteConnection>) -> Result<()> {
+  let artist_repository = ArtistRepository::new(sqlite_connection);
+  artist_repository.delete_all().await
+}
+
+pub fn build_artist_event_subscribers(
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
+  settings: Arc<Settings>,
+) -> Result<Vec<EventSubscriber>> {
+  let reset_sqlite_connection = Arc::clone(&sqlite_connection);
+  Ok(vec![EventSubscriberBuilder::default()
+    .id("update_artist_read_models")
+    .stream(Stream::Parser)
+    .stream(Stream::File)
+    .batch_size(250)
+    .redis_connection_pool(Arc::clone(&redis_connection_pool))
+    .sqlite_connection(Arc::clone(&sqlite_connection))
+    .settings(Arc::clone(&settings))
+    .generate_ordered_processing_group_id(Arc::new(|row| match &row.payload.event {
+      Event::FileParsed {
+        file_name,
+        data: ParsedFileData::Artist(_),
+        ..
+      } => Some(file_name.to_string()),
+      _ => None,
+    }))
+    .is_barrier(Arc::new(|row| is_artist_deletion(&row.payload.event)))
+    .handle(Arc::new(|context| {
+      Box::pin(async move { update_artist_read_models(context).await })
+    }))
+    .reset(Arc::new(move || {
+      let sqlite_connection = Arc::clone(&reset_sqlite_connection);
+      Box::pin(async move { reset_artist_read_models(sqlite_connection).await })
+    }))
+    .build()?])
+}
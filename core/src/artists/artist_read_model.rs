@@ -0,0 +1,103 @@
+use crate::{
+  files::file_metadata::file_name::FileName,
+  parser::parsed_file_data::{ParsedArtist, ParsedArtistReference},
+  proto,
+};
+use chrono::NaiveDate;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct ArtistReadModelReference {
+  pub name: String,
+  pub file_name: FileName,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct ArtistReadModel {
+  pub name: String,
+  pub file_name: FileName,
+  pub formed_date: Option<NaiveDate>,
+  pub disbanded_date: Option<NaiveDate>,
+  pub location: Option<String>,
+  pub country: Option<String>,
+  pub genres: Vec<String>,
+  pub members: Vec<ArtistReadModelReference>,
+  pub member_of: Vec<ArtistReadModelReference>,
+  pub related_artists: Vec<ArtistReadModelReference>,
+}
+
+impl From<&ParsedArtistReference> for ArtistReadModelReference {
+  fn from(parsed_artist: &ParsedArtistReference) -> Self {
+    Self {
+      name: parsed_artist.name.clone(),
+      file_name: parsed_artist.file_name.clone(),
+    }
+  }
+}
+
+impl ArtistReadModel {
+  pub fn from_parsed_artist(file_name: &FileName, parsed_artist: ParsedArtist) -> Self {
+    Self {
+      name: parsed_artist.name,
+      file_name: file_name.clone(),
+      formed_date: parsed_artist.formed_date,
+      disbanded_date: parsed_artist.disbanded_date,
+      location: parsed_artist.location,
+      country: parsed_artist.country,
+      genres: parsed_artist.genres,
+      members: parsed_artist
+        .members
+        .iter()
+        .map(ArtistReadModelReference::from)
+        .collect(),
+      member_of: parsed_artist
+        .member_of
+        .iter()
+        .map(ArtistReadModelReference::from)
+        .collect(),
+      related_artists: parsed_artist
+        .related_artists
+        .iter()
+        .map(ArtistReadModelReference::from)
+        .collect(),
+    }
+  }
+}
+
+impl From<ArtistReadModelReference> for proto::ArtistReference {
+  fn from(val: ArtistReadModelReference) -> Self {
+    proto::ArtistReference {
+      name: val.name,
+      file_name: val.file_name.to_string(),
+    }
+  }
+}
+
+impl From<ArtistReadModel> for proto::Artist {
+  fn from(val: ArtistReadModel) -> Self {
+    proto::Artist {
+      file_name: val.file_name.to_string(),
+      name: val.name,
+      formed_date: val.formed_date.map(|date| date.to_string()),
+      disbanded_date: val.disbanded_date.map(|date| date.to_string()),
+      location: val.location,
+      country: val.country,
+      genres: val.genres,
+      members: val
+        .members
+        .into_iter()
+        .map(|artist| artist.into())
+        .collect(),
+      member_of: val
+        .member_of
+        .into_iter()
+        .map(|artist| artist.into())
+        .collect(),
+      related_artists: val
+        .related_artists
+        .into_iter()
+        .map(|artist| artist.into())
+        .collect(),
+    }
+  }
+}
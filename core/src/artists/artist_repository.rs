@@ -0,0 +1,411 @@
+use super::artist_read_model::{ArtistReadModel, ArtistReadModelReference};
+use crate::{
+  albums::album_search_index::SearchPagination, files::file_metadata::file_name::FileName,
+  sqlite::SqliteConnection,
+};
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use rusqlite::{params, types::Value, Transaction};
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+use tracing::{error, instrument};
+
+const MEMBER: &str = "member";
+const MEMBER_OF: &str = "member_of";
+const RELATED: &str = "related";
+
+/**
+ * Page size of artist searches that don't ask for one.
+ */
+pub const DEFAULT_ARTIST_SEARCH_LIMIT: usize = 20;
+
+#[derive(Debug)]
+pub struct ArtistSearchResult {
+  pub artists: Vec<ArtistReadModel>,
+  pub total: usize,
+}
+
+/**
+ * Artist rows are shared with the album read model, which creates them for every referenced
+ * artist. This repository only owns the details parsed from artist pages.
+ */
+#[derive(Debug, Clone)]
+pub struct ArtistRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+fn upsert_artist(tx: &Transaction, artist: &ArtistReadModelReference) -> rusqlite::Result<i64> {
+  tx.query_row(
+    "
+    INSERT INTO artists (file_name, name)
+    VALUES (?, ?)
+    ON CONFLICT(file_name) DO UPDATE SET name = excluded.name
+    RETURNING id
+    ",
+    params![artist.file_name.to_string(), artist.name],
+    |row| row.get(0),
+  )
+}
+
+fn to_file_name(value: String) -> rusqlite::Result<FileName> {
+  FileName::try_from(value).map_err(|e| {
+    error!(message = e.to_string(), "Failed to parse artist file name");
+    rusqlite::Error::ExecuteReturnedResults
+  })
+}
+
+impl ArtistRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  #[instrument(skip_all, fields(file_name = artist.file_name.to_string()))]
+  pub async fn put(&self, artist: ArtistReadModel) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        let artist_id: i64 = tx.query_row(
+          "
+          INSERT INTO artists (file_name, name, formed_date, disbanded_date, location, country)
+          VALUES (?, ?, ?, ?, ?, ?)
+          ON CONFLICT(file_name) DO UPDATE SET
+            name = excluded.name,
+            formed_date = excluded.formed_date,
+            disbanded_date = excluded.disbanded_date,
+            location = excluded.location,
+            country = excluded.country
+          RETURNING id
+          ",
+          params![
+            artist.file_name.to_string(),
+            artist.name,
+            artist.formed_date,
+            artist.disbanded_date,
+            artist.location,
+            artist.country
+          ],
+          |row| row.get(0),
+        )?;
+
+        tx.execute(
+          "DELETE FROM artist_genres WHERE artist_id = ?",
+          params![artist_id],
+        )?;
+        for genre in artist.genres {
+          let genre_id: i64 = tx.query_row(
+            "
+            INSERT INTO genres (name)
+            VALUES (?)
+            ON CONFLICT(name) DO UPDATE SET name = excluded.name
+            RETURNING id
+            ",
+            params![genre],
+            |row| row.get(0),
+          )?;
+          tx.execute(
+            "
+            INSERT INTO artist_genres (artist_id, genre_id)
+            VALUES (?, ?)
+            ON CONFLICT DO NOTHING
+            ",
+            params![artist_id, genre_id],
+          )?;
+        }
+
+        tx.execute(
+          "DELETE FROM artist_relationships WHERE artist_id = ?",
+          params![artist_id],
+        )?;
+        for (relationship, related_artists) in [
+          (MEMBER, artist.members),
+          (MEMBER_OF, artist.member_of),
+          (RELATED, artist.related_artists),
+        ] {
+          for related_artist in related_artists {
+            let related_artist_id = upsert_artist(&tx, &related_artist)?;
+            tx.execute(
+              "
+              INSERT INTO artist_relationships (artist_id, related_artist_id, relationship)
+              VALUES (?, ?, ?)
+              ON CONFLICT DO NOTHING
+              ",
+              params![artist_id, related_artist_id, relationship],
+            )?;
+          }
+        }
+
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put artist");
+        anyhow!("Failed to put artist")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn find(&self, file_name: &FileName) -> Result<Option<ArtistReadModel>> {
+    Ok(self.find_many(vec![file_name.clone()]).await?.pop())
+  }
+
+  /**
+   * Loads the artists with their genres and relationships in one query each, in the order of the
+   * given file names. Unknown artists are left out.
+   */
+  #[instrument(skip_all, fields(count = file_names.len()))]
+  pub async fn find_many(&self, file_names: Vec<FileName>) -> Result<Vec<ArtistReadModel>> {
+    let file_name_params = file_names
+      .iter()
+      .map(|file_name| Value::from(file_name.to_string()))
+      .collect::<Vec<Value>>();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT id, file_name, name, formed_date, disbanded_date, location, country
+          FROM artists
+          WHERE file_name IN rarray(?)
+          ",
+        )?;
+        let mut artists = stmt
+          .query_map([Rc::new(file_name_params)], |row| {
+            Ok((
+              row.get::<_, i64>(0)?,
+              ArtistReadModel {
+                file_name: to_file_name(row.get::<_, String>(1)?)?,
+                name: row.get::<_, String>(2)?,
+                formed_date: row.get::<_, Option<NaiveDate>>(3)?,
+                disbanded_date: row.get::<_, Option<NaiveDate>>(4)?,
+                location: row.get::<_, Option<String>>(5)?,
+                country: row.get::<_, Option<String>>(6)?,
+                ..Default::default()
+              },
+            ))
+          })?
+          .collect::<Result<HashMap<_, _>, _>>()?;
+        let artist_id_params = Rc::new(
+          artists
+            .keys()
+            .map(|artist_id| Value::from(*artist_id))
+            .collect::<Vec<Value>>(),
+        );
+
+        let mut stmt = conn.prepare(
+          "
+          SELECT artist_genres.artist_id, genres.name
+          FROM artist_genres
+          JOIN genres ON artist_genres.genre_id = genres.id
+          WHERE artist_genres.artist_id IN rarray(?)
+          ",
+        )?;
+        let genres = stmt
+          .query_map([Rc::clone(&artist_id_params)], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        for (artist_id, genre) in genres {
+          if let Some(artist) = artists.get_mut(&artist_id) {
+            artist.genres.push(genre);
+          }
+        }
+
+        let mut stmt = conn.prepare(
+          "
+          SELECT
+            artist_relationships.artist_id,
+            artist_relationships.relationship,
+            artists.file_name,
+            artists.name
+          FROM artist_relationships
+          JOIN artists ON artist_relationships.related_artist_id = artists.id
+          WHERE artist_relationships.artist_id IN rarray(?)
+          ",
+        )?;
+        let relationships = stmt
+          .query_map([Rc::clone(&artist_id_params)], |row| {
+            Ok((
+              row.get::<_, i64>(0)?,
+              row.get::<_, String>(1)?,
+              ArtistReadModelReference {
+                file_name: to_file_name(row.get::<_, String>(2)?)?,
+                name: row.get::<_, String>(3)?,
+              },
+            ))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        for (artist_id, relationship, related_artist) in relationships {
+          if let Some(artist) = artists.get_mut(&artist_id) {
+            match relationship.as_str() {
+              MEMBER => artist.members.push(related_artist),
+              MEMBER_OF => artist.member_of.push(related_artist),
+              RELATED => artist.related_artists.push(related_artist),
+              _ => {}
+            }
+          }
+        }
+
+        let mut artists = artists
+          .into_values()
+          .map(|artist| (artist.file_name.clone(), artist))
+          .collect::<HashMap<_, _>>();
+        Ok(
+          file_names
+            .iter()
+            .filter_map(|file_name| artists.remove(file_name))
+            .collect(),
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find artists");
+        anyhow!("Failed to find artists")
+      })?
+  }
+
+  /**
+   * Finds artists whose name contains the query, ignoring ASCII case. Artists with the most albums
+   * come first.
+   */
+  #[instrument(skip(self))]
+  pub async fn search(
+    &self,
+    query: &str,
+    pagination: Option<&SearchPagination>,
+  ) -> Result<ArtistSearchResult> {
+    let pattern = format!(
+      "%{}%",
+      query
+        .trim()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+    );
+    let limit = pagination
+      .and_then(|p| p.limit)
+      .unwrap_or(DEFAULT_ARTIST_SEARCH_LIMIT) as i64;
+    let offset = pagination.and_then(|p| p.offset).unwrap_or(0) as i64;
+    let (file_names, total) = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let total = conn.query_row(
+          "SELECT COUNT(*) FROM artists WHERE name LIKE ?1 ESCAPE '\\'",
+          params![pattern],
+          |row| row.get::<_, i64>(0),
+        )?;
+        let mut stmt = conn.prepare(
+          "
+          SELECT artists.file_name
+          FROM artists
+          LEFT JOIN album_artists ON album_artists.artist_id = artists.id
+          WHERE artists.name LIKE ?1 ESCAPE '\\'
+          GROUP BY artists.id
+          ORDER BY COUNT(album_artists.album_id) DESC, artists.name
+          LIMIT ?2 OFFSET ?3
+          ",
+        )?;
+        let file_names = stmt
+          .query_map(params![pattern, limit, offset], |row| {
+            to_file_name(row.get::<_, String>(0)?)
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>((file_names, total as usize))
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to search artists");
+        anyhow!("Failed to search artists")
+      })??;
+    Ok(ArtistSearchResult {
+      artists: self.find_many(file_names).await?,
+      total,
+    })
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get(&self, file_name: &FileName) -> Result<ArtistReadModel> {
+    match self.find(file_name).await? {
+      Some(artist) => Ok(artist),
+      None => anyhow::bail!("Artist does not exist"),
+    }
+  }
+
+  /**
+   * Clears the parsed details of an artist. The artist row itself is kept, since albums may
+   * still reference it.
+   */
+  #[instrument(skip(self))]
+  pub async fn delete(&self, file_name: &FileName) -> Result<()> {
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+          "
+          DELETE FROM artist_genres
+          WHERE artist_id IN (SELECT id FROM artists WHERE file_name = ?1)
+          ",
+          params![file_name],
+        )?;
+        tx.execute(
+          "
+          DELETE FROM artist_relationships
+          WHERE artist_id IN (SELECT id FROM artists WHERE file_name = ?1)
+          ",
+          params![file_name],
+        )?;
+        tx.execute(
+          "
+          UPDATE artists
+          SET formed_date = NULL, disbanded_date = NULL, location = NULL, country = NULL
+          WHERE file_name = ?1
+          ",
+          params![file_name],
+        )?;
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete artist");
+        anyhow!("Failed to delete artist")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete_all(&self) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(|conn| {
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM artist_genres", [])?;
+        tx.execute("DELETE FROM artist_relationships", [])?;
+        tx.execute(
+          "
+          UPDATE artists
+          SET formed_date = NULL, disbanded_date = NULL, location = NULL, country = NULL
+          ",
+          [],
+        )?;
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete all artists");
+        anyhow!("Failed to delete all artists")
+      })?
+  }
+}
@@ -0,0 +1,119 @@
+use super::{
+  artist_aggregates::{ArtistAggregates, DEFAULT_ARTIST_AGGREGATE_LIMIT},
+  artist_read_model::ArtistReadModel,
+  artist_repository::ArtistRepository,
+};
+use crate::{
+  albums::{
+    album_read_model::AlbumReadModel, album_repository::AlbumRepository,
+    album_search_index::SearchPagination,
+  },
+  files::file_metadata::file_name::FileName,
+  proto,
+  sqlite::SqliteConnection,
+};
+use anyhow::Error;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+pub struct ArtistService {
+  artist_repository: ArtistRepository,
+  album_repository: Arc<dyn AlbumRepository + Send + Sync + 'static>,
+}
+
+impl ArtistService {
+  pub fn new(
+    sqlite_connection: Arc<SqliteConnection>,
+    album_repository: Arc<dyn AlbumRepository + Send + Sync + 'static>,
+  ) -> Self {
+    Self {
+      artist_repository: ArtistRepository::new(sqlite_connection),
+      album_repository,
+    }
+  }
+
+  async fn get_artist_and_albums(
+    &self,
+    file_name: &FileName,
+  ) -> Result<(ArtistReadModel, Vec<AlbumReadModel>), Status> {
+    let artist = self
+      .artist_repository
+      .find(file_name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?
+      .ok_or(Status::not_found("Artist not found"))?;
+    let albums = self
+      .album_repository
+      .find_artist_albums(vec![file_name.clone()])
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok((artist, albums))
+  }
+}
+
+#[tonic::async_trait]
+impl proto::ArtistService for ArtistService {
+  async fn get_artist(
+    &self,
+    request: Request<proto::GetArtistRequest>,
+  ) -> Result<Response<proto::GetArtistReply>, Status> {
+    let file_name = FileName::try_from(request.into_inner().file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let (artist, albums) = self.get_artist_and_albums(&file_name).await?;
+    let aggregates =
+      ArtistAggregates::from_albums(&file_name, &albums, DEFAULT_ARTIST_AGGREGATE_LIMIT);
+    Ok(Response::new(proto::GetArtistReply {
+      artist: Some(artist.into()),
+      aggregates: Some(aggregates.into()),
+    }))
+  }
+
+  async fn search_artists(
+    &self,
+    request: Request<proto::SearchArtistsRequest>,
+  ) -> Result<Response<proto::SearchArtistsReply>, Status> {
+    let request = request.into_inner();
+    let pagination: Option<SearchPagination> = request
+      .pagination
+      .map(|p| p.try_into())
+      .transpose()
+      .map_err(|e: Error| {
+        Status::invalid_argument(format!("Invalid pagination: {}", e.to_string()))
+      })?;
+    let result = self
+      .artist_repository
+      .search(&request.query, pagination.as_ref())
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::SearchArtistsReply {
+      artists: result
+        .artists
+        .into_iter()
+        .map(|artist| artist.into())
+        .collect(),
+      total: result.total as u32,
+    }))
+  }
+
+  async fn get_artist_albums(
+    &self,
+    request: Request<proto::GetArtistAlbumsRequest>,
+  ) -> Result<Response<proto::GetArtistAlbumsReply>, Status> {
+    let request = request.into_inner();
+    let file_name =
+      FileName::try_from(request.file_name).map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let include_duplicates = request.include_duplicates.unwrap_or(false);
+    let (_, mut albums) = self.get_artist_and_albums(&file_name).await?;
+    albums.retain(|album| include_duplicates || album.duplicate_of.is_none());
+    albums.sort_by(|a, b| {
+      a.release_date
+        .is_none()
+        .cmp(&b.release_date.is_none())
+        .then_with(|| a.release_date.cmp(&b.release_date))
+        .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(Response::new(proto::GetArtistAlbumsReply {
+      albums: albums.into_iter().map(|album| album.into()).collect(),
+    }))
+  }
+}
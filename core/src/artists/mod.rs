@@ -0,0 +1,5 @@
+pub mod artist_aggregates;
+pub mod artist_event_subscribers;
+pub mod artist_read_model;
+pub mod artist_repository;
+pub mod artist_service;
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use core::{
+  files::{file_content_store::FileContentStore, file_metadata::file_name::FileName},
+  parser::parser_fixtures::{list_parser_fixtures, ParserFixture},
+  settings::Settings,
+};
+use dotenv::dotenv;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::env;
+
+lazy_static! {
+  static ref SCRIPT_RE: Regex = Regex::new(r"(?is)<script\b.*?</script>").unwrap();
+  static ref COMMENT_RE: Regex = Regex::new(r"(?s)<!--.*?-->").unwrap();
+  static ref PROFILE_LINK_RE: Regex = Regex::new(r#"/~[^"'/\s<>]+"#).unwrap();
+  static ref REVIEW_USER_RE: Regex =
+    Regex::new(r#"(?s)(class="[^"]*\breview_user\b[^"]*"[^>]*>)\s*(?:<a\b[^>]*>)?[^<]*(?:</a>)?"#)
+      .unwrap();
+}
+
+/**
+ * Strips scripts, comments and user identities from a crawled page before it is checked in.
+ * Review authors are replaced with a placeholder, which is what their snapshots will show.
+ */
+fn anonymize_page(content: &str) -> String {
+  let content = SCRIPT_RE.replace_all(content, "");
+  let content = COMMENT_RE.replace_all(&content, "");
+  let content = REVIEW_USER_RE.replace_all(&content, "${1}anonymous");
+  PROFILE_LINK_RE
+    .replace_all(&content, "/~anonymous")
+    .to_string()
+}
+
+/**
+ * Refreshes the parser fixture corpus.
+ *
+ * Without arguments, re-renders the snapshot of every checked in fixture, which is how parser
+ * changes are accepted. With file names as arguments, pulls those pages from the content store,
+ * anonymizes them and adds them to the corpus along with their snapshots.
+ */
+#[tokio::main]
+async fn main() -> Result<()> {
+  let file_names = env::args()
+    .skip(1)
+    .map(FileName::try_from)
+    .collect::<Result<Vec<FileName>>>()?;
+
+  if file_names.is_empty() {
+    for fixture in list_parser_fixtures()? {
+      fixture.write_snapshot()?;
+      println!("Refreshed {}", fixture.snapshot_path.display());
+    }
+    return Ok(());
+  }
+
+  dotenv().ok();
+  let settings = Settings::new()?;
+  let file_content_store = FileContentStore::new(&settings.file.content_store)?;
+  for file_name in file_names {
+    let fixture = ParserFixture::for_file_name(&file_name);
+    let content = file_content_store.get(&file_name).await?;
+    fixture.write_page(&anonymize_page(&content))?;
+    fixture.write_snapshot()?;
+    println!("Added {}", fixture.page_path.display());
+  }
+  Ok(())
+}
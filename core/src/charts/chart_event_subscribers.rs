@@ -0,0 +1,85 @@
+use super::{chart_read_model::ChartReadModelPage, chart_repository::ChartRepository};
+use crate::{
+  events::{
+    event::{Event, Stream},
+    event_subscriber::{EventSubscriber, EventSubscriberBuilder, SubscriberContext},
+  },
+  files::file_metadata::page_type::PageType,
+  parser::parsed_file_data::ParsedFileData,
+  settings::Settings,
+  sqlite::SqliteConnection,
+};
+use anyhow::Result;
+use rustis::{bb8::Pool, client::PooledClientManager};
+use std::sync::Arc;
+
+fn is_chart_deletion(event: &Event) -> bool {
+  match event {
+    Event::FileDeleted { file_name, .. } => file_name.page_type() == PageType::Chart,
+    _ => false,
+  }
+}
+
+/**
+ * Projects both parsed and deleted chart pages, so that a replay applies deletions in order with
+ * the parses they follow.
+ */
+async fn update_chart_read_models(context: SubscriberContext) -> Result<()> {
+  let chart_repository = ChartRepository::new(Arc::clone(&context.sqlite_connection));
+  match context.payload.event {
+    Event::FileParsed {
+      file_name,
+      data: ParsedFileData::Chart(parsed_chart),
+      ..
+    } => {
+      if let Some(page) = ChartReadModelPage::from_parsed_chart(&file_name, parsed_chart) {
+        chart_repository.put_page(page).await?;
+      }
+    }
+    Event::FileDeleted { file_name, .. } if file_name.page_type() == PageType::Chart => {
+      chart_repository.delete_page(&file_name).await?;
+    }
+    _ => {}
+  }
+  Ok(())
+}
+
+async fn reset_chart_read_models(sqlite_connection: Arc<SqliteConnection>) -> Result<()> {
+  let chart_repository = ChartRepository::new(sqlite_connection);
+  chart_repository.delete_all().await
+}
+
+pub fn build_chart_event_subscribers(
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
+  settings: Arc<Settings>,
+) -> Result<Vec<EventSubscriber>> {
+  let reset_sqlite_connection = Arc::clone(&sqlite_connection);
+  Ok(vec![EventSubscriberBuilder::default()
+    .id("update_chart_read_models")
+    .stream(Stream::Parser)
+    .stream(Stream::File)
+    .batch_size(250)
+    .redis_connection_pool(Arc::clone(&redis_connection_pool))
+    .sqlite_connection(Arc::clone(&sqlite_connection))
+    .settings(Arc::clone(&settings))
+    .generate_ordered_processing_group_id(Arc::new(|row| match &row.payload.event {
+      Event::FileParsed {
+        file_name,
+        data: ParsedFileData::Chart(_),
+        ..
+      } => file_name
+        .chart_first_page()
+        .map(|file_name| file_name.to_string()),
+      _ => None,
+    }))
+    .is_barrier(Arc::new(|row| is_chart_deletion(&row.payload.event)))
+    .handle(Arc::new(|context| {
+      Box::pin(async move { update_chart_read_models(context).await })
+    }))
+    .reset(Arc::new(move || {
+      let sqlite_connection = Arc::clone(&reset_sqlite_connection);
+      Box::pin(async move { reset_chart_read_models(sqlite_connection).await })
+    }))
+    .build()?])
+}
@@ -0,0 +1,91 @@
+use crate::{
+  files::file_metadata::file_name::FileName,
+  parser::parsed_file_data::{ParsedChart, ParsedChartAlbum},
+  proto,
+};
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct ChartReadModelAlbum {
+  pub file_name: FileName,
+  pub name: String,
+  pub rating: f32,
+  pub rating_count: u32,
+  pub position: Option<u32>,
+  pub page_number: u32,
+}
+
+/**
+ * A single parsed page of a chart, keyed by the file name of the chart's first page.
+ */
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct ChartReadModelPage {
+  pub file_name: FileName,
+  pub page_number: u32,
+  pub total_pages: Option<u32>,
+  pub albums: Vec<ChartReadModelAlbum>,
+}
+
+/**
+ * Ranked membership of a chart across all of its parsed pages.
+ */
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct ChartReadModel {
+  pub file_name: FileName,
+  pub total_pages: Option<u32>,
+  pub albums: Vec<ChartReadModelAlbum>,
+}
+
+impl ChartReadModelAlbum {
+  pub fn from_parsed_chart_album(page_number: u32, parsed_album: ParsedChartAlbum) -> Self {
+    Self {
+      file_name: parsed_album.file_name,
+      name: parsed_album.name,
+      rating: parsed_album.rating,
+      rating_count: parsed_album.rating_count,
+      position: parsed_album.position,
+      page_number,
+    }
+  }
+}
+
+impl ChartReadModelPage {
+  pub fn from_parsed_chart(file_name: &FileName, parsed_chart: ParsedChart) -> Option<Self> {
+    let page_number = file_name
+      .chart_page_number()
+      .unwrap_or(parsed_chart.page_number);
+    Some(Self {
+      file_name: file_name.chart_first_page()?,
+      page_number,
+      total_pages: parsed_chart.total_pages,
+      albums: parsed_chart
+        .albums
+        .into_iter()
+        .map(|album| ChartReadModelAlbum::from_parsed_chart_album(page_number, album))
+        .collect(),
+    })
+  }
+}
+
+impl From<ChartReadModelAlbum> for proto::ChartAlbum {
+  fn from(val: ChartReadModelAlbum) -> Self {
+    proto::ChartAlbum {
+      file_name: val.file_name.to_string(),
+      name: val.name,
+      rating: val.rating,
+      rating_count: val.rating_count,
+      position: val.position,
+      page_number: val.page_number,
+    }
+  }
+}
+
+impl From<ChartReadModel> for proto::Chart {
+  fn from(val: ChartReadModel) -> Self {
+    proto::Chart {
+      file_name: val.file_name.to_string(),
+      total_pages: val.total_pages,
+      albums: val.albums.into_iter().map(|album| album.into()).collect(),
+    }
+  }
+}
@@ -0,0 +1,221 @@
+use super::chart_read_model::{ChartReadModel, ChartReadModelAlbum, ChartReadModelPage};
+use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
+use anyhow::{anyhow, Result};
+use rusqlite::{params, OptionalExtension};
+use std::sync::Arc;
+use tracing::{error, instrument};
+
+/**
+ * Stores chart membership page by page, so that re-parsing one page of a chart only replaces
+ * the albums ranked on that page.
+ */
+#[derive(Debug, Clone)]
+pub struct ChartRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+fn to_file_name(value: String) -> rusqlite::Result<FileName> {
+  FileName::try_from(value).map_err(|e| {
+    error!(message = e.to_string(), "Failed to parse chart file name");
+    rusqlite::Error::ExecuteReturnedResults
+  })
+}
+
+impl ChartRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  #[instrument(skip_all, fields(file_name = page.file_name.to_string(), page_number = page.page_number))]
+  pub async fn put_page(&self, page: ChartReadModelPage) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        let chart_id: i64 = tx.query_row(
+          "
+          INSERT INTO charts (file_name, total_pages)
+          VALUES (?, ?)
+          ON CONFLICT(file_name) DO UPDATE SET
+            total_pages = COALESCE(excluded.total_pages, charts.total_pages)
+          RETURNING id
+          ",
+          params![page.file_name.to_string(), page.total_pages],
+          |row| row.get(0),
+        )?;
+        tx.execute(
+          "DELETE FROM chart_albums WHERE chart_id = ? AND page_number = ?",
+          params![chart_id, page.page_number],
+        )?;
+        for album in page.albums {
+          tx.execute(
+            "
+            INSERT INTO chart_albums (
+              chart_id, page_number, position, file_name, name, rating, rating_count
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(chart_id, file_name) DO UPDATE SET
+              page_number = excluded.page_number,
+              position = excluded.position,
+              name = excluded.name,
+              rating = excluded.rating,
+              rating_count = excluded.rating_count
+            ",
+            params![
+              chart_id,
+              album.page_number,
+              album.position,
+              album.file_name.to_string(),
+              album.name,
+              album.rating,
+              album.rating_count
+            ],
+          )?;
+        }
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put chart page");
+        anyhow!("Failed to put chart page")
+      })?
+  }
+
+  /**
+   * Finds a chart by the file name of any of its pages.
+   */
+  #[instrument(skip(self))]
+  pub async fn find(&self, file_name: &FileName) -> Result<Option<ChartReadModel>> {
+    let file_name = match file_name.chart_first_page() {
+      Some(file_name) => file_name.to_string(),
+      None => return Ok(None),
+    };
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let chart = conn
+          .query_row(
+            "SELECT id, file_name, total_pages FROM charts WHERE file_name = ?",
+            params![file_name],
+            |row| {
+              Ok((
+                row.get::<_, i64>(0)?,
+                ChartReadModel {
+                  file_name: to_file_name(row.get::<_, String>(1)?)?,
+                  total_pages: row.get::<_, Option<u32>>(2)?,
+                  albums: vec![],
+                },
+              ))
+            },
+          )
+          .optional()?;
+        let (chart_id, mut chart) = match chart {
+          Some(chart) => chart,
+          None => return Ok(None),
+        };
+
+        let mut stmt = conn.prepare(
+          "
+          SELECT file_name, name, rating, rating_count, position, page_number
+          FROM chart_albums
+          WHERE chart_id = ?
+          ORDER BY page_number, position IS NULL, position
+          ",
+        )?;
+        chart.albums = stmt
+          .query_map(params![chart_id], |row| {
+            Ok(ChartReadModelAlbum {
+              file_name: to_file_name(row.get::<_, String>(0)?)?,
+              name: row.get::<_, String>(1)?,
+              rating: row.get::<_, f32>(2)?,
+              rating_count: row.get::<_, u32>(3)?,
+              position: row.get::<_, Option<u32>>(4)?,
+              page_number: row.get::<_, u32>(5)?,
+            })
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(chart))
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find chart");
+        anyhow!("Failed to find chart")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get(&self, file_name: &FileName) -> Result<ChartReadModel> {
+    match self.find(file_name).await? {
+      Some(chart) => Ok(chart),
+      None => anyhow::bail!("Chart does not exist"),
+    }
+  }
+
+  /**
+   * Deletes the albums ranked on a single chart page, and the chart itself once none of its
+   * pages remain.
+   */
+  #[instrument(skip(self))]
+  pub async fn delete_page(&self, file_name: &FileName) -> Result<()> {
+    let (chart_file_name, page_number) =
+      match (file_name.chart_first_page(), file_name.chart_page_number()) {
+        (Some(chart_file_name), Some(page_number)) => (chart_file_name.to_string(), page_number),
+        _ => return Ok(()),
+      };
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+          "
+          DELETE FROM chart_albums
+          WHERE chart_id IN (SELECT id FROM charts WHERE file_name = ?1) AND page_number = ?2
+          ",
+          params![chart_file_name, page_number],
+        )?;
+        tx.execute(
+          "
+          DELETE FROM charts
+          WHERE file_name = ?1
+            AND NOT EXISTS (SELECT 1 FROM chart_albums WHERE chart_albums.chart_id = charts.id)
+          ",
+          params![chart_file_name],
+        )?;
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete chart page");
+        anyhow!("Failed to delete chart page")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete_all(&self) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(|conn| {
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM chart_albums", [])?;
+        tx.execute("DELETE FROM charts", [])?;
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete all charts");
+        anyhow!("Failed to delete all charts")
+      })?
+  }
+}
@@ -0,0 +1,36 @@
+use super::chart_repository::ChartRepository;
+use crate::{files::file_metadata::file_name::FileName, proto, sqlite::SqliteConnection};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+pub struct ChartService {
+  chart_repository: ChartRepository,
+}
+
+impl ChartService {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self {
+      chart_repository: ChartRepository::new(sqlite_connection),
+    }
+  }
+}
+
+#[tonic::async_trait]
+impl proto::ChartService for ChartService {
+  async fn get_chart(
+    &self,
+    request: Request<proto::GetChartRequest>,
+  ) -> Result<Response<proto::GetChartReply>, Status> {
+    let file_name = FileName::try_from(request.into_inner().file_name)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    let chart = self
+      .chart_repository
+      .find(&file_name)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?
+      .ok_or(Status::not_found("Chart not found"))?;
+    Ok(Response::new(proto::GetChartReply {
+      chart: Some(chart.into()),
+    }))
+  }
+}
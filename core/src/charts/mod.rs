@@ -0,0 +1,4 @@
+pub mod chart_event_subscribers;
+pub mod chart_read_model;
+pub mod chart_repository;
+pub mod chart_service;
@@ -1,6 +1,6 @@
 pub mod crawler;
 pub mod crawler_interactor;
 pub mod crawler_service;
-mod crawler_state_repository;
+pub mod crawler_state_repository;
 mod crawler_worker;
 pub mod priority_queue;
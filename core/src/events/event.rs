@@ -1,6 +1,9 @@
+use super::event_upcaster::{EVENT_SCHEMA_VERSION, EVENT_UPCASTER_REGISTRY};
 use crate::files::file_metadata::file_name::FileName;
+use crate::files::file_metadata::page_type::PageType;
 use crate::lookup::album_search_lookup::AlbumSearchLookup;
 use crate::parser::parsed_file_data::ParsedFileData;
+use crate::parser::parser_error::ParserError;
 use crate::profile::profile::ProfileId;
 use crate::proto;
 use anyhow::{anyhow, Result};
@@ -29,12 +32,13 @@ pub enum Event {
     file_id: Ulid,
     file_name: FileName,
     data: ParsedFileData,
+    parser_version: u32,
   },
   FileParseFailed {
     #[serde(with = "ulid_as_u128")]
     file_id: Ulid,
     file_name: FileName,
-    error: String,
+    error: ParserError,
   },
   ProfileAlbumAdded {
     profile_id: ProfileId,
@@ -44,6 +48,33 @@ pub enum Event {
   LookupAlbumSearchUpdated {
     lookup: AlbumSearchLookup,
   },
+  ParserDegraded {
+    page_type: PageType,
+    error: Option<String>,
+    failure_rate: f32,
+    baseline_failure_rate: f32,
+    sample_count: u32,
+  },
+}
+
+impl Event {
+  pub fn file_name(&self) -> Option<&FileName> {
+    match self {
+      Event::FileSaved { file_name, .. }
+      | Event::FileDeleted { file_name, .. }
+      | Event::FileParsed { file_name, .. }
+      | Event::FileParseFailed { file_name, .. }
+      | Event::ProfileAlbumAdded { file_name, .. } => Some(file_name),
+      Event::LookupAlbumSearchUpdated { .. } | Event::ParserDegraded { .. } => None,
+    }
+  }
+
+  pub fn page_type(&self) -> Option<PageType> {
+    match self {
+      Event::ParserDegraded { page_type, .. } => Some(page_type.clone()),
+      _ => self.file_name().map(|file_name| file_name.page_type()),
+    }
+  }
 }
 
 impl From<Event> for proto::Event {
@@ -66,10 +97,12 @@ impl From<Event> for proto::Event {
           file_id,
           file_name,
           data,
+          parser_version,
         } => proto::event::Event::FileParsed(proto::FileParsedEvent {
           file_id: file_id.to_string(),
           file_name: file_name.to_string(),
           data: Some(data.into()),
+          parser_version,
         }),
         Event::FileParseFailed {
           file_id,
@@ -78,7 +111,8 @@ impl From<Event> for proto::Event {
         } => proto::event::Event::FileParseFailed(proto::FileParseFailedEvent {
           file_id: file_id.to_string(),
           file_name: file_name.to_string(),
-          error,
+          error: error.to_string(),
+          parser_error: Some(error.into()),
         }),
         Event::ProfileAlbumAdded {
           profile_id,
@@ -94,6 +128,19 @@ impl From<Event> for proto::Event {
             lookup: Some(lookup.into()),
           })
         }
+        Event::ParserDegraded {
+          page_type,
+          error,
+          failure_rate,
+          baseline_failure_rate,
+          sample_count,
+        } => proto::event::Event::ParserDegraded(proto::ParserDegradedEvent {
+          page_type: proto::PageType::from(page_type).into(),
+          error,
+          failure_rate,
+          baseline_failure_rate,
+          sample_count,
+        }),
       }),
     }
   }
@@ -137,6 +184,10 @@ impl From<EventPayload> for HashMap<String, String> {
       "metadata".to_string(),
       serde_json::to_string(&val.metadata.unwrap_or(HashMap::new())).unwrap(),
     );
+    result.insert(
+      "schema_version".to_string(),
+      EVENT_SCHEMA_VERSION.to_string(),
+    );
     if let Some(correlation_id) = val.correlation_id {
       result.insert("correlation_id".to_string(), correlation_id);
     }
@@ -147,11 +198,20 @@ impl From<EventPayload> for HashMap<String, String> {
 impl TryFrom<&HashMap<String, String>> for EventPayload {
   type Error = anyhow::Error;
 
+  /**
+   * Payloads without a schema version predate versioning, and are upcast from the first version.
+   */
   fn try_from(value: &HashMap<String, String>) -> Result<Self> {
-    let event = serde_json::from_str::<Event>(
+    let schema_version = value
+      .get("schema_version")
+      .map(|value| value.parse::<u32>())
+      .transpose()?
+      .unwrap_or(1);
+    let event = EVENT_UPCASTER_REGISTRY.deserialize(
       value
         .get("event")
         .ok_or(anyhow!("event not found in payload"))?,
+      schema_version,
     )?;
     let correlation_id = value.get("correlation_id").map(|value| value.to_string());
     let causation_id = value.get("causation_id").map(|value| value.to_string());
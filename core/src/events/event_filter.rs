@@ -0,0 +1,67 @@
+use super::event::{EventKind, EventPayload};
+use crate::{files::file_metadata::page_type::PageType, proto};
+use anyhow::{anyhow, Result};
+
+/**
+ * Server-side filter applied to events before they are handed to a consumer.
+ * Empty lists match everything.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+  pub event_kinds: Vec<EventKind>,
+  pub page_types: Vec<PageType>,
+  pub correlation_id_prefix: Option<String>,
+}
+
+impl EventFilter {
+  pub fn matches(&self, payload: &EventPayload) -> bool {
+    if !self.event_kinds.is_empty() && !self.event_kinds.contains(&payload.event.kind()) {
+      return false;
+    }
+
+    if !self.page_types.is_empty() {
+      match payload.event.page_type() {
+        Some(page_type) if self.page_types.contains(&page_type) => {}
+        _ => return false,
+      }
+    }
+
+    if let Some(prefix) = &self.correlation_id_prefix {
+      match &payload.correlation_id {
+        Some(correlation_id) if correlation_id.starts_with(prefix) => {}
+        _ => return false,
+      }
+    }
+
+    true
+  }
+}
+
+impl TryFrom<proto::EventStreamFilter> for EventFilter {
+  type Error = anyhow::Error;
+
+  fn try_from(value: proto::EventStreamFilter) -> Result<Self> {
+    let event_kinds = value
+      .event_kinds
+      .iter()
+      .map(|kind| {
+        kind
+          .parse::<EventKind>()
+          .map_err(|_| anyhow!("Invalid event kind: {}", kind))
+      })
+      .collect::<Result<Vec<_>>>()?;
+    let page_types = value
+      .page_types
+      .iter()
+      .map(|page_type| {
+        PageType::try_from(*page_type).map_err(|_| anyhow!("Invalid page type: {}", page_type))
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    Ok(Self {
+      event_kinds,
+      page_types,
+      correlation_id_prefix: value.correlation_id_prefix,
+    })
+  }
+}
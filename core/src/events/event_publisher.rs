@@ -1,4 +1,5 @@
 use super::event::{EventPayload, Stream};
+use super::event_upcaster::EVENT_SCHEMA_VERSION;
 use crate::{settings::Settings, sqlite::SqliteConnection};
 use anyhow::{anyhow, Result};
 use std::sync::Arc;
@@ -21,7 +22,7 @@ impl EventPublisher {
   pub async fn publish(&self, stream: Stream, payload: EventPayload) -> Result<()> {
     self.sqlite_connection.write().await?.interact(move |conn| {
       conn.execute(
-        "INSERT INTO events (correlation_id, causation_id, event, metadata, stream) VALUES (?1, ?2, ?3, ?4, ?5)",
+        "INSERT INTO events (correlation_id, causation_id, event, metadata, stream, schema_version) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
         (
           &payload.correlation_id,
           &payload.causation_id,
@@ -30,6 +31,7 @@ impl EventPublisher {
           serde_json::to_string(&payload.metadata)
               .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?,
           &stream.tag(),
+          EVENT_SCHEMA_VERSION,
         ),
       )?;
       Ok(())
@@ -46,7 +48,7 @@ impl EventPublisher {
       let transaction = conn.transaction()?;
       for payload in payloads {
         let mut statement = transaction.prepare(
-        "INSERT INTO events (correlation_id, causation_id, event, metadata, stream) VALUES (?1, ?2, ?3, ?4, ?5)",
+        "INSERT INTO events (correlation_id, causation_id, event, metadata, stream, schema_version) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
         )?;
         statement.execute((
           &payload.correlation_id,
@@ -56,6 +58,7 @@ impl EventPublisher {
           serde_json::to_string(&payload.metadata)
               .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?,
           &stream.tag(),
+          EVENT_SCHEMA_VERSION,
         ))?;
       }
       transaction.commit()?;
@@ -1,9 +1,10 @@
+use super::event_filter::EventFilter;
 use super::event_subscriber_repository::{
-  EventSubscriberRepository, EventSubscriberRow, EventSubscriberStatus,
+  EventSubscriberRepository, EventSubscriberRow, EventSubscriberStatus, ExternalEventSubscriber,
 };
-use crate::{proto, sqlite::SqliteConnection};
+use crate::{proto, sqlite::SqliteConnection, webhooks::webhook::WEBHOOK_SUBSCRIBER_ID_PREFIX};
 use futures::{try_join, Stream};
-use std::{pin::Pin, sync::Arc, time::Duration};
+use std::{collections::HashSet, pin::Pin, sync::Arc, time::Duration};
 use tokio::time::sleep;
 use tonic::{Request, Response, Status, Streaming};
 
@@ -13,6 +14,23 @@ impl Into<proto::EventSubscriberStatus> for EventSubscriberStatus {
       EventSubscriberStatus::Paused => proto::EventSubscriberStatus::SubscriberPaused,
       EventSubscriberStatus::Running => proto::EventSubscriberStatus::SubscriberRunning,
       EventSubscriberStatus::Draining => proto::EventSubscriberStatus::SubscriberDraining,
+      EventSubscriberStatus::ReplayRequested => {
+        proto::EventSubscriberStatus::SubscriberReplayRequested
+      }
+      EventSubscriberStatus::Replaying => proto::EventSubscriberStatus::SubscriberReplaying,
+    }
+  }
+}
+
+impl Into<proto::ExternalEventSubscriber> for ExternalEventSubscriber {
+  fn into(self) -> proto::ExternalEventSubscriber {
+    proto::ExternalEventSubscriber {
+      stream_ids: self.streams.iter().map(|stream| stream.tag()).collect(),
+      owner: self.owner,
+      description: self.description,
+      created_at: self.created_at.to_string(),
+      last_seen_at: self.last_seen_at.map(|val| val.to_string()),
+      lag: self.lag,
     }
   }
 }
@@ -23,18 +41,83 @@ impl Into<proto::EventSubscriberSnapshot> for EventSubscriberRow {
       id: self.id,
       cursor: self.cursor,
       status: Into::<proto::EventSubscriberStatus>::into(self.status).into(),
+      external: self.external.map(|external| external.into()),
     }
   }
 }
 
+/**
+ * The next batch of events after the cursor of a subscriber that match the filter, or None once
+ * the subscriber is caught up. Filtered out events still advance the cursor, so a batch without
+ * any matching event is skipped over without a round trip to the consumer.
+ */
+pub async fn next_event_stream_reply(
+  event_subscriber_repository: &EventSubscriberRepository,
+  stream: &super::event::Stream,
+  subscriber_id: &str,
+  filter: Option<&EventFilter>,
+  max_batch_size: usize,
+) -> anyhow::Result<Option<proto::EventStreamReply>> {
+  loop {
+    let event_list = event_subscriber_repository
+      .get_events_after_cursor(&vec![stream.clone()], subscriber_id, max_batch_size)
+      .await?;
+    let tail_cursor = match event_list.tail_cursor() {
+      Some(tail_cursor) => tail_cursor,
+      None => return Ok(None),
+    };
+    let items = event_list
+      .rows
+      .into_iter()
+      .filter(|row| match filter {
+        Some(filter) => filter.matches(&row.payload),
+        None => true,
+      })
+      .map(|row| proto::EventStreamItem {
+        entry_id: row.id.clone(),
+        payload: Some(row.payload.into()),
+        stream_id: stream.tag(),
+        timestamp: row
+          .id
+          .clone()
+          .split('-')
+          .next()
+          .expect("Invalid event stream item ID")
+          .parse::<u64>()
+          .expect("Invalid event stream item ID"),
+      })
+      .collect::<Vec<_>>();
+
+    if items.is_empty() {
+      event_subscriber_repository
+        .set_cursor(subscriber_id, &tail_cursor)
+        .await?;
+      continue;
+    }
+
+    return Ok(Some(proto::EventStreamReply {
+      items,
+      cursor: tail_cursor,
+    }));
+  }
+}
+
 pub struct EventService {
   event_subscriber_repository: EventSubscriberRepository,
+  /**
+   * Ids of the built-in subscribers, which external subscribers can't register with.
+   */
+  internal_subscriber_ids: HashSet<String>,
 }
 
 impl EventService {
-  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+  pub fn new(
+    sqlite_connection: Arc<SqliteConnection>,
+    internal_subscriber_ids: HashSet<String>,
+  ) -> Self {
     Self {
       event_subscriber_repository: EventSubscriberRepository::new(sqlite_connection),
+      internal_subscriber_ids,
     }
   }
 }
@@ -88,6 +171,137 @@ impl proto::EventService for EventService {
     Ok(Response::new(reply))
   }
 
+  async fn register_subscriber(
+    &self,
+    request: Request<proto::RegisterSubscriberRequest>,
+  ) -> Result<Response<()>, Status> {
+    let request = request.into_inner();
+    if request.subscriber_id.is_empty() {
+      return Err(Status::invalid_argument("Subscriber ID is required"));
+    }
+    let streams = request
+      .stream_ids
+      .into_iter()
+      .map(super::event::Stream::try_from)
+      .collect::<anyhow::Result<Vec<_>>>()
+      .map_err(|err| Status::invalid_argument(err.to_string()))?;
+    if streams.is_empty() {
+      return Err(Status::invalid_argument("At least one stream is required"));
+    }
+    if self
+      .internal_subscriber_ids
+      .contains(&request.subscriber_id)
+      || request
+        .subscriber_id
+        .starts_with(WEBHOOK_SUBSCRIBER_ID_PREFIX)
+    {
+      return Err(Status::already_exists(format!(
+        "Subscriber {} is an internal subscriber",
+        request.subscriber_id
+      )));
+    }
+    self
+      .event_subscriber_repository
+      .register_external_subscriber(
+        &request.subscriber_id,
+        streams,
+        request.owner,
+        request.description,
+      )
+      .await
+      .map_err(|err| Status::internal(err.to_string()))?;
+    Ok(Response::new(()))
+  }
+
+  async fn unregister_subscriber(
+    &self,
+    request: Request<proto::UnregisterSubscriberRequest>,
+  ) -> Result<Response<()>, Status> {
+    let request = request.into_inner();
+    let deleted = self
+      .event_subscriber_repository
+      .unregister_external_subscriber(&request.subscriber_id)
+      .await
+      .map_err(|err| Status::internal(err.to_string()))?;
+    if !deleted {
+      return Err(Status::not_found(format!(
+        "External subscriber not found: {}",
+        request.subscriber_id
+      )));
+    }
+    Ok(Response::new(()))
+  }
+
+  async fn replay_subscribers(
+    &self,
+    request: Request<proto::ReplaySubscribersRequest>,
+  ) -> Result<Response<()>, Status> {
+    let request = request.into_inner();
+    for subscriber_id in &request.subscriber_ids {
+      let status = self
+        .event_subscriber_repository
+        .get_status(subscriber_id)
+        .await
+        .map_err(|err| Status::internal(err.to_string()))?;
+      if status.is_none() {
+        return Err(Status::not_found(format!(
+          "Subscriber not found: {}",
+          subscriber_id
+        )));
+      }
+    }
+    for subscriber_id in &request.subscriber_ids {
+      self
+        .event_subscriber_repository
+        .set_status(subscriber_id, EventSubscriberStatus::ReplayRequested)
+        .await
+        .map_err(|err| Status::internal(err.to_string()))?;
+    }
+    Ok(Response::new(()))
+  }
+
+  async fn get_replay_progress(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<proto::GetReplayProgressReply>, Status> {
+    let (subscribers, stream_tails) = try_join!(
+      self.event_subscriber_repository.get_subscribers(),
+      self.event_subscriber_repository.get_stream_tails(),
+    )
+    .map_err(|err| Status::internal(err.to_string()))?;
+    let tail = stream_tails
+      .into_iter()
+      .filter_map(|(_, tail)| tail.parse::<u64>().ok())
+      .max()
+      .unwrap_or(0);
+    let subscribers = subscribers
+      .into_iter()
+      .filter(|subscriber| {
+        matches!(
+          subscriber.status,
+          EventSubscriberStatus::ReplayRequested | EventSubscriberStatus::Replaying
+        )
+      })
+      .map(|subscriber| {
+        let cursor = subscriber.cursor.parse::<u64>().unwrap_or(0);
+        // The cursor is only rewound once the subscriber picks up the replay request
+        let progress = match subscriber.status {
+          EventSubscriberStatus::ReplayRequested => 0.0,
+          _ if tail == 0 => 1.0,
+          _ => (cursor.min(tail) as f32) / (tail as f32),
+        };
+        proto::SubscriberReplayProgress {
+          subscriber_id: subscriber.id,
+          status: Into::<proto::EventSubscriberStatus>::into(subscriber.status).into(),
+          cursor: subscriber.cursor,
+          tail: tail.to_string(),
+          progress,
+        }
+      })
+      .collect();
+    Ok(Response::new(proto::GetReplayProgressReply { subscribers }))
+  }
+
   async fn stream(
     &self,
     request: Request<Streaming<proto::EventStreamRequest>>,
@@ -96,41 +310,53 @@ impl proto::EventService for EventService {
     let event_subscriber_repository = self.event_subscriber_repository.clone();
     let output_stream = async_stream::try_stream! {
       while let Ok(Some(event_stream_request)) = input_stream.message().await {
-        loop {
+        let filter = event_stream_request
+          .filter
+          .clone()
+          .map(EventFilter::try_from)
+          .transpose()
+          .map_err(|err| Status::invalid_argument(err.to_string()))?;
+        let registered_streams = event_subscriber_repository
+          .get_external_subscriber_streams(&event_stream_request.subscriber_id)
+          .await
+          .map_err(|err| Status::internal(err.to_string()))?;
+        if let Some(registered_streams) = registered_streams {
           let stream_id = super::event::Stream::try_from(event_stream_request.stream_id.clone())
             .map_err(|err| Status::invalid_argument(err.to_string()))?;
-          if let Some(cursor) = event_stream_request.cursor.clone() {
-            event_subscriber_repository.set_cursor(
-              &event_stream_request.subscriber_id,
-              &cursor,
-            )
+          if !registered_streams.contains(&stream_id) {
+            Err(Status::permission_denied(format!(
+              "Subscriber {} is not registered for stream {}",
+              event_stream_request.subscriber_id,
+              stream_id.tag()
+            )))?;
+          }
+          event_subscriber_repository
+            .touch_external_subscriber(&event_stream_request.subscriber_id)
             .await
             .map_err(|err| Status::internal(err.to_string()))?;
-          }
-          let event_list = event_subscriber_repository.get_events_after_cursor(
-            &vec![stream_id.clone()],
+        }
+        if let Some(cursor) = event_stream_request.cursor.clone() {
+          event_subscriber_repository.set_cursor(
             &event_stream_request.subscriber_id,
+            &cursor,
+          )
+          .await
+          .map_err(|err| Status::internal(err.to_string()))?;
+        }
+        loop {
+          let stream_id = super::event::Stream::try_from(event_stream_request.stream_id.clone())
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+          let reply = next_event_stream_reply(
+            &event_subscriber_repository,
+            &stream_id,
+            &event_stream_request.subscriber_id,
+            filter.as_ref(),
             event_stream_request.max_batch_size.unwrap_or(10) as usize,
           )
           .await
           .map_err(|err| Status::internal(err.to_string()))?;
-
-          let tail_cursor = event_list.tail_cursor().clone();
-          if let Some(tail_cursor) = tail_cursor {
-            yield proto::EventStreamReply {
-              items: event_list.rows.into_iter().map(|row| {
-                proto::EventStreamItem {
-                  entry_id: row.id.clone(),
-                  payload: Some(row.payload.into()),
-                  stream_id: stream_id.tag(),
-                  timestamp: row.id.clone().split('-').next()
-                    .expect("Invalid event stream item ID")
-                    .parse::<u64>()
-                    .expect("Invalid event stream item ID")
-                }
-              }).collect(),
-              cursor: tail_cursor.clone(),
-            };
+          if let Some(reply) = reply {
+            yield reply;
             break;
           }
           sleep(Duration::from_secs(2)).await;
@@ -2,8 +2,11 @@ use crate::settings::Settings;
 use crate::sqlite::SqliteConnection;
 
 use super::event::{EventPayload, Stream};
-use super::event_subscriber_repository::{EventRow, EventSubscriberRepository};
+use super::event_subscriber_repository::{
+  EventRow, EventSubscriberRepository, EventSubscriberStatus,
+};
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use derive_builder::Builder;
 use futures::future::{join_all, BoxFuture};
 use iter_tools::Itertools;
@@ -11,11 +14,12 @@ use rustis::{bb8::Pool, client::PooledClientManager};
 use std::collections::HashMap;
 use std::{sync::Arc, time::Duration};
 use tokio::time::sleep;
-use tracing::{debug, error};
+use tracing::{debug, error, info};
 
 pub struct SubscriberContext {
   pub entry_id: String,
   pub stream: Stream,
+  pub created_at: NaiveDateTime,
   pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
   pub sqlite_connection: Arc<SqliteConnection>,
   pub settings: Arc<Settings>,
@@ -26,6 +30,11 @@ pub struct SubscriberContext {
 pub struct EventSubscriber {
   #[builder(default = "10")]
   pub batch_size: usize,
+  /**
+   * Batch size used while replaying the event log, where throughput matters more than latency.
+   */
+  #[builder(default = "1000")]
+  pub replay_batch_size: usize,
   pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
   pub sqlite_connection: Arc<SqliteConnection>,
   pub settings: Arc<Settings>,
@@ -48,6 +57,26 @@ pub struct EventSubscriber {
   )]
   generate_ordered_processing_group_id:
     Option<Arc<dyn Fn(&EventRow) -> Option<String> + Send + Sync>>,
+  /**
+   * Truncates the projection maintained by this subscriber. Called when a replay is requested, before the cursor is rewound to the start of the event log.
+   */
+  #[builder(default, setter(strip_option))]
+  reset: Option<Arc<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>>,
+  /**
+   * Called once a replay has caught up with the tail of the event log, before the subscriber is set back to running.
+   */
+  #[builder(default, setter(strip_option))]
+  finish_replay: Option<Arc<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>>,
+  /**
+   * Returns whether an event is a barrier. A barrier is processed on its own, after every event before it in the batch and before every event after it.
+   */
+  #[builder(default, setter(strip_option))]
+  is_barrier: Option<Arc<dyn Fn(&EventRow) -> bool + Send + Sync>>,
+  /**
+   * Whether a failed event keeps the cursor in place, so that its batch is polled again instead of skipped. Handlers must be idempotent, as the rest of the batch is processed again too.
+   */
+  #[builder(default)]
+  retry_failed_batches: bool,
 }
 
 impl EventSubscriberBuilder {
@@ -88,10 +117,50 @@ impl EventSubscriber {
       .await
   }
 
-  pub async fn poll(&self) -> Result<Option<String>> {
+  /**
+   * Starts a pending replay, if any. Returns whether the subscriber is currently replaying.
+   */
+  pub async fn prepare_replay(&self) -> Result<bool> {
+    match self
+      .event_subscriber_repository
+      .get_status(&self.id)
+      .await?
+    {
+      Some(EventSubscriberStatus::ReplayRequested) => {
+        info!(subscriber_id = self.id, "Starting replay");
+        if let Some(reset) = &self.reset {
+          reset().await?;
+        }
+        self.set_cursor("0").await?;
+        self
+          .event_subscriber_repository
+          .set_status(&self.id, EventSubscriberStatus::Replaying)
+          .await?;
+        Ok(true)
+      }
+      Some(EventSubscriberStatus::Replaying) => Ok(true),
+      _ => Ok(false),
+    }
+  }
+
+  /**
+   * Finishes a replay that has caught up with the tail of the event log.
+   */
+  pub async fn complete_replay(&self) -> Result<()> {
+    if let Some(finish_replay) = &self.finish_replay {
+      finish_replay().await?;
+    }
+    info!(subscriber_id = self.id, "Replay completed");
+    self
+      .event_subscriber_repository
+      .complete_replay(&self.id)
+      .await
+  }
+
+  pub async fn poll(&self, batch_size: usize) -> Result<Option<String>> {
     let event_list = self
       .event_subscriber_repository
-      .get_events_after_cursor(&self.streams, &self.id, self.batch_size)
+      .get_events_after_cursor(&self.streams, &self.id, batch_size)
       .await?;
     let stream_tags = self.streams.iter().map(|s| s.tag()).join(",");
     debug!(
@@ -102,8 +171,27 @@ impl EventSubscriber {
     );
     let tail_cursor = event_list.tail_cursor();
 
+    let mut rows = vec![];
+    for row in event_list.rows {
+      if self.is_barrier.as_ref().is_some_and(|f| f(&row)) {
+        self.process(std::mem::take(&mut rows)).await?;
+        self.process(vec![row]).await?;
+      } else {
+        rows.push(row);
+      }
+    }
+    self.process(rows).await?;
+
+    Ok(tail_cursor)
+  }
+
+  async fn process(&self, rows: Vec<EventRow>) -> Result<()> {
+    if rows.is_empty() {
+      return Ok(());
+    }
+    let stream_tags = self.streams.iter().map(|s| s.tag()).join(",");
     let mut ordered_processing_groups: HashMap<String, Vec<EventRow>> = HashMap::new();
-    for (key, group) in &event_list.rows.into_iter().group_by(|row| {
+    for (key, group) in &rows.into_iter().group_by(|row| {
       self
         .generate_ordered_processing_group_id
         .as_ref()
@@ -116,7 +204,7 @@ impl EventSubscriber {
         .extend(group);
     }
 
-    join_all(
+    let results = join_all(
       ordered_processing_groups
         .into_iter()
         .map(|(group_id, group)| {
@@ -154,6 +242,7 @@ impl EventSubscriber {
                 entry_id: entry_id.clone(),
                 payload: payload.clone(),
                 stream: row.stream.clone(),
+                created_at: row.created_at,
               })
               .await
               .map_err(|err| {
@@ -175,7 +264,15 @@ impl EventSubscriber {
     )
     .await;
 
-    Ok(tail_cursor)
+    let error = results.into_iter().find_map(|result| match result {
+      Ok(Ok(())) => None,
+      Ok(Err(err)) => Some(err),
+      Err(err) => Some(err.into()),
+    });
+    match error {
+      Some(err) if self.retry_failed_batches => Err(err),
+      _ => Ok(()),
+    }
   }
 
   pub async fn sleep(&self) {
@@ -184,11 +281,29 @@ impl EventSubscriber {
 
   pub async fn run(&self) -> Result<()> {
     loop {
-      match self.poll().await {
+      let replaying = match self.prepare_replay().await {
+        Ok(replaying) => replaying,
+        Err(error) => {
+          error!("Error preparing replay: {}", error);
+          self.sleep().await;
+          continue;
+        }
+      };
+      let batch_size = if replaying {
+        self.replay_batch_size
+      } else {
+        self.batch_size
+      };
+      match self.poll(batch_size).await {
         Ok(Some(tail_cursor)) => {
           self.set_cursor(&tail_cursor).await?;
         }
         Ok(None) => {
+          if replaying {
+            if let Err(error) = self.complete_replay().await {
+              error!("Error finishing replay: {}", error);
+            }
+          }
           self.sleep().await;
         }
         Err(error) => {
@@ -1,6 +1,8 @@
-use super::event::{Event, EventPayload, EventPayloadBuilder, Stream, StreamKind};
+use super::event::{EventPayload, EventPayloadBuilder, Stream, StreamKind};
+use super::event_upcaster::EVENT_UPCASTER_REGISTRY;
 use crate::sqlite::SqliteConnection;
 use anyhow::{anyhow, Result};
+use chrono::NaiveDateTime;
 use rusqlite::{params, types::Value};
 use std::{collections::HashMap, rc::Rc, sync::Arc};
 use tracing::{error, instrument};
@@ -10,7 +12,7 @@ pub struct EventSubscriberRepository {
   sqlite_connection: Arc<SqliteConnection>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EventSubscriberStatus {
   Paused = 0,
   Running = 1,
@@ -18,6 +20,14 @@ pub enum EventSubscriberStatus {
    * Draining means that the subscriber is running, but will not process any new events.
    */
   Draining = 2,
+  /**
+   * A replay has been requested. The subscriber will reset its projection and rewind its cursor on its next poll.
+   */
+  ReplayRequested = 3,
+  /**
+   * The subscriber is re-processing the event log from the start, until it catches up with the tail.
+   */
+  Replaying = 4,
 }
 
 impl TryFrom<u32> for EventSubscriberStatus {
@@ -28,16 +38,35 @@ impl TryFrom<u32> for EventSubscriberStatus {
       0 => Ok(EventSubscriberStatus::Paused),
       1 => Ok(EventSubscriberStatus::Running),
       2 => Ok(EventSubscriberStatus::Draining),
+      3 => Ok(EventSubscriberStatus::ReplayRequested),
+      4 => Ok(EventSubscriberStatus::Replaying),
       _ => Err(anyhow!("Invalid event subscriber status")),
     }
   }
 }
 
+#[derive(Debug, Clone)]
+pub struct ExternalEventSubscriber {
+  pub streams: Vec<Stream>,
+  pub owner: Option<String>,
+  pub description: Option<String>,
+  pub created_at: NaiveDateTime,
+  pub last_seen_at: Option<NaiveDateTime>,
+  /**
+   * Number of events in the subscribed streams that are past the subscriber's cursor.
+   */
+  pub lag: u32,
+}
+
 #[derive(Debug, Clone)]
 pub struct EventSubscriberRow {
   pub id: String,
   pub cursor: String,
   pub status: EventSubscriberStatus,
+  /**
+   * Registration details, for subscribers consuming the event stream over RPC.
+   */
+  pub external: Option<ExternalEventSubscriber>,
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +74,7 @@ pub struct EventRow {
   pub id: String,
   pub stream: Stream,
   pub payload: EventPayload,
+  pub created_at: NaiveDateTime,
 }
 
 pub struct EventList {
@@ -64,10 +94,12 @@ fn map_event_row(row: &rusqlite::Row<'_>) -> Result<EventRow, rusqlite::Error> {
       .correlation_id(row.get::<_, Option<String>>(1)?)
       .causation_id(row.get::<_, Option<String>>(2)?)
       .event(
-        serde_json::from_str::<Event>(&row.get::<_, String>(3)?).map_err(|err| {
-          error!(message = err.to_string(), "Failed to deserialize event");
-          rusqlite::Error::ExecuteReturnedResults
-        })?,
+        EVENT_UPCASTER_REGISTRY
+          .deserialize(&row.get::<_, String>(3)?, row.get::<_, u32>(6)?)
+          .map_err(|err| {
+            error!(message = err.to_string(), "Failed to deserialize event");
+            rusqlite::Error::ExecuteReturnedResults
+          })?,
       )
       .metadata(
         row
@@ -83,6 +115,7 @@ fn map_event_row(row: &rusqlite::Row<'_>) -> Result<EventRow, rusqlite::Error> {
       error!(message = err.to_string(), "Failed to parse stream");
       rusqlite::Error::ExecuteReturnedResults
     })?,
+    created_at: row.get::<_, NaiveDateTime>(7)?,
   })
 }
 
@@ -114,9 +147,61 @@ impl EventSubscriberRepository {
       .read()
       .await?
       .interact(|conn| {
-        let mut statement = conn.prepare("SELECT id, cursor, status FROM event_subscribers")?;
+        let mut statement = conn.prepare(
+          "
+          SELECT
+            s.id,
+            s.cursor,
+            s.status,
+            e.id,
+            e.owner,
+            e.description,
+            e.created_at,
+            e.last_seen_at,
+            (
+              SELECT group_concat(stream)
+              FROM external_event_subscriber_streams
+              WHERE subscriber_id = s.id
+            ),
+            CASE WHEN e.id IS NULL THEN NULL ELSE (
+              SELECT COUNT(*)
+              FROM events
+              WHERE events.id > s.cursor AND (
+                events.stream IN (
+                  SELECT stream FROM external_event_subscriber_streams WHERE subscriber_id = s.id
+                ) OR EXISTS (
+                  SELECT 1 FROM external_event_subscriber_streams
+                  WHERE subscriber_id = s.id AND stream = ?1
+                )
+              )
+            ) END
+          FROM event_subscribers s
+          LEFT JOIN external_event_subscribers e ON e.id = s.id
+          ",
+        )?;
         let rows = statement
-          .query_map([], |row| {
+          .query_map([Stream::Global.tag()], |row| {
+            let external = match row.get::<_, Option<String>>(3)? {
+              Some(_) => Some(ExternalEventSubscriber {
+                owner: row.get::<_, Option<String>>(4)?,
+                description: row.get::<_, Option<String>>(5)?,
+                created_at: row.get::<_, NaiveDateTime>(6)?,
+                last_seen_at: row.get::<_, Option<NaiveDateTime>>(7)?,
+                streams: row
+                  .get::<_, Option<String>>(8)?
+                  .unwrap_or_default()
+                  .split(',')
+                  .filter(|tag| !tag.is_empty())
+                  .map(|tag| Stream::try_from(tag.to_string()))
+                  .collect::<Result<Vec<_>>>()
+                  .map_err(|e| {
+                    error!(message = e.to_string(), "Failed to get subscribers");
+                    rusqlite::Error::ExecuteReturnedResults
+                  })?,
+                lag: row.get::<_, Option<u32>>(9)?.unwrap_or(0),
+              }),
+              None => None,
+            };
             Ok(EventSubscriberRow {
               id: row.get::<_, String>(0)?,
               cursor: row.get::<_, u32>(1)?.to_string(),
@@ -124,6 +209,7 @@ impl EventSubscriberRepository {
                 error!(message = e.to_string(), "Failed to get subscribers");
                 rusqlite::Error::ExecuteReturnedResults
               })?,
+              external,
             })
           })?
           .collect::<Result<Vec<_>, _>>()?;
@@ -136,6 +222,160 @@ impl EventSubscriberRepository {
       })?
   }
 
+  /**
+   * Returns the streams an external subscriber is registered for, or `None` if the subscriber is not registered.
+   */
+  #[instrument(skip(self))]
+  pub async fn get_external_subscriber_streams(
+    &self,
+    subscriber_id: &str,
+  ) -> Result<Option<Vec<Stream>>> {
+    let subscriber_id = subscriber_id.to_string();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let is_registered = conn
+          .prepare("SELECT 1 FROM external_event_subscribers WHERE id = ?")?
+          .exists([&subscriber_id])?;
+        if !is_registered {
+          return Ok(None);
+        }
+        let mut statement = conn.prepare(
+          "SELECT stream FROM external_event_subscriber_streams WHERE subscriber_id = ?",
+        )?;
+        let streams = statement
+          .query_map([&subscriber_id], |row| row.get::<_, String>(0))?
+          .collect::<Result<Vec<_>, _>>()?
+          .into_iter()
+          .map(Stream::try_from)
+          .collect::<Result<Vec<_>>>()?;
+        Ok(Some(streams))
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to get external subscriber streams"
+        );
+        anyhow!("Failed to get external subscriber streams")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn register_external_subscriber(
+    &self,
+    subscriber_id: &str,
+    streams: Vec<Stream>,
+    owner: Option<String>,
+    description: Option<String>,
+  ) -> Result<()> {
+    let subscriber_id = subscriber_id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let transaction = conn.transaction()?;
+        transaction.execute(
+          "
+          INSERT INTO external_event_subscribers (id, owner, description)
+          VALUES (?1, ?2, ?3)
+          ON CONFLICT (id) DO UPDATE SET owner = ?2, description = ?3
+          ",
+          params![subscriber_id, owner, description],
+        )?;
+        transaction.execute(
+          "DELETE FROM external_event_subscriber_streams WHERE subscriber_id = ?",
+          [&subscriber_id],
+        )?;
+        for stream in streams {
+          transaction.execute(
+            "INSERT INTO external_event_subscriber_streams (subscriber_id, stream) VALUES (?1, ?2)",
+            params![subscriber_id, stream.tag()],
+          )?;
+        }
+        transaction.execute(
+          "
+          INSERT INTO event_subscribers (id, cursor)
+          VALUES (?1, 0)
+          ON CONFLICT (id) DO NOTHING
+          ",
+          [&subscriber_id],
+        )?;
+        transaction.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to register external subscriber"
+        );
+        anyhow!("Failed to register external subscriber")
+      })?
+  }
+
+  /**
+   * Deletes an external subscriber along with its cursor. Returns false if no such subscriber is registered.
+   */
+  #[instrument(skip(self))]
+  pub async fn unregister_external_subscriber(&self, subscriber_id: &str) -> Result<bool> {
+    let subscriber_id = subscriber_id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let transaction = conn.transaction()?;
+        let deleted = transaction.execute(
+          "DELETE FROM external_event_subscribers WHERE id = ?",
+          [&subscriber_id],
+        )?;
+        if deleted > 0 {
+          transaction.execute(
+            "DELETE FROM event_subscribers WHERE id = ?",
+            [&subscriber_id],
+          )?;
+        }
+        transaction.commit()?;
+        Ok(deleted > 0)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to unregister external subscriber"
+        );
+        anyhow!("Failed to unregister external subscriber")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn touch_external_subscriber(&self, subscriber_id: &str) -> Result<()> {
+    let subscriber_id = subscriber_id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "UPDATE external_event_subscribers SET last_seen_at = CURRENT_TIMESTAMP WHERE id = ?",
+          [subscriber_id],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to touch external subscriber"
+        );
+        anyhow!("Failed to touch external subscriber")
+      })?
+  }
+
   pub async fn get_stream_tails(&self) -> Result<Vec<(Stream, String)>> {
     self
       .sqlite_connection
@@ -222,6 +462,81 @@ impl EventSubscriberRepository {
       })?
   }
 
+  #[instrument(skip(self))]
+  pub async fn get_status(&self, subscriber_id: &str) -> Result<Option<EventSubscriberStatus>> {
+    let subscriber_id = subscriber_id.to_string();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare("SELECT status FROM event_subscribers WHERE id = ?")?;
+        let mut rows = statement.query_map([subscriber_id], |row| row.get::<_, u32>(0))?;
+        rows
+          .next()
+          .transpose()?
+          .map(EventSubscriberStatus::try_from)
+          .transpose()
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get status");
+        anyhow!("Failed to get status")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn set_status(&self, subscriber_id: &str, status: EventSubscriberStatus) -> Result<()> {
+    let subscriber_id = subscriber_id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          INSERT INTO event_subscribers (id, cursor, status)
+          VALUES (?1, 0, ?2)
+          ON CONFLICT (id) DO UPDATE SET status = ?2
+          ",
+        )?;
+        statement.execute(params![subscriber_id, status as u32])?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to set status");
+        anyhow!("Failed to set status")
+      })?
+  }
+
+  /**
+   * Moves a subscriber from `Replaying` back to `Running`, unless another replay was requested in the meantime.
+   */
+  #[instrument(skip(self))]
+  pub async fn complete_replay(&self, subscriber_id: &str) -> Result<()> {
+    let subscriber_id = subscriber_id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let mut statement =
+          conn.prepare("UPDATE event_subscribers SET status = ?1 WHERE id = ?2 AND status = ?3")?;
+        statement.execute(params![
+          EventSubscriberStatus::Running as u32,
+          subscriber_id,
+          EventSubscriberStatus::Replaying as u32
+        ])?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to complete replay");
+        anyhow!("Failed to complete replay")
+      })?
+  }
+
   #[instrument(skip(self))]
   pub async fn delete_cursor(&self, subscriber_id: &str) -> Result<()> {
     let subscriber_id = subscriber_id.to_string();
@@ -263,7 +578,7 @@ impl EventSubscriberRepository {
         if is_global {
           let mut statement = conn.prepare(
             "
-            SELECT id, correlation_id, causation_id, event, metadata, stream
+            SELECT id, correlation_id, causation_id, event, metadata, stream, schema_version, created_at
             FROM events
             WHERE id > ?1
             ORDER BY id ASC
@@ -277,7 +592,7 @@ impl EventSubscriberRepository {
         } else {
           let mut statement = conn.prepare(
             "
-            SELECT id, correlation_id, causation_id, event, metadata, stream
+            SELECT id, correlation_id, causation_id, event, metadata, stream, schema_version, created_at
             FROM events
             WHERE stream IN rarray(?1) AND id > ?2
             ORDER BY id ASC
@@ -0,0 +1,185 @@
+use super::event::Event;
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, value::RawValue, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/**
+ * Version of the serialized `Event` schema written by this build. Bump it whenever the
+ * serialized shape of an event changes, and register an upcaster migrating payloads from
+ * the previous version.
+ */
+pub const EVENT_SCHEMA_VERSION: u32 = 5;
+
+/**
+ * Migrates a serialized event payload from one schema version to the next.
+ */
+pub type EventUpcaster = fn(Value) -> Result<Value>;
+
+/**
+ * Event fields holding ULIDs, which are serialized as 128 bit integers. `Value` can only hold
+ * those as lossy floats, so they are kept as raw JSON while the rest of the payload is upcast.
+ */
+const RAW_EVENT_FIELDS: [&str; 1] = ["file_id"];
+
+#[derive(Serialize, Deserialize)]
+struct StoredEvent {
+  #[serde(rename = "type")]
+  event_type: String,
+  data: BTreeMap<String, Box<RawValue>>,
+}
+
+pub struct EventUpcasterRegistry {
+  upcasters: HashMap<u32, EventUpcaster>,
+}
+
+lazy_static! {
+  pub static ref EVENT_UPCASTER_REGISTRY: EventUpcasterRegistry = EventUpcasterRegistry::default();
+}
+
+impl Default for EventUpcasterRegistry {
+  fn default() -> Self {
+    let mut registry = Self::new();
+    registry.register(1, upcast_v1_parsed_album_defaults);
+    registry.register(2, upcast_v2_parser_version);
+    registry.register(3, upcast_v3_parsed_chart);
+    registry.register(4, upcast_v4_parser_error);
+    registry
+  }
+}
+
+impl EventUpcasterRegistry {
+  pub fn new() -> Self {
+    Self {
+      upcasters: HashMap::new(),
+    }
+  }
+
+  /**
+   * Registers an upcaster migrating payloads from `from_version` to `from_version + 1`.
+   */
+  pub fn register(&mut self, from_version: u32, upcaster: EventUpcaster) {
+    self.upcasters.insert(from_version, upcaster);
+  }
+
+  pub fn upcast(&self, mut event: Value, schema_version: u32) -> Result<Value> {
+    if schema_version > EVENT_SCHEMA_VERSION {
+      return Err(anyhow!(
+        "Event schema version {} is newer than the supported version {}",
+        schema_version,
+        EVENT_SCHEMA_VERSION
+      ));
+    }
+    for version in schema_version..EVENT_SCHEMA_VERSION {
+      let upcaster = self.upcasters.get(&version).ok_or(anyhow!(
+        "No upcaster registered for event schema version {}",
+        version
+      ))?;
+      event = upcaster(event)?;
+    }
+    Ok(event)
+  }
+
+  pub fn deserialize(&self, event: &str, schema_version: u32) -> Result<Event> {
+    if schema_version == EVENT_SCHEMA_VERSION {
+      return Ok(serde_json::from_str::<Event>(event)?);
+    }
+    let stored = serde_json::from_str::<StoredEvent>(event)?;
+    let mut raw_fields = BTreeMap::new();
+    let mut data = Map::new();
+    for (key, value) in stored.data {
+      if RAW_EVENT_FIELDS.contains(&key.as_str()) {
+        raw_fields.insert(key, value);
+      } else {
+        data.insert(key, serde_json::from_str::<Value>(value.get())?);
+      }
+    }
+
+    let mut value = self.upcast(
+      json!({ "type": stored.event_type, "data": data }),
+      schema_version,
+    )?;
+    let event_type = value["type"]
+      .as_str()
+      .ok_or(anyhow!("Upcast event has no type"))?
+      .to_string();
+    let mut data = match value["data"].take() {
+      Value::Object(data) => data
+        .into_iter()
+        .map(|(key, value)| Ok((key, serde_json::value::to_raw_value(&value)?)))
+        .collect::<Result<BTreeMap<_, _>>>()?,
+      _ => return Err(anyhow!("Upcast event has no data")),
+    };
+    data.extend(raw_fields);
+    let stored = StoredEvent { event_type, data };
+    Ok(serde_json::from_str::<Event>(&serde_json::to_string(
+      &stored,
+    )?)?)
+  }
+}
+
+/**
+ * Version 1 album payloads predate `languages`, `credits` and `cover_image_url`.
+ */
+fn upcast_v1_parsed_album_defaults(mut event: Value) -> Result<Value> {
+  if event["type"] != "FileParsed" || event["data"]["data"]["type"] != "Album" {
+    return Ok(event);
+  }
+  if let Some(album) = event["data"]["data"]["data"].as_object_mut() {
+    album.entry("languages").or_insert(json!([]));
+    album.entry("credits").or_insert(json!([]));
+    album.entry("cover_image_url").or_insert(Value::Null);
+  }
+  Ok(event)
+}
+
+/**
+ * Version 2 `FileParsed` payloads predate parser versioning, and were all produced by the
+ * first version of each parser.
+ */
+fn upcast_v2_parser_version(mut event: Value) -> Result<Value> {
+  if event["type"] != "FileParsed" {
+    return Ok(event);
+  }
+  if let Some(data) = event["data"].as_object_mut() {
+    data.entry("parser_version").or_insert(json!(1));
+  }
+  Ok(event)
+}
+
+/**
+ * Version 3 chart payloads were a bare list of albums, parsed from the first page only.
+ */
+fn upcast_v3_parsed_chart(mut event: Value) -> Result<Value> {
+  if event["type"] != "FileParsed" || event["data"]["data"]["type"] != "Chart" {
+    return Ok(event);
+  }
+  let albums = event["data"]["data"]["data"].take();
+  if albums.is_array() {
+    event["data"]["data"]["data"] = json!({
+      "albums": albums,
+      "page_number": 1,
+      "total_pages": null,
+    });
+  } else {
+    event["data"]["data"]["data"] = albums;
+  }
+  Ok(event)
+}
+
+/**
+ * Version 4 `FileParseFailed` payloads carried the parser error as a free-form message.
+ */
+fn upcast_v4_parser_error(mut event: Value) -> Result<Value> {
+  if event["type"] != "FileParseFailed" {
+    return Ok(event);
+  }
+  if let Some(message) = event["data"]["error"].as_str() {
+    event["data"]["error"] = json!({
+      "code": "unknown",
+      "message": message,
+    });
+  }
+  Ok(event)
+}
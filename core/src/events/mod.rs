@@ -1,5 +1,7 @@
 pub mod event;
+pub mod event_filter;
 pub mod event_publisher;
 pub mod event_service;
 pub mod event_subscriber;
 pub mod event_subscriber_repository;
+pub mod event_upcaster;
@@ -69,6 +69,51 @@ impl FileName {
   pub fn page_type(&self) -> PageType {
     PageType::try_from(self.0.as_str()).unwrap()
   }
+
+  /**
+   * Chart file names are "charts/{kind}/{release type}/{date}/{filters}/{page}", so only a segment
+   * after the date is a page number. Dates can be numeric too, e.g. "charts/top/album/2014".
+   */
+  fn split_chart_page(&self) -> (&str, u32) {
+    self
+      .0
+      .rsplit_once('/')
+      .filter(|(base, _)| base.split('/').count() >= 4)
+      .and_then(|(base, page)| page.parse::<u32>().ok().map(|page| (base, page)))
+      .unwrap_or((self.0.as_str(), 1))
+  }
+
+  /**
+   * The page number of a chart file name. Chart pages other than the first end in "/{page}".
+   */
+  pub fn chart_page_number(&self) -> Option<u32> {
+    match self.page_type() {
+      PageType::Chart => Some(self.split_chart_page().1),
+      _ => None,
+    }
+  }
+
+  /**
+   * The file name of the first page of a chart, which identifies the chart across its pages.
+   */
+  pub fn chart_first_page(&self) -> Option<FileName> {
+    match self.page_type() {
+      PageType::Chart => Some(FileName(self.split_chart_page().0.to_string())),
+      _ => None,
+    }
+  }
+
+  pub fn chart_page(&self, page_number: u32) -> Result<FileName> {
+    let first_page = self.chart_first_page().ok_or(anyhow::Error::msg(format!(
+      "Not a chart file name: {}",
+      self.to_string()
+    )))?;
+    if page_number <= 1 {
+      Ok(first_page)
+    } else {
+      FileName::try_from(format!("{}/{}", first_page.to_string(), page_number))
+    }
+  }
 }
 
 impl TryInto<FileName> for ChartParameters {
@@ -110,6 +155,10 @@ impl TryInto<FileName> for ChartParameters {
       );
     }
 
+    if self.page_number > 1 {
+      file_name.push_str(format!("/{}", self.page_number).as_str());
+    }
+
     FileName::try_from(file_name)
   }
 }
@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenreHierarchyEntry {
+  pub name: String,
+  #[serde(default)]
+  pub parents: Vec<String>,
+}
+
+/**
+ * Genre tree, where a genre can have several parents (e.g. "Post-Punk" under "Rock"). Names are
+ * the genre names found on albums.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct GenreHierarchy {
+  parents: HashMap<String, Vec<String>>,
+  children: HashMap<String, Vec<String>>,
+}
+
+impl GenreHierarchy {
+  pub fn new(entries: Vec<GenreHierarchyEntry>) -> Self {
+    let mut hierarchy = Self::default();
+    for entry in entries {
+      for parent in entry.parents {
+        hierarchy.add_edge(&entry.name, &parent);
+      }
+    }
+    hierarchy
+  }
+
+  /**
+   * Reads an importable hierarchy: a JSON array of `{ "name": "Post-Punk", "parents": ["Rock"] }`.
+   */
+  pub fn from_json(content: &str) -> Result<Self> {
+    let entries = serde_json::from_str::<Vec<GenreHierarchyEntry>>(content)
+      .map_err(|e| anyhow!("Invalid genre hierarchy: {}", e))?;
+    if let Some(entry) = entries.iter().find(|entry| {
+      entry.name.trim().is_empty() || entry.parents.iter().any(|p| p.trim().is_empty())
+    }) {
+      return Err(anyhow!(
+        "Invalid genre hierarchy: empty genre name in entry {:?}",
+        entry
+      ));
+    }
+    Ok(Self::new(entries))
+  }
+
+  fn add_edge(&mut self, genre: &str, parent: &str) {
+    let genre = genre.trim().to_string();
+    let parent = parent.trim().to_string();
+    if genre == parent {
+      return;
+    }
+    let parents = self.parents.entry(genre.clone()).or_default();
+    if !parents.contains(&parent) {
+      parents.push(parent.clone());
+      self.children.entry(parent).or_default().push(genre);
+    }
+  }
+
+  pub fn entries(&self) -> Vec<GenreHierarchyEntry> {
+    let mut entries = self
+      .parents
+      .iter()
+      .map(|(name, parents)| GenreHierarchyEntry {
+        name: name.clone(),
+        parents: parents.clone(),
+      })
+      .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+  }
+
+  pub fn genre_count(&self) -> usize {
+    self
+      .parents
+      .keys()
+      .chain(self.children.keys())
+      .collect::<HashSet<_>>()
+      .len()
+  }
+
+  /**
+   * Walks the given edges breadth first, so that cycles in imported data terminate.
+   */
+  fn walk(edges: &HashMap<String, Vec<String>>, genre: &str) -> Vec<String> {
+    let mut visited = HashSet::from([genre.to_string()]);
+    let mut queue = VecDeque::from([genre.to_string()]);
+    let mut result = vec![];
+    while let Some(current) = queue.pop_front() {
+      for next in edges.get(&current).into_iter().flatten() {
+        if visited.insert(next.clone()) {
+          result.push(next.clone());
+          queue.push_back(next.clone());
+        }
+      }
+    }
+    result
+  }
+
+  pub fn ancestors(&self, genre: &str) -> Vec<String> {
+    Self::walk(&self.parents, genre)
+  }
+
+  pub fn descendants(&self, genre: &str) -> Vec<String> {
+    Self::walk(&self.children, genre)
+  }
+
+  fn with_walked(genres: &[String], walk: impl Fn(&str) -> Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    genres
+      .iter()
+      .flat_map(|genre| std::iter::once(genre.clone()).chain(walk(genre)))
+      .filter(|genre| seen.insert(genre.clone()))
+      .collect()
+  }
+
+  /**
+   * The given genres followed by their ancestors, without duplicates.
+   */
+  pub fn with_ancestors(&self, genres: &[String]) -> Vec<String> {
+    Self::with_walked(genres, |genre| self.ancestors(genre))
+  }
+
+  /**
+   * The given genres followed by their descendants, without duplicates.
+   */
+  pub fn with_descendants(&self, genres: &[String]) -> Vec<String> {
+    Self::with_walked(genres, |genre| self.descendants(genre))
+  }
+}
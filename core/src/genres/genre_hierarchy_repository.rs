@@ -0,0 +1,81 @@
+use super::genre_hierarchy::{GenreHierarchy, GenreHierarchyEntry};
+use crate::sqlite::SqliteConnection;
+use anyhow::{anyhow, Result};
+use rusqlite::params;
+use std::{collections::HashMap, sync::Arc};
+use tracing::{error, instrument};
+
+#[derive(Debug, Clone)]
+pub struct GenreHierarchyRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl GenreHierarchyRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  /**
+   * Replaces the whole hierarchy, as imports are complete genre trees.
+   */
+  #[instrument(skip_all)]
+  pub async fn replace(&self, hierarchy: &GenreHierarchy) -> Result<()> {
+    let entries = hierarchy.entries();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM genre_parents", [])?;
+        {
+          let mut statement =
+            tx.prepare("INSERT INTO genre_parents (genre, parent_genre) VALUES (?, ?)")?;
+          for entry in entries {
+            for parent in entry.parents {
+              statement.execute(params![entry.name, parent])?;
+            }
+          }
+        }
+        tx.commit()?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to replace genre hierarchy");
+        anyhow!("Failed to replace genre hierarchy")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get(&self) -> Result<GenreHierarchy> {
+    let entries = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut statement =
+          conn.prepare("SELECT genre, parent_genre FROM genre_parents ORDER BY genre")?;
+        let mut parents = HashMap::<String, Vec<String>>::new();
+        let rows = statement.query_map([], |row| {
+          Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for row in rows {
+          let (genre, parent) = row?;
+          parents.entry(genre).or_default().push(parent);
+        }
+        Ok::<_, rusqlite::Error>(
+          parents
+            .into_iter()
+            .map(|(name, parents)| GenreHierarchyEntry { name, parents })
+            .collect::<Vec<_>>(),
+        )
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get genre hierarchy");
+        anyhow!("Failed to get genre hierarchy")
+      })??;
+    Ok(GenreHierarchy::new(entries))
+  }
+}
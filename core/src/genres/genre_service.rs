@@ -0,0 +1,65 @@
+use super::{
+  genre_hierarchy::{GenreHierarchy, GenreHierarchyEntry},
+  genre_hierarchy_repository::GenreHierarchyRepository,
+};
+use crate::{proto, sqlite::SqliteConnection};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+
+impl From<GenreHierarchyEntry> for proto::GenreHierarchyEntry {
+  fn from(val: GenreHierarchyEntry) -> Self {
+    proto::GenreHierarchyEntry {
+      name: val.name,
+      parents: val.parents,
+    }
+  }
+}
+
+pub struct GenreService {
+  genre_hierarchy_repository: GenreHierarchyRepository,
+}
+
+impl GenreService {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self {
+      genre_hierarchy_repository: GenreHierarchyRepository::new(sqlite_connection),
+    }
+  }
+}
+
+#[tonic::async_trait]
+impl proto::GenreService for GenreService {
+  async fn import_genre_hierarchy(
+    &self,
+    request: Request<proto::ImportGenreHierarchyRequest>,
+  ) -> Result<Response<proto::ImportGenreHierarchyReply>, Status> {
+    let hierarchy = GenreHierarchy::from_json(&request.into_inner().content)
+      .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    self
+      .genre_hierarchy_repository
+      .replace(&hierarchy)
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::ImportGenreHierarchyReply {
+      genre_count: hierarchy.genre_count() as u32,
+    }))
+  }
+
+  async fn get_genre_hierarchy(
+    &self,
+    _: Request<()>,
+  ) -> Result<Response<proto::GetGenreHierarchyReply>, Status> {
+    let hierarchy = self
+      .genre_hierarchy_repository
+      .get()
+      .await
+      .map_err(|e| Status::internal(e.to_string()))?;
+    Ok(Response::new(proto::GetGenreHierarchyReply {
+      genres: hierarchy
+        .entries()
+        .into_iter()
+        .map(|entry| entry.into())
+        .collect(),
+    }))
+  }
+}
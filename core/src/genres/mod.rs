@@ -0,0 +1,3 @@
+pub mod genre_hierarchy;
+pub mod genre_hierarchy_repository;
+pub mod genre_service;
@@ -0,0 +1,11 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/**
+ * HMAC-SHA256 as specified in RFC 2104.
+ */
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+  let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+  mac.update(message);
+  mac.finalize().into_bytes().to_vec()
+}
@@ -1,3 +1,4 @@
 pub mod fifo_queue;
+pub mod hmac;
 pub mod math;
 pub mod redisearch;
@@ -1,7 +1,10 @@
 pub mod albums;
+pub mod artists;
+pub mod charts;
 pub mod crawler;
 pub mod events;
 pub mod files;
+pub mod genres;
 pub mod helpers;
 pub mod lookup;
 pub mod ops;
@@ -15,3 +18,4 @@ pub mod settings;
 pub mod spotify;
 pub mod sqlite;
 pub mod tracing;
+pub mod webhooks;
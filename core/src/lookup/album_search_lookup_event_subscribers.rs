@@ -7,7 +7,8 @@ use super::{
 };
 use crate::{
   albums::{
-    album_read_model::AlbumReadModel, album_repository::AlbumRepository,
+    album_read_model::{AlbumReadModel, AlbumReadModelCredit},
+    album_repository::AlbumRepository,
     sqlite_album_repository::SqliteAlbumRepository,
   },
   crawler::{
@@ -21,7 +22,8 @@ use crate::{
   },
   files::file_metadata::{file_name::FileName, page_type::PageType},
   parser::parsed_file_data::{
-    ParsedAlbum, ParsedArtistReference, ParsedCredit, ParsedFileData, ParsedTrack,
+    ParsedAlbum, ParsedArtistReference, ParsedChartPosition, ParsedCredit, ParsedExternalLink,
+    ParsedFileData, ParsedLabel, ParsedTrack,
   },
   settings::Settings,
   sqlite::SqliteConnection,
@@ -32,6 +34,16 @@ use rustis::{bb8::Pool, client::PooledClientManager};
 use std::sync::Arc;
 use tracing::{info, instrument, warn};
 
+fn to_parsed_credit(credit: &AlbumReadModelCredit) -> ParsedCredit {
+  ParsedCredit {
+    artist: ParsedArtistReference {
+      name: credit.artist.name.clone(),
+      file_name: credit.artist.file_name.clone(),
+    },
+    roles: credit.roles.clone(),
+  }
+}
+
 impl From<AlbumReadModel> for ParsedAlbum {
   fn from(album: AlbumReadModel) -> Self {
     Self {
@@ -57,6 +69,7 @@ impl From<AlbumReadModel> for ParsedAlbum {
           duration_seconds: track.duration_seconds,
           rating: track.rating,
           position: track.position.clone(),
+          credits: track.credits.iter().map(to_parsed_credit).collect(),
         })
         .collect::<Vec<ParsedTrack>>(),
       release_date: album.release_date,
@@ -64,15 +77,37 @@ impl From<AlbumReadModel> for ParsedAlbum {
       credits: album
         .credits
         .iter()
-        .map(|credit| ParsedCredit {
-          artist: ParsedArtistReference {
-            name: credit.artist.name.clone(),
-            file_name: credit.artist.file_name.clone(),
-          },
-          roles: credit.roles.clone(),
-        })
+        .map(to_parsed_credit)
         .collect::<Vec<ParsedCredit>>(),
       cover_image_url: album.cover_image_url,
+      reviews: vec![],
+      external_links: album
+        .external_links
+        .iter()
+        .map(|link| ParsedExternalLink {
+          source: link.source.clone(),
+          id: link.id.clone(),
+          url: link.url.clone(),
+        })
+        .collect::<Vec<ParsedExternalLink>>(),
+      release_type: album.release_type,
+      labels: album
+        .labels
+        .iter()
+        .map(|label| ParsedLabel {
+          name: label.name.clone(),
+          catalog_number: label.catalog_number.clone(),
+        })
+        .collect::<Vec<ParsedLabel>>(),
+      chart_positions: album
+        .chart_positions
+        .iter()
+        .map(|chart_position| ParsedChartPosition {
+          chart: chart_position.chart.clone(),
+          position: chart_position.position,
+        })
+        .collect::<Vec<ParsedChartPosition>>(),
+      total_duration_seconds: album.total_duration_seconds,
     }
   }
 }
@@ -155,7 +190,7 @@ impl AlbumSearchLookup {
               query: self.query().clone(),
               last_updated_at: chrono::Utc::now().naive_utc(),
               file_processing_correlation_id: correlation_id.clone(),
-              album_search_file_parse_error: error,
+              album_search_file_parse_error: error.to_string(),
             })
           } else {
             None
@@ -169,7 +204,7 @@ impl AlbumSearchLookup {
               last_updated_at: chrono::Utc::now().naive_utc(),
               file_processing_correlation_id: correlation_id.clone(),
               parsed_album_search_result: self.parsed_album_search_result().unwrap(),
-              album_file_parse_error: error,
+              album_file_parse_error: error.to_string(),
             })
           } else {
             None
@@ -407,11 +442,7 @@ pub fn build_album_search_lookup_event_subscribers(
 ) -> Result<Vec<EventSubscriber>> {
   let orchestrator = Arc::new(AlbumSearchLookupOrchestrator {
     crawler_interactor: Arc::clone(&crawler_interactor),
-    lookup_interactor: LookupInteractor::new(
-      Arc::clone(&settings),
-      Arc::clone(&redis_connection_pool),
-      Arc::clone(&sqlite_connection),
-    ),
+    lookup_interactor: LookupInteractor::new(Arc::clone(&settings), Arc::clone(&sqlite_connection)),
     event_publisher: EventPublisher::new(Arc::clone(&settings), Arc::clone(&sqlite_connection)),
     album_repository: Arc::new(SqliteAlbumRepository::new(Arc::clone(&sqlite_connection))),
   });
@@ -1,91 +1,68 @@
 use super::album_search_lookup::{AlbumSearchLookup, AlbumSearchLookupQuery};
-use crate::{
-  files::file_metadata::file_name::FileName,
-  helpers::redisearch::{does_ft_index_exist, escape_tag_value},
-};
+use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
 use anyhow::{anyhow, Result};
-use rustis::{
-  bb8::Pool,
-  client::{BatchPreparedCommand, PooledClientManager},
-  commands::{
-    FtAggregateOptions, FtCreateOptions, FtFieldSchema, FtFieldType, FtIndexDataType, FtReducer,
-    FtSearchOptions, GenericCommands, HashCommands, SearchCommands,
-  },
-};
-use std::{collections::HashMap, sync::Arc};
-use tracing::warn;
-
-const NAMESPACE: &str = "lookup:album_search";
-const INDEX_NAME: &str = "lookup:album_search_idx";
-
-fn key(query: &AlbumSearchLookupQuery) -> String {
-  format!("{}:{}", NAMESPACE, query.to_encoded_string())
-}
+use rusqlite::{params, OptionalExtension};
+use std::{collections::HashMap, rc::Rc, sync::Arc};
+use tracing::{error, instrument, warn};
 
 pub struct AggregatedStatus {
   pub status: String,
   pub count: u32,
 }
 
-impl From<Vec<(String, String)>> for AggregatedStatus {
-  fn from(val: Vec<(String, String)>) -> Self {
-    let mut status = None;
-    let mut count = None;
-
-    for (key, value) in val {
-      match key.as_str() {
-        "status" => status = Some(value),
-        "count" => count = Some(value.parse().expect("invalid count")),
-        _ => {}
-      }
-    }
-
-    Self {
-      status: status.expect("status not found"),
-      count: count.expect("count not found"),
+fn to_lookup(json: String) -> Option<AlbumSearchLookup> {
+  match serde_json::from_str::<AlbumSearchLookup>(&json) {
+    Ok(lookup) => Some(lookup),
+    Err(err) => {
+      warn!("Failed to deserialize AlbumSearchLookup: {}", err);
+      None
     }
   }
 }
 
+#[derive(Debug, Clone)]
 pub struct AlbumSearchLookupRepository {
-  pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
 }
 
 impl AlbumSearchLookupRepository {
-  pub async fn setup_index(&self) -> Result<()> {
-    let connection = self.redis_connection_pool.get().await?;
-    if !does_ft_index_exist(&connection, INDEX_NAME).await {
-      connection
-        .ft_create(
-          INDEX_NAME,
-          FtCreateOptions::default()
-            .on(FtIndexDataType::Hash)
-            .prefix(format!("{}:", NAMESPACE)),
-          [
-            FtFieldSchema::identifier("status").field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("album_search_file_name").field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("album_file_name").field_type(FtFieldType::Tag),
-          ],
-        )
-        .await?;
-    }
-    Ok(())
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
   }
 
+  #[instrument(skip(self))]
   pub async fn find(&self, query: &AlbumSearchLookupQuery) -> Result<Option<AlbumSearchLookup>> {
-    let res: HashMap<String, String> = self
-      .redis_connection_pool
-      .get()
+    let encoded_query = query.to_encoded_string();
+    let json = self
+      .sqlite_connection
+      .read()
       .await?
-      .hgetall(key(query))
-      .await?;
-
-    match res.is_empty() {
-      true => Ok(None),
-      false => Ok(Some(AlbumSearchLookup::try_from(res)?)),
-    }
+      .interact(move |conn| {
+        conn
+          .query_row(
+            "SELECT lookup FROM album_search_lookups WHERE encoded_query = ?",
+            [encoded_query],
+            |row| row.get::<_, String>(0),
+          )
+          .optional()
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find album search lookup"
+        );
+        anyhow!("Failed to find album search lookup")
+      })??;
+    json
+      .map(|json| serde_json::from_str::<AlbumSearchLookup>(&json).map_err(|e| e.into()))
+      .transpose()
   }
 
+  /**
+   * Returns the lookup for each query in the order given, or None if a query has no lookup.
+   */
+  #[instrument(skip_all, fields(count = queries.len()))]
   pub async fn find_many(
     &self,
     queries: Vec<&AlbumSearchLookupQuery>,
@@ -94,64 +71,85 @@ impl AlbumSearchLookupRepository {
       return Ok(vec![]);
     }
 
-    if queries.len() == 1 {
-      return Ok(vec![self.find(queries[0]).await?]);
-    }
-
-    let connection = self.redis_connection_pool.get().await?;
-    let mut pipeline = connection.create_pipeline();
-    for query in queries {
-      pipeline
-        .hgetall::<String, _, _, HashMap<String, String>>(key(query))
-        .queue();
-    }
-    let results: Vec<HashMap<String, String>> = pipeline.execute().await?;
-    results
-      .into_iter()
-      .map(|res| match res.is_empty() {
-        true => Ok(None),
-        false => Ok(Some(AlbumSearchLookup::try_from(res)?)),
+    let encoded_queries = queries
+      .iter()
+      .map(|query| query.to_encoded_string())
+      .collect::<Vec<_>>();
+    let encoded_query_params = encoded_queries
+      .iter()
+      .map(|encoded_query| rusqlite::types::Value::from(encoded_query.clone()))
+      .collect::<Vec<_>>();
+    let mut lookups = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT encoded_query, lookup
+          FROM album_search_lookups
+          WHERE encoded_query IN rarray(?)
+          ",
+        )?;
+        let lookups = stmt
+          .query_map([Rc::new(encoded_query_params)], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+          })?
+          .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok::<_, rusqlite::Error>(lookups)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find album search lookups"
+        );
+        anyhow!("Failed to find album search lookups")
+      })??;
+
+    encoded_queries
+      .iter()
+      .map(|encoded_query| match lookups.remove(encoded_query) {
+        Some(json) => Ok(Some(serde_json::from_str::<AlbumSearchLookup>(&json)?)),
+        None => Ok(None),
       })
       .collect::<Result<Vec<_>>>()
   }
 
+  #[instrument(skip(self))]
   pub async fn find_many_by_album_file_name(
     &self,
     file_name: &FileName,
   ) -> Result<Vec<AlbumSearchLookup>> {
-    let connection = self.redis_connection_pool.get().await?;
-    let search_result = connection
-      .ft_search(
-        INDEX_NAME,
-        format!(
-          "@album_file_name:{{ {} }}",
-          escape_tag_value(&file_name.to_string())
-        ),
-        FtSearchOptions::default().limit(0, 10000),
-      )
-      .await?;
-    let result = search_result
-      .results
-      .iter()
-      .map(|r| {
-        let lookup: Result<AlbumSearchLookup> = r
-          .values
-          .clone()
-          .into_iter()
-          .collect::<HashMap<_, _>>()
-          .try_into();
-        lookup
-      })
-      .filter_map(|r| match r {
-        Ok(lookup) => Some(lookup),
-        Err(err) => {
-          warn!("Failed to deserialize AlbumSearchLookup: {}", err);
-          None
-        }
+    let file_name = file_name.to_string();
+    let lookups = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT lookup
+          FROM album_search_lookups
+          WHERE album_file_name = ?
+          LIMIT 10000
+          ",
+        )?;
+        let lookups = stmt
+          .query_map([file_name], |row| row.get::<_, String>(0))?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(lookups)
       })
-      .collect::<Vec<_>>();
-
-    Ok(result)
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find album search lookups by album file name"
+        );
+        anyhow!("Failed to find album search lookups by album file name")
+      })??;
+
+    Ok(lookups.into_iter().filter_map(to_lookup).collect())
   }
 
   pub async fn get(&self, query: &AlbumSearchLookupQuery) -> Result<AlbumSearchLookup> {
@@ -161,43 +159,93 @@ impl AlbumSearchLookupRepository {
     }
   }
 
+  #[instrument(skip_all)]
   pub async fn put(&self, lookup: &AlbumSearchLookup) -> Result<()> {
-    let key = key(lookup.query());
-    let map: HashMap<String, String> = (*lookup).clone().into();
+    let encoded_query = lookup.query().to_encoded_string();
+    let status = lookup.status_string();
+    let album_file_name = HashMap::<String, String>::from(lookup.clone()).remove("album_file_name");
+    let json = serde_json::to_string(lookup)?;
     self
-      .redis_connection_pool
-      .get()
+      .sqlite_connection
+      .write()
       .await?
-      .hset(key, map)
-      .await?;
-    Ok(())
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO album_search_lookups (encoded_query, status, album_file_name, lookup)
+          VALUES (?1, ?2, ?3, ?4)
+          ON CONFLICT (encoded_query) DO UPDATE SET
+            status = ?2,
+            album_file_name = ?3,
+            lookup = ?4
+          ",
+          params![encoded_query, status, album_file_name, json],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put album search lookup");
+        anyhow!("Failed to put album search lookup")
+      })?
   }
 
+  #[instrument(skip(self))]
   pub async fn aggregate_statuses(&self) -> Result<Vec<AggregatedStatus>> {
-    let connection = self.redis_connection_pool.get().await?;
-    let result = connection
-      .ft_aggregate(
-        INDEX_NAME,
-        "*",
-        FtAggregateOptions::default().groupby("@status", FtReducer::count().as_name("count")),
-      )
-      .await?;
-    let aggregates = result
-      .results
-      .iter()
-      .map(|r| AggregatedStatus::from(r.to_owned()))
-      .collect::<Vec<_>>();
-
-    Ok(aggregates)
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT status, COUNT(*)
+          FROM album_search_lookups
+          GROUP BY status
+          ORDER BY status
+          ",
+        )?;
+        let aggregates = stmt
+          .query_map([], |row| {
+            Ok(AggregatedStatus {
+              status: row.get::<_, String>(0)?,
+              count: row.get::<_, u32>(1)?,
+            })
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(aggregates)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to aggregate album search lookup statuses"
+        );
+        anyhow!("Failed to aggregate album search lookup statuses")
+      })?
   }
 
+  #[instrument(skip(self))]
   pub async fn delete(&self, query: &AlbumSearchLookupQuery) -> Result<()> {
+    let encoded_query = query.to_encoded_string();
     self
-      .redis_connection_pool
-      .get()
+      .sqlite_connection
+      .write()
       .await?
-      .del(key(query))
-      .await?;
-    Ok(())
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM album_search_lookups WHERE encoded_query = ?",
+          [encoded_query],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to delete album search lookup"
+        );
+        anyhow!("Failed to delete album search lookup")
+      })?
   }
 }
@@ -14,7 +14,6 @@ use crate::{
   sqlite::SqliteConnection,
 };
 use anyhow::Result;
-use rustis::{bb8::Pool, client::PooledClientManager};
 use std::sync::Arc;
 
 pub struct LookupInteractor {
@@ -23,15 +22,11 @@ pub struct LookupInteractor {
 }
 
 impl LookupInteractor {
-  pub fn new(
-    settings: Arc<Settings>,
-    redis_connection_pool: Arc<Pool<PooledClientManager>>,
-    sqlite_connection: Arc<SqliteConnection>,
-  ) -> Self {
+  pub fn new(settings: Arc<Settings>, sqlite_connection: Arc<SqliteConnection>) -> Self {
     Self {
-      album_search_lookup_repository: AlbumSearchLookupRepository {
-        redis_connection_pool: Arc::clone(&redis_connection_pool),
-      },
+      album_search_lookup_repository: AlbumSearchLookupRepository::new(Arc::clone(
+        &sqlite_connection,
+      )),
       event_publisher: EventPublisher {
         settings,
         sqlite_connection,
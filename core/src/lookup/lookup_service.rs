@@ -5,7 +5,6 @@ use crate::{
   settings::Settings,
   sqlite::SqliteConnection,
 };
-use rustis::{bb8::Pool, client::PooledClientManager};
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
 
@@ -51,13 +50,9 @@ pub struct LookupService {
 }
 
 impl LookupService {
-  pub fn new(
-    settings: Arc<Settings>,
-    redis_connection_pool: Arc<Pool<PooledClientManager>>,
-    sqlite_connection: Arc<SqliteConnection>,
-  ) -> Self {
+  pub fn new(settings: Arc<Settings>, sqlite_connection: Arc<SqliteConnection>) -> Self {
     Self {
-      lookup_interactor: LookupInteractor::new(settings, redis_connection_pool, sqlite_connection),
+      lookup_interactor: LookupInteractor::new(settings, sqlite_connection),
     }
   }
 }
@@ -2,34 +2,40 @@ use anyhow::Result;
 use core::{
   albums::{
     album_event_subscribers::build_album_event_subscribers,
-    redis_album_search_index::RedisAlbumSearchIndex,
+    album_search_index::{build_album_search_index, setup_album_search_index, AlbumSearchIndex},
     sqlite_album_repository::SqliteAlbumRepository,
   },
+  artists::artist_event_subscribers::build_artist_event_subscribers,
+  charts::chart_event_subscribers::build_chart_event_subscribers,
   crawler::{crawler::Crawler, crawler_interactor::CrawlerInteractor},
   events::event_subscriber::EventSubscriber,
   files::file_metadata::file_name::FileName,
   helpers::fifo_queue::FifoQueue,
   lookup::lookup_event_subscribers::build_lookup_event_subscribers,
   parser::{
+    outdated_file_reparser::start_outdated_file_reparser,
+    parser_degradation_detector::start_parser_degradation_detector,
     parser_event_subscribers::build_parser_event_subscribers, retry::start_parser_retry_consumer,
   },
   profile::profile_event_subscribers::build_profile_event_subscribers,
   recommendations::recommendation_event_subscribers::build_recommendation_event_subscribers,
-  redis::{build_redis_connection_pool, setup_redis_indexes},
+  redis::{build_redis_connection_pool, import_redis_stack_data},
   rpc::RpcServer,
   settings::Settings,
   sqlite::SqliteConnection,
   tracing::setup_tracing,
+  webhooks::webhook_event_subscribers::start_webhook_event_subscribers,
 };
 use dotenv::dotenv;
 use mimalloc::MiMalloc;
 use rustis::{bb8::Pool, client::PooledClientManager};
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 use tokio::task;
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
+#[allow(clippy::too_many_arguments)]
 fn run_rpc_server(
   settings: Arc<Settings>,
   redis_connection_pool: Arc<Pool<PooledClientManager>>,
@@ -37,7 +43,8 @@ fn run_rpc_server(
   crawler_interactor: Arc<CrawlerInteractor>,
   parser_retry_queue: Arc<FifoQueue<FileName>>,
   album_repository: Arc<SqliteAlbumRepository>,
-  album_search_index: Arc<RedisAlbumSearchIndex>,
+  album_search_index: Arc<dyn AlbumSearchIndex + Send + Sync + 'static>,
+  internal_subscriber_ids: HashSet<String>,
 ) -> task::JoinHandle<()> {
   let rpc_server = RpcServer::new(
     settings,
@@ -47,6 +54,7 @@ fn run_rpc_server(
     parser_retry_queue,
     album_repository,
     album_search_index,
+    internal_subscriber_ids,
   );
 
   task::spawn(async move {
@@ -59,7 +67,7 @@ fn start_event_subscribers(
   redis_connection_pool: Arc<Pool<PooledClientManager>>,
   sqlite_connection: Arc<SqliteConnection>,
   crawler_interactor: Arc<CrawlerInteractor>,
-) -> Result<()> {
+) -> Result<HashSet<String>> {
   let mut event_subscribers: Vec<EventSubscriber> = Vec::new();
   event_subscribers.extend(build_album_event_subscribers(
     Arc::clone(&redis_connection_pool),
@@ -67,6 +75,16 @@ fn start_event_subscribers(
     settings.clone(),
     Arc::clone(&crawler_interactor),
   )?);
+  event_subscribers.extend(build_artist_event_subscribers(
+    Arc::clone(&redis_connection_pool),
+    Arc::clone(&sqlite_connection),
+    settings.clone(),
+  )?);
+  event_subscribers.extend(build_chart_event_subscribers(
+    Arc::clone(&redis_connection_pool),
+    Arc::clone(&sqlite_connection),
+    settings.clone(),
+  )?);
   event_subscribers.extend(build_parser_event_subscribers(
     Arc::clone(&redis_connection_pool),
     Arc::clone(&sqlite_connection),
@@ -89,10 +107,14 @@ fn start_event_subscribers(
     settings,
     Arc::clone(&crawler_interactor),
   )?);
+  let subscriber_ids = event_subscribers
+    .iter()
+    .map(|subscriber| subscriber.id.clone())
+    .collect::<HashSet<String>>();
   event_subscribers.into_iter().for_each(|subscriber| {
     task::spawn(async move { subscriber.run().await });
   });
-  Ok(())
+  Ok(subscriber_ids)
 }
 
 #[tokio::main]
@@ -104,7 +126,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
   let sqlite_connection = Arc::new(SqliteConnection::new(Arc::clone(&settings)).await?);
 
   let redis_connection_pool = Arc::new(build_redis_connection_pool(settings.redis.clone()).await?);
-  setup_redis_indexes(redis_connection_pool.clone()).await?;
+  setup_album_search_index(
+    &settings,
+    Arc::clone(&redis_connection_pool),
+    Arc::clone(&sqlite_connection),
+  )
+  .await?;
+  import_redis_stack_data(
+    Arc::clone(&redis_connection_pool),
+    Arc::clone(&sqlite_connection),
+  )
+  .await?;
 
   let parser_retry_queue: Arc<FifoQueue<FileName>> = Arc::new(FifoQueue::new(
     Arc::clone(&redis_connection_pool),
@@ -116,6 +148,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Arc::clone(&sqlite_connection),
     Arc::clone(&settings),
   )?;
+  start_outdated_file_reparser(
+    Arc::clone(&redis_connection_pool),
+    Arc::clone(&sqlite_connection),
+    Arc::clone(&settings),
+  )?;
 
   let crawler = Arc::new(Crawler::new(
     Arc::clone(&settings),
@@ -123,18 +160,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Arc::clone(&sqlite_connection),
   )?);
   crawler.run()?;
+  start_parser_degradation_detector(
+    Arc::clone(&sqlite_connection),
+    Arc::clone(&settings),
+    Arc::clone(&crawler.crawler_interactor),
+  )?;
 
-  start_event_subscribers(
+  let internal_subscriber_ids = start_event_subscribers(
     Arc::clone(&settings),
     Arc::clone(&redis_connection_pool),
     Arc::clone(&sqlite_connection),
     Arc::clone(&crawler.crawler_interactor),
   )?;
+  start_webhook_event_subscribers(
+    Arc::clone(&redis_connection_pool),
+    Arc::clone(&sqlite_connection),
+    Arc::clone(&settings),
+  )?;
 
   let album_repository = Arc::new(SqliteAlbumRepository::new(Arc::clone(&sqlite_connection)));
-  let album_search_index = Arc::new(RedisAlbumSearchIndex::new(Arc::clone(
-    &redis_connection_pool,
-  )));
+  let album_search_index = build_album_search_index(
+    &settings,
+    Arc::clone(&redis_connection_pool),
+    Arc::clone(&sqlite_connection),
+  );
   run_rpc_server(
     Arc::clone(&settings),
     Arc::clone(&redis_connection_pool),
@@ -143,6 +192,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Arc::clone(&parser_retry_queue),
     Arc::clone(&album_repository),
     Arc::clone(&album_search_index),
+    internal_subscriber_ids,
   )
   .await?;
 
@@ -45,11 +45,9 @@ impl OperationsService {
       file_interactor: FileInteractor::new(
         settings,
         Arc::clone(&redis_connection_pool),
-        sqlite_connection,
+        Arc::clone(&sqlite_connection),
       ),
-      failed_parse_files_repository: FailedParseFilesRepository {
-        redis_connection_pool: Arc::clone(&redis_connection_pool),
-      },
+      failed_parse_files_repository: FailedParseFilesRepository::new(sqlite_connection),
     }
   }
 }
@@ -1,31 +1,169 @@
 use super::{
   dom::{
-    get_link_tag_href, get_meta_value, get_node_inner_text, get_tag_inner_text, query_select_first,
+    get_link_tag_href, get_meta_value, get_meta_value_as, get_node_inner_text, get_tag_inner_text,
+    query_select_first,
+  },
+  parsed_file_data::{
+    ExternalLinkSource, ParsedAlbum, ParsedArtistReference, ParsedChartPosition, ParsedCredit,
+    ParsedExternalLink, ParsedLabel, ParsedReview, ParsedTrack,
+  },
+  parser_error::{ParserError, ParserResultExt},
+  util::{
+    clean_album_name, clean_artist_name, expand_role_tracks, parse_chart_positions,
+    parse_duration_seconds, parse_release_date,
   },
-  parsed_file_data::{ParsedAlbum, ParsedArtistReference, ParsedCredit, ParsedTrack},
-  util::{clean_album_name, clean_artist_name, parse_release_date},
 };
 use crate::files::file_metadata::file_name::FileName;
-use anyhow::{Error, Result};
+use chrono::NaiveDate;
+use htmlescape::decode_html;
+use serde_json::Value;
+use std::collections::HashMap;
 use tracing::{instrument, warn};
 
+fn to_external_link(source: &str, id: &str, attributes: &Value) -> Option<ParsedExternalLink> {
+  let (source, url) = match source {
+    "spotify" => (
+      ExternalLinkSource::Spotify,
+      format!("https://open.spotify.com/album/{}", id),
+    ),
+    "bandcamp" => (
+      ExternalLinkSource::Bandcamp,
+      format!("https://{}", attributes["url"].as_str()?),
+    ),
+    "applemusic" => (
+      ExternalLinkSource::AppleMusic,
+      format!(
+        "https://music.apple.com/{}/album/{}",
+        attributes["loc"].as_str().unwrap_or("us"),
+        id
+      ),
+    ),
+    "youtube" => (
+      ExternalLinkSource::YouTube,
+      match attributes["type"].as_str() {
+        Some("playlist") => format!("https://www.youtube.com/playlist?list={}", id),
+        _ => format!("https://www.youtube.com/watch?v={}", id),
+      },
+    ),
+    "discogs" => (
+      ExternalLinkSource::Discogs,
+      format!(
+        "https://www.discogs.com/{}/{}",
+        attributes["type"].as_str().unwrap_or("release"),
+        id
+      ),
+    ),
+    _ => return None,
+  };
+  Some(ParsedExternalLink {
+    source,
+    id: id.to_string(),
+    url,
+  })
+}
+
+/**
+ * Media links are embedded as JSON in the `data-links` attribute, keyed by source and then by ID.
+ */
+fn parse_external_links(dom: &tl::VDom) -> Vec<ParsedExternalLink> {
+  let links = dom
+    .query_selector("#media_link_button_container_top")
+    .and_then(|mut iter| iter.next())
+    .and_then(|node| node.get(dom.parser()))
+    .and_then(|node| node.as_tag())
+    .and_then(|tag| tag.attributes().get("data-links"))
+    .flatten()
+    .map(|content| content.as_utf8_str().to_string())
+    .and_then(|links| decode_html(&links).ok())
+    .and_then(|links| serde_json::from_str::<HashMap<String, HashMap<String, Value>>>(&links).ok());
+
+  let mut external_links = links
+    .map(|links| {
+      links
+        .iter()
+        .flat_map(|(source, ids)| {
+          ids
+            .iter()
+            .filter_map(|(id, attributes)| to_external_link(source, id, attributes))
+        })
+        .collect::<Vec<ParsedExternalLink>>()
+    })
+    .unwrap_or(vec![]);
+  // JSON object key order is not preserved, so sort to keep the parsed output stable.
+  external_links.sort_by_key(|link| (link.source.to_string(), link.id.clone()));
+  external_links
+}
+
+fn get_album_info_value<'a>(
+  parser: &'a tl::Parser<'a>,
+  container: &'a tl::HTMLTag<'a>,
+  key: &str,
+) -> Option<String> {
+  query_select_first(parser, container, ".album_info")
+    .ok()?
+    .query_selector(parser, "tr")?
+    .find_map(|node| {
+      let tag = node.get(parser).and_then(|node| node.as_tag())?;
+      let row_key = get_tag_inner_text(parser, tag, "th").ok()?;
+      if row_key == key {
+        get_tag_inner_text(parser, tag, "td").ok()
+      } else {
+        None
+      }
+    })
+}
+
+/**
+ * Labels and catalog numbers are listed per issue, we use the ones of the issue the page is for.
+ */
+fn parse_labels(dom: &tl::VDom) -> Vec<ParsedLabel> {
+  let issue = dom
+    .query_selector(".release_view")
+    .and_then(|mut iter| iter.next())
+    .or_else(|| {
+      dom
+        .query_selector(".issue_info")
+        .and_then(|mut iter| iter.next())
+    })
+    .and_then(|node| node.get(dom.parser()))
+    .and_then(|node| node.as_tag());
+  let issue = match issue {
+    Some(issue) => issue,
+    None => return vec![],
+  };
+
+  let get_texts = |selector: &'static str| {
+    issue
+      .query_selector(dom.parser(), selector)
+      .map(|iter| {
+        iter
+          .filter_map(|node| get_node_inner_text(dom.parser(), &node).ok())
+          .filter(|text| !text.is_empty())
+          .collect::<Vec<String>>()
+      })
+      .unwrap_or(vec![])
+  };
+  let catalog_numbers = get_texts(".catalog_num");
+
+  get_texts(".label_name")
+    .into_iter()
+    .enumerate()
+    .map(|(i, name)| ParsedLabel {
+      name,
+      catalog_number: catalog_numbers.get(i).cloned(),
+    })
+    .collect()
+}
+
 #[instrument(skip(file_content))]
-pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
+pub fn parse_album(file_content: &str) -> Result<ParsedAlbum, ParserError> {
   let dom = tl::parse(file_content, tl::ParserOptions::default())?;
 
-  let name = clean_album_name(get_meta_value(&dom, "name")?);
+  let name = clean_album_name(get_meta_value(&dom, "name").in_field("name")?);
 
-  let rating = get_meta_value(&dom, "ratingValue").and_then(|rating| {
-    rating
-      .parse::<f32>()
-      .map_err(|err| anyhow::anyhow!("Failed to parse rating: {}", err))
-  })?;
+  let rating = get_meta_value_as::<f32>(&dom, "ratingValue").in_field("rating")?;
 
-  let rating_count = get_meta_value(&dom, "ratingCount").and_then(|rating_count| {
-    rating_count
-      .parse::<u32>()
-      .map_err(|err| anyhow::anyhow!("Failed to parse rating count: {}", err))
-  })?;
+  let rating_count = get_meta_value_as::<u32>(&dom, "ratingCount").in_field("rating_count")?;
 
   let release_date = dom
     .query_selector(".issue_year.ymd")
@@ -43,9 +181,10 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
     .and_then(|mut iter| iter.next())
     .and_then(|node| node.get(dom.parser()))
     .and_then(|node| node.as_tag())
-    .ok_or(anyhow::anyhow!("No release page container found"))?;
+    .ok_or(ParserError::element_not_found(".release_page"))?;
 
-  let cover_image_url = query_select_first(dom.parser(), container, ".page_release_art_frame")?
+  let cover_image_url = query_select_first(dom.parser(), container, ".page_release_art_frame")
+    .in_field("cover_image_url")?
     .query_selector(dom.parser(), "img")
     .and_then(|mut iter| iter.next())
     .and_then(|node| node.get(dom.parser()))
@@ -54,7 +193,8 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
     .flatten()
     .map(|content| format!("https:{}", content.as_utf8_str().to_string()));
 
-  let artists = query_select_first(dom.parser(), container, "span[itemprop='byArtist']")?
+  let artists = query_select_first(dom.parser(), container, "span[itemprop='byArtist']")
+    .in_field("artists")?
     .query_selector(dom.parser(), "a")
     .map(|iter| {
       iter
@@ -72,16 +212,19 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
         })
         .collect::<Vec<ParsedArtistReference>>()
     })
-    .ok_or(anyhow::anyhow!("Failed to parse artists"))?;
+    .ok_or(ParserError::element_not_found("a"))
+    .in_field("artists")?;
 
-  let primary_genres = query_select_first(dom.parser(), container, ".release_pri_genres")?
+  let primary_genres = query_select_first(dom.parser(), container, ".release_pri_genres")
+    .in_field("primary_genres")?
     .query_selector(dom.parser(), ".genre")
     .map(|iter| {
       iter
         .map(|node| get_node_inner_text(dom.parser(), &node).unwrap())
         .collect::<Vec<String>>()
     })
-    .ok_or(anyhow::anyhow!("Failed to parse primary genres"))?;
+    .ok_or(ParserError::element_not_found(".genre"))
+    .in_field("primary_genres")?;
 
   let secondary_genres = match query_select_first(dom.parser(), container, ".release_sec_genres") {
     Ok(node) => node
@@ -91,11 +234,13 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
           .map(|node| get_node_inner_text(dom.parser(), &node).unwrap())
           .collect::<Vec<String>>()
       })
-      .ok_or(anyhow::anyhow!("Failed to parse secondary genres"))?,
+      .ok_or(ParserError::element_not_found(".genre"))
+      .in_field("secondary_genres")?,
     Err(_) => vec![],
   };
 
-  let descriptors = query_select_first(dom.parser(), container, ".release_descriptors")?
+  let descriptors = query_select_first(dom.parser(), container, ".release_descriptors")
+    .in_field("descriptors")?
     .query_selector(dom.parser(), "meta")
     .map(|iter| {
       iter
@@ -111,9 +256,24 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
         })
         .collect::<Vec<String>>()
     })
-    .ok_or(anyhow::anyhow!("Failed to parse descriptors"))?;
+    .ok_or(ParserError::element_not_found("meta"))
+    .in_field("descriptors")?;
+
+  let release_type = get_album_info_value(dom.parser(), container, "Type");
+
+  let chart_positions = get_album_info_value(dom.parser(), container, "Ranked")
+    .map(|ranked| {
+      parse_chart_positions(&ranked)
+        .into_iter()
+        .map(|(chart, position)| ParsedChartPosition { chart, position })
+        .collect::<Vec<ParsedChartPosition>>()
+    })
+    .unwrap_or(vec![]);
+
+  let labels = parse_labels(&dom);
 
-  let languages = query_select_first(dom.parser(), container, ".album_info")?
+  let languages = query_select_first(dom.parser(), container, ".album_info")
+    .in_field("languages")?
     .query_selector(dom.parser(), "tr")
     .map(|iter| {
       iter
@@ -148,7 +308,8 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
     })
     .unwrap_or(vec![]);
 
-  let tracks = query_select_first(dom.parser(), container, "#tracks")?
+  let mut tracks = query_select_first(dom.parser(), container, "#tracks")
+    .in_field("tracks")?
     .query_selector(dom.parser(), ".tracklist_line")
     .map(|iter| {
       iter
@@ -181,13 +342,39 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
             rating,
             duration_seconds,
             position,
+            credits: vec![],
           }
         })
         .collect::<Vec<ParsedTrack>>()
     })
-    .ok_or(anyhow::anyhow!("Failed to parse tracks"))?;
+    .ok_or(ParserError::element_not_found(".tracklist_line"))
+    .in_field("tracks")?;
+
+  let total_duration_seconds = dom
+    .query_selector(".tracklist_total")
+    .and_then(|mut iter| iter.next())
+    .and_then(|node| get_node_inner_text(dom.parser(), &node).ok())
+    .and_then(|text| {
+      text
+        .rsplit(' ')
+        .next()
+        .and_then(|duration| parse_duration_seconds(duration).ok())
+    })
+    .or_else(|| {
+      if !tracks.is_empty() && tracks.iter().all(|track| track.duration_seconds.is_some()) {
+        Some(
+          tracks
+            .iter()
+            .filter_map(|track| track.duration_seconds)
+            .sum(),
+        )
+      } else {
+        None
+      }
+    });
 
-  let credits = match query_select_first(dom.parser(), container, "#credits_") {
+  // Roles are paired with the tracks they are limited to, if any.
+  let credit_roles = match query_select_first(dom.parser(), container, "#credits_") {
     Ok(tag) => tag
       .query_selector(dom.parser(), "li")
       .map(|iter| {
@@ -217,29 +404,121 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
                     let text = get_node_inner_text(dom.parser(), &node)?;
                     let tag = node.get(dom.parser()).and_then(|node| node.as_tag());
                     if tag.is_none() {
-                      return Ok::<String, Error>(text);
+                      return Ok::<(String, Option<String>), ParserError>((text, None));
                     }
                     let tag = tag.unwrap();
                     let role_tracks = get_tag_inner_text(dom.parser(), tag, ".role_tracks");
                     if role_tracks.is_err() {
-                      return Ok(text);
+                      return Ok((text, None));
                     }
                     let role_tracks = role_tracks.unwrap();
-                    Ok(text.replace(&role_tracks, ""))
+                    // Roles with tracks used to keep the whitespace before the tracks, which
+                    // made them differ from the same role without tracks. Album parser
+                    // version 5 trims them, so older albums are re-parsed.
+                    Ok((
+                      text.replace(&role_tracks, "").trim().to_string(),
+                      Some(role_tracks),
+                    ))
                   })
-                  .filter_map(|role: Result<String, _>| role.ok())
-                  .collect::<Vec<String>>()
+                  .filter_map(|role: Result<(String, Option<String>), _>| role.ok())
+                  .collect::<Vec<(String, Option<String>)>>()
               })
               .unwrap_or(vec![]);
 
-            Some(ParsedCredit { artist, roles })
+            Some((artist, roles))
           })
-          .collect::<Vec<ParsedCredit>>()
+          .collect::<Vec<(ParsedArtistReference, Vec<(String, Option<String>)>)>>()
       })
       .unwrap_or(vec![]),
     Err(_) => vec![],
   };
 
+  let credits = credit_roles
+    .iter()
+    .map(|(artist, roles)| ParsedCredit {
+      artist: artist.clone(),
+      roles: roles.iter().map(|(role, _)| role.clone()).collect(),
+    })
+    .collect::<Vec<ParsedCredit>>();
+
+  let track_positions = tracks
+    .iter()
+    .filter_map(|track| track.position.clone())
+    .collect::<Vec<String>>();
+  for (artist, roles) in &credit_roles {
+    for (role, role_tracks) in roles {
+      let positions = match role_tracks {
+        Some(role_tracks) => expand_role_tracks(role_tracks, &track_positions),
+        None => continue,
+      };
+      for track in tracks.iter_mut().filter(|track| {
+        track
+          .position
+          .as_ref()
+          .is_some_and(|p| positions.contains(p))
+      }) {
+        match track
+          .credits
+          .iter_mut()
+          .find(|credit| credit.artist.file_name == artist.file_name)
+        {
+          Some(credit) => credit.roles.push(role.clone()),
+          None => track.credits.push(ParsedCredit {
+            artist: artist.clone(),
+            roles: vec![role.clone()],
+          }),
+        }
+      }
+    }
+  }
+
+  let reviews = dom
+    .query_selector(".review")
+    .map(|iter| {
+      iter
+        .filter_map(|node| {
+          let tag = node.get(dom.parser()).and_then(|node| node.as_tag())?;
+
+          let author = get_tag_inner_text(dom.parser(), tag, ".review_user").ok()?;
+          let text = get_tag_inner_text(dom.parser(), tag, ".review_body").ok()?;
+
+          let rating = query_select_first(dom.parser(), tag, ".review_rating")
+            .ok()
+            .and_then(|tag| tag.query_selector(dom.parser(), "img"))
+            .and_then(|mut iter| iter.next())
+            .and_then(|node| node.get(dom.parser()))
+            .and_then(|node| node.as_tag())
+            .and_then(|tag| tag.attributes().get("alt"))
+            .flatten()
+            .map(|content| content.as_utf8_str().to_string())
+            .and_then(|alt| {
+              alt
+                .split(' ')
+                .next()
+                .and_then(|rating| rating.parse::<f32>().ok())
+            });
+
+          let date = get_tag_inner_text(dom.parser(), tag, ".review_date")
+            .ok()
+            .and_then(|date| {
+              NaiveDate::parse_from_str(&date, "%b %d %Y")
+                .ok()
+                .or_else(|| parse_release_date(date).ok())
+            });
+
+          Some(ParsedReview {
+            author,
+            rating,
+            date,
+            text,
+          })
+        })
+        .collect::<Vec<ParsedReview>>()
+    })
+    .unwrap_or(vec![]);
+
+  let external_links = parse_external_links(&dom);
+
   Ok(ParsedAlbum {
     name,
     rating,
@@ -253,5 +532,11 @@ pub fn parse_album(file_content: &str) -> Result<ParsedAlbum> {
     languages,
     credits,
     cover_image_url,
+    reviews,
+    external_links,
+    release_type,
+    labels,
+    chart_positions,
+    total_duration_seconds,
   })
 }
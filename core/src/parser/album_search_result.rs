@@ -3,27 +3,32 @@ use crate::files::file_metadata::file_name::FileName;
 use super::{
   dom::{get_link_tag_href, query_select_first},
   parsed_file_data::{ParsedAlbumSearchResult, ParsedArtistReference},
+  parser_error::ParserError,
   util::clean_artist_name,
 };
-use anyhow::Result;
 use tracing::{instrument, warn};
 
 #[instrument(skip(file_content))]
-pub fn parse_album_search_result(file_content: &str) -> Result<ParsedAlbumSearchResult> {
+pub fn parse_album_search_result(
+  file_content: &str,
+) -> Result<ParsedAlbumSearchResult, ParserError> {
   let dom = tl::parse(file_content, tl::ParserOptions::default())?;
 
   let results = dom
     .query_selector(".infobox")
-    .ok_or(anyhow::anyhow!("No search results found"))?
-    .map(|node| -> Result<ParsedAlbumSearchResult> {
+    .ok_or(ParserError::no_results(".infobox"))?
+    .map(|node| -> Result<ParsedAlbumSearchResult, ParserError> {
       let tag = node
         .get(dom.parser())
         .and_then(|node| node.as_tag())
-        .ok_or(anyhow::anyhow!("Failed to get tag for search result"))?;
+        .ok_or(ParserError::element_not_found(".infobox"))?;
 
       let item = query_select_first(dom.parser(), tag, ".searchpage")?;
       let name = item.inner_text(dom.parser()).trim().to_string();
-      let file_name = FileName::try_from(get_link_tag_href(item)?)?;
+      let href = get_link_tag_href(item)?;
+      let file_name = FileName::try_from(href.clone()).map_err(|_| {
+        ParserError::invalid_value(&href, Some(".searchpage")).in_field("file_name")
+      })?;
       let artists = tag
         .query_selector(dom.parser(), ".artist")
         .unwrap()
@@ -58,6 +63,6 @@ pub fn parse_album_search_result(file_content: &str) -> Result<ParsedAlbumSearch
   results
     .iter()
     .find(|result| result.file_name.page_type().is_album())
-    .ok_or(anyhow::anyhow!("No album found in search results"))
+    .ok_or(ParserError::no_results(".searchpage"))
     .map(|album| album.clone())
 }
@@ -1,20 +1,23 @@
 use super::{
-  dom::{self, get_link_tag_href, get_meta_value},
-  parsed_file_data::{ParsedArtist, ParsedArtistAlbum},
+  dom::{self, get_link_tag_href, get_meta_value, has_class},
+  parsed_file_data::{ParsedArtist, ParsedArtistAlbum, ParsedArtistReference},
+  parser_error::{ParserError, ParserResultExt},
+  util::{clean_artist_name, parse_release_date},
 };
 use crate::files::file_metadata::file_name::FileName;
-use anyhow::Result;
-use tl::VDom;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use tl::{NodeHandle, VDom};
 use tracing::instrument;
 
-fn parse_artist_album(dom: &VDom, selector: &str) -> Result<Vec<ParsedArtistAlbum>> {
+fn parse_artist_album(dom: &VDom, selector: &str) -> Result<Vec<ParsedArtistAlbum>, ParserError> {
   dom
     .query_selector(selector)
     .and_then(|mut iter| iter.next())
     .and_then(|node| node.get(dom.parser()))
     .and_then(|node| node.as_tag())
     .and_then(|tag| tag.query_selector(dom.parser(), ".album"))
-    .ok_or(anyhow::anyhow!("No artist albums found"))
+    .ok_or(ParserError::element_not_found(selector))
     .map(|iter| {
       iter
         .map(|node| {
@@ -32,10 +35,111 @@ fn parse_artist_album(dom: &VDom, selector: &str) -> Result<Vec<ParsedArtistAlbu
     })
 }
 
+/**
+ * The artist info section is a flat list of `.info_hdr` elements, each followed by an
+ * `.info_content` element. Returns the content nodes keyed by their header text.
+ */
+fn get_artist_info_sections(dom: &VDom) -> HashMap<String, NodeHandle> {
+  let mut sections = HashMap::new();
+  let container = dom
+    .query_selector(".artist_info")
+    .and_then(|mut iter| iter.next())
+    .and_then(|node| node.get(dom.parser()))
+    .and_then(|node| node.as_tag());
+  let container = match container {
+    Some(container) => container,
+    None => return sections,
+  };
+
+  let mut header: Option<String> = None;
+  for node in container.children().top().iter() {
+    if has_class(dom.parser(), node, "info_hdr") {
+      header = dom::get_node_inner_text(dom.parser(), node).ok();
+    } else if has_class(dom.parser(), node, "info_content") {
+      if let Some(header) = header.take() {
+        sections.insert(header, *node);
+      }
+    }
+  }
+  sections
+}
+
+fn get_section_text(
+  dom: &VDom,
+  sections: &HashMap<String, NodeHandle>,
+  headers: &[&str],
+) -> Option<String> {
+  headers
+    .iter()
+    .find_map(|header| sections.get(*header))
+    .and_then(|node| dom::get_node_inner_text(dom.parser(), node).ok())
+    .filter(|text| !text.is_empty())
+}
+
+fn get_section_links(
+  dom: &VDom,
+  sections: &HashMap<String, NodeHandle>,
+  header: &str,
+  selector: &str,
+) -> Vec<(String, String)> {
+  sections
+    .get(header)
+    .and_then(|node| node.get(dom.parser()))
+    .and_then(|node| node.as_tag())
+    .and_then(|tag| tag.query_selector(dom.parser(), selector))
+    .map(|iter| {
+      iter
+        .filter_map(|node| {
+          let tag = node.get(dom.parser()).and_then(|node| node.as_tag())?;
+          let name = dom::get_node_inner_text(dom.parser(), &node).ok()?;
+          let href = get_link_tag_href(tag).ok()?;
+          Some((name, href))
+        })
+        .collect()
+    })
+    .unwrap_or(vec![])
+}
+
+fn get_section_artists(
+  dom: &VDom,
+  sections: &HashMap<String, NodeHandle>,
+  header: &str,
+) -> Vec<ParsedArtistReference> {
+  get_section_links(dom, sections, header, "a.artist")
+    .into_iter()
+    .filter_map(|(name, href)| {
+      Some(ParsedArtistReference {
+        name: clean_artist_name(&name).to_string(),
+        file_name: FileName::try_from(href).ok()?,
+      })
+    })
+    .collect()
+}
+
+/**
+ * Splits values such as "13 March 1985, Abingdon, Oxfordshire, England, United Kingdom"
+ * into a date and a location. Either part may be missing.
+ */
+fn parse_date_and_location(value: &str) -> (Option<NaiveDate>, Option<String>) {
+  let (first, rest) = match value.split_once(',') {
+    Some((first, rest)) => (first.trim(), Some(rest.trim())),
+    None => (value.trim(), None),
+  };
+  match parse_release_date(first.to_string()) {
+    Ok(date) => (
+      Some(date),
+      rest
+        .filter(|rest| !rest.is_empty())
+        .map(|rest| rest.to_string()),
+    ),
+    Err(_) => (None, Some(value.trim().to_string())),
+  }
+}
+
 #[instrument(skip(file_content))]
-pub fn parse_artist(file_content: &str) -> Result<ParsedArtist> {
+pub fn parse_artist(file_content: &str) -> Result<ParsedArtist, ParserError> {
   let dom = tl::parse(file_content, tl::ParserOptions::default())?;
-  let name = get_meta_value(&dom, "name")?;
+  let name = get_meta_value(&dom, "name").in_field("name")?;
   let albums = parse_artist_album(&dom, "#disco_type_s").unwrap_or(vec![]);
   let mixtapes = parse_artist_album(&dom, "#disco_type_m").unwrap_or(vec![]);
   let eps = parse_artist_album(&dom, "#disco_type_e").unwrap_or(vec![]);
@@ -45,5 +149,39 @@ pub fn parse_artist(file_content: &str) -> Result<ParsedArtist> {
     .chain(eps.into_iter())
     .collect();
 
-  Ok(ParsedArtist { name, albums })
+  let sections = get_artist_info_sections(&dom);
+
+  let (formed_date, formed_location) = get_section_text(&dom, &sections, &["Formed", "Born"])
+    .map(|value| parse_date_and_location(&value))
+    .unwrap_or((None, None));
+  let (disbanded_date, _) = get_section_text(&dom, &sections, &["Disbanded", "Died"])
+    .map(|value| parse_date_and_location(&value))
+    .unwrap_or((None, None));
+  let location = get_section_text(&dom, &sections, &["Currently"]).or(formed_location);
+  let country = location
+    .as_ref()
+    .and_then(|location| location.rsplit(',').next())
+    .map(|country| country.trim().to_string())
+    .filter(|country| !country.is_empty());
+
+  let genres = get_section_links(&dom, &sections, "Genres", "a.genre")
+    .into_iter()
+    .map(|(name, _)| name)
+    .collect();
+  let members = get_section_artists(&dom, &sections, "Members");
+  let member_of = get_section_artists(&dom, &sections, "Member of");
+  let related_artists = get_section_artists(&dom, &sections, "Related Artists");
+
+  Ok(ParsedArtist {
+    name,
+    albums,
+    formed_date,
+    disbanded_date,
+    location,
+    country,
+    genres,
+    members,
+    member_of,
+    related_artists,
+  })
 }
@@ -1,141 +1,194 @@
 use super::{
-  dom::{get_link_tag_href, get_node_inner_text, get_tag_inner_text, query_select_first},
-  parsed_file_data::{ParsedArtistReference, ParsedChartAlbum},
+  dom::{
+    get_link_tag_href, get_node_inner_text, get_tag_inner_text, has_class, parse_text,
+    query_select_first,
+  },
+  parsed_file_data::{ParsedArtistReference, ParsedChart, ParsedChartAlbum},
+  parser_error::{ParserError, ParserResultExt},
   util::{clean_artist_name, parse_release_date},
 };
 use crate::files::file_metadata::file_name::FileName;
-use anyhow::{Ok, Result};
+use tl::VDom;
 use tracing::{instrument, warn};
 
+/**
+ * Returns the current page number and the total number of pages from the chart pagination
+ * links. Charts without pagination links have a single page.
+ */
+fn parse_pagination(dom: &VDom) -> (u32, Option<u32>) {
+  let pages = dom
+    .query_selector(".ui_pagination_number")
+    .map(|nodes| {
+      nodes
+        .filter_map(|node| {
+          get_node_inner_text(dom.parser(), &node)
+            .ok()
+            .and_then(|text| text.replace(',', "").parse::<u32>().ok())
+            .map(|page| (page, has_class(dom.parser(), &node, "selected")))
+        })
+        .collect::<Vec<(u32, bool)>>()
+    })
+    .unwrap_or_default();
+
+  if pages.is_empty() {
+    return (1, Some(1));
+  }
+
+  let page_number = pages
+    .iter()
+    .find(|(_, selected)| *selected)
+    .map(|(page, _)| *page)
+    .unwrap_or(1);
+  let total_pages = pages.iter().map(|(page, _)| *page).max();
+  (page_number, total_pages)
+}
+
 #[instrument(skip(file_content))]
-pub fn parse_chart(file_content: &str) -> Result<Vec<ParsedChartAlbum>> {
+pub fn parse_chart(file_content: &str) -> Result<ParsedChart, ParserError> {
   let dom = tl::parse(file_content, tl::ParserOptions::default())?;
+  let (page_number, total_pages) = parse_pagination(&dom);
   let handle = dom
     .query_selector(".page_charts_section_charts_item")
-    .ok_or(anyhow::anyhow!("No charts found"))?;
-
-  let albums: Vec<ParsedChartAlbum> = handle
-    .map(|item| match item.get(dom.parser()) {
-      Some(node) => {
-        let tag = node.as_tag().unwrap();
+    .ok_or(ParserError::no_results(".page_charts_section_charts_item"))?;
 
-        let name = get_tag_inner_text(dom.parser(), tag, ".page_charts_section_charts_item_title")?;
+  let albums = handle
+    .map(
+      |item| match item.get(dom.parser()).and_then(|node| node.as_tag()) {
+        Some(tag) => {
+          let name =
+            get_tag_inner_text(dom.parser(), tag, ".page_charts_section_charts_item_title")
+              .in_field("name")?;
 
-        let rating = get_tag_inner_text(
-          dom.parser(),
-          tag,
-          ".page_charts_section_charts_item_details_average_num",
-        )?
-        .parse::<f32>()?;
+          let rating = get_tag_inner_text(
+            dom.parser(),
+            tag,
+            ".page_charts_section_charts_item_details_average_num",
+          )
+          .and_then(|rating| {
+            parse_text::<f32>(
+              &rating,
+              ".page_charts_section_charts_item_details_average_num",
+            )
+          })
+          .in_field("rating")?;
 
-        let rating_count = get_tag_inner_text(
-          dom.parser(),
-          query_select_first(
+          let rating_count = query_select_first(
             dom.parser(),
             tag,
             ".page_charts_section_charts_item_details_ratings",
-          )?,
-          ".full",
-        )?
-        .replace(',', "")
-        .parse::<u32>()?;
-
-        let artists = query_select_first(
-          dom.parser(),
-          tag,
-          ".page_charts_section_charts_item_credited_links_primary",
-        )?
-        .query_selector(dom.parser(), "a")
-        .unwrap()
-        .map(|node| ParsedArtistReference {
-          name: clean_artist_name(get_node_inner_text(dom.parser(), &node).unwrap().as_str())
-            .to_string(),
-          file_name: FileName::try_from(
-            get_link_tag_href(node.get(dom.parser()).unwrap().as_tag().unwrap()).unwrap(),
           )
-          .unwrap(),
-        })
-        .collect::<Vec<ParsedArtistReference>>();
-
-        let primary_genres = query_select_first(
-          dom.parser(),
-          tag,
-          ".page_charts_section_charts_item_genres_primary",
-        )?
-        .query_selector(dom.parser(), "a")
-        .ok_or(anyhow::anyhow!("No primary genres found"))?
-        .map(|genre| get_node_inner_text(dom.parser(), &genre).unwrap())
-        .collect::<Vec<String>>();
-
-        let secondary_genres = query_select_first(
-          dom.parser(),
-          tag,
-          ".page_charts_section_charts_item_genres_secondary",
-        )
-        .map(|tag| {
-          tag.query_selector(dom.parser(), "a").map(|genres| {
-            genres
-              .map(|genre| get_node_inner_text(dom.parser(), &genre).unwrap())
-              .collect::<Vec<String>>()
+          .and_then(|ratings| get_tag_inner_text(dom.parser(), ratings, ".full"))
+          .and_then(|rating_count| parse_text::<u32>(&rating_count.replace(',', ""), ".full"))
+          .in_field("rating_count")?;
+
+          let artists = query_select_first(
+            dom.parser(),
+            tag,
+            ".page_charts_section_charts_item_credited_links_primary",
+          )
+          .in_field("artists")?
+          .query_selector(dom.parser(), "a")
+          .unwrap()
+          .map(|node| ParsedArtistReference {
+            name: clean_artist_name(get_node_inner_text(dom.parser(), &node).unwrap().as_str())
+              .to_string(),
+            file_name: FileName::try_from(
+              get_link_tag_href(node.get(dom.parser()).unwrap().as_tag().unwrap()).unwrap(),
+            )
+            .unwrap(),
           })
-        })
-        .unwrap_or(Some(Vec::new()))
-        .unwrap_or(Vec::new());
-
-        let descriptors = query_select_first(
-          dom.parser(),
-          tag,
-          ".page_charts_section_charts_item_genre_descriptors",
-        )
-        .map(|tag| {
-          tag.query_selector(dom.parser(), "span").map(|descriptors| {
-            descriptors
-              .map(|descriptor| get_node_inner_text(dom.parser(), &descriptor).unwrap())
-              .collect::<Vec<String>>()
+          .collect::<Vec<ParsedArtistReference>>();
+
+          let primary_genres = query_select_first(
+            dom.parser(),
+            tag,
+            ".page_charts_section_charts_item_genres_primary",
+          )
+          .in_field("primary_genres")?
+          .query_selector(dom.parser(), "a")
+          .ok_or(ParserError::element_not_found("a"))
+          .in_field("primary_genres")?
+          .map(|genre| get_node_inner_text(dom.parser(), &genre).unwrap())
+          .collect::<Vec<String>>();
+
+          let secondary_genres = query_select_first(
+            dom.parser(),
+            tag,
+            ".page_charts_section_charts_item_genres_secondary",
+          )
+          .map(|tag| {
+            tag.query_selector(dom.parser(), "a").map(|genres| {
+              genres
+                .map(|genre| get_node_inner_text(dom.parser(), &genre).unwrap())
+                .collect::<Vec<String>>()
+            })
           })
-        })
-        .unwrap_or(Some(Vec::new()))
-        .unwrap_or(Vec::new());
+          .unwrap_or(Some(Vec::new()))
+          .unwrap_or(Vec::new());
 
-        let file_name = FileName::try_from(
-          get_link_tag_href(query_select_first(
+          let descriptors = query_select_first(
             dom.parser(),
             tag,
-            ".page_charts_section_charts_item_link",
-          )?)
-          .unwrap(),
-        )?;
-
-        let release_date_string = get_tag_inner_text(
-          dom.parser(),
-          query_select_first(dom.parser(), tag, ".page_charts_section_charts_item_date")?,
-          "span",
-        )
-        .ok();
-
-        let release_date =
-          release_date_string.and_then(|date_string| parse_release_date(date_string).ok());
-
-        let data = ParsedChartAlbum {
-          file_name,
-          name,
-          rating,
-          rating_count,
-          artists,
-          primary_genres,
-          secondary_genres,
-          descriptors,
-          release_date,
-        };
-        Ok(data)
-      }
-      None => {
-        warn!("No album node found");
-        Err(anyhow::anyhow!("No album node found"))
-      }
-    })
-    .map(|res| res.unwrap())
-    .collect();
+            ".page_charts_section_charts_item_genre_descriptors",
+          )
+          .map(|tag| {
+            tag.query_selector(dom.parser(), "span").map(|descriptors| {
+              descriptors
+                .map(|descriptor| get_node_inner_text(dom.parser(), &descriptor).unwrap())
+                .collect::<Vec<String>>()
+            })
+          })
+          .unwrap_or(Some(Vec::new()))
+          .unwrap_or(Vec::new());
+
+          let href = query_select_first(dom.parser(), tag, ".page_charts_section_charts_item_link")
+            .and_then(get_link_tag_href)
+            .in_field("file_name")?;
+          let file_name = FileName::try_from(href.clone()).map_err(|_| {
+            ParserError::invalid_value(&href, Some(".page_charts_section_charts_item_link"))
+              .in_field("file_name")
+          })?;
+
+          let release_date_string =
+            query_select_first(dom.parser(), tag, ".page_charts_section_charts_item_date")
+              .and_then(|date| get_tag_inner_text(dom.parser(), date, "span"))
+              .ok();
+
+          let release_date =
+            release_date_string.and_then(|date_string| parse_release_date(date_string).ok());
+
+          let position =
+            get_tag_inner_text(dom.parser(), tag, ".page_charts_section_charts_item_number")
+              .ok()
+              .and_then(|position| position.replace(',', "").parse::<u32>().ok());
+
+          let data = ParsedChartAlbum {
+            file_name,
+            name,
+            rating,
+            rating_count,
+            artists,
+            primary_genres,
+            secondary_genres,
+            descriptors,
+            release_date,
+            position,
+          };
+          Ok(data)
+        }
+        None => {
+          warn!("No album node found");
+          Err(ParserError::element_not_found(
+            ".page_charts_section_charts_item",
+          ))
+        }
+      },
+    )
+    .collect::<Result<Vec<ParsedChartAlbum>, ParserError>>()?;
 
-  Ok(albums)
+  Ok(ParsedChart {
+    albums,
+    page_number,
+    total_pages,
+  })
 }
@@ -1,6 +1,9 @@
-use anyhow::Result;
+use super::parser_error::ParserError;
 use htmlescape::decode_html;
-use tl::VDom;
+use std::str::FromStr;
+use tl::{NodeHandle, Parser, VDom};
+
+type Result<T> = std::result::Result<T, ParserError>;
 
 pub fn query_select_first<'a>(
   parser: &'a tl::Parser<'a>,
@@ -10,14 +13,15 @@ pub fn query_select_first<'a>(
   tag
     .query_selector(parser, selector)
     .and_then(|mut iter| iter.next())
-    .ok_or(anyhow::anyhow!(
-      "No element found for selector: {}",
-      selector
-    ))?
-    .get(parser)
-    .ok_or(anyhow::anyhow!("Failed to get parser node"))?
-    .as_tag()
-    .ok_or(anyhow::anyhow!("Failed to convert node to tag"))
+    .and_then(|node| node.get(parser))
+    .and_then(|node| node.as_tag())
+    .ok_or(ParserError::element_not_found(selector))
+}
+
+fn decode_text(value: &str, selector: Option<&str>) -> Result<String> {
+  decode_html(value)
+    .map(|text| text.trim().to_string())
+    .map_err(|_| ParserError::invalid_value(value, selector))
 }
 
 pub fn get_tag_inner_text<'a>(
@@ -26,11 +30,7 @@ pub fn get_tag_inner_text<'a>(
   selector: &'a str,
 ) -> Result<String> {
   query_select_first(parser, tag, selector)
-    .map(|tag| tag.inner_text(parser).trim().to_string())
-    .and_then(|value| {
-      decode_html(&value).map_err(|err| anyhow::anyhow!("Failed to decode html: {:?}", err))
-    })
-    .map(|text| text.trim().to_string())
+    .and_then(|tag| decode_text(tag.inner_text(parser).trim(), Some(selector)))
 }
 
 pub fn get_node_inner_text<'a>(
@@ -39,19 +39,26 @@ pub fn get_node_inner_text<'a>(
 ) -> Result<String> {
   node
     .get(parser)
-    .ok_or(anyhow::anyhow!("Failed to get parser node"))?
-    .as_tag()
-    .ok_or(anyhow::anyhow!("Failed to convert node to tag"))
-    .and_then(|tag| {
-      decode_html(tag.inner_text(parser).as_ref())
-        .map_err(|err| anyhow::anyhow!("Failed to decode html: {:?}", err))
+    .and_then(|node| node.as_tag())
+    .ok_or(ParserError::InvalidHtml {
+      message: "Node is not a tag".to_string(),
     })
-    .map(|text| text.trim().to_string())
+    .and_then(|tag| decode_text(tag.inner_text(parser).as_ref(), None))
+}
+
+/**
+ * Parses the text of an element, reporting the selector it was found at if it is invalid.
+ */
+pub fn parse_text<T: FromStr>(text: &str, selector: &str) -> Result<T> {
+  text
+    .parse::<T>()
+    .map_err(|_| ParserError::invalid_value(text, Some(selector)))
 }
 
 pub fn get_meta_value<'a>(dom: &'a VDom, name: &'a str) -> Result<String> {
-  dom
-    .query_selector(&format!("meta[itemprop=\"{}\"]", name))
+  let selector = format!("meta[itemprop=\"{}\"]", name);
+  let value = dom
+    .query_selector(&selector)
     .and_then(|mut iter| iter.next())
     .and_then(|node| node.get(dom.parser()))
     .and_then(|node| node.as_tag())
@@ -59,11 +66,13 @@ pub fn get_meta_value<'a>(dom: &'a VDom, name: &'a str) -> Result<String> {
     .flatten()
     .map(|content| content.as_utf8_str())
     .map(|name| name.to_string())
-    .ok_or(anyhow::anyhow!("No meta value found for name: {}", name))
-    .and_then(|value| {
-      decode_html(&value).map_err(|err| anyhow::anyhow!("Failed to decode html: {:?}", err))
-    })
-    .map(|text| text.trim().to_string())
+    .ok_or(ParserError::element_not_found(&selector))?;
+  decode_text(&value, Some(&selector))
+}
+
+pub fn get_meta_value_as<T: FromStr>(dom: &VDom, name: &str) -> Result<T> {
+  let value = get_meta_value(dom, name)?;
+  parse_text(&value, &format!("meta[itemprop=\"{}\"]", name))
 }
 
 pub fn get_link_tag_href(tag: &tl::HTMLTag) -> Result<String> {
@@ -78,5 +87,20 @@ pub fn get_link_tag_href(tag: &tl::HTMLTag) -> Result<String> {
         .trim_end_matches('/')
         .to_string()
     })
-    .ok_or(anyhow::anyhow!("No href found"))
+    .ok_or(ParserError::element_not_found("[href]"))
+}
+
+pub fn has_class(parser: &Parser, node: &NodeHandle, class: &str) -> bool {
+  node
+    .get(parser)
+    .and_then(|node| node.as_tag())
+    .and_then(|tag| tag.attributes().get("class"))
+    .flatten()
+    .map(|classes| {
+      classes
+        .as_utf8_str()
+        .split_whitespace()
+        .any(|value| value == class)
+    })
+    .unwrap_or(false)
 }
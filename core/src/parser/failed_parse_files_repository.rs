@@ -1,27 +1,24 @@
+use super::parser_error::{split_aggregation_key, ParserError};
 use crate::{
   files::file_metadata::{file_name::FileName, page_type::PageType},
-  helpers::redisearch::{does_ft_index_exist, escape_tag_value},
+  sqlite::SqliteConnection,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chrono::NaiveDateTime;
-use rustis::{
-  bb8::Pool,
-  client::PooledClientManager,
-  commands::{
-    FtAggregateOptions, FtCreateOptions, FtFieldSchema, FtFieldType, FtIndexDataType, FtReducer,
-    FtSearchOptions, GenericCommands, HashCommands, SearchCommands,
-  },
-};
+use rusqlite::params;
 use std::{collections::HashMap, sync::Arc};
-use tracing::info;
+use tracing::{error, instrument};
 
 #[derive(Debug, Clone)]
 pub struct FailedParseFile {
   pub file_name: FileName,
-  pub error: String,
+  pub error: ParserError,
   pub last_attempted_at: NaiveDateTime,
 }
 
+/**
+ * Reads the hashes stored by the former Redis repository, for the one-off import into SQLite.
+ */
 impl From<HashMap<String, String>> for FailedParseFile {
   fn from(values: HashMap<String, String>) -> Self {
     let file_name = FileName(
@@ -30,7 +27,12 @@ impl From<HashMap<String, String>> for FailedParseFile {
         .expect("file_name not found")
         .to_string(),
     );
-    let error = values.get("error").expect("error not found").to_string();
+    let error = values
+      .get("error_details")
+      .and_then(|details| serde_json::from_str::<ParserError>(details).ok())
+      .unwrap_or_else(|| ParserError::Unknown {
+        message: values.get("error").expect("error not found").to_string(),
+      });
     let last_attempted_at = NaiveDateTime::parse_from_str(
       values
         .get("last_attempted_at")
@@ -46,145 +48,169 @@ impl From<HashMap<String, String>> for FailedParseFile {
   }
 }
 
-impl From<FailedParseFile> for Vec<(String, String)> {
-  fn from(val: FailedParseFile) -> Self {
-    vec![
-      ("file_name".to_string(), val.file_name.to_string()),
-      (
-        "page_type".to_string(),
-        val.file_name.page_type().to_string(),
-      ),
-      ("error".to_string(), val.error),
-      (
-        "last_attempted_at".to_string(),
-        val.last_attempted_at.to_string(),
-      ),
-    ]
-  }
-}
-
+/**
+ * Failures grouped by aggregation key: the error code, and the field if known.
+ */
 pub struct AggregatedError {
   pub error: String,
+  pub code: String,
+  pub field: Option<String>,
   pub count: u64,
 }
 
-impl From<Vec<(String, String)>> for AggregatedError {
-  fn from(values: Vec<(String, String)>) -> Self {
-    let error = values
-      .iter()
-      .find(|(k, _)| k == "error")
-      .expect("error not found")
-      .1
-      .to_string();
-    let count = values
-      .iter()
-      .find(|(k, _)| k == "count")
-      .expect("count not found")
-      .1
-      .parse()
-      .expect("invalid count");
-
-    Self { error, count }
+impl AggregatedError {
+  fn new(error: String, count: u64) -> Self {
+    let (code, field) = split_aggregation_key(&error);
+    Self {
+      error,
+      code,
+      field,
+      count,
+    }
   }
 }
 
+fn map_failed_parse_file_row(row: &rusqlite::Row<'_>) -> Result<FailedParseFile, rusqlite::Error> {
+  let error = row.get::<_, String>(1)?;
+  let error = serde_json::from_str::<ParserError>(&row.get::<_, String>(2)?)
+    .unwrap_or(ParserError::Unknown { message: error });
+  Ok(FailedParseFile {
+    file_name: FileName(row.get::<_, String>(0)?),
+    error,
+    last_attempted_at: row.get::<_, NaiveDateTime>(3)?,
+  })
+}
+
+#[derive(Debug, Clone)]
 pub struct FailedParseFilesRepository {
-  pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
 }
 
 impl FailedParseFilesRepository {
-  fn namespace(&self) -> &str {
-    "failed_parse_files"
-  }
-
-  fn key(&self, file_name: &FileName) -> String {
-    format!("{}:{}", self.namespace(), file_name.to_string())
-  }
-
-  fn search_index_name(&self) -> String {
-    format!("{}_idx", self.namespace())
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
   }
 
+  #[instrument(skip(self))]
   pub async fn put(&self, failed_parse_file: FailedParseFile) -> Result<()> {
-    let connection = self.redis_connection_pool.get().await?;
-    let items: Vec<(String, String)> = failed_parse_file.clone().try_into()?;
-    connection
-      .hset(self.key(&failed_parse_file.file_name), items)
-      .await?;
-    Ok(())
+    let file_name = failed_parse_file.file_name.to_string();
+    let page_type = failed_parse_file.file_name.page_type().to_string();
+    let error = failed_parse_file.error.aggregation_key();
+    let error_details = serde_json::to_string(&failed_parse_file.error)?;
+    let last_attempted_at = failed_parse_file.last_attempted_at;
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO failed_parse_files (file_name, page_type, error, error_details, last_attempted_at)
+          VALUES (?1, ?2, ?3, ?4, ?5)
+          ON CONFLICT (file_name) DO UPDATE SET
+            page_type = ?2,
+            error = ?3,
+            error_details = ?4,
+            last_attempted_at = ?5
+          ",
+          params![file_name, page_type, error, error_details, last_attempted_at],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put failed parse file");
+        anyhow!("Failed to put failed parse file")
+      })?
   }
 
+  #[instrument(skip(self))]
   pub async fn remove(&self, file_name: &FileName) -> Result<()> {
-    let connection = self.redis_connection_pool.get().await?;
-    connection.del(self.key(file_name)).await?;
-    Ok(())
-  }
-
-  pub async fn setup_index(&self) -> Result<()> {
-    let connection = self.redis_connection_pool.get().await?;
-    if !does_ft_index_exist(&connection, &self.search_index_name()).await {
-      info!("Creating new search index: {}", self.search_index_name());
-      connection
-        .ft_create(
-          self.search_index_name(),
-          FtCreateOptions::default()
-            .on(FtIndexDataType::Hash)
-            .prefix(format!("{}:", self.namespace())),
-          [
-            FtFieldSchema::identifier("error").field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("page_type").field_type(FtFieldType::Tag),
-          ],
-        )
-        .await?;
-    }
-    Ok(())
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM failed_parse_files WHERE file_name = ?",
+          [file_name],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to remove failed parse file"
+        );
+        anyhow!("Failed to remove failed parse file")
+      })?
   }
 
+  #[instrument(skip(self))]
   pub async fn find_many(&self, error: Option<&str>) -> Result<Vec<FailedParseFile>> {
-    let connection = self.redis_connection_pool.get().await?;
-    let result = connection
-      .ft_search(
-        self.search_index_name(),
-        error
-          .map(|e| format!("@error:{{ {} }}", escape_tag_value(e)))
-          .unwrap_or(String::from("")),
-        FtSearchOptions::default().limit(0, 10000),
-      )
-      .await?;
-    let file_names = result
-      .results
-      .iter()
-      .map(|row| {
-        let values = row
-          .values
-          .iter()
-          .map(|(k, v)| (k.to_string(), v.to_string()))
-          .collect::<HashMap<_, _>>();
-        FailedParseFile::from(values)
+    let error = error.map(|error| error.to_string());
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT file_name, error, error_details, last_attempted_at
+          FROM failed_parse_files
+          WHERE ?1 IS NULL OR error = ?1
+          ORDER BY file_name
+          LIMIT 10000
+          ",
+        )?;
+        let failed_parse_files = stmt
+          .query_map([error], map_failed_parse_file_row)?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(failed_parse_files)
       })
-      .collect::<Vec<_>>();
-
-    Ok(file_names)
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find failed parse files");
+        anyhow!("Failed to find failed parse files")
+      })?
   }
 
+  #[instrument(skip(self))]
   pub async fn aggregate_errors(
     &self,
-    _page_type: Option<PageType>, // FIXME
+    page_type: Option<PageType>,
   ) -> Result<Vec<AggregatedError>> {
-    let connection = self.redis_connection_pool.get().await?;
-    let result = connection
-      .ft_aggregate(
-        self.search_index_name(),
-        "*",
-        FtAggregateOptions::default().groupby("@error", FtReducer::count().as_name("count")),
-      )
-      .await?;
-    let aggregates = result
-      .results
-      .iter()
-      .map(|r| AggregatedError::from(r.to_owned()))
-      .collect::<Vec<_>>();
-
-    Ok(aggregates)
+    let page_type = page_type.map(|page_type| page_type.to_string());
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(
+          "
+          SELECT error, COUNT(*)
+          FROM failed_parse_files
+          WHERE ?1 IS NULL OR page_type = ?1
+          GROUP BY error
+          ORDER BY COUNT(*) DESC, error
+          ",
+        )?;
+        let aggregates = stmt
+          .query_map([page_type], |row| {
+            Ok(AggregatedError::new(
+              row.get::<_, String>(0)?,
+              row.get::<_, u64>(1)?,
+            ))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(aggregates)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to aggregate parse errors");
+        anyhow!("Failed to aggregate parse errors")
+      })?
   }
 }
@@ -4,9 +4,15 @@ mod artist;
 mod chart;
 mod dom;
 pub mod failed_parse_files_repository;
+pub mod outdated_file_reparser;
 pub mod parsed_file_data;
+pub mod parsed_file_version_repository;
 pub mod parser;
+pub mod parser_degradation_detector;
+pub mod parser_error;
 pub mod parser_event_subscribers;
+pub mod parser_fixtures;
+pub mod parser_health_repository;
 pub mod parser_service;
 pub mod retry;
-mod util;
+pub mod util;
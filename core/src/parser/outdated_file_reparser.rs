@@ -0,0 +1,101 @@
+use super::{
+  parsed_file_version_repository::ParsedFileVersionRepository, parser::parse_file_on_store,
+};
+use crate::{
+  events::event_publisher::EventPublisher,
+  files::{file_content_store::FileContentStore, file_interactor::FileInteractor},
+  settings::Settings,
+  sqlite::SqliteConnection,
+};
+use anyhow::Result;
+use rustis::{bb8::Pool, client::PooledClientManager};
+use std::{sync::Arc, time::Duration};
+use tokio::{spawn, time::sleep};
+use tracing::{error, info};
+use ulid::Ulid;
+
+/**
+ * Files are re-parsed asynchronously, so their parser version is only updated once the parser
+ * subscribers catch up. Until then, or until this expires, they are not re-parsed again.
+ */
+const REPARSE_CLAIM_TTL: Duration = Duration::from_secs(60 * 60);
+
+/**
+ * Periodically re-parses files from the content store that were last parsed by an older
+ * parser version. At most `parser.reparse_batch_size` files are re-parsed every
+ * `parser.reparse_interval_seconds`, to keep the load on the parser subscribers bounded.
+ */
+pub fn start_outdated_file_reparser(
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
+  settings: Arc<Settings>,
+) -> Result<()> {
+  let file_content_store = FileContentStore::new(&settings.file.content_store)?;
+  let event_publisher = EventPublisher::new(Arc::clone(&settings), Arc::clone(&sqlite_connection));
+  let file_interactor = FileInteractor::new(
+    Arc::clone(&settings),
+    Arc::clone(&redis_connection_pool),
+    Arc::clone(&sqlite_connection),
+  );
+  let parsed_file_version_repository =
+    ParsedFileVersionRepository::new(Arc::clone(&sqlite_connection));
+
+  spawn(async move {
+    loop {
+      sleep(Duration::from_secs(
+        settings.parser.reparse_interval_seconds as u64,
+      ))
+      .await;
+
+      let file_names = match parsed_file_version_repository
+        .claim_outdated(
+          settings.parser.reparse_batch_size as usize,
+          REPARSE_CLAIM_TTL,
+        )
+        .await
+      {
+        Ok(file_names) => file_names,
+        Err(e) => {
+          error!(
+            error = e.to_string().as_str(),
+            "Failed to find outdated parsed files"
+          );
+          continue;
+        }
+      };
+
+      if !file_names.is_empty() {
+        info!(count = file_names.len(), "Re-parsing outdated files");
+      }
+
+      for file_name in file_names {
+        match file_interactor.get_file_metadata(&file_name).await {
+          Ok(file_metadata) => {
+            if let Err(e) = parse_file_on_store(
+              file_content_store.clone(),
+              event_publisher.clone(),
+              file_metadata.id,
+              file_name,
+              Some(format!("reparse:{}", Ulid::new().to_string())),
+            )
+            .await
+            {
+              error!(
+                error = e.to_string().as_str(),
+                "Failed to re-parse outdated file"
+              );
+            }
+          }
+          Err(e) => {
+            error!(
+              error = e.to_string().as_str(),
+              "Failed to get file metadata"
+            );
+          }
+        }
+      }
+    }
+  });
+
+  Ok(())
+}
@@ -1,6 +1,8 @@
 use crate::files::file_metadata::file_name::FileName;
+use anyhow::{bail, Error, Result};
 use chrono::NaiveDate;
 use serde_derive::{Deserialize, Serialize};
+use std::str::FromStr;
 use unidecode::unidecode;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -15,6 +17,8 @@ pub struct ParsedTrack {
   pub duration_seconds: Option<u32>,
   pub rating: Option<f32>,
   pub position: Option<String>,
+  #[serde(default)]
+  pub credits: Vec<ParsedCredit>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -23,6 +27,72 @@ pub struct ParsedCredit {
   pub roles: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ParsedLabel {
+  pub name: String,
+  pub catalog_number: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ParsedChartPosition {
+  /**
+   * The chart the album is ranked on, e.g. "1997" or "overall".
+   */
+  pub chart: String,
+  pub position: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ParsedReview {
+  pub author: String,
+  pub rating: Option<f32>,
+  pub date: Option<NaiveDate>,
+  pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExternalLinkSource {
+  Spotify,
+  Bandcamp,
+  AppleMusic,
+  YouTube,
+  Discogs,
+}
+
+impl ToString for ExternalLinkSource {
+  fn to_string(&self) -> String {
+    match self {
+      ExternalLinkSource::Spotify => "spotify".to_string(),
+      ExternalLinkSource::Bandcamp => "bandcamp".to_string(),
+      ExternalLinkSource::AppleMusic => "apple_music".to_string(),
+      ExternalLinkSource::YouTube => "youtube".to_string(),
+      ExternalLinkSource::Discogs => "discogs".to_string(),
+    }
+  }
+}
+
+impl FromStr for ExternalLinkSource {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "spotify" => Ok(ExternalLinkSource::Spotify),
+      "bandcamp" => Ok(ExternalLinkSource::Bandcamp),
+      "apple_music" => Ok(ExternalLinkSource::AppleMusic),
+      "youtube" => Ok(ExternalLinkSource::YouTube),
+      "discogs" => Ok(ExternalLinkSource::Discogs),
+      _ => bail!("Invalid external link source: {}", s),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ParsedExternalLink {
+  pub source: ExternalLinkSource,
+  pub id: String,
+  pub url: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ParsedAlbum {
   pub name: String,
@@ -40,6 +110,18 @@ pub struct ParsedAlbum {
   pub credits: Vec<ParsedCredit>,
   #[serde(default)]
   pub cover_image_url: Option<String>,
+  #[serde(default)]
+  pub reviews: Vec<ParsedReview>,
+  #[serde(default)]
+  pub external_links: Vec<ParsedExternalLink>,
+  #[serde(default)]
+  pub release_type: Option<String>,
+  #[serde(default)]
+  pub labels: Vec<ParsedLabel>,
+  #[serde(default)]
+  pub chart_positions: Vec<ParsedChartPosition>,
+  #[serde(default)]
+  pub total_duration_seconds: Option<u32>,
 }
 
 impl ParsedAlbum {
@@ -59,6 +141,18 @@ pub struct ParsedChartAlbum {
   pub secondary_genres: Vec<String>,
   pub descriptors: Vec<String>,
   pub release_date: Option<NaiveDate>,
+  /**
+   * Rank of the album within the chart, across all of its pages.
+   */
+  #[serde(default)]
+  pub position: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ParsedChart {
+  pub albums: Vec<ParsedChartAlbum>,
+  pub page_number: u32,
+  pub total_pages: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -71,6 +165,28 @@ pub struct ParsedArtistAlbum {
 pub struct ParsedArtist {
   pub name: String,
   pub albums: Vec<ParsedArtistAlbum>,
+  /**
+   * Date the group formed, or the birth date of an individual.
+   */
+  #[serde(default)]
+  pub formed_date: Option<NaiveDate>,
+  /**
+   * Date the group disbanded, or the date of death of an individual.
+   */
+  #[serde(default)]
+  pub disbanded_date: Option<NaiveDate>,
+  #[serde(default)]
+  pub location: Option<String>,
+  #[serde(default)]
+  pub country: Option<String>,
+  #[serde(default)]
+  pub genres: Vec<String>,
+  #[serde(default)]
+  pub members: Vec<ParsedArtistReference>,
+  #[serde(default)]
+  pub member_of: Vec<ParsedArtistReference>,
+  #[serde(default)]
+  pub related_artists: Vec<ParsedArtistReference>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -83,7 +199,7 @@ pub struct ParsedAlbumSearchResult {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "type", content = "data")]
 pub enum ParsedFileData {
-  Chart(Vec<ParsedChartAlbum>),
+  Chart(ParsedChart),
   Album(ParsedAlbum),
   Artist(ParsedArtist),
   AlbumSearchResult(ParsedAlbumSearchResult),
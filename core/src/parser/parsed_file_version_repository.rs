@@ -0,0 +1,189 @@
+use super::parser::parser_version;
+use crate::{
+  files::file_metadata::{file_name::FileName, page_type::PageType},
+  sqlite::SqliteConnection,
+};
+use anyhow::{anyhow, Result};
+use rusqlite::params;
+use std::{sync::Arc, time::Duration};
+use tracing::{error, instrument};
+
+/**
+ * Tracks the parser version that last successfully parsed each file, so files parsed by
+ * an older parser can be found and re-parsed.
+ */
+#[derive(Debug, Clone)]
+pub struct ParsedFileVersionRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+const PAGE_TYPES: [PageType; 4] = [
+  PageType::Artist,
+  PageType::Album,
+  PageType::Chart,
+  PageType::AlbumSearchResult,
+];
+
+impl ParsedFileVersionRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  #[instrument(skip(self))]
+  pub async fn put_parsed(&self, file_name: &FileName, parser_version: u32) -> Result<()> {
+    let page_type = file_name.page_type().to_string();
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO parsed_file_versions (file_name, page_type, parser_version, failed_parser_version, updated_at)
+          VALUES (?1, ?2, ?3, NULL, CURRENT_TIMESTAMP)
+          ON CONFLICT (file_name) DO UPDATE SET
+            parser_version = ?3,
+            failed_parser_version = NULL,
+            updated_at = CURRENT_TIMESTAMP
+          ",
+          params![file_name, page_type, parser_version],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put parsed file version");
+        anyhow!("Failed to put parsed file version")
+      })?
+  }
+
+  /**
+   * Records that the given parser version failed to parse a previously parsed file, so it
+   * is not picked up for re-parsing again until the parser version is bumped.
+   */
+  #[instrument(skip(self))]
+  pub async fn put_failed(&self, file_name: &FileName, parser_version: u32) -> Result<()> {
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          UPDATE parsed_file_versions
+          SET failed_parser_version = ?2, updated_at = CURRENT_TIMESTAMP
+          WHERE file_name = ?1
+          ",
+          params![file_name, parser_version],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to put failed parser version"
+        );
+        anyhow!("Failed to put failed parser version")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete(&self, file_name: &FileName) -> Result<()> {
+    let file_name = file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM parsed_file_versions WHERE file_name = ?",
+          [file_name],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to delete parsed file version"
+        );
+        anyhow!("Failed to delete parsed file version")
+      })?
+  }
+
+  /**
+   * Claims up to `limit` files that were last parsed by an older version of their page type's
+   * parser, and have not already failed to parse with the current version. Claimed files are
+   * recorded as enqueued with the current version, so that they are not claimed again while their
+   * re-parse is processed. Claims expire after `claim_ttl`, in case a re-parse never completes.
+   */
+  #[instrument(skip(self))]
+  pub async fn claim_outdated(&self, limit: usize, claim_ttl: Duration) -> Result<Vec<FileName>> {
+    let current_versions = PAGE_TYPES
+      .iter()
+      .map(|page_type| (page_type.to_string(), parser_version(page_type)))
+      .collect::<Vec<_>>();
+    let claim_ttl = format!("-{} seconds", claim_ttl.as_secs());
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let tx = conn.transaction()?;
+        let mut file_names = vec![];
+        {
+          let mut select_statement = tx.prepare(
+            "
+            SELECT file_name
+            FROM parsed_file_versions
+            WHERE page_type = ?1
+              AND parser_version < ?2
+              AND (failed_parser_version IS NULL OR failed_parser_version < ?2)
+              AND (
+                enqueued_parser_version IS NULL
+                OR enqueued_parser_version < ?2
+                OR enqueued_at <= datetime('now', ?3)
+              )
+            ORDER BY updated_at ASC
+            LIMIT ?4
+            ",
+          )?;
+          let mut update_statement = tx.prepare(
+            "
+            UPDATE parsed_file_versions
+            SET enqueued_parser_version = ?2, enqueued_at = CURRENT_TIMESTAMP
+            WHERE file_name = ?1
+            ",
+          )?;
+          for (page_type, version) in current_versions {
+            if file_names.len() >= limit {
+              break;
+            }
+            let remaining = limit - file_names.len();
+            let rows = select_statement
+              .query_map(params![page_type, version, claim_ttl, remaining], |row| {
+                row.get::<_, String>(0)
+              })?
+              .collect::<Result<Vec<_>, _>>()?;
+            for file_name in rows {
+              update_statement.execute(params![file_name, version])?;
+              file_names.push(FileName(file_name));
+            }
+          }
+        }
+        tx.commit()?;
+        Ok(file_names)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to claim outdated parsed files"
+        );
+        anyhow!("Failed to claim outdated parsed files")
+      })?
+  }
+}
@@ -1,4 +1,4 @@
-use super::parsed_file_data::ParsedFileData;
+use super::{parsed_file_data::ParsedFileData, parser_error::ParserError};
 use crate::{
   events::{
     event::{Event, EventPayloadBuilder, Stream},
@@ -17,6 +17,37 @@ use anyhow::Result;
 use tracing::{info, instrument, warn};
 use ulid::Ulid;
 
+/**
+ * Version of the parser for each page type. Bump the version of a page type whenever its
+ * parser changes in a way that should be applied to files which were already parsed, and
+ * the outdated file reparser will re-parse them from the content store.
+ */
+pub fn parser_version(page_type: &PageType) -> u32 {
+  match page_type {
+    PageType::Artist => 2,
+    PageType::Album => 5,
+    PageType::Chart => 2,
+    PageType::AlbumSearchResult => 1,
+  }
+}
+
+/**
+ * Parses the content of a file without touching the content store or publishing events.
+ */
+pub fn parse_file_content(
+  page_type: &PageType,
+  file_content: &str,
+) -> std::result::Result<ParsedFileData, ParserError> {
+  match page_type {
+    PageType::Chart => parse_chart(file_content).map(ParsedFileData::Chart),
+    PageType::Album => parse_album(file_content).map(ParsedFileData::Album),
+    PageType::Artist => parse_artist(file_content).map(ParsedFileData::Artist),
+    PageType::AlbumSearchResult => {
+      parse_album_search_result(file_content).map(ParsedFileData::AlbumSearchResult)
+    }
+  }
+}
+
 #[instrument(skip(file_content_store, event_publisher))]
 pub async fn parse_file_on_store(
   file_content_store: FileContentStore,
@@ -27,14 +58,7 @@ pub async fn parse_file_on_store(
 ) -> Result<ParsedFileData> {
   let file_content = file_content_store.get(&file_name).await?;
 
-  let parse_result: Result<ParsedFileData> = match file_name.page_type() {
-    PageType::Chart => parse_chart(&file_content).map(ParsedFileData::Chart),
-    PageType::Album => parse_album(&file_content).map(ParsedFileData::Album),
-    PageType::Artist => parse_artist(&file_content).map(ParsedFileData::Artist),
-    PageType::AlbumSearchResult => {
-      parse_album_search_result(&file_content).map(ParsedFileData::AlbumSearchResult)
-    }
-  };
+  let parse_result = parse_file_content(&file_name.page_type(), &file_content);
 
   let event = match &parse_result {
     Ok(file_data) => {
@@ -42,6 +66,7 @@ pub async fn parse_file_on_store(
         file_id = file_id.to_string(),
         file_name = file_name.to_string(),
         page_type = file_name.page_type().to_string(),
+        parser_version = parser_version(&file_name.page_type()),
         "File parsed"
       );
 
@@ -49,6 +74,7 @@ pub async fn parse_file_on_store(
         file_id,
         file_name: file_name.clone(),
         data: file_data.clone(),
+        parser_version: parser_version(&file_name.page_type()),
       }
     }
     Err(error) => {
@@ -63,7 +89,7 @@ pub async fn parse_file_on_store(
       Event::FileParseFailed {
         file_id,
         file_name: file_name.clone(),
-        error: error.to_string(),
+        error: error.clone(),
       }
     }
   };
@@ -78,5 +104,5 @@ pub async fn parse_file_on_store(
     )
     .await?;
 
-  parse_result
+  parse_result.map_err(anyhow::Error::from)
 }
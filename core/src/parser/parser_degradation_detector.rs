@@ -0,0 +1,212 @@
+use super::parser_health_repository::{
+  ParseOutcomeCount, ParserDegradation, ParserHealthRepository,
+};
+use crate::{
+  crawler::{crawler_interactor::CrawlerInteractor, crawler_state_repository::CrawlerStatus},
+  events::{
+    event::{Event, EventPayloadBuilder, Stream},
+    event_publisher::EventPublisher,
+  },
+  files::file_metadata::page_type::PageType,
+  settings::{ParserDegradationSettings, Settings},
+  sqlite::SqliteConnection,
+};
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime, Utc};
+use std::{collections::HashMap, sync::Arc};
+use tokio::{spawn, time::sleep};
+use tracing::{error, warn};
+
+#[derive(Default)]
+struct OutcomeTally {
+  total: u32,
+  failures: u32,
+  errors: HashMap<String, u32>,
+}
+
+impl OutcomeTally {
+  fn failure_rate(&self) -> f32 {
+    rate(self.failures, self.total)
+  }
+
+  fn error_rate(&self, error: &str) -> f32 {
+    rate(self.errors.get(error).copied().unwrap_or(0), self.total)
+  }
+}
+
+fn rate(count: u32, total: u32) -> f32 {
+  if total == 0 {
+    0.0
+  } else {
+    count as f32 / total as f32
+  }
+}
+
+fn tally(counts: Vec<ParseOutcomeCount>) -> HashMap<String, (PageType, OutcomeTally)> {
+  let mut tallies: HashMap<String, (PageType, OutcomeTally)> = HashMap::new();
+  for count in counts {
+    let (_, tally) = tallies
+      .entry(count.page_type.to_string())
+      .or_insert_with(|| (count.page_type.clone(), OutcomeTally::default()));
+    tally.total += count.count;
+    if let Some(error) = count.error {
+      tally.failures += count.count;
+      *tally.errors.entry(error).or_default() += count.count;
+    }
+  }
+  tallies
+}
+
+/**
+ * Compares failure rates in the current window against the baseline that precedes it,
+ * both for each page type as a whole and for each individual error.
+ */
+pub fn detect_degradations(
+  window: Vec<ParseOutcomeCount>,
+  baseline: Vec<ParseOutcomeCount>,
+  settings: &ParserDegradationSettings,
+  detected_at: NaiveDateTime,
+) -> Vec<ParserDegradation> {
+  let threshold = settings.failure_rate_increase_percent as f32 / 100.0;
+  let baseline = tally(baseline);
+  let empty_tally = OutcomeTally::default();
+  let mut degradations = vec![];
+
+  for (key, (page_type, window_tally)) in tally(window) {
+    if window_tally.total < settings.min_samples {
+      continue;
+    }
+    let baseline_tally = baseline
+      .get(&key)
+      .map(|(_, tally)| tally)
+      .unwrap_or(&empty_tally);
+
+    if window_tally.failure_rate() - baseline_tally.failure_rate() >= threshold {
+      degradations.push(ParserDegradation {
+        page_type: page_type.clone(),
+        error: None,
+        failure_rate: window_tally.failure_rate(),
+        baseline_failure_rate: baseline_tally.failure_rate(),
+        sample_count: window_tally.total,
+        detected_at,
+      });
+    }
+
+    for error in window_tally.errors.keys() {
+      let failure_rate = window_tally.error_rate(error);
+      let baseline_failure_rate = baseline_tally.error_rate(error);
+      if failure_rate - baseline_failure_rate >= threshold {
+        degradations.push(ParserDegradation {
+          page_type: page_type.clone(),
+          error: Some(error.clone()),
+          failure_rate,
+          baseline_failure_rate,
+          sample_count: window_tally.total,
+          detected_at,
+        });
+      }
+    }
+  }
+
+  degradations
+}
+
+async fn check_parser_health(
+  parser_health_repository: &ParserHealthRepository,
+  event_publisher: &EventPublisher,
+  crawler_interactor: &CrawlerInteractor,
+  settings: &ParserDegradationSettings,
+) -> Result<()> {
+  let now = Utc::now().naive_utc();
+  let window_start = now - Duration::seconds(settings.window_seconds as i64);
+  let baseline_start = window_start - Duration::seconds(settings.baseline_seconds as i64);
+  parser_health_repository
+    .delete_outcomes_before(baseline_start)
+    .await?;
+
+  let window = parser_health_repository
+    .get_outcome_counts(window_start, now)
+    .await?;
+  let baseline = parser_health_repository
+    .get_outcome_counts(baseline_start, window_start)
+    .await?;
+
+  for degradation in detect_degradations(window, baseline, settings, now) {
+    if !parser_health_repository
+      .put_degradation(&degradation)
+      .await?
+    {
+      continue;
+    }
+
+    warn!(
+      page_type = degradation.page_type.to_string(),
+      error = degradation.error.clone(),
+      failure_rate = degradation.failure_rate,
+      baseline_failure_rate = degradation.baseline_failure_rate,
+      sample_count = degradation.sample_count,
+      "Parser degraded, pausing crawler"
+    );
+
+    if crawler_interactor.get_status().await? != CrawlerStatus::Paused {
+      crawler_interactor.set_status(CrawlerStatus::Paused).await?;
+    }
+
+    event_publisher
+      .publish(
+        Stream::Parser,
+        EventPayloadBuilder::default()
+          .event(Event::ParserDegraded {
+            page_type: degradation.page_type,
+            error: degradation.error,
+            failure_rate: degradation.failure_rate,
+            baseline_failure_rate: degradation.baseline_failure_rate,
+            sample_count: degradation.sample_count,
+          })
+          .build()?,
+      )
+      .await?;
+  }
+
+  Ok(())
+}
+
+/**
+ * Periodically compares parse failure rates over a sliding window against a baseline.
+ * When a spike is detected, which usually means the markup of the crawled site changed,
+ * the crawler is paused and a `ParserDegraded` event is emitted. Degradations remain on
+ * the parser monitor until they are cleared.
+ */
+pub fn start_parser_degradation_detector(
+  sqlite_connection: Arc<SqliteConnection>,
+  settings: Arc<Settings>,
+  crawler_interactor: Arc<CrawlerInteractor>,
+) -> Result<()> {
+  let parser_health_repository = ParserHealthRepository::new(Arc::clone(&sqlite_connection));
+  let event_publisher = EventPublisher::new(Arc::clone(&settings), Arc::clone(&sqlite_connection));
+
+  spawn(async move {
+    loop {
+      sleep(std::time::Duration::from_secs(
+        settings.parser.degradation.check_interval_seconds as u64,
+      ))
+      .await;
+
+      if let Err(e) = check_parser_health(
+        &parser_health_repository,
+        &event_publisher,
+        &crawler_interactor,
+        &settings.parser.degradation,
+      )
+      .await
+      {
+        error!(
+          error = e.to_string().as_str(),
+          "Failed to check parser health"
+        );
+      }
+    }
+  });
+
+  Ok(())
+}
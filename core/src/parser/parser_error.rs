@@ -0,0 +1,182 @@
+use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+
+/**
+ * Error produced by the page parsers. Each variant has a stable code, and carries the parsed
+ * field and the CSS selector involved where they are known, so that failures can be grouped
+ * regardless of the values found on the page.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ParserError {
+  InvalidHtml {
+    message: String,
+  },
+  ElementNotFound {
+    field: Option<String>,
+    selector: String,
+  },
+  InvalidValue {
+    field: Option<String>,
+    selector: Option<String>,
+    value: String,
+  },
+  NoResults {
+    selector: String,
+  },
+  /**
+   * Errors recorded before parser errors were typed.
+   */
+  Unknown {
+    message: String,
+  },
+}
+
+pub const PARSER_ERROR_CODES: [&str; 5] = [
+  "invalid_html",
+  "element_not_found",
+  "invalid_value",
+  "no_results",
+  "unknown",
+];
+
+impl ParserError {
+  pub fn element_not_found(selector: &str) -> Self {
+    ParserError::ElementNotFound {
+      field: None,
+      selector: selector.to_string(),
+    }
+  }
+
+  pub fn invalid_value(value: &str, selector: Option<&str>) -> Self {
+    ParserError::InvalidValue {
+      field: None,
+      selector: selector.map(|selector| selector.to_string()),
+      value: value.to_string(),
+    }
+  }
+
+  pub fn no_results(selector: &str) -> Self {
+    ParserError::NoResults {
+      selector: selector.to_string(),
+    }
+  }
+
+  pub fn code(&self) -> &'static str {
+    match self {
+      ParserError::InvalidHtml { .. } => "invalid_html",
+      ParserError::ElementNotFound { .. } => "element_not_found",
+      ParserError::InvalidValue { .. } => "invalid_value",
+      ParserError::NoResults { .. } => "no_results",
+      ParserError::Unknown { .. } => "unknown",
+    }
+  }
+
+  pub fn field(&self) -> Option<&str> {
+    match self {
+      ParserError::ElementNotFound { field, .. } | ParserError::InvalidValue { field, .. } => {
+        field.as_deref()
+      }
+      _ => None,
+    }
+  }
+
+  pub fn selector(&self) -> Option<&str> {
+    match self {
+      ParserError::ElementNotFound { selector, .. } | ParserError::NoResults { selector } => {
+        Some(selector.as_str())
+      }
+      ParserError::InvalidValue { selector, .. } => selector.as_deref(),
+      _ => None,
+    }
+  }
+
+  /**
+   * Sets the field the error occurred in, unless a more specific one was already set.
+   */
+  pub fn in_field(mut self, name: &str) -> Self {
+    match &mut self {
+      ParserError::ElementNotFound { field, .. } | ParserError::InvalidValue { field, .. }
+        if field.is_none() =>
+      {
+        *field = Some(name.to_string());
+      }
+      _ => {}
+    }
+    self
+  }
+
+  /**
+   * Key failures are grouped by: the error code, followed by the field if known.
+   */
+  pub fn aggregation_key(&self) -> String {
+    match self.field() {
+      Some(field) => format!("{}:{}", self.code(), field),
+      None => self.code().to_string(),
+    }
+  }
+}
+
+/**
+ * Splits an aggregation key into its code and field. Keys recorded before parser errors were
+ * typed hold the error message, and are reported as unknown.
+ */
+pub fn split_aggregation_key(key: &str) -> (String, Option<String>) {
+  let (code, field) = match key.split_once(':') {
+    Some((code, field)) => (code, Some(field.to_string())),
+    None => (key, None),
+  };
+  if PARSER_ERROR_CODES.contains(&code) {
+    (code.to_string(), field)
+  } else {
+    ("unknown".to_string(), None)
+  }
+}
+
+impl fmt::Display for ParserError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParserError::InvalidHtml { message } => write!(f, "Invalid HTML: {}", message),
+      ParserError::ElementNotFound { field, selector } => match field {
+        Some(field) => write!(f, "No element found for {}: {}", field, selector),
+        None => write!(f, "No element found for selector: {}", selector),
+      },
+      ParserError::InvalidValue {
+        field,
+        selector,
+        value,
+      } => {
+        write!(f, "Invalid value \"{}\"", value)?;
+        if let Some(field) = field {
+          write!(f, " for {}", field)?;
+        }
+        if let Some(selector) = selector {
+          write!(f, " at {}", selector)?;
+        }
+        Ok(())
+      }
+      ParserError::NoResults { selector } => write!(f, "No results found for: {}", selector),
+      ParserError::Unknown { message } => write!(f, "{}", message),
+    }
+  }
+}
+
+impl std::error::Error for ParserError {}
+
+impl From<tl::ParseError> for ParserError {
+  fn from(error: tl::ParseError) -> Self {
+    ParserError::InvalidHtml {
+      message: format!("{:?}", error),
+    }
+  }
+}
+
+pub trait ParserResultExt<T> {
+  fn in_field(self, field: &str) -> Result<T, ParserError>;
+}
+
+impl<T> ParserResultExt<T> for Result<T, ParserError> {
+  fn in_field(self, field: &str) -> Result<T, ParserError> {
+    self.map_err(|error| error.in_field(field))
+  }
+}
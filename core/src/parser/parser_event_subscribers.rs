@@ -1,6 +1,8 @@
 use super::{
   failed_parse_files_repository::{FailedParseFile, FailedParseFilesRepository},
-  parser::parse_file_on_store,
+  parsed_file_version_repository::ParsedFileVersionRepository,
+  parser::{parse_file_on_store, parser_version},
+  parser_health_repository::ParserHealthRepository,
 };
 use crate::{
   events::{
@@ -37,9 +39,8 @@ async fn parse_saved_file(context: SubscriberContext) -> Result<()> {
 }
 
 async fn populate_failed_parse_files_repository(context: SubscriberContext) -> Result<()> {
-  let failed_parse_files_repository = FailedParseFilesRepository {
-    redis_connection_pool: Arc::clone(&context.redis_connection_pool),
-  };
+  let failed_parse_files_repository =
+    FailedParseFilesRepository::new(Arc::clone(&context.sqlite_connection));
   match context.payload.event {
     Event::FileParseFailed {
       file_id: _,
@@ -54,12 +55,63 @@ async fn populate_failed_parse_files_repository(context: SubscriberContext) -> R
         })
         .await?;
     }
+    Event::FileParsed { file_name, .. } => {
+      failed_parse_files_repository.remove(&file_name).await?;
+    }
+    _ => {}
+  }
+  Ok(())
+}
+
+/**
+ * Projects both parse outcomes and deleted files, so that a replay applies deletions in order with
+ * the parses they follow.
+ */
+async fn update_parsed_file_versions(context: SubscriberContext) -> Result<()> {
+  let parsed_file_version_repository =
+    ParsedFileVersionRepository::new(Arc::clone(&context.sqlite_connection));
+  match context.payload.event {
     Event::FileParsed {
-      file_id: _,
       file_name,
-      data: _,
+      parser_version,
+      ..
     } => {
-      failed_parse_files_repository.remove(&file_name).await?;
+      parsed_file_version_repository
+        .put_parsed(&file_name, parser_version)
+        .await?;
+    }
+    Event::FileParseFailed { file_name, .. } => {
+      parsed_file_version_repository
+        .put_failed(&file_name, parser_version(&file_name.page_type()))
+        .await?;
+    }
+    Event::FileDeleted { file_name, .. } => {
+      parsed_file_version_repository.delete(&file_name).await?;
+    }
+    _ => {}
+  }
+  Ok(())
+}
+
+async fn record_parse_outcomes(context: SubscriberContext) -> Result<()> {
+  let parser_health_repository =
+    ParserHealthRepository::new(Arc::clone(&context.sqlite_connection));
+  match context.payload.event {
+    Event::FileParsed { file_name, .. } => {
+      parser_health_repository
+        .record_outcome(&file_name.page_type(), None, context.created_at)
+        .await?;
+    }
+    Event::FileParseFailed {
+      file_name, error, ..
+    } => {
+      parser_health_repository
+        .record_outcome(
+          &file_name.page_type(),
+          Some(error.aggregation_key()),
+          context.created_at,
+        )
+        .await?;
     }
     _ => {}
   }
@@ -94,5 +146,36 @@ pub fn build_parser_event_subscribers(
         Box::pin(async move { populate_failed_parse_files_repository(context).await })
       }))
       .build()?,
+    EventSubscriberBuilder::default()
+      .id("update_parsed_file_versions")
+      .redis_connection_pool(Arc::clone(&redis_connection_pool))
+      .sqlite_connection(Arc::clone(&sqlite_connection))
+      .settings(Arc::clone(&settings))
+      .batch_size(250)
+      .stream(Stream::Parser)
+      .stream(Stream::File)
+      // Events of the same file are processed in order, so a deletion can't race its parse
+      .generate_ordered_processing_group_id(Arc::new(|row| {
+        row
+          .payload
+          .event
+          .file_name()
+          .map(|file_name| file_name.to_string())
+      }))
+      .handle(Arc::new(|context| {
+        Box::pin(async move { update_parsed_file_versions(context).await })
+      }))
+      .build()?,
+    EventSubscriberBuilder::default()
+      .id("record_parse_outcomes")
+      .redis_connection_pool(Arc::clone(&redis_connection_pool))
+      .sqlite_connection(Arc::clone(&sqlite_connection))
+      .settings(Arc::clone(&settings))
+      .batch_size(250)
+      .stream(Stream::Parser)
+      .handle(Arc::new(|context| {
+        Box::pin(async move { record_parse_outcomes(context).await })
+      }))
+      .build()?,
   ])
 }
@@ -0,0 +1,115 @@
+use super::parser::parse_file_content;
+use crate::files::file_metadata::{file_name::FileName, page_type::PageType};
+use anyhow::{anyhow, Result};
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+/**
+ * Directory holding the parser fixture corpus. Each page type has its own directory of sample
+ * pages (`{name}.html`), each with the expected parser output next to it (`{name}.json`).
+ */
+pub const PARSER_FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/parser");
+
+pub const PAGE_TYPES: [PageType; 4] = [
+  PageType::Album,
+  PageType::Artist,
+  PageType::Chart,
+  PageType::AlbumSearchResult,
+];
+
+#[derive(Debug, Clone)]
+pub struct ParserFixture {
+  pub page_type: PageType,
+  pub name: String,
+  pub page_path: PathBuf,
+  pub snapshot_path: PathBuf,
+}
+
+impl ParserFixture {
+  pub fn new(page_type: PageType, name: &str) -> Self {
+    let dir = Path::new(PARSER_FIXTURES_DIR).join(page_type.to_string());
+    Self {
+      page_type,
+      name: name.to_string(),
+      page_path: dir.join(format!("{}.html", name)),
+      snapshot_path: dir.join(format!("{}.json", name)),
+    }
+  }
+
+  /**
+   * Fixture for a crawled file, named after its file name.
+   */
+  pub fn for_file_name(file_name: &FileName) -> Self {
+    let name = file_name
+      .to_string()
+      .replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+    Self::new(file_name.page_type(), &name)
+  }
+
+  /**
+   * Parses the sample page and serializes the output the same way snapshots are written.
+   */
+  pub fn render(&self) -> Result<String> {
+    let content = fs::read_to_string(&self.page_path)?;
+    let data = parse_file_content(&self.page_type, &content)
+      .map_err(|e| anyhow!("Failed to parse {}: {}", self.page_path.display(), e))?;
+    Ok(format!("{}\n", serde_json::to_string_pretty(&data)?))
+  }
+
+  pub fn read_snapshot(&self) -> Result<String> {
+    fs::read_to_string(&self.snapshot_path)
+      .map_err(|e| anyhow!("Failed to read {}: {}", self.snapshot_path.display(), e))
+  }
+
+  /**
+   * Writes the sample page as given, so crawled pages must be anonymized first.
+   */
+  pub fn write_page(&self, content: &str) -> Result<()> {
+    if let Some(dir) = self.page_path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    fs::write(&self.page_path, content)?;
+    Ok(())
+  }
+
+  pub fn write_snapshot(&self) -> Result<()> {
+    fs::write(&self.snapshot_path, self.render()?)?;
+    Ok(())
+  }
+}
+
+/**
+ * Lists all fixtures, ordered by page type and name.
+ */
+pub fn list_parser_fixtures() -> Result<Vec<ParserFixture>> {
+  let mut fixtures = vec![];
+  for page_type in PAGE_TYPES {
+    let dir = Path::new(PARSER_FIXTURES_DIR).join(page_type.to_string());
+    if !dir.exists() {
+      continue;
+    }
+    let mut names = fs::read_dir(&dir)?
+      .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+      .filter(|path| {
+        path
+          .extension()
+          .is_some_and(|extension| extension == "html")
+      })
+      .filter_map(|path| {
+        path
+          .file_stem()
+          .and_then(|name| name.to_str())
+          .map(|name| name.to_string())
+      })
+      .collect::<Vec<String>>();
+    names.sort();
+    fixtures.extend(
+      names
+        .iter()
+        .map(|name| ParserFixture::new(page_type.clone(), name)),
+    );
+  }
+  Ok(fixtures)
+}
@@ -0,0 +1,256 @@
+use crate::{files::file_metadata::page_type::PageType, sqlite::SqliteConnection};
+use anyhow::{anyhow, Result};
+use chrono::NaiveDateTime;
+use rusqlite::params;
+use std::sync::Arc;
+use tracing::{error, instrument};
+
+/**
+ * Width of the buckets parse outcomes are counted in.
+ */
+const BUCKET_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone)]
+pub struct ParseOutcomeCount {
+  pub page_type: PageType,
+  /**
+   * None for successful parses.
+   */
+  pub error: Option<String>,
+  pub count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParserDegradation {
+  pub page_type: PageType,
+  /**
+   * The error whose rate spiked, or None if the overall failure rate of the page type spiked.
+   */
+  pub error: Option<String>,
+  pub failure_rate: f32,
+  pub baseline_failure_rate: f32,
+  pub sample_count: u32,
+  pub detected_at: NaiveDateTime,
+}
+
+fn parse_page_type(value: &str) -> Result<PageType, rusqlite::Error> {
+  match value {
+    "artist" => Ok(PageType::Artist),
+    "album" => Ok(PageType::Album),
+    "chart" => Ok(PageType::Chart),
+    "album_search_result" => Ok(PageType::AlbumSearchResult),
+    _ => {
+      error!(page_type = value, "Invalid page type");
+      Err(rusqlite::Error::ExecuteReturnedResults)
+    }
+  }
+}
+
+fn to_error(value: String) -> Option<String> {
+  if value.is_empty() {
+    None
+  } else {
+    Some(value)
+  }
+}
+
+/**
+ * Stores parse outcome counts over time and the currently detected parser degradations.
+ */
+#[derive(Debug, Clone)]
+pub struct ParserHealthRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+impl ParserHealthRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  #[instrument(skip(self))]
+  pub async fn record_outcome(
+    &self,
+    page_type: &PageType,
+    error: Option<String>,
+    occurred_at: NaiveDateTime,
+  ) -> Result<()> {
+    let page_type = page_type.to_string();
+    let error = error.unwrap_or_default();
+    let timestamp = occurred_at.timestamp();
+    let bucket = timestamp - timestamp.rem_euclid(BUCKET_SECONDS);
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO parse_outcomes (bucket, page_type, error, count)
+          VALUES (?1, ?2, ?3, 1)
+          ON CONFLICT (bucket, page_type, error) DO UPDATE SET count = count + 1
+          ",
+          params![bucket, page_type, error],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to record parse outcome");
+        anyhow!("Failed to record parse outcome")
+      })?
+  }
+
+  /**
+   * Counts parse outcomes per page type and error that occurred in [from, to).
+   */
+  #[instrument(skip(self))]
+  pub async fn get_outcome_counts(
+    &self,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+  ) -> Result<Vec<ParseOutcomeCount>> {
+    let from = from.timestamp();
+    let to = to.timestamp();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT page_type, error, SUM(count)
+          FROM parse_outcomes
+          WHERE bucket >= ?1 AND bucket < ?2
+          GROUP BY page_type, error
+          ",
+        )?;
+        let rows = statement
+          .query_map(params![from, to], |row| {
+            Ok(ParseOutcomeCount {
+              page_type: parse_page_type(&row.get::<_, String>(0)?)?,
+              error: to_error(row.get::<_, String>(1)?),
+              count: row.get::<_, u32>(2)?,
+            })
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to get parse outcome counts"
+        );
+        anyhow!("Failed to get parse outcome counts")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete_outcomes_before(&self, before: NaiveDateTime) -> Result<()> {
+    let before = before.timestamp();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute("DELETE FROM parse_outcomes WHERE bucket < ?", [before])?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete parse outcomes");
+        anyhow!("Failed to delete parse outcomes")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn get_degradations(&self) -> Result<Vec<ParserDegradation>> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT page_type, error, failure_rate, baseline_failure_rate, sample_count, detected_at
+          FROM parser_degradations
+          ORDER BY detected_at ASC
+          ",
+        )?;
+        let rows = statement
+          .query_map([], |row| {
+            Ok(ParserDegradation {
+              page_type: parse_page_type(&row.get::<_, String>(0)?)?,
+              error: to_error(row.get::<_, String>(1)?),
+              failure_rate: row.get::<_, f32>(2)?,
+              baseline_failure_rate: row.get::<_, f32>(3)?,
+              sample_count: row.get::<_, u32>(4)?,
+              detected_at: row.get::<_, NaiveDateTime>(5)?,
+            })
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get parser degradations");
+        anyhow!("Failed to get parser degradations")
+      })?
+  }
+
+  /**
+   * Records a degradation. Returns false if a degradation was already recorded for the same
+   * page type and error, in which case the existing record is left untouched.
+   */
+  #[instrument(skip(self))]
+  pub async fn put_degradation(&self, degradation: &ParserDegradation) -> Result<bool> {
+    let degradation = degradation.clone();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let inserted = conn.execute(
+          "
+          INSERT INTO parser_degradations (page_type, error, failure_rate, baseline_failure_rate, sample_count, detected_at)
+          VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+          ON CONFLICT (page_type, error) DO NOTHING
+          ",
+          params![
+            degradation.page_type.to_string(),
+            degradation.error.unwrap_or_default(),
+            degradation.failure_rate,
+            degradation.baseline_failure_rate,
+            degradation.sample_count,
+            degradation.detected_at,
+          ],
+        )?;
+        Ok(inserted > 0)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put parser degradation");
+        anyhow!("Failed to put parser degradation")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn clear_degradations(&self) -> Result<()> {
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(|conn| {
+        conn.execute("DELETE FROM parser_degradations", [])?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to clear parser degradations"
+        );
+        anyhow!("Failed to clear parser degradations")
+      })?
+  }
+}
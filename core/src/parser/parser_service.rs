@@ -2,9 +2,12 @@ use super::{
   failed_parse_files_repository::{AggregatedError, FailedParseFilesRepository},
   parsed_file_data::{
     ParsedAlbum, ParsedAlbumSearchResult, ParsedArtist, ParsedArtistAlbum, ParsedArtistReference,
-    ParsedChartAlbum, ParsedCredit, ParsedFileData, ParsedTrack,
+    ParsedChartAlbum, ParsedChartPosition, ParsedCredit, ParsedExternalLink, ParsedFileData,
+    ParsedLabel, ParsedReview, ParsedTrack,
   },
   parser::parse_file_on_store,
+  parser_error::ParserError,
+  parser_health_repository::{ParserDegradation, ParserHealthRepository},
 };
 use crate::{
   events::event_publisher::EventPublisher,
@@ -16,7 +19,7 @@ use crate::{
   helpers::fifo_queue::FifoQueue,
   proto::{
     self, EnqueueRetriesRequest, GetAggregatedFailureErrorsReply,
-    GetAggregatedFailureErrorsRequest, ParseFileOnContentStoreReply,
+    GetAggregatedFailureErrorsRequest, GetParserMonitorReply, ParseFileOnContentStoreReply,
     ParseFileOnContentStoreRequest,
   },
   settings::Settings,
@@ -32,6 +35,7 @@ use ulid::Ulid;
 pub struct ParserService {
   sqlite_connection: Arc<SqliteConnection>,
   failed_parse_files_repository: FailedParseFilesRepository,
+  parser_health_repository: ParserHealthRepository,
   file_interactor: FileInteractor,
   settings: Arc<Settings>,
   parser_retry_queue: Arc<FifoQueue<FileName>>,
@@ -51,6 +55,17 @@ impl TryFrom<i32> for PageType {
   }
 }
 
+impl From<ParserError> for proto::ParserError {
+  fn from(val: ParserError) -> Self {
+    proto::ParserError {
+      code: val.code().to_string(),
+      field: val.field().map(|field| field.to_string()),
+      selector: val.selector().map(|selector| selector.to_string()),
+      message: val.to_string(),
+    }
+  }
+}
+
 impl From<ParsedArtistReference> for proto::ParsedArtistReference {
   fn from(val: ParsedArtistReference) -> Self {
     proto::ParsedArtistReference {
@@ -77,6 +92,7 @@ impl Into<proto::ParsedChartAlbum> for ParsedChartAlbum {
       secondary_genres: self.secondary_genres,
       descriptors: self.descriptors,
       release_date: self.release_date.map(|val| val.to_string()),
+      position: self.position,
     }
   }
 }
@@ -88,6 +104,29 @@ impl From<ParsedTrack> for proto::ParsedTrack {
       duration_seconds: val.duration_seconds,
       rating: val.rating,
       position: val.position,
+      credits: val
+        .credits
+        .into_iter()
+        .map(|credit| credit.into())
+        .collect(),
+    }
+  }
+}
+
+impl From<ParsedLabel> for proto::ParsedLabel {
+  fn from(val: ParsedLabel) -> Self {
+    proto::ParsedLabel {
+      name: val.name,
+      catalog_number: val.catalog_number,
+    }
+  }
+}
+
+impl From<ParsedChartPosition> for proto::ParsedChartPosition {
+  fn from(val: ParsedChartPosition) -> Self {
+    proto::ParsedChartPosition {
+      chart: val.chart,
+      position: val.position,
     }
   }
 }
@@ -101,6 +140,27 @@ impl From<ParsedCredit> for proto::ParsedCredit {
   }
 }
 
+impl From<ParsedReview> for proto::ParsedReview {
+  fn from(val: ParsedReview) -> Self {
+    proto::ParsedReview {
+      author: val.author,
+      rating: val.rating,
+      date: val.date.map(|val| val.to_string()),
+      text: val.text,
+    }
+  }
+}
+
+impl From<ParsedExternalLink> for proto::ParsedExternalLink {
+  fn from(val: ParsedExternalLink) -> Self {
+    proto::ParsedExternalLink {
+      source: val.source.to_string(),
+      id: val.id,
+      url: val.url,
+    }
+  }
+}
+
 impl Into<proto::ParsedAlbum> for ParsedAlbum {
   fn into(self) -> proto::ParsedAlbum {
     proto::ParsedAlbum {
@@ -123,6 +183,24 @@ impl Into<proto::ParsedAlbum> for ParsedAlbum {
         .into_iter()
         .map(|credit| credit.into())
         .collect(),
+      reviews: self
+        .reviews
+        .into_iter()
+        .map(|review| review.into())
+        .collect(),
+      external_links: self
+        .external_links
+        .into_iter()
+        .map(|link| link.into())
+        .collect(),
+      release_type: self.release_type,
+      labels: self.labels.into_iter().map(|label| label.into()).collect(),
+      chart_positions: self
+        .chart_positions
+        .into_iter()
+        .map(|chart_position| chart_position.into())
+        .collect(),
+      total_duration_seconds: self.total_duration_seconds,
     }
   }
 }
@@ -144,6 +222,26 @@ impl From<ParsedArtist> for proto::ParsedArtist {
     proto::ParsedArtist {
       name: val.name,
       albums,
+      formed_date: val.formed_date.map(|val| val.to_string()),
+      disbanded_date: val.disbanded_date.map(|val| val.to_string()),
+      location: val.location,
+      country: val.country,
+      genres: val.genres,
+      members: val
+        .members
+        .into_iter()
+        .map(|artist| artist.into())
+        .collect(),
+      member_of: val
+        .member_of
+        .into_iter()
+        .map(|artist| artist.into())
+        .collect(),
+      related_artists: val
+        .related_artists
+        .into_iter()
+        .map(|artist| artist.into())
+        .collect(),
     }
   }
 }
@@ -169,8 +267,12 @@ impl From<ParsedFileData> for proto::ParsedFileData {
     match val {
       ParsedFileData::Chart(data) => {
         let albums: Vec<proto::ParsedChartAlbum> =
-          data.into_iter().map(|album| album.into()).collect();
-        let chart = proto::ParsedChart { albums };
+          data.albums.into_iter().map(|album| album.into()).collect();
+        let chart = proto::ParsedChart {
+          albums,
+          page_number: data.page_number,
+          total_pages: data.total_pages,
+        };
 
         proto::ParsedFileData {
           data: Some(proto::parsed_file_data::Data::Chart(chart)),
@@ -191,6 +293,19 @@ impl From<ParsedFileData> for proto::ParsedFileData {
   }
 }
 
+impl From<ParserDegradation> for proto::ParserDegradation {
+  fn from(val: ParserDegradation) -> Self {
+    proto::ParserDegradation {
+      page_type: proto::PageType::from(val.page_type).into(),
+      error: val.error,
+      failure_rate: val.failure_rate,
+      baseline_failure_rate: val.baseline_failure_rate,
+      sample_count: val.sample_count,
+      detected_at: val.detected_at.to_string(),
+    }
+  }
+}
+
 impl ParserService {
   pub fn new(
     settings: Arc<Settings>,
@@ -199,14 +314,15 @@ impl ParserService {
     parser_retry_queue: Arc<FifoQueue<FileName>>,
   ) -> Self {
     Self {
-      failed_parse_files_repository: FailedParseFilesRepository {
-        redis_connection_pool: Arc::clone(&redis_connection_pool),
-      },
+      failed_parse_files_repository: FailedParseFilesRepository::new(Arc::clone(
+        &sqlite_connection,
+      )),
       file_interactor: FileInteractor::new(
         Arc::clone(&settings),
         Arc::clone(&redis_connection_pool),
         Arc::clone(&sqlite_connection),
       ),
+      parser_health_repository: ParserHealthRepository::new(Arc::clone(&sqlite_connection)),
       parser_retry_queue,
       sqlite_connection,
       settings,
@@ -239,9 +355,16 @@ impl proto::ParserService for ParserService {
       errors: aggregated_errors
         .into_iter()
         .map(
-          |AggregatedError { error, count }| proto::AggregatedFailureError {
+          |AggregatedError {
+             error,
+             code,
+             field,
+             count,
+           }| proto::AggregatedFailureError {
             error,
             count: count as u32,
+            code,
+            field,
           },
         )
         .collect(),
@@ -314,4 +437,38 @@ impl proto::ParserService for ParserService {
       })?;
     Ok(Response::new(()))
   }
+
+  async fn get_monitor(&self, _: Request<()>) -> Result<Response<GetParserMonitorReply>, Status> {
+    let degradations = self
+      .parser_health_repository
+      .get_degradations()
+      .await
+      .map_err(|err| {
+        error!(err = err.to_string(), "failed to get parser degradations");
+        Status::internal("failed to get parser degradations")
+      })?;
+    let status = if degradations.is_empty() {
+      proto::ParserHealthStatus::ParserHealthy
+    } else {
+      proto::ParserHealthStatus::ParserDegraded
+    };
+    Ok(Response::new(GetParserMonitorReply {
+      monitor: Some(proto::ParserMonitor {
+        status: status.into(),
+        degradations: degradations.into_iter().map(|val| val.into()).collect(),
+      }),
+    }))
+  }
+
+  async fn clear_degradations(&self, _: Request<()>) -> Result<Response<()>, Status> {
+    self
+      .parser_health_repository
+      .clear_degradations()
+      .await
+      .map_err(|err| {
+        error!(err = err.to_string(), "failed to clear parser degradations");
+        Status::internal("failed to clear parser degradations")
+      })?;
+    Ok(Response::new(()))
+  }
 }
@@ -38,3 +38,68 @@ pub fn clean_artist_name(artist_name: &str) -> &str {
 pub fn clean_album_name(album_name: String) -> String {
   album_name.replace('’', "'")
 }
+
+/**
+ * Parses durations formatted as "mm:ss" or "hh:mm:ss" into seconds.
+ */
+pub fn parse_duration_seconds(duration: &str) -> Result<u32> {
+  let parts = duration
+    .trim()
+    .split(':')
+    .map(|part| part.trim().parse::<u32>())
+    .collect::<Result<Vec<u32>, _>>()
+    .map_err(|_| anyhow::anyhow!("Invalid duration: {}", duration))?;
+  match parts.as_slice() {
+    [minutes, seconds] => Ok(minutes * 60 + seconds),
+    [hours, minutes, seconds] => Ok(hours * 3600 + minutes * 60 + seconds),
+    _ => Err(anyhow::anyhow!("Invalid duration: {}", duration)),
+  }
+}
+
+/**
+ * Parses chart rankings such as "#3 for 1997, #120 overall" into (chart, position) pairs.
+ */
+pub fn parse_chart_positions(ranked: &str) -> Vec<(String, u32)> {
+  ranked
+    .split('#')
+    .filter_map(|ranking| {
+      let ranking = ranking.trim().trim_end_matches(',');
+      let (position, chart) = ranking.split_once(' ')?;
+      let position = position.replace(',', "").parse::<u32>().ok()?;
+      let chart = chart.trim();
+      let chart = chart.strip_prefix("for ").unwrap_or(chart).trim();
+      if chart.is_empty() {
+        None
+      } else {
+        Some((chart.to_string(), position))
+      }
+    })
+    .collect()
+}
+
+/**
+ * Expands the track list of a credit role, e.g. "1-3, 5" or "A1 to A3, B2", into the
+ * positions of the credited tracks, given the positions of all tracks in album order.
+ */
+pub fn expand_role_tracks(role_tracks: &str, track_positions: &[String]) -> Vec<String> {
+  let index_of = |position: &str| track_positions.iter().position(|p| p == position);
+  role_tracks
+    .split(',')
+    .flat_map(|part| {
+      let part = part.trim();
+      let range = part
+        .split_once(" to ")
+        .or_else(|| part.split_once('-'))
+        .map(|(start, end)| (start.trim(), end.trim()));
+      match range {
+        Some((start, end)) => match (index_of(start), index_of(end)) {
+          (Some(start), Some(end)) if start <= end => track_positions[start..=end].to_vec(),
+          _ => vec![],
+        },
+        None => index_of(part)
+          .map(|index| vec![track_positions[index].clone()])
+          .unwrap_or(vec![]),
+      }
+    })
+    .collect()
+}
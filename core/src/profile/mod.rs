@@ -4,6 +4,7 @@ pub mod profile_interactor;
 pub mod profile_repository;
 pub mod profile_service;
 pub mod profile_summary;
+pub mod rym_export;
 mod spotify_import_event_subscribers;
 pub mod spotify_import_lookup_subscription;
 pub mod spotify_import_repository;
@@ -2,6 +2,7 @@ use super::{
   profile::{Profile, ProfileId},
   profile_repository::ProfileRepository,
   profile_summary::ProfileSummary,
+  rym_export::{RymExportEntry, RymExportTarget},
   spotify_import_lookup_subscription::{
     build_spotify_import_lookup_subscriptions, SpotifyImportLookupSubscription,
   },
@@ -14,10 +15,12 @@ use crate::{
     event_publisher::EventPublisher,
   },
   files::file_metadata::file_name::FileName,
+  genres::genre_hierarchy_repository::GenreHierarchyRepository,
   lookup::{
     album_search_lookup::{AlbumSearchLookup, AlbumSearchLookupQuery, AlbumSearchLookupStatus},
     lookup_interactor::LookupInteractor,
   },
+  parser::parsed_file_data::ExternalLinkSource,
   settings::Settings,
   spotify::spotify_client::{SpotifyClient, SpotifyTrack},
   sqlite::SqliteConnection,
@@ -25,7 +28,7 @@ use crate::{
 use anyhow::Result;
 use futures::future::join_all;
 use rustis::{bb8::Pool, client::PooledClientManager};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use tracing::{instrument, warn};
 
 pub struct PendingSpotifyImport {
@@ -41,6 +44,7 @@ pub struct ProfileInteractor {
   spotify_client: SpotifyClient,
   lookup_interactor: LookupInteractor,
   spotify_import_repository: SpotifyImportRepository,
+  genre_hierarchy_repository: GenreHierarchyRepository,
 }
 
 impl ProfileInteractor {
@@ -51,20 +55,16 @@ impl ProfileInteractor {
     album_repository: Arc<dyn AlbumRepository + Send + Sync + 'static>,
   ) -> Self {
     Self {
-      profile_repository: ProfileRepository {
-        redis_connection_pool: Arc::clone(&redis_connection_pool),
-      },
+      profile_repository: ProfileRepository::new(Arc::clone(&sqlite_connection)),
       album_repository: Arc::clone(&album_repository),
       event_publisher: EventPublisher::new(Arc::clone(&settings), Arc::clone(&sqlite_connection)),
       spotify_client: SpotifyClient::new(&settings.spotify, Arc::clone(&redis_connection_pool)),
       lookup_interactor: LookupInteractor::new(
         Arc::clone(&settings),
-        Arc::clone(&redis_connection_pool),
         Arc::clone(&sqlite_connection),
       ),
-      spotify_import_repository: SpotifyImportRepository {
-        redis_connection_pool: Arc::clone(&redis_connection_pool),
-      },
+      spotify_import_repository: SpotifyImportRepository::new(Arc::clone(&sqlite_connection)),
+      genre_hierarchy_repository: GenreHierarchyRepository::new(Arc::clone(&sqlite_connection)),
     }
   }
 
@@ -156,29 +156,85 @@ impl ProfileInteractor {
     id: &ProfileId,
   ) -> Result<(ProfileSummary, Vec<AlbumReadModel>)> {
     let profile = self.profile_repository.get(id).await?;
-    let albums = if !profile.albums.is_empty() {
-      self
-        .album_repository
-        .find_many(profile.albums.keys().cloned().collect())
-        .await?
-    } else {
-      vec![]
-    };
+    let albums = self.find_profile_albums(&profile).await?;
     Ok((profile.summarize(&albums), albums))
   }
 
+  async fn find_profile_albums(&self, profile: &Profile) -> Result<Vec<AlbumReadModel>> {
+    if profile.albums.is_empty() {
+      return Ok(vec![]);
+    }
+    self
+      .album_repository
+      .find_many(profile.albums.keys().cloned().collect())
+      .await
+  }
+
   #[instrument(skip(self))]
-  pub async fn get_profile_summary(&self, id: &ProfileId) -> Result<ProfileSummary> {
-    let (profile_summary, _) = self.get_profile_summary_and_albums(id).await?;
-    Ok(profile_summary)
+  pub async fn get_profile_summary(
+    &self,
+    id: &ProfileId,
+    roll_up_genres: bool,
+  ) -> Result<ProfileSummary> {
+    let profile = self.profile_repository.get(id).await?;
+    let albums = self.find_profile_albums(&profile).await?;
+    let genre_hierarchy = if roll_up_genres {
+      Some(self.genre_hierarchy_repository.get().await?)
+    } else {
+      None
+    };
+    Ok(profile.summarize_with_genre_hierarchy(&albums, genre_hierarchy.as_ref()))
   }
 
-  async fn import_spotify_tracks(
+  /**
+   * Puts albums that can be matched to their Spotify album ID through the external links of
+   * parsed albums on the profile, and returns the tracks that could not be matched.
+   */
+  #[instrument(skip(self, spotify_tracks), fields(count = spotify_tracks.len()))]
+  async fn put_spotify_albums_matched_by_id(
     &self,
     id: &ProfileId,
     spotify_tracks: Vec<SpotifyTrack>,
+  ) -> Result<Vec<SpotifyTrack>> {
+    let mut spotify_album_ids = spotify_tracks
+      .iter()
+      .map(|track| track.album.spotify_id.clone())
+      .collect::<Vec<String>>();
+    spotify_album_ids.sort();
+    spotify_album_ids.dedup();
+    let matched_file_names = self
+      .album_repository
+      .find_file_names_by_external_ids(ExternalLinkSource::Spotify, spotify_album_ids)
+      .await?;
+
+    let mut factors: HashMap<FileName, u32> = HashMap::new();
+    let mut unmatched_tracks = vec![];
+    for track in spotify_tracks {
+      match matched_file_names.get(&track.album.spotify_id) {
+        Some(file_name) => *factors.entry(file_name.clone()).or_default() += 1,
+        None => unmatched_tracks.push(track),
+      }
+    }
+
+    if !factors.is_empty() {
+      self
+        .put_many_albums_on_profile(id, factors.into_iter().collect())
+        .await?;
+    }
+
+    Ok(unmatched_tracks)
+  }
+
+  /**
+   * Subscribes the profile to album search lookups, and puts the albums of lookups which are
+   * already complete on the profile. The others are put on the profile once their lookup
+   * completes. Subscriptions are not specific to Spotify imports.
+   */
+  async fn import_lookup_subscriptions(
+    &self,
+    id: &ProfileId,
+    subscriptions: Vec<SpotifyImportLookupSubscription>,
   ) -> Result<()> {
-    let subscriptions = build_spotify_import_lookup_subscriptions(id, spotify_tracks);
     join_all(subscriptions.iter().map(|subscription| async move {
       self
         .spotify_import_repository
@@ -236,6 +292,70 @@ impl ProfileInteractor {
     Ok(())
   }
 
+  async fn import_spotify_tracks(
+    &self,
+    id: &ProfileId,
+    spotify_tracks: Vec<SpotifyTrack>,
+  ) -> Result<()> {
+    let spotify_tracks = self
+      .put_spotify_albums_matched_by_id(id, spotify_tracks)
+      .await?;
+    let subscriptions = build_spotify_import_lookup_subscriptions(id, spotify_tracks);
+    self.import_lookup_subscriptions(id, subscriptions).await
+  }
+
+  /**
+   * Imports the entries of a collection or ratings export, creating the profile if it does not
+   * exist yet. Factors are derived from the exported ratings, and albums rated 2.5 stars or lower
+   * are left out. Entries without a release URL go through the album search lookup.
+   */
+  #[instrument(skip(self, entries))]
+  pub async fn import_rym_export(
+    &self,
+    id: &ProfileId,
+    name: Option<String>,
+    entries: Vec<RymExportEntry>,
+  ) -> Result<Profile> {
+    if !self.profile_repository.exists(id).await? {
+      self
+        .profile_repository
+        .insert(id.clone(), name.unwrap_or(id.to_string()))
+        .await?;
+    }
+
+    let mut factors: HashMap<FileName, u32> = HashMap::new();
+    let mut lookup_factors: HashMap<AlbumSearchLookupQuery, u32> = HashMap::new();
+    for entry in entries {
+      let factor = entry.factor();
+      if factor == 0 {
+        continue;
+      }
+      let current = match entry.target {
+        RymExportTarget::FileName(file_name) => factors.entry(file_name).or_default(),
+        RymExportTarget::Lookup(query) => lookup_factors.entry(query).or_default(),
+      };
+      *current = (*current).max(factor);
+    }
+
+    if !factors.is_empty() {
+      self
+        .put_many_albums_on_profile(id, factors.into_iter().collect())
+        .await?;
+    }
+    let subscriptions = lookup_factors
+      .into_iter()
+      .map(|(query, factor)| SpotifyImportLookupSubscription {
+        album_search_lookup_encoded_query: query.to_encoded_string(),
+        album_search_lookup_query: query,
+        profile_id: id.clone(),
+        factor,
+      })
+      .collect::<Vec<SpotifyImportLookupSubscription>>();
+    self.import_lookup_subscriptions(id, subscriptions).await?;
+
+    self.get_profile(id).await
+  }
+
   pub async fn import_saved_spotify_tracks(&self, id: &ProfileId) -> Result<()> {
     let spotify_tracks = self.spotify_client.get_saved_tracks().await?;
     self.import_spotify_tracks(id, spotify_tracks).await
@@ -1,35 +1,77 @@
 use super::profile::{Profile, ProfileId};
-use crate::files::file_metadata::file_name::FileName;
-use anyhow::{bail, Error, Result};
-use chrono::Utc;
-use futures::future::join_all;
-use rustis::{
-  bb8::Pool,
-  client::PooledClientManager,
-  commands::{GenericCommands, JsonCommands, JsonGetOptions, SetCondition},
-};
-use std::sync::Arc;
-use tracing::warn;
+use crate::{files::file_metadata::file_name::FileName, sqlite::SqliteConnection};
+use anyhow::{anyhow, bail, Result};
+use chrono::{NaiveDateTime, Utc};
+use rusqlite::{params, OptionalExtension};
+use std::{collections::HashMap, sync::Arc};
+use tracing::{error, instrument, warn};
 
+#[derive(Debug, Clone)]
 pub struct ProfileRepository {
-  pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
 }
 
-impl ProfileRepository {
-  pub fn key(&self, id: &ProfileId) -> String {
-    format!("profile:{}", id.to_string())
-  }
+fn to_profile(
+  (id, name, last_updated_at): (String, String, NaiveDateTime),
+  albums: Vec<(String, u32)>,
+) -> Result<Profile> {
+  Ok(Profile {
+    id: ProfileId::try_from(id)?,
+    name,
+    albums: albums
+      .into_iter()
+      .map(|(file_name, factor)| Ok((FileName::try_from(file_name)?, factor)))
+      .collect::<Result<HashMap<_, _>>>()?,
+    last_updated_at,
+  })
+}
 
-  pub fn profile_album_path(&self, album_file_name: &FileName) -> String {
-    format!("$.albums[\"{}\"]", album_file_name.to_string())
+impl ProfileRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
   }
 
+  #[instrument(skip(self))]
   pub async fn find(&self, id: &ProfileId) -> Result<Option<Profile>> {
-    let connection = self.redis_connection_pool.get().await?;
-    let json: Option<String> = connection
-      .json_get(self.key(id), JsonGetOptions::default())
-      .await?;
-    Ok(json.map(|json| serde_json::from_str(&json).unwrap()))
+    let id = id.to_string();
+    let row = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let profile = conn
+          .query_row(
+            "SELECT id, name, last_updated_at FROM profiles WHERE id = ?",
+            [&id],
+            |row| {
+              Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, NaiveDateTime>(2)?,
+              ))
+            },
+          )
+          .optional()?;
+        let Some(profile) = profile else {
+          return Ok::<_, rusqlite::Error>(None);
+        };
+        let mut stmt =
+          conn.prepare("SELECT file_name, factor FROM profile_albums WHERE profile_id = ?")?;
+        let albums = stmt
+          .query_map([&id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some((profile, albums)))
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find profile");
+        anyhow!("Failed to find profile")
+      })??;
+    row
+      .map(|(profile, albums)| to_profile(profile, albums))
+      .transpose()
   }
 
   pub async fn get(&self, id: &ProfileId) -> Result<Profile> {
@@ -40,33 +82,86 @@ impl ProfileRepository {
     }
   }
 
+  #[instrument(skip(self))]
   pub async fn get_all(&self) -> Result<Vec<Profile>> {
-    let connection = self.redis_connection_pool.get().await?;
-    let keys: Vec<String> = connection.keys("profile:*").await?;
-    let futures = keys.into_iter().map(|key| async {
-      let json: String = connection.json_get(key, JsonGetOptions::default()).await?;
-      Ok::<Profile, Error>(serde_json::from_str(&json).unwrap())
-    });
-    let profiles = join_all(futures)
+    let (profiles, mut albums) = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut stmt =
+          conn.prepare("SELECT id, name, last_updated_at FROM profiles ORDER BY id")?;
+        let profiles = stmt
+          .query_map([], |row| {
+            Ok((
+              row.get::<_, String>(0)?,
+              row.get::<_, String>(1)?,
+              row.get::<_, NaiveDateTime>(2)?,
+            ))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        let mut stmt = conn.prepare("SELECT profile_id, file_name, factor FROM profile_albums")?;
+        let mut albums: HashMap<String, Vec<(String, u32)>> = HashMap::new();
+        for row in stmt.query_map([], |row| {
+          Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, u32>(2)?,
+          ))
+        })? {
+          let (profile_id, file_name, factor) = row?;
+          albums
+            .entry(profile_id)
+            .or_default()
+            .push((file_name, factor));
+        }
+        Ok::<_, rusqlite::Error>((profiles, albums))
+      })
       .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to get all profiles");
+        anyhow!("Failed to get all profiles")
+      })??;
+
+    let profiles = profiles
       .into_iter()
-      .filter_map(|profile: Result<Profile>| match profile {
-        Ok(profile) => Some(profile),
-        Err(_) => {
-          warn!("Failed to deserialize profile");
-          None
+      .filter_map(|profile| {
+        let profile_albums = albums.remove(&profile.0).unwrap_or_default();
+        match to_profile(profile, profile_albums) {
+          Ok(profile) => Some(profile),
+          Err(_) => {
+            warn!("Failed to deserialize profile");
+            None
+          }
         }
       })
       .collect();
     Ok(profiles)
   }
 
+  #[instrument(skip(self))]
   pub async fn exists(&self, id: &ProfileId) -> Result<bool> {
-    let connection = self.redis_connection_pool.get().await?;
-    let result: usize = connection.exists(self.key(id)).await?;
-    Ok(result == 1)
+    let id = id.to_string();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let exists = conn.query_row(
+          "SELECT EXISTS (SELECT 1 FROM profiles WHERE id = ?)",
+          [id],
+          |row| row.get::<_, bool>(0),
+        )?;
+        Ok(exists)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to check if profile exists");
+        anyhow!("Failed to check if profile exists")
+      })?
   }
 
+  #[instrument(skip(self))]
   pub async fn insert(&self, id: ProfileId, name: String) -> Result<Profile> {
     if self.exists(&id).await? {
       bail!("Profile already exists")
@@ -77,29 +172,50 @@ impl ProfileRepository {
       last_updated_at: Utc::now().naive_utc(),
       albums: Default::default(),
     };
+    let id = profile.id.to_string();
+    let name = profile.name.clone();
+    let last_updated_at = profile.last_updated_at;
     self
-      .redis_connection_pool
-      .get()
+      .sqlite_connection
+      .write()
       .await?
-      .json_set(
-        self.key(&profile.id),
-        "$",
-        serde_json::to_string(&profile)?,
-        SetCondition::default(),
-      )
-      .await?;
+      .interact(move |conn| {
+        conn.execute(
+          "INSERT INTO profiles (id, name, last_updated_at) VALUES (?1, ?2, ?3)",
+          params![id, name, last_updated_at],
+        )?;
+        Ok::<_, rusqlite::Error>(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to insert profile");
+        anyhow!("Failed to insert profile")
+      })??;
     Ok(profile)
   }
 
+  #[instrument(skip(self))]
   pub async fn delete(&self, id: &ProfileId) -> Result<()> {
     if !self.exists(id).await? {
       bail!("Profile does not exist")
     }
-    let connection = self.redis_connection_pool.get().await?;
-    connection.del(self.key(id)).await?;
-    Ok(())
+    let id = id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute("DELETE FROM profiles WHERE id = ?", [id])?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete profile");
+        anyhow!("Failed to delete profile")
+      })?
   }
 
+  #[instrument(skip(self))]
   pub async fn is_album_on_profile(
     &self,
     id: &ProfileId,
@@ -108,38 +224,68 @@ impl ProfileRepository {
     if !self.exists(id).await? {
       bail!("Profile does not exist")
     }
-    let connection = self.redis_connection_pool.get().await?;
-    let json: Option<String> = connection
-      .json_get(
-        self.key(id),
-        JsonGetOptions::default().path(self.profile_album_path(album_file_name)),
-      )
-      .await?;
-    Ok(json.is_some() && json.unwrap() != "[]")
+    let id = id.to_string();
+    let album_file_name = album_file_name.to_string();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let exists = conn.query_row(
+          "
+          SELECT EXISTS (
+            SELECT 1 FROM profile_albums WHERE profile_id = ?1 AND file_name = ?2
+          )
+          ",
+          params![id, album_file_name],
+          |row| row.get::<_, bool>(0),
+        )?;
+        Ok(exists)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to check if album is on profile"
+        );
+        anyhow!("Failed to check if album is on profile")
+      })?
   }
 
+  #[instrument(skip(self))]
   pub async fn put_album_on_profile(
     &self,
     id: &ProfileId,
     album_file_name: &FileName,
     factor: u32,
   ) -> Result<(Profile, bool)> {
-    if !self.exists(id).await? {
-      bail!("Profile does not exist")
-    }
     let new_addition = !self.is_album_on_profile(id, album_file_name).await?;
-    let connection = self.redis_connection_pool.get().await?;
-    connection
-      .json_set(
-        self.key(id),
-        self.profile_album_path(album_file_name),
-        factor,
-        SetCondition::default(),
-      )
-      .await?;
+    let profile_id = id.to_string();
+    let album_file_name = album_file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO profile_albums (profile_id, file_name, factor)
+          VALUES (?1, ?2, ?3)
+          ON CONFLICT (profile_id, file_name) DO UPDATE SET factor = ?3
+          ",
+          params![profile_id, album_file_name, factor],
+        )?;
+        Ok::<_, rusqlite::Error>(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to put album on profile");
+        anyhow!("Failed to put album on profile")
+      })??;
     Ok((self.get(id).await?, new_addition))
   }
 
+  #[instrument(skip(self))]
   pub async fn remove_album_from_profile(
     &self,
     id: &ProfileId,
@@ -148,10 +294,26 @@ impl ProfileRepository {
     if !self.exists(id).await? {
       bail!("Profile does not exist")
     }
-    let connection = self.redis_connection_pool.get().await?;
-    connection
-      .json_del(self.key(id), self.profile_album_path(album_file_name))
-      .await?;
-    Ok(())
+    let id = id.to_string();
+    let album_file_name = album_file_name.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM profile_albums WHERE profile_id = ?1 AND file_name = ?2",
+          params![id, album_file_name],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to remove album from profile"
+        );
+        anyhow!("Failed to remove album from profile")
+      })?
   }
 }
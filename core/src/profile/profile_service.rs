@@ -2,6 +2,7 @@ use super::{
   profile::{Profile, ProfileId},
   profile_interactor::ProfileInteractor,
   profile_summary::{ItemWithFactor, ProfileSummary},
+  rym_export::parse_rym_export,
 };
 use crate::{
   albums::album_repository::AlbumRepository,
@@ -183,7 +184,7 @@ impl proto::ProfileService for ProfileService {
     })?;
     let profile_summary = self
       .profile_interactor
-      .get_profile_summary(&id)
+      .get_profile_summary(&id, request.roll_up_genres.unwrap_or(false))
       .await
       .map_err(|err| {
         error!("failed to get profile summary: {:?}", err);
@@ -306,6 +307,33 @@ impl proto::ProfileService for ProfileService {
     Ok(Response::new(()))
   }
 
+  async fn import_rym_export(
+    &self,
+    request: Request<proto::ImportRymExportRequest>,
+  ) -> Result<Response<proto::ImportRymExportReply>, Status> {
+    let inner = request.into_inner();
+    let profile_id = ProfileId::try_from(inner.profile_id).map_err(|err| {
+      error!("invalid profile id: {:?}", err);
+      Status::invalid_argument("invalid profile id")
+    })?;
+    let entries = parse_rym_export(&inner.content).map_err(|err| {
+      error!("invalid rym export: {:?}", err);
+      Status::invalid_argument(format!("invalid rym export: {}", err))
+    })?;
+    let profile = self
+      .profile_interactor
+      .import_rym_export(&profile_id, inner.profile_name, entries)
+      .await
+      .map_err(|err| {
+        error!("failed to import rym export: {:?}", err);
+        Status::internal("failed to import rym export")
+      })?;
+
+    Ok(Response::new(proto::ImportRymExportReply {
+      profile: Some(profile.into()),
+    }))
+  }
+
   async fn get_pending_spotify_imports(
     &self,
     request: Request<proto::GetPendingSpotifyImportsRequest>,
@@ -2,6 +2,7 @@ use super::profile::{Profile, ProfileId};
 use crate::{
   albums::album_read_model::AlbumReadModel,
   files::file_metadata::file_name::FileName,
+  genres::genre_hierarchy::GenreHierarchy,
   helpers::math::{desc_sort_by, median},
 };
 use chrono::Datelike;
@@ -56,8 +57,20 @@ pub struct ProfileSummary {
 }
 
 impl Profile {
-  #[instrument(skip_all, fields(id = %self.id.to_string(), len = album_read_models.len()))]
   pub fn summarize(&self, album_read_models: &[AlbumReadModel]) -> ProfileSummary {
+    self.summarize_with_genre_hierarchy(album_read_models, None)
+  }
+
+  /**
+   * With a genre hierarchy, the factor of each album is also added to the ancestors of its
+   * genres, once per album.
+   */
+  #[instrument(skip_all, fields(id = %self.id.to_string(), len = album_read_models.len()))]
+  pub fn summarize_with_genre_hierarchy(
+    &self,
+    album_read_models: &[AlbumReadModel],
+    genre_hierarchy: Option<&GenreHierarchy>,
+  ) -> ProfileSummary {
     let album_read_models_map = album_read_models
       .into_par_iter()
       .map(|album_read_model| (album_read_model.file_name.clone(), album_read_model))
@@ -87,14 +100,22 @@ impl Profile {
           .or_insert(*factor);
       }
 
-      for genre in &album.primary_genres {
+      let (primary_genres, secondary_genres) = match genre_hierarchy {
+        Some(genre_hierarchy) => (
+          genre_hierarchy.with_ancestors(&album.primary_genres),
+          genre_hierarchy.with_ancestors(&album.secondary_genres),
+        ),
+        None => (album.primary_genres.clone(), album.secondary_genres.clone()),
+      };
+
+      for genre in &primary_genres {
         primary_genres_map
           .entry(genre.clone())
           .and_modify(|c| *c += factor)
           .or_insert(*factor);
       }
 
-      for genre in &album.secondary_genres {
+      for genre in &secondary_genres {
         secondary_genres_map
           .entry(genre.clone())
           .and_modify(|c| *c += factor)
@@ -0,0 +1,179 @@
+use crate::{
+  files::file_metadata::file_name::FileName, lookup::album_search_lookup::AlbumSearchLookupQuery,
+};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/**
+ * Factor of albums which are in the collection but were not rated, the same as an album with a
+ * single saved track in a Spotify import.
+ */
+const UNRATED_FACTOR: u32 = 1;
+
+/**
+ * Ratings up to this one (2.5 stars) don't count towards the profile.
+ */
+const MAX_IGNORED_RATING: u32 = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RymExportTarget {
+  FileName(FileName),
+  Lookup(AlbumSearchLookupQuery),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RymExportEntry {
+  pub target: RymExportTarget,
+  /**
+   * Rating out of 10, as exported. Unrated entries have no rating.
+   */
+  pub rating: Option<u32>,
+}
+
+impl RymExportEntry {
+  /**
+   * Weight of the album in the profile. Ratings range from 1 to 10 (half stars), and only the
+   * ones above 2.5 stars count: 3 stars maps to 1 and every half star above adds 1, up to 5 for
+   * 5 stars. Albums rated lower have a factor of 0 and are left out of the profile.
+   */
+  pub fn factor(&self) -> u32 {
+    match self.rating {
+      Some(rating) => rating.saturating_sub(MAX_IGNORED_RATING),
+      None => UNRATED_FACTOR,
+    }
+  }
+}
+
+/**
+ * Splits CSV content into records, handling quoted fields with escaped quotes and line breaks.
+ */
+pub fn parse_csv_records(content: &str) -> Vec<Vec<String>> {
+  let mut records = vec![];
+  let mut record = vec![];
+  let mut field = String::new();
+  let mut in_quotes = false;
+  let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match (c, in_quotes) {
+      ('"', true) if chars.peek() == Some(&'"') => {
+        field.push('"');
+        chars.next();
+      }
+      ('"', true) => in_quotes = false,
+      ('"', false) if field.is_empty() => in_quotes = true,
+      (',', false) => record.push(std::mem::take(&mut field)),
+      ('\r', false) => {}
+      ('\n', false) => {
+        record.push(std::mem::take(&mut field));
+        records.push(std::mem::take(&mut record));
+      }
+      (c, _) => field.push(c),
+    }
+  }
+  if !field.is_empty() || !record.is_empty() {
+    record.push(field);
+    records.push(record);
+  }
+
+  records
+    .into_iter()
+    .filter(|record| record.iter().any(|field| !field.trim().is_empty()))
+    .collect()
+}
+
+fn to_file_name(url: &str) -> Option<FileName> {
+  let path = url
+    .trim()
+    .trim_start_matches("https://")
+    .trim_start_matches("http://")
+    .trim_start_matches("www.")
+    .trim_start_matches("rateyourmusic.com");
+  FileName::try_from(path.to_string())
+    .ok()
+    .filter(|file_name| file_name.page_type().is_album())
+}
+
+fn parse_rating(rating: &str) -> Option<u32> {
+  rating
+    .trim()
+    .parse::<u32>()
+    .ok()
+    .filter(|rating| (1..=10).contains(rating))
+}
+
+/**
+ * Parses a collection or ratings export. Columns are matched by their header, so that both the
+ * plain export (artist name split into first and last name, release title) and exports with a
+ * release URL column are supported. Entries with a release URL map to its file name, the others
+ * are looked up by artist and album name.
+ */
+pub fn parse_rym_export(content: &str) -> Result<Vec<RymExportEntry>> {
+  let mut records = parse_csv_records(content).into_iter();
+  let header = records
+    .next()
+    .ok_or(anyhow!("Export is empty"))?
+    .into_iter()
+    .enumerate()
+    .map(|(i, column)| (column.trim().to_lowercase().replace('_', " "), i))
+    .collect::<HashMap<String, usize>>();
+  let column = |names: &[&str]| names.iter().find_map(|name| header.get(*name).copied());
+
+  let url_column = column(&["url", "release url", "rym url"]);
+  let first_name_column = column(&["first name"]);
+  let last_name_column = column(&["last name"]);
+  let first_name_localized_column = column(&["first name localized"]);
+  let last_name_localized_column = column(&["last name localized"]);
+  let artist_column = column(&["artist", "artist name"]);
+  let title_column = column(&["title", "album", "album name"]);
+  let rating_column = column(&["rating"]);
+
+  if url_column.is_none() && title_column.is_none() {
+    return Err(anyhow!(
+      "Export has neither a release URL nor a title column"
+    ));
+  }
+
+  let entries = records
+    .filter_map(|record| {
+      let value = |column: Option<usize>| {
+        column
+          .and_then(|column| record.get(column))
+          .map(|value| value.trim().to_string())
+          .filter(|value| !value.is_empty())
+      };
+      let join_name = |first: Option<String>, last: Option<String>| {
+        let name = [first, last]
+          .into_iter()
+          .flatten()
+          .collect::<Vec<String>>()
+          .join(" ");
+        Some(name).filter(|name| !name.is_empty())
+      };
+
+      let rating = value(rating_column).and_then(|rating| parse_rating(&rating));
+      if let Some(file_name) = value(url_column).and_then(|url| to_file_name(&url)) {
+        return Some(RymExportEntry {
+          target: RymExportTarget::FileName(file_name),
+          rating,
+        });
+      }
+
+      let artist_name = value(artist_column)
+        .or_else(|| join_name(value(first_name_column), value(last_name_column)))
+        .or_else(|| {
+          join_name(
+            value(first_name_localized_column),
+            value(last_name_localized_column),
+          )
+        })?;
+      let album_name = value(title_column)?;
+      Some(RymExportEntry {
+        target: RymExportTarget::Lookup(AlbumSearchLookupQuery::new(album_name, artist_name)),
+        rating,
+      })
+    })
+    .collect();
+
+  Ok(entries)
+}
@@ -1,103 +1,80 @@
 use super::{
   profile::ProfileId, spotify_import_lookup_subscription::SpotifyImportLookupSubscription,
 };
-use crate::{
-  helpers::redisearch::{does_ft_index_exist, escape_tag_value},
-  lookup::album_search_lookup::AlbumSearchLookupQuery,
-};
-use anyhow::{anyhow, Error, Result};
-use rustis::{
-  bb8::Pool,
-  client::PooledClientManager,
-  commands::{
-    FtCreateOptions, FtFieldSchema, FtFieldType, FtIndexDataType, FtSearchOptions, JsonCommands,
-    SearchCommands, SetCondition,
-  },
-};
+use crate::{lookup::album_search_lookup::AlbumSearchLookupQuery, sqlite::SqliteConnection};
+use anyhow::{anyhow, Result};
+use rusqlite::params;
 use std::sync::Arc;
-use tracing::{info, warn};
+use tracing::{error, instrument, warn};
 
+#[derive(Debug, Clone)]
 pub struct SpotifyImportRepository {
-  pub redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
 }
 
-impl TryFrom<Vec<(String, String)>> for SpotifyImportLookupSubscription {
-  type Error = Error;
-
-  fn try_from(values: Vec<(String, String)>) -> Result<Self> {
-    let json = values.get(0).map(|(_, json)| json).ok_or(anyhow!(
-      "invalid SpotifyImportLookupSubscription: missing json"
-    ))?;
-    let subscription: SpotifyImportLookupSubscription = serde_json::from_str(json)?;
-    Ok(subscription)
-  }
+fn to_subscription(
+  (album_search_lookup_encoded_query, profile_id, album_search_lookup_query, factor): (
+    String,
+    String,
+    String,
+    u32,
+  ),
+) -> Result<SpotifyImportLookupSubscription> {
+  Ok(SpotifyImportLookupSubscription {
+    album_search_lookup_encoded_query,
+    album_search_lookup_query: serde_json::from_str(&album_search_lookup_query)?,
+    profile_id: ProfileId::try_from(profile_id)?,
+    factor,
+  })
 }
 
-const NAMESPACE: &str = "profile_spotify_import";
-const INDEX_NAME: &str = "profile_spotify_import_idx";
-
 impl SpotifyImportRepository {
-  fn lookup_subscriptions_key(
-    &self,
-    album_search_lookup_encoded_query: String,
-    profile_id: &ProfileId,
-  ) -> String {
-    format!(
-      "{}:{}:{}",
-      NAMESPACE,
-      album_search_lookup_encoded_query,
-      profile_id.to_string()
-    )
-  }
-
-  pub async fn setup_index(&self) -> Result<()> {
-    let connection = self.redis_connection_pool.get().await?;
-
-    if !does_ft_index_exist(&connection, INDEX_NAME).await {
-      info!("Creating new search index: {}", INDEX_NAME);
-      connection
-        .ft_create(
-          INDEX_NAME,
-          FtCreateOptions::default()
-            .on(FtIndexDataType::Json)
-            .prefix(format!("{}:", NAMESPACE)),
-          [
-            FtFieldSchema::identifier("$.album_search_lookup_encoded_query")
-              .as_attribute("album_search_lookup_encoded_query")
-              .field_type(FtFieldType::Tag),
-            FtFieldSchema::identifier("$.profile_id")
-              .as_attribute("profile_id")
-              .field_type(FtFieldType::Tag),
-          ],
-        )
-        .await?;
-    }
-
-    Ok(())
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
   }
 
   async fn find_subscriptions(
     &self,
-    key: String,
+    column: &'static str,
     value: String,
   ) -> Result<Vec<SpotifyImportLookupSubscription>> {
-    let connection = self.redis_connection_pool.get().await?;
-    let search_result = connection
-      .ft_search(
-        INDEX_NAME,
-        format!("@{}:{{ {} }}", key, escape_tag_value(&value)),
-        FtSearchOptions::default().limit(0, 10000),
-      )
-      .await?;
-
-    let result = search_result
-      .results
-      .iter()
-      .map(|result| {
-        let subscription: Result<SpotifyImportLookupSubscription> =
-          result.values.clone().try_into();
-        subscription
+    let rows = self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+          "
+          SELECT album_search_lookup_encoded_query, profile_id, album_search_lookup_query, factor
+          FROM spotify_import_lookup_subscriptions
+          WHERE {} = ?
+          ",
+          column
+        ))?;
+        let rows = stmt
+          .query_map([value], |row| {
+            Ok((
+              row.get::<_, String>(0)?,
+              row.get::<_, String>(1)?,
+              row.get::<_, String>(2)?,
+              row.get::<_, u32>(3)?,
+            ))
+          })?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, rusqlite::Error>(rows)
       })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find Spotify import lookup subscriptions"
+        );
+        anyhow!("Failed to find Spotify import lookup subscriptions")
+      })??;
+
+    let subscriptions = rows
+      .into_iter()
+      .map(to_subscription)
       .filter_map(|r| match r {
         Ok(subscription) => Some(subscription),
         Err(e) => {
@@ -110,72 +87,98 @@ impl SpotifyImportRepository {
       })
       .collect::<Vec<_>>();
 
-    Ok(result)
+    Ok(subscriptions)
   }
 
+  #[instrument(skip(self))]
   pub async fn find_subscriptions_by_query(
     &self,
     album_search_lookup_query: &AlbumSearchLookupQuery,
   ) -> Result<Vec<SpotifyImportLookupSubscription>> {
     self
       .find_subscriptions(
-        "album_search_lookup_encoded_query".to_string(),
+        "album_search_lookup_encoded_query",
         album_search_lookup_query.to_encoded_string(),
       )
       .await
   }
 
+  #[instrument(skip(self))]
   pub async fn find_subscriptions_by_profile_id(
     &self,
     profile_id: &ProfileId,
   ) -> Result<Vec<SpotifyImportLookupSubscription>> {
     self
-      .find_subscriptions("profile_id".to_string(), profile_id.to_string())
+      .find_subscriptions("profile_id", profile_id.to_string())
       .await
   }
 
+  #[instrument(skip(self))]
   pub async fn put_subscription(
     &self,
     album_search_lookup_query: &AlbumSearchLookupQuery,
     profile_id: &ProfileId,
     factor: u32,
   ) -> Result<()> {
-    let subscription = SpotifyImportLookupSubscription {
-      album_search_lookup_encoded_query: album_search_lookup_query.to_encoded_string(),
-      album_search_lookup_query: album_search_lookup_query.clone(),
-      profile_id: profile_id.clone(),
-      factor,
-    };
+    let encoded_query = album_search_lookup_query.to_encoded_string();
+    let query = serde_json::to_string(album_search_lookup_query)?;
+    let profile_id = profile_id.to_string();
     self
-      .redis_connection_pool
-      .get()
+      .sqlite_connection
+      .write()
       .await?
-      .json_set(
-        self.lookup_subscriptions_key(album_search_lookup_query.to_encoded_string(), profile_id),
-        "$",
-        serde_json::to_string(&subscription)?,
-        SetCondition::default(),
-      )
-      .await?;
-
-    Ok(())
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO spotify_import_lookup_subscriptions (album_search_lookup_encoded_query, profile_id, album_search_lookup_query, factor)
+          VALUES (?1, ?2, ?3, ?4)
+          ON CONFLICT (album_search_lookup_encoded_query, profile_id) DO UPDATE SET
+            album_search_lookup_query = ?3,
+            factor = ?4
+          ",
+          params![encoded_query, profile_id, query, factor],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to put Spotify import lookup subscription"
+        );
+        anyhow!("Failed to put Spotify import lookup subscription")
+      })?
   }
 
+  #[instrument(skip(self))]
   pub async fn delete_subscription(
     &self,
     profile_id: &ProfileId,
     album_search_lookup_query: &AlbumSearchLookupQuery,
   ) -> Result<()> {
+    let encoded_query = album_search_lookup_query.to_encoded_string();
+    let profile_id = profile_id.to_string();
     self
-      .redis_connection_pool
-      .get()
+      .sqlite_connection
+      .write()
       .await?
-      .json_del(
-        self.lookup_subscriptions_key(album_search_lookup_query.to_encoded_string(), profile_id),
-        "$",
-      )
-      .await?;
-
-    Ok(())
+      .interact(move |conn| {
+        conn.execute(
+          "
+          DELETE FROM spotify_import_lookup_subscriptions
+          WHERE album_search_lookup_encoded_query = ?1 AND profile_id = ?2
+          ",
+          params![encoded_query, profile_id],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to delete Spotify import lookup subscription"
+        );
+        anyhow!("Failed to delete Spotify import lookup subscription")
+      })?
   }
 }
@@ -1,9 +1,12 @@
 tonic::include_proto!("lute");
 
 pub use album_service_server::{AlbumService, AlbumServiceServer};
+pub use artist_service_server::{ArtistService, ArtistServiceServer};
+pub use chart_service_server::{ChartService, ChartServiceServer};
 pub use crawler_service_server::{CrawlerService, CrawlerServiceServer};
 pub use event_service_server::{EventService, EventServiceServer};
 pub use file_service_server::{FileService, FileServiceServer};
+pub use genre_service_server::{GenreService, GenreServiceServer};
 pub use lookup_service_server::{LookupService, LookupServiceServer};
 pub use lute_server::{Lute, LuteServer};
 pub use operations_service_server::{OperationsService, OperationsServiceServer};
@@ -11,4 +14,5 @@ pub use parser_service_server::{ParserService, ParserServiceServer};
 pub use profile_service_server::{ProfileService, ProfileServiceServer};
 pub use recommendation_service_server::{RecommendationService, RecommendationServiceServer};
 pub use spotify_service_server::{SpotifyService, SpotifyServiceServer};
+pub use webhook_service_server::{WebhookService, WebhookServiceServer};
 pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("lute_descriptor");
@@ -131,6 +131,16 @@ impl TryFrom<proto::AlbumRecommendationSettings> for AlbumRecommendationSettings
       min_release_year: value.min_release_year,
       max_release_year: value.max_release_year,
       exclude_known_artists: value.exclude_known_artists,
+      min_rating: value.min_rating,
+      max_rating: value.max_rating,
+      min_rating_count: value.min_rating_count,
+      include_credited_artists: value.include_credited_artists,
+      include_credit_roles: value.include_credit_roles,
+      min_track_count: value.min_track_count,
+      max_track_count: value.max_track_count,
+      min_duration_seconds: value.min_duration_seconds,
+      max_duration_seconds: value.max_duration_seconds,
+      exclude_descriptors: value.exclude_descriptors,
     })
   }
 }
@@ -21,6 +21,16 @@ pub struct AlbumRecommendationSettings {
   pub min_release_year: Option<u32>,
   pub max_release_year: Option<u32>,
   pub exclude_known_artists: Option<bool>,
+  pub min_rating: Option<f32>,
+  pub max_rating: Option<f32>,
+  pub min_rating_count: Option<u32>,
+  pub include_credited_artists: Vec<String>,
+  pub include_credit_roles: Vec<String>,
+  pub min_track_count: Option<u32>,
+  pub max_track_count: Option<u32>,
+  pub min_duration_seconds: Option<u32>,
+  pub max_duration_seconds: Option<u32>,
+  pub exclude_descriptors: Vec<String>,
 }
 
 impl Default for AlbumRecommendationSettings {
@@ -36,6 +46,16 @@ impl Default for AlbumRecommendationSettings {
       min_release_year: None,
       max_release_year: None,
       exclude_known_artists: Some(true),
+      min_rating: None,
+      max_rating: None,
+      min_rating_count: None,
+      include_credited_artists: vec![],
+      include_credit_roles: vec![],
+      min_track_count: None,
+      max_track_count: None,
+      min_duration_seconds: None,
+      max_duration_seconds: None,
+      exclude_descriptors: vec![],
     }
   }
 }
@@ -56,6 +76,16 @@ impl AlbumRecommendationSettings {
       .exclude_languages(self.exclude_languages.clone())
       .min_release_year(self.min_release_year.clone())
       .max_release_year(self.max_release_year.clone())
+      .min_rating(self.min_rating)
+      .max_rating(self.max_rating)
+      .min_rating_count(self.min_rating_count)
+      .include_credited_artists(self.include_credited_artists.clone())
+      .include_credit_roles(self.include_credit_roles.clone())
+      .min_track_count(self.min_track_count)
+      .max_track_count(self.max_track_count)
+      .min_duration_seconds(self.min_duration_seconds)
+      .max_duration_seconds(self.max_duration_seconds)
+      .exclude_descriptors(self.exclude_descriptors.clone())
       .min_primary_genre_count(1)
       .min_secondary_genre_count(1)
       .min_descriptor_count(5);
@@ -1,16 +1,25 @@
 use crate::{
-  albums::redis_album_search_index::RedisAlbumSearchIndex,
-  lookup::album_search_lookup_repository::AlbumSearchLookupRepository,
-  parser::failed_parse_files_repository::FailedParseFilesRepository,
-  profile::spotify_import_repository::SpotifyImportRepository, settings::RedisSettings,
+  lookup::{
+    album_search_lookup::AlbumSearchLookup,
+    album_search_lookup_repository::AlbumSearchLookupRepository,
+  },
+  parser::failed_parse_files_repository::{FailedParseFile, FailedParseFilesRepository},
+  profile::{
+    profile::Profile, profile_repository::ProfileRepository,
+    spotify_import_lookup_subscription::SpotifyImportLookupSubscription,
+    spotify_import_repository::SpotifyImportRepository,
+  },
+  settings::RedisSettings,
+  sqlite::SqliteConnection,
 };
 use anyhow::Result;
 use rustis::{
   bb8::{ErrorSink, Pool},
   client::PooledClientManager,
+  commands::{GenericCommands, HashCommands, JsonCommands, JsonGetOptions},
 };
-use std::{sync::Arc, time::Duration};
-use tracing::error;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tracing::{error, warn};
 
 #[derive(Debug)]
 struct RedisConnectionErrorSink;
@@ -39,30 +48,90 @@ pub async fn build_redis_connection_pool(
     .map_err(|e| e.into())
 }
 
-pub async fn setup_redis_indexes(
+/**
+ * Moves failed parse files, album search lookups, profiles and Spotify import subscriptions that
+ * were stored in Redis before they moved to SQLite. Each key is deleted once it is copied, so
+ * this is a no-op after the first run, and JSON commands are only sent when JSON keys exist.
+ */
+pub async fn import_redis_stack_data(
   redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
 ) -> Result<()> {
-  FailedParseFilesRepository {
-    redis_connection_pool: Arc::clone(&redis_connection_pool),
+  let connection = redis_connection_pool.get().await?;
+
+  let failed_parse_files_repository =
+    FailedParseFilesRepository::new(Arc::clone(&sqlite_connection));
+  let keys: Vec<String> = connection.keys("failed_parse_files:*").await?;
+  for key in keys {
+    let values: HashMap<String, String> = connection.hgetall(&key).await?;
+    if !values.is_empty() {
+      failed_parse_files_repository
+        .put(FailedParseFile::from(values))
+        .await?;
+    }
+    connection.del(key).await?;
   }
-  .setup_index()
-  .await?;
 
-  AlbumSearchLookupRepository {
-    redis_connection_pool: Arc::clone(&redis_connection_pool),
+  let album_search_lookup_repository =
+    AlbumSearchLookupRepository::new(Arc::clone(&sqlite_connection));
+  let keys: Vec<String> = connection.keys("lookup:album_search:*").await?;
+  for key in keys {
+    let values: HashMap<String, String> = connection.hgetall(&key).await?;
+    match AlbumSearchLookup::try_from(values) {
+      Ok(lookup) => album_search_lookup_repository.put(&lookup).await?,
+      Err(e) => warn!(
+        key,
+        "Skipping album search lookup that failed to deserialize: {}", e
+      ),
+    }
+    connection.del(key).await?;
   }
-  .setup_index()
-  .await?;
 
-  SpotifyImportRepository {
-    redis_connection_pool: Arc::clone(&redis_connection_pool),
+  let profile_repository = ProfileRepository::new(Arc::clone(&sqlite_connection));
+  let keys: Vec<String> = connection.keys("profile:*").await?;
+  for key in keys {
+    let json: Option<String> = connection.json_get(&key, JsonGetOptions::default()).await?;
+    match json.map(|json| serde_json::from_str::<Profile>(&json)) {
+      Some(Ok(profile)) => {
+        if !profile_repository.exists(&profile.id).await? {
+          profile_repository
+            .insert(profile.id.clone(), profile.name.clone())
+            .await?;
+        }
+        for (file_name, factor) in profile.albums {
+          profile_repository
+            .put_album_on_profile(&profile.id, &file_name, factor)
+            .await?;
+        }
+      }
+      Some(Err(e)) => warn!(key, "Skipping profile that failed to deserialize: {}", e),
+      None => {}
+    }
+    connection.del(key).await?;
   }
-  .setup_index()
-  .await?;
 
-  RedisAlbumSearchIndex::new(Arc::clone(&redis_connection_pool))
-    .setup_index()
-    .await?;
+  let spotify_import_repository = SpotifyImportRepository::new(Arc::clone(&sqlite_connection));
+  let keys: Vec<String> = connection.keys("profile_spotify_import:*").await?;
+  for key in keys {
+    let json: Option<String> = connection.json_get(&key, JsonGetOptions::default()).await?;
+    match json.map(|json| serde_json::from_str::<SpotifyImportLookupSubscription>(&json)) {
+      Some(Ok(subscription)) => {
+        spotify_import_repository
+          .put_subscription(
+            &subscription.album_search_lookup_query,
+            &subscription.profile_id,
+            subscription.factor,
+          )
+          .await?
+      }
+      Some(Err(e)) => warn!(
+        key,
+        "Skipping Spotify import subscription that failed to deserialize: {}", e
+      ),
+      None => {}
+    }
+    connection.del(key).await?;
+  }
 
   Ok(())
 }
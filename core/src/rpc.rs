@@ -3,30 +3,35 @@ use crate::{
     album_repository::AlbumRepository, album_search_index::AlbumSearchIndex,
     album_service::AlbumService,
   },
+  artists::artist_service::ArtistService,
+  charts::chart_service::ChartService,
   crawler::{crawler_interactor::CrawlerInteractor, crawler_service::CrawlerService},
   events::event_service::EventService,
   files::{
     file_interactor::FileInteractor, file_metadata::file_name::FileName, file_service::FileService,
   },
+  genres::genre_service::GenreService,
   helpers::fifo_queue::FifoQueue,
   lookup::lookup_service::LookupService,
   ops::OperationsService,
   parser::parser_service::ParserService,
   profile::profile_service::ProfileService,
   proto::{
-    AlbumServiceServer, CrawlerServiceServer, EventServiceServer, FileServiceServer,
-    HealthCheckReply, LookupServiceServer, Lute, LuteServer, OperationsServiceServer,
-    ParserServiceServer, ProfileServiceServer, RecommendationServiceServer, SpotifyServiceServer,
+    AlbumServiceServer, ArtistServiceServer, ChartServiceServer, CrawlerServiceServer,
+    EventServiceServer, FileServiceServer, GenreServiceServer, HealthCheckReply,
+    LookupServiceServer, Lute, LuteServer, OperationsServiceServer, ParserServiceServer,
+    ProfileServiceServer, RecommendationServiceServer, SpotifyServiceServer, WebhookServiceServer,
     FILE_DESCRIPTOR_SET,
   },
   recommendations::recommendation_service::RecommendationService,
   settings::Settings,
   spotify::{spotify_client::SpotifyClient, spotify_service::SpotifyService},
   sqlite::SqliteConnection,
+  webhooks::webhook_service::WebhookService,
 };
 use anyhow::Result;
 use rustis::{bb8::Pool, client::PooledClientManager};
-use std::{net::SocketAddr, sync::Arc};
+use std::{collections::HashSet, net::SocketAddr, sync::Arc};
 use tonic::{transport::Server, Request, Response, Status};
 use tracing::info;
 
@@ -44,6 +49,9 @@ pub struct RpcServer {
   file_service: Arc<FileService>,
   crawler_service: Arc<CrawlerService>,
   album_service: Arc<AlbumService>,
+  artist_service: Arc<ArtistService>,
+  chart_service: Arc<ChartService>,
+  genre_service: Arc<GenreService>,
   spotify_service: Arc<SpotifyService>,
   operations_service: Arc<OperationsService>,
   parser_service: Arc<ParserService>,
@@ -51,9 +59,11 @@ pub struct RpcServer {
   lookup_service: Arc<LookupService>,
   recommendation_service: Arc<RecommendationService>,
   event_service: Arc<EventService>,
+  webhook_service: Arc<WebhookService>,
 }
 
 impl RpcServer {
+  #[allow(clippy::too_many_arguments)]
   pub fn new(
     settings: Arc<Settings>,
     redis_connection_pool: Arc<Pool<PooledClientManager>>,
@@ -62,6 +72,7 @@ impl RpcServer {
     parser_retry_queue: Arc<FifoQueue<FileName>>,
     album_repository: Arc<dyn AlbumRepository + Send + Sync + 'static>,
     album_search_index: Arc<dyn AlbumSearchIndex + Send + Sync + 'static>,
+    internal_subscriber_ids: HashSet<String>,
   ) -> Self {
     Self {
       settings: Arc::clone(&settings),
@@ -76,9 +87,17 @@ impl RpcServer {
         crawler_interactor: Arc::clone(&crawler_interactor),
       }),
       album_service: Arc::new(AlbumService::new(
+        Arc::clone(&settings),
+        Arc::clone(&sqlite_connection),
         Arc::clone(&album_repository),
         Arc::clone(&album_search_index),
       )),
+      artist_service: Arc::new(ArtistService::new(
+        Arc::clone(&sqlite_connection),
+        Arc::clone(&album_repository),
+      )),
+      chart_service: Arc::new(ChartService::new(Arc::clone(&sqlite_connection))),
+      genre_service: Arc::new(GenreService::new(Arc::clone(&sqlite_connection))),
       spotify_service: Arc::new(SpotifyService {
         spotify_client: SpotifyClient::new(&settings.spotify, Arc::clone(&redis_connection_pool)),
       }),
@@ -102,7 +121,6 @@ impl RpcServer {
       )),
       lookup_service: Arc::new(LookupService::new(
         Arc::clone(&settings),
-        Arc::clone(&redis_connection_pool),
         Arc::clone(&sqlite_connection),
       )),
       recommendation_service: Arc::new(RecommendationService::new(
@@ -112,7 +130,11 @@ impl RpcServer {
         Arc::clone(&album_repository),
         Arc::clone(&album_search_index),
       )),
-      event_service: Arc::new(EventService::new(Arc::clone(&sqlite_connection))),
+      event_service: Arc::new(EventService::new(
+        Arc::clone(&sqlite_connection),
+        internal_subscriber_ids,
+      )),
+      webhook_service: Arc::new(WebhookService::new(Arc::clone(&sqlite_connection))),
     }
   }
 
@@ -143,6 +165,15 @@ impl RpcServer {
       .add_service(tonic_web::enable(AlbumServiceServer::from_arc(Arc::clone(
         &self.album_service,
       ))))
+      .add_service(tonic_web::enable(ArtistServiceServer::from_arc(
+        Arc::clone(&self.artist_service),
+      )))
+      .add_service(tonic_web::enable(ChartServiceServer::from_arc(Arc::clone(
+        &self.chart_service,
+      ))))
+      .add_service(tonic_web::enable(GenreServiceServer::from_arc(Arc::clone(
+        &self.genre_service,
+      ))))
       .add_service(tonic_web::enable(SpotifyServiceServer::from_arc(
         Arc::clone(&self.spotify_service),
       )))
@@ -164,6 +195,9 @@ impl RpcServer {
       .add_service(tonic_web::enable(EventServiceServer::from_arc(Arc::clone(
         &self.event_service,
       ))))
+      .add_service(tonic_web::enable(WebhookServiceServer::from_arc(
+        Arc::clone(&self.webhook_service),
+      )))
       .serve(addr)
       .await?;
 
@@ -43,6 +43,18 @@ pub struct CrawlerRateLimitSettings {
   pub max_requests: u32,
 }
 
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct CrawlerChartSettings {
+  /**
+   * Whether to enqueue the following pages of a chart once a chart page is parsed.
+   */
+  pub follow_pages: bool,
+  /**
+   * The last chart page to enqueue when following chart pages.
+   */
+  pub max_page: u32,
+}
+
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
 pub struct CrawlerProxySettings {
   pub host: String,
@@ -59,6 +71,7 @@ pub struct CrawlerSettings {
   pub max_queue_size: u32,
   pub wait_time_seconds: u32,
   pub rate_limit: CrawlerRateLimitSettings,
+  pub chart: CrawlerChartSettings,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
@@ -81,6 +94,18 @@ pub struct SpotifySettings {
 pub struct ParserSettings {
   pub concurrency: u16,
   pub retry_concurrency: u16,
+  pub reparse_batch_size: u16,
+  pub reparse_interval_seconds: u32,
+  pub degradation: ParserDegradationSettings,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct ParserDegradationSettings {
+  pub window_seconds: u32,
+  pub baseline_seconds: u32,
+  pub min_samples: u32,
+  pub failure_rate_increase_percent: u32,
+  pub check_interval_seconds: u32,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
@@ -88,6 +113,94 @@ pub struct OpenAISettings {
   pub api_key: String,
 }
 
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct LocalEmbeddingSettings {
+  /**
+   * Directory of a BERT sentence-embedding model, with config.json, tokenizer.json and
+   * model.safetensors (e.g. all-MiniLM-L6-v2).
+   */
+  pub model_dir: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct TagEmbeddingSettings {
+  pub enabled: bool,
+  pub dimensions: usize,
+  /**
+   * Number of most frequent tags that get a vector.
+   */
+  pub max_tags: usize,
+  /**
+   * Minimum number of albums a tag must appear on to get a vector.
+   */
+  pub min_tag_count: u32,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct WebhookSettings {
+  pub max_retries: u32,
+  pub timeout_seconds: u32,
+  /**
+   * Delay before a failed delivery is retried again, doubled after every further failure.
+   */
+  pub retry_interval_seconds: u32,
+}
+
+/**
+ * Where albums are searched. Everything else that used Redis Stack is stored in SQLite, so with
+ * the SQLite backend a plain Redis server is enough.
+ */
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlbumSearchBackend {
+  /**
+   * RediSearch, which requires the Redis JSON and search modules.
+   */
+  #[default]
+  Redis,
+  /**
+   * SQLite full text search, stored next to the album read models.
+   */
+  Sqlite,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct AlbumSearchSettings {
+  pub backend: AlbumSearchBackend,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlbumNameNormalization {
+  /**
+   * Transliterated to ASCII and compared case-insensitively.
+   */
+  #[default]
+  Ascii,
+  /**
+   * As ASCII, ignoring punctuation and whitespace.
+   */
+  Alphanumeric,
+  /**
+   * As alphanumeric, ignoring bracketed edition suffixes such as "(Deluxe Edition)".
+   */
+  IgnoreEditions,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct AlbumDuplicateSettings {
+  pub name_normalization: AlbumNameNormalization,
+  /**
+   * Share of the artists of both albums that must be shared, in percent. Any shared artist
+   * matches at 0.
+   */
+  pub min_artist_overlap_percent: u32,
+  /**
+   * Maximum number of days between the release dates of duplicates, when both are known.
+   */
+  pub release_date_window_days: Option<u32>,
+}
+
 #[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
 pub struct Settings {
   pub crawler: CrawlerSettings,
@@ -99,6 +212,11 @@ pub struct Settings {
   pub tracing: TracingSettings,
   pub parser: ParserSettings,
   pub openai: Option<OpenAISettings>,
+  pub local_embedding: Option<LocalEmbeddingSettings>,
+  pub tag_embedding: TagEmbeddingSettings,
+  pub webhook: WebhookSettings,
+  pub album_search: AlbumSearchSettings,
+  pub album_duplicates: AlbumDuplicateSettings,
 }
 
 impl Settings {
@@ -124,8 +242,34 @@ impl Settings {
         Duration::days(1).num_seconds(),
       )?
       .set_default("crawler.rate_limit.max_requests", 2000)?
+      .set_default("crawler.chart.follow_pages", false)?
+      .set_default("crawler.chart.max_page", 5)?
       .set_default("parser.concurrency", 20)?
       .set_default("parser.retry_concurrency", 20)?
+      .set_default("parser.reparse_batch_size", 20)?
+      .set_default("parser.reparse_interval_seconds", 60)?
+      .set_default(
+        "parser.degradation.window_seconds",
+        Duration::hours(1).num_seconds(),
+      )?
+      .set_default(
+        "parser.degradation.baseline_seconds",
+        Duration::days(7).num_seconds(),
+      )?
+      .set_default("parser.degradation.min_samples", 20)?
+      .set_default("parser.degradation.failure_rate_increase_percent", 30)?
+      .set_default("parser.degradation.check_interval_seconds", 60)?
+      .set_default("webhook.max_retries", 5)?
+      .set_default("webhook.timeout_seconds", 10)?
+      .set_default("webhook.retry_interval_seconds", 60)?
+      .set_default("album_search.backend", "redis")?
+      .set_default("tag_embedding.enabled", false)?
+      .set_default("tag_embedding.dimensions", 128)?
+      .set_default("tag_embedding.max_tags", 5000)?
+      .set_default("tag_embedding.min_tag_count", 5)?
+      .set_default("album_duplicates.name_normalization", "ascii")?
+      .set_default("album_duplicates.min_artist_overlap_percent", 0)?
+      .set_default("album_duplicates.release_date_window_days", None::<u32>)?
       .set_default("tracing.service_name", "core")?
       .set_default("tracing.service_namespace", "lute")?
       .set_default("tracing.resource_labels", HashMap::<String, String>::new())?
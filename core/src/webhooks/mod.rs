@@ -0,0 +1,4 @@
+pub mod webhook;
+pub mod webhook_event_subscribers;
+pub mod webhook_repository;
+pub mod webhook_service;
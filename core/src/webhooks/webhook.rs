@@ -0,0 +1,51 @@
+use crate::events::event::EventKind;
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Clone)]
+pub struct Webhook {
+  pub id: String,
+  pub url: String,
+  /**
+   * Key used to sign requests. The `X-Lute-Signature` header is the HMAC-SHA256 of the
+   * `X-Lute-Timestamp` header and the body, joined with a dot.
+   */
+  pub secret: Option<String>,
+  /**
+   * Event kinds delivered to this webhook. Empty means all events.
+   */
+  pub event_kinds: Vec<EventKind>,
+  pub created_at: NaiveDateTime,
+  pub last_delivered_at: Option<NaiveDateTime>,
+  pub last_error: Option<String>,
+}
+
+impl Webhook {
+  pub fn subscriber_id(&self) -> String {
+    webhook_subscriber_id(&self.id)
+  }
+}
+
+/**
+ * Prefix of the ids of webhook event subscribers, which are reserved for webhooks.
+ */
+pub const WEBHOOK_SUBSCRIBER_ID_PREFIX: &str = "webhook:";
+
+pub fn webhook_subscriber_id(webhook_id: &str) -> String {
+  format!("{}{}", WEBHOOK_SUBSCRIBER_ID_PREFIX, webhook_id)
+}
+
+/**
+ * A delivery that failed every immediate retry, kept to be retried later.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedWebhookDelivery {
+  pub webhook_id: String,
+  pub entry_id: String,
+  pub event_kind: String,
+  /**
+   * The request body, as signed and sent on the first attempt.
+   */
+  pub body: String,
+  pub attempts: u32,
+  pub last_error: String,
+}
@@ -0,0 +1,387 @@
+use super::{
+  webhook::{webhook_subscriber_id, FailedWebhookDelivery, Webhook},
+  webhook_repository::WebhookRepository,
+};
+use crate::{
+  events::{
+    event::{Event, EventPayload, Stream},
+    event_filter::EventFilter,
+    event_subscriber::{EventSubscriber, EventSubscriberBuilder, SubscriberContext},
+    event_subscriber_repository::EventSubscriberRepository,
+  },
+  helpers::hmac::hmac_sha256,
+  settings::Settings,
+  sqlite::SqliteConnection,
+};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use data_encoding::HEXLOWER;
+use rustis::{bb8::Pool, client::PooledClientManager};
+use serde_derive::Serialize;
+use serde_json::Value;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{spawn, task::JoinHandle, time::sleep};
+use tokio_retry::{
+  strategy::{jitter, ExponentialBackoff},
+  Retry,
+};
+use tracing::{error, info, warn};
+use ulid::Ulid;
+
+/**
+ * Upper bound of the delay between retries of a failed delivery.
+ */
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(24 * 60 * 60);
+const RETRY_BATCH_SIZE: usize = 100;
+
+#[derive(Serialize)]
+struct WebhookRequestBody<'a> {
+  entry_id: &'a str,
+  stream: String,
+  payload: Value,
+}
+
+/**
+ * The payload as sent to webhooks. File ids are ULID strings, as in the RPC API, rather than
+ * the 128 bit numbers events are stored with, which most JSON parsers can't represent.
+ */
+fn webhook_payload(payload: &EventPayload) -> Result<Value> {
+  let mut payload = payload.clone();
+  let file_id = match &mut payload.event {
+    Event::FileSaved { file_id, .. }
+    | Event::FileDeleted { file_id, .. }
+    | Event::FileParsed { file_id, .. }
+    | Event::FileParseFailed { file_id, .. } => Some(std::mem::replace(file_id, Ulid::nil())),
+    _ => None,
+  };
+  let mut value = serde_json::to_value(&payload)?;
+  if let Some(file_id) = file_id {
+    value["event"]["data"]["file_id"] = Value::String(file_id.to_string());
+  }
+  Ok(value)
+}
+
+async fn send(
+  client: &reqwest::Client,
+  webhook: &Webhook,
+  entry_id: &str,
+  event_kind: &str,
+  body: &str,
+) -> Result<()> {
+  let timestamp = Utc::now().timestamp().to_string();
+  let mut request = client
+    .post(&webhook.url)
+    .header("Content-Type", "application/json")
+    .header("X-Lute-Webhook-Id", &webhook.id)
+    .header("X-Lute-Event-Id", entry_id)
+    .header("X-Lute-Event-Kind", event_kind)
+    .header("X-Lute-Timestamp", &timestamp);
+  if let Some(secret) = &webhook.secret {
+    // The timestamp is signed along with the body, so that receivers can reject captured
+    // deliveries that are replayed later.
+    let signature = hmac_sha256(
+      secret.as_bytes(),
+      format!("{}.{}", timestamp, body).as_bytes(),
+    );
+    request = request.header(
+      "X-Lute-Signature",
+      format!("sha256={}", HEXLOWER.encode(&signature)),
+    );
+  }
+  let response = request.body(body.to_string()).send().await?;
+  if !response.status().is_success() {
+    return Err(anyhow!(
+      "Webhook responded with status {}",
+      response.status()
+    ));
+  }
+  Ok(())
+}
+
+/**
+ * Delay before the next retry of a delivery that failed the given number of times.
+ */
+fn retry_delay(settings: &Settings, attempts: u32) -> Duration {
+  let interval = Duration::from_secs(settings.webhook.retry_interval_seconds as u64);
+  interval
+    .saturating_mul(1 << attempts.saturating_sub(1).min(16))
+    .min(MAX_RETRY_DELAY)
+}
+
+async fn deliver_webhook(
+  client: reqwest::Client,
+  webhook: Arc<Webhook>,
+  context: SubscriberContext,
+) -> Result<()> {
+  let filter = EventFilter {
+    event_kinds: webhook.event_kinds.clone(),
+    ..Default::default()
+  };
+  if !filter.matches(&context.payload) {
+    return Ok(());
+  }
+
+  let event_kind = context.payload.event.kind().to_string();
+  let body = serde_json::to_string(&WebhookRequestBody {
+    entry_id: &context.entry_id,
+    stream: context.stream.tag(),
+    payload: webhook_payload(&context.payload)?,
+  })?;
+  let retry_strategy = ExponentialBackoff::from_millis(2)
+    .factor(250)
+    .max_delay(Duration::from_secs(60))
+    .map(jitter)
+    .take(context.settings.webhook.max_retries as usize);
+  let result = Retry::spawn(retry_strategy, || async {
+    let result = send(&client, &webhook, &context.entry_id, &event_kind, &body).await;
+    if let Err(err) = &result {
+      warn!(
+        webhook_id = webhook.id.as_str(),
+        entry_id = context.entry_id.as_str(),
+        error = err.to_string(),
+        "Webhook delivery attempt failed"
+      );
+    }
+    result
+  })
+  .await;
+
+  let webhook_repository = WebhookRepository::new(Arc::clone(&context.sqlite_connection));
+  if let Err(err) = &result {
+    // Kept for later instead of failing the event, so that the cursor moves past it.
+    webhook_repository
+      .put_failed_delivery(
+        &FailedWebhookDelivery {
+          webhook_id: webhook.id.clone(),
+          entry_id: context.entry_id.clone(),
+          event_kind,
+          body,
+          attempts: 1,
+          last_error: err.to_string(),
+        },
+        retry_delay(&context.settings, 1),
+      )
+      .await?;
+  }
+  webhook_repository
+    .set_delivery_result(
+      &webhook.id,
+      result.as_ref().err().map(|err| err.to_string()),
+    )
+    .await
+}
+
+/**
+ * Retries the failed deliveries that are due, once each. Deliveries that fail again are
+ * rescheduled with a longer delay. Events delivered this way arrive out of order.
+ */
+pub async fn retry_failed_webhook_deliveries(
+  client: &reqwest::Client,
+  webhook_repository: &WebhookRepository,
+  webhooks: &[Webhook],
+  settings: &Settings,
+) -> Result<()> {
+  for delivery in webhook_repository
+    .find_due_failed_deliveries(RETRY_BATCH_SIZE)
+    .await?
+  {
+    // Deliveries of deleted webhooks are deleted with them.
+    let webhook = match webhooks
+      .iter()
+      .find(|webhook| webhook.id == delivery.webhook_id)
+    {
+      Some(webhook) => webhook,
+      None => continue,
+    };
+    match send(
+      client,
+      webhook,
+      &delivery.entry_id,
+      &delivery.event_kind,
+      &delivery.body,
+    )
+    .await
+    {
+      Ok(()) => {
+        info!(
+          webhook_id = webhook.id.as_str(),
+          entry_id = delivery.entry_id.as_str(),
+          attempts = delivery.attempts + 1,
+          "Delivered failed webhook delivery"
+        );
+        webhook_repository
+          .delete_failed_delivery(&delivery.webhook_id, &delivery.entry_id)
+          .await?;
+        webhook_repository
+          .set_delivery_result(&webhook.id, None)
+          .await?;
+      }
+      Err(err) => {
+        let attempts = delivery.attempts + 1;
+        warn!(
+          webhook_id = webhook.id.as_str(),
+          entry_id = delivery.entry_id.as_str(),
+          attempts,
+          error = err.to_string(),
+          "Webhook delivery retry failed"
+        );
+        webhook_repository
+          .put_failed_delivery(
+            &FailedWebhookDelivery {
+              attempts,
+              last_error: err.to_string(),
+              ..delivery
+            },
+            retry_delay(settings, attempts),
+          )
+          .await?;
+        webhook_repository
+          .set_delivery_result(&webhook.id, Some(err.to_string()))
+          .await?;
+      }
+    }
+  }
+  Ok(())
+}
+
+fn build_webhook_client(settings: &Settings) -> Result<reqwest::Client> {
+  Ok(
+    reqwest::ClientBuilder::new()
+      .timeout(Duration::from_secs(settings.webhook.timeout_seconds as u64))
+      .build()?,
+  )
+}
+
+/**
+ * Delivers every event to the webhook in order, in batches.
+ */
+pub fn build_webhook_event_subscriber(
+  webhook: &Webhook,
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
+  settings: Arc<Settings>,
+) -> Result<EventSubscriber> {
+  let client = build_webhook_client(&settings)?;
+  let webhook = Arc::new(webhook.clone());
+  let group_id = webhook.id.clone();
+  Ok(
+    EventSubscriberBuilder::default()
+      .id(webhook.subscriber_id())
+      .stream(Stream::Global)
+      .batch_size(100)
+      .redis_connection_pool(redis_connection_pool)
+      .sqlite_connection(sqlite_connection)
+      .settings(settings)
+      .generate_ordered_processing_group_id(Arc::new(move |_| Some(group_id.clone())))
+      .retry_failed_batches(true)
+      .handle(Arc::new(move |context| {
+        let client = client.clone();
+        let webhook = Arc::clone(&webhook);
+        Box::pin(async move { deliver_webhook(client, webhook, context).await })
+      }))
+      .build()?,
+  )
+}
+
+/**
+ * Keeps one running event subscriber per configured webhook, picking up webhooks created or
+ * deleted over RPC.
+ */
+pub fn start_webhook_event_subscribers(
+  redis_connection_pool: Arc<Pool<PooledClientManager>>,
+  sqlite_connection: Arc<SqliteConnection>,
+  settings: Arc<Settings>,
+) -> Result<()> {
+  let webhook_repository = WebhookRepository::new(Arc::clone(&sqlite_connection));
+  let event_subscriber_repository = EventSubscriberRepository::new(Arc::clone(&sqlite_connection));
+  let client = build_webhook_client(&settings)?;
+
+  spawn(async move {
+    let mut running: HashMap<String, JoinHandle<()>> = HashMap::new();
+    loop {
+      match webhook_repository.find_all().await {
+        Ok(webhooks) => {
+          for webhook in &webhooks {
+            match running.get(&webhook.id) {
+              Some(handle) if !handle.is_finished() => continue,
+              // The subscriber stopped on an error or panicked, so it is started again
+              Some(_) => warn!(
+                webhook_id = webhook.id.as_str(),
+                "Restarting stopped webhook subscriber"
+              ),
+              None => {}
+            }
+            match build_webhook_event_subscriber(
+              webhook,
+              Arc::clone(&redis_connection_pool),
+              Arc::clone(&sqlite_connection),
+              Arc::clone(&settings),
+            ) {
+              Ok(subscriber) => {
+                info!(
+                  webhook_id = webhook.id.as_str(),
+                  "Starting webhook subscriber"
+                );
+                running.insert(
+                  webhook.id.clone(),
+                  spawn(async move {
+                    if let Err(err) = subscriber.run().await {
+                      error!(error = err.to_string(), "Webhook subscriber stopped");
+                    }
+                  }),
+                );
+              }
+              Err(err) => {
+                error!(
+                  webhook_id = webhook.id.as_str(),
+                  error = err.to_string(),
+                  "Failed to build webhook subscriber"
+                );
+              }
+            }
+          }
+          let removed_webhook_ids = running
+            .keys()
+            .filter(|webhook_id| !webhooks.iter().any(|webhook| &webhook.id == *webhook_id))
+            .cloned()
+            .collect::<Vec<_>>();
+          for webhook_id in removed_webhook_ids {
+            let subscriber_id = webhook_subscriber_id(&webhook_id);
+            info!(
+              subscriber_id = subscriber_id.as_str(),
+              "Stopping webhook subscriber"
+            );
+            if let Some(handle) = running.remove(&webhook_id) {
+              handle.abort();
+            }
+            // The subscriber may have written its cursor after the webhook was deleted
+            if let Err(err) = event_subscriber_repository
+              .delete_cursor(&subscriber_id)
+              .await
+            {
+              error!(
+                error = err.to_string(),
+                "Failed to delete webhook subscriber cursor"
+              );
+            }
+          }
+          if let Err(err) =
+            retry_failed_webhook_deliveries(&client, &webhook_repository, &webhooks, &settings)
+              .await
+          {
+            error!(
+              error = err.to_string(),
+              "Failed to retry failed webhook deliveries"
+            );
+          }
+        }
+        Err(err) => {
+          error!(error = err.to_string(), "Failed to load webhooks");
+        }
+      }
+      sleep(Duration::from_secs(5)).await;
+    }
+  });
+
+  Ok(())
+}
@@ -0,0 +1,335 @@
+use super::webhook::{FailedWebhookDelivery, Webhook};
+use crate::{events::event::EventKind, sqlite::SqliteConnection};
+use anyhow::{anyhow, Result};
+use chrono::NaiveDateTime;
+use rusqlite::params;
+use std::{sync::Arc, time::Duration};
+use tracing::{error, instrument};
+
+#[derive(Debug, Clone)]
+pub struct WebhookRepository {
+  sqlite_connection: Arc<SqliteConnection>,
+}
+
+fn map_webhook_row(row: &rusqlite::Row<'_>) -> Result<Webhook, rusqlite::Error> {
+  let event_kinds = serde_json::from_str::<Vec<String>>(&row.get::<_, String>(3)?)
+    .map_err(|e| {
+      rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
+    })?
+    .into_iter()
+    .map(|kind| kind.parse::<EventKind>())
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| {
+      error!(
+        message = e.to_string(),
+        "Failed to parse webhook event kinds"
+      );
+      rusqlite::Error::ExecuteReturnedResults
+    })?;
+  Ok(Webhook {
+    id: row.get::<_, String>(0)?,
+    url: row.get::<_, String>(1)?,
+    secret: row.get::<_, Option<String>>(2)?,
+    event_kinds,
+    created_at: row.get::<_, NaiveDateTime>(4)?,
+    last_delivered_at: row.get::<_, Option<NaiveDateTime>>(5)?,
+    last_error: row.get::<_, Option<String>>(6)?,
+  })
+}
+
+fn map_failed_delivery_row(
+  row: &rusqlite::Row<'_>,
+) -> Result<FailedWebhookDelivery, rusqlite::Error> {
+  Ok(FailedWebhookDelivery {
+    webhook_id: row.get::<_, String>(0)?,
+    entry_id: row.get::<_, String>(1)?,
+    event_kind: row.get::<_, String>(2)?,
+    body: row.get::<_, String>(3)?,
+    attempts: row.get::<_, u32>(4)?,
+    last_error: row.get::<_, String>(5)?,
+  })
+}
+
+impl WebhookRepository {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self { sqlite_connection }
+  }
+
+  #[instrument(skip(self, secret))]
+  pub async fn create(
+    &self,
+    id: &str,
+    url: &str,
+    secret: Option<String>,
+    event_kinds: &[EventKind],
+  ) -> Result<()> {
+    let id = id.to_string();
+    let url = url.to_string();
+    let event_kinds = serde_json::to_string(
+      &event_kinds
+        .iter()
+        .map(|kind| kind.to_string())
+        .collect::<Vec<_>>(),
+    )?;
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "INSERT INTO webhooks (id, url, secret, event_kinds) VALUES (?1, ?2, ?3, ?4)",
+          params![id, url, secret, event_kinds],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to create webhook");
+        anyhow!("Failed to create webhook")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn find(&self, id: &str) -> Result<Option<Webhook>> {
+    let id = id.to_string();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT id, url, secret, event_kinds, created_at, last_delivered_at, last_error
+          FROM webhooks
+          WHERE id = ?
+          ",
+        )?;
+        let mut rows = statement.query_map([id], map_webhook_row)?;
+        Ok(rows.next().transpose()?)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find webhook");
+        anyhow!("Failed to find webhook")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn find_all(&self) -> Result<Vec<Webhook>> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(|conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT id, url, secret, event_kinds, created_at, last_delivered_at, last_error
+          FROM webhooks
+          ORDER BY created_at ASC
+          ",
+        )?;
+        let rows = statement
+          .query_map([], map_webhook_row)?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to find webhooks");
+        anyhow!("Failed to find webhooks")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete(&self, id: &str) -> Result<bool> {
+    let id = id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        let deleted = conn.execute("DELETE FROM webhooks WHERE id = ?", [id])?;
+        Ok(deleted > 0)
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to delete webhook");
+        anyhow!("Failed to delete webhook")
+      })?
+  }
+
+  /**
+   * Records the outcome of the latest delivery attempt.
+   */
+  #[instrument(skip(self))]
+  pub async fn set_delivery_result(&self, id: &str, error: Option<String>) -> Result<()> {
+    let id = id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        match error {
+          Some(error) => conn.execute(
+            "UPDATE webhooks SET last_error = ?1 WHERE id = ?2",
+            params![error, id],
+          )?,
+          None => conn.execute(
+            "UPDATE webhooks SET last_delivered_at = CURRENT_TIMESTAMP, last_error = NULL WHERE id = ?",
+            [id],
+          )?,
+        };
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(message = e.to_string(), "Failed to set webhook delivery result");
+        anyhow!("Failed to set webhook delivery result")
+      })?
+  }
+
+  /**
+   * Stores a failed delivery, or updates it after another failed attempt, to be retried after the
+   * given delay.
+   */
+  #[instrument(skip(self, delivery))]
+  pub async fn put_failed_delivery(
+    &self,
+    delivery: &FailedWebhookDelivery,
+    retry_in: Duration,
+  ) -> Result<()> {
+    let delivery = delivery.clone();
+    let retry_in = format!("+{} seconds", retry_in.as_secs());
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "
+          INSERT INTO webhook_failed_deliveries
+            (webhook_id, entry_id, event_kind, body, attempts, last_error, next_attempt_at)
+          VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now', ?7))
+          ON CONFLICT (webhook_id, entry_id) DO UPDATE SET
+            attempts = excluded.attempts,
+            last_error = excluded.last_error,
+            next_attempt_at = excluded.next_attempt_at
+          ",
+          params![
+            delivery.webhook_id,
+            delivery.entry_id,
+            delivery.event_kind,
+            delivery.body,
+            delivery.attempts,
+            delivery.last_error,
+            retry_in
+          ],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to put failed webhook delivery"
+        );
+        anyhow!("Failed to put failed webhook delivery")
+      })?
+  }
+
+  /**
+   * Failed deliveries that are due to be retried, oldest first.
+   */
+  #[instrument(skip(self))]
+  pub async fn find_due_failed_deliveries(
+    &self,
+    limit: usize,
+  ) -> Result<Vec<FailedWebhookDelivery>> {
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT webhook_id, entry_id, event_kind, body, attempts, last_error
+          FROM webhook_failed_deliveries
+          WHERE next_attempt_at <= CURRENT_TIMESTAMP
+          ORDER BY next_attempt_at ASC, created_at ASC
+          LIMIT ?
+          ",
+        )?;
+        let rows = statement
+          .query_map([limit], map_failed_delivery_row)?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find failed webhook deliveries"
+        );
+        anyhow!("Failed to find failed webhook deliveries")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn find_failed_deliveries(
+    &self,
+    webhook_id: &str,
+  ) -> Result<Vec<FailedWebhookDelivery>> {
+    let webhook_id = webhook_id.to_string();
+    self
+      .sqlite_connection
+      .read()
+      .await?
+      .interact(move |conn| {
+        let mut statement = conn.prepare(
+          "
+          SELECT webhook_id, entry_id, event_kind, body, attempts, last_error
+          FROM webhook_failed_deliveries
+          WHERE webhook_id = ?
+          ORDER BY created_at ASC
+          ",
+        )?;
+        let rows = statement
+          .query_map([webhook_id], map_failed_delivery_row)?
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to find failed webhook deliveries"
+        );
+        anyhow!("Failed to find failed webhook deliveries")
+      })?
+  }
+
+  #[instrument(skip(self))]
+  pub async fn delete_failed_delivery(&self, webhook_id: &str, entry_id: &str) -> Result<()> {
+    let webhook_id = webhook_id.to_string();
+    let entry_id = entry_id.to_string();
+    self
+      .sqlite_connection
+      .write()
+      .await?
+      .interact(move |conn| {
+        conn.execute(
+          "DELETE FROM webhook_failed_deliveries WHERE webhook_id = ?1 AND entry_id = ?2",
+          params![webhook_id, entry_id],
+        )?;
+        Ok(())
+      })
+      .await
+      .map_err(|e| {
+        error!(
+          message = e.to_string(),
+          "Failed to delete failed webhook delivery"
+        );
+        anyhow!("Failed to delete failed webhook delivery")
+      })?
+  }
+}
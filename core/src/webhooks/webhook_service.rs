@@ -0,0 +1,149 @@
+use super::{
+  webhook::{webhook_subscriber_id, Webhook},
+  webhook_repository::WebhookRepository,
+};
+use crate::{
+  events::{event::EventKind, event_subscriber_repository::EventSubscriberRepository},
+  proto::{self, CreateWebhookReply, CreateWebhookRequest, DeleteWebhookRequest, GetWebhooksReply},
+  sqlite::SqliteConnection,
+};
+use reqwest::Url;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::error;
+use ulid::Ulid;
+
+impl From<Webhook> for proto::Webhook {
+  fn from(val: Webhook) -> Self {
+    proto::Webhook {
+      id: val.id,
+      url: val.url,
+      event_kinds: val
+        .event_kinds
+        .into_iter()
+        .map(|kind| kind.to_string())
+        .collect(),
+      has_secret: val.secret.is_some(),
+      created_at: val.created_at.to_string(),
+      last_delivered_at: val.last_delivered_at.map(|val| val.to_string()),
+      last_error: val.last_error,
+    }
+  }
+}
+
+pub struct WebhookService {
+  webhook_repository: WebhookRepository,
+  event_subscriber_repository: EventSubscriberRepository,
+}
+
+impl WebhookService {
+  pub fn new(sqlite_connection: Arc<SqliteConnection>) -> Self {
+    Self {
+      webhook_repository: WebhookRepository::new(Arc::clone(&sqlite_connection)),
+      event_subscriber_repository: EventSubscriberRepository::new(sqlite_connection),
+    }
+  }
+}
+
+#[tonic::async_trait]
+impl proto::WebhookService for WebhookService {
+  async fn create_webhook(
+    &self,
+    request: Request<CreateWebhookRequest>,
+  ) -> Result<Response<CreateWebhookReply>, Status> {
+    let request = request.into_inner();
+    let url = Url::parse(&request.url)
+      .map_err(|err| Status::invalid_argument(format!("Invalid webhook URL: {}", err)))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+      return Err(Status::invalid_argument(
+        "Webhook URL must use http or https",
+      ));
+    }
+    let event_kinds = request
+      .event_kinds
+      .iter()
+      .map(|kind| {
+        kind
+          .parse::<EventKind>()
+          .map_err(|_| Status::invalid_argument(format!("Invalid event kind: {}", kind)))
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    // New webhooks only receive events published from now on
+    let id = Ulid::new().to_string();
+    let tail = self
+      .event_subscriber_repository
+      .get_stream_tails()
+      .await
+      .map_err(|err| {
+        error!("Error: {:?}", err);
+        Status::internal("Failed to get stream tails")
+      })?
+      .into_iter()
+      .filter_map(|(_, tail)| tail.parse::<u64>().ok())
+      .max()
+      .unwrap_or(0);
+    self
+      .event_subscriber_repository
+      .set_cursor(&webhook_subscriber_id(&id), &tail.to_string())
+      .await
+      .map_err(|err| {
+        error!("Error: {:?}", err);
+        Status::internal("Failed to set webhook cursor")
+      })?;
+    self
+      .webhook_repository
+      .create(&id, url.as_str(), request.secret, &event_kinds)
+      .await
+      .map_err(|err| {
+        error!("Error: {:?}", err);
+        Status::internal("Failed to create webhook")
+      })?;
+    let webhook = self
+      .webhook_repository
+      .find(&id)
+      .await
+      .map_err(|err| {
+        error!("Error: {:?}", err);
+        Status::internal("Failed to get webhook")
+      })?
+      .ok_or(Status::internal("Failed to get webhook"))?;
+
+    Ok(Response::new(CreateWebhookReply {
+      webhook: Some(webhook.into()),
+    }))
+  }
+
+  async fn get_webhooks(&self, _: Request<()>) -> Result<Response<GetWebhooksReply>, Status> {
+    let webhooks = self.webhook_repository.find_all().await.map_err(|err| {
+      error!("Error: {:?}", err);
+      Status::internal("Failed to get webhooks")
+    })?;
+    Ok(Response::new(GetWebhooksReply {
+      webhooks: webhooks.into_iter().map(Into::into).collect(),
+    }))
+  }
+
+  async fn delete_webhook(
+    &self,
+    request: Request<DeleteWebhookRequest>,
+  ) -> Result<Response<()>, Status> {
+    let id = request.into_inner().id;
+    let deleted = self.webhook_repository.delete(&id).await.map_err(|err| {
+      error!("Error: {:?}", err);
+      Status::internal("Failed to delete webhook")
+    })?;
+    if !deleted {
+      return Err(Status::not_found(format!("Webhook not found: {}", id)));
+    }
+    self
+      .event_subscriber_repository
+      .delete_cursor(&webhook_subscriber_id(&id))
+      .await
+      .map_err(|err| {
+        error!("Error: {:?}", err);
+        Status::internal("Failed to delete webhook cursor")
+      })?;
+    Ok(Response::new(()))
+  }
+}
@@ -0,0 +1,162 @@
+use chrono::NaiveDate;
+use core::{
+  albums::{
+    album_duplicate_rules::AlbumDuplicateRules,
+    album_read_model::{AlbumReadModel, AlbumReadModelArtist},
+  },
+  files::file_metadata::file_name::FileName,
+  settings::{AlbumDuplicateSettings, AlbumNameNormalization},
+};
+
+fn rules(
+  name_normalization: AlbumNameNormalization,
+  min_artist_overlap_percent: u32,
+  release_date_window_days: Option<u32>,
+) -> AlbumDuplicateRules {
+  AlbumDuplicateRules::new(AlbumDuplicateSettings {
+    name_normalization,
+    min_artist_overlap_percent,
+    release_date_window_days,
+  })
+}
+
+fn album(file_name: &str, name: &str, artists: &[&str]) -> AlbumReadModel {
+  AlbumReadModel {
+    name: name.to_string(),
+    file_name: FileName::try_from(file_name.to_string()).unwrap(),
+    artists: artists
+      .iter()
+      .map(|artist| AlbumReadModelArtist {
+        name: artist.to_string(),
+        file_name: FileName::try_from(format!("artist/{}", artist)).unwrap(),
+      })
+      .collect(),
+    ..Default::default()
+  }
+}
+
+fn released(mut album: AlbumReadModel, year: i32, month: u32, day: u32) -> AlbumReadModel {
+  album.release_date = NaiveDate::from_ymd_opt(year, month, day);
+  album
+}
+
+#[test]
+fn normalizes_names() {
+  let sigur_ros = album(
+    "release/album/sigur-ros/agaetis-byrjun",
+    "Ágætis byrjun",
+    &["sigur-ros"],
+  );
+  let radiohead = album(
+    "release/album/radiohead/ok-computer-oknotok",
+    "OK Computer (OKNOTOK)",
+    &["radiohead"],
+  );
+  let ascii = rules(AlbumNameNormalization::Ascii, 0, None);
+  assert_eq!(ascii.normalize_name(&sigur_ros), "agaetis byrjun");
+  assert_eq!(ascii.normalize_name(&radiohead), "ok computer (oknotok)");
+  let alphanumeric = rules(AlbumNameNormalization::Alphanumeric, 0, None);
+  assert_eq!(alphanumeric.normalize_name(&sigur_ros), "agaetisbyrjun");
+  assert_eq!(alphanumeric.normalize_name(&radiohead), "okcomputeroknotok");
+}
+
+#[test]
+fn ignores_edition_suffixes() {
+  let ignore_editions = rules(AlbumNameNormalization::IgnoreEditions, 0, None);
+  assert_eq!(
+    ignore_editions.normalize_name(&album(
+      "release/album/slowdive/souvlaki-deluxe-edition",
+      "Souvlaki (Deluxe Edition)",
+      &["slowdive"],
+    )),
+    "souvlaki"
+  );
+  assert_eq!(
+    ignore_editions.normalize_name(&album(
+      "release/album/radiohead/in-rainbows-remaster",
+      "In Rainbows [2016 Remaster]",
+      &["radiohead"],
+    )),
+    "inrainbows"
+  );
+  assert_eq!(
+    ignore_editions.normalize_name(&album(
+      "release/album/portishead/roseland-nyc-live",
+      "Roseland NYC (Live)",
+      &["portishead"],
+    )),
+    "roselandnyclive"
+  );
+}
+
+#[test]
+fn matches_edition_only_when_ignoring_editions() {
+  let original = album("release/album/slowdive/souvlaki", "Souvlaki", &["slowdive"]);
+  let deluxe = album(
+    "release/album/slowdive/souvlaki-deluxe-edition",
+    "Souvlaki (Deluxe Edition)",
+    &["slowdive"],
+  );
+  assert!(rules(AlbumNameNormalization::IgnoreEditions, 0, None).is_match(&original, &deluxe));
+  assert!(!rules(AlbumNameNormalization::Alphanumeric, 0, None).is_match(&original, &deluxe));
+  assert!(!rules(AlbumNameNormalization::Ascii, 0, None).is_match(&original, &deluxe));
+}
+
+#[test]
+fn applies_artist_overlap_threshold() {
+  let solo = album("release/album/thom-yorke/amok", "Amok", &["thom-yorke"]);
+  let collaboration = album(
+    "release/album/atoms-for-peace/amok",
+    "Amok",
+    &["thom-yorke", "flea"],
+  );
+  let unrelated = album(
+    "release/album/red-hot-chili-peppers/amok",
+    "Amok",
+    &["red-hot-chili-peppers"],
+  );
+  assert!(rules(AlbumNameNormalization::Ascii, 0, None).is_match(&solo, &collaboration));
+  assert!(rules(AlbumNameNormalization::Ascii, 50, None).is_match(&solo, &collaboration));
+  assert!(!rules(AlbumNameNormalization::Ascii, 51, None).is_match(&solo, &collaboration));
+  assert!(!rules(AlbumNameNormalization::Ascii, 0, None).is_match(&solo, &unrelated));
+}
+
+#[test]
+fn applies_release_date_window() {
+  let original = released(
+    album("release/album/slowdive/souvlaki", "Souvlaki", &["slowdive"]),
+    1993,
+    5,
+    17,
+  );
+  let us_release = released(
+    album(
+      "release/album/slowdive/souvlaki-us",
+      "Souvlaki",
+      &["slowdive"],
+    ),
+    1993,
+    6,
+    6,
+  );
+  let reissue = released(
+    album(
+      "release/album/slowdive/souvlaki-reissue",
+      "Souvlaki",
+      &["slowdive"],
+    ),
+    2005,
+    9,
+    26,
+  );
+  let undated = album(
+    "release/album/slowdive/souvlaki-undated",
+    "Souvlaki",
+    &["slowdive"],
+  );
+  let rules = rules(AlbumNameNormalization::Ascii, 0, Some(30));
+  assert!(rules.is_match(&original, &us_release));
+  assert!(!rules.is_match(&original, &reissue));
+  assert!(rules.is_match(&original, &undated));
+  assert!(rules.is_match(&reissue, &reissue));
+}
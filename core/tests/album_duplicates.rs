@@ -0,0 +1,232 @@
+mod common;
+
+use common::{block_on, test_settings, test_sqlite_connection};
+use core::{
+  albums::{
+    album_interactor::AlbumInteractor,
+    album_read_model::{AlbumReadModel, AlbumReadModelArtist},
+    album_repository::AlbumRepository,
+    sqlite_album_repository::SqliteAlbumRepository,
+    sqlite_album_search_index::SqliteAlbumSearchIndex,
+  },
+  files::file_metadata::file_name::FileName,
+  settings::AlbumNameNormalization,
+};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const SOUVLAKI: &str = "release/album/slowdive/souvlaki";
+const SOUVLAKI_DELUXE: &str = "release/album/slowdive/souvlaki-deluxe-edition";
+const PYGMALION: &str = "release/album/slowdive/pygmalion";
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+fn album(file_name_value: &str, name: &str, rating_count: u32) -> AlbumReadModel {
+  AlbumReadModel {
+    name: name.to_string(),
+    file_name: file_name(file_name_value),
+    rating: 3.9,
+    rating_count,
+    artists: vec![AlbumReadModelArtist {
+      name: "Slowdive".to_string(),
+      file_name: file_name("artist/slowdive"),
+    }],
+    ..Default::default()
+  }
+}
+
+fn souvlaki() -> AlbumReadModel {
+  album(SOUVLAKI, "Souvlaki", 100)
+}
+
+fn souvlaki_deluxe() -> AlbumReadModel {
+  album(SOUVLAKI_DELUXE, "Souvlaki (Deluxe Edition)", 10)
+}
+
+fn pygmalion() -> AlbumReadModel {
+  album(PYGMALION, "Pygmalion", 50)
+}
+
+struct TestAlbums {
+  interactor: AlbumInteractor,
+  repository: Arc<SqliteAlbumRepository>,
+  _dir: TempDir,
+}
+
+impl TestAlbums {
+  async fn new(name_normalization: AlbumNameNormalization) -> Self {
+    let (mut settings, dir) = test_settings();
+    settings.album_duplicates.name_normalization = name_normalization;
+    let sqlite_connection = test_sqlite_connection(Arc::new(settings.clone())).await;
+    let repository = Arc::new(SqliteAlbumRepository::new(Arc::clone(&sqlite_connection)));
+    let interactor = AlbumInteractor::new(
+      &settings,
+      Arc::clone(&sqlite_connection),
+      repository.clone(),
+      Arc::new(SqliteAlbumSearchIndex::new(sqlite_connection)),
+    );
+    Self {
+      interactor,
+      repository,
+      _dir: dir,
+    }
+  }
+
+  async fn get(&self, value: &str) -> AlbumReadModel {
+    self.repository.get(&file_name(value)).await.unwrap()
+  }
+
+  async fn duplicate_of(&self, value: &str) -> Option<FileName> {
+    self.get(value).await.duplicate_of
+  }
+
+  async fn duplicates(&self, value: &str) -> Vec<FileName> {
+    let mut duplicates = self.get(value).await.duplicates;
+    duplicates.sort();
+    duplicates
+  }
+}
+
+fn file_names(values: &[&str]) -> Vec<FileName> {
+  let mut file_names = values
+    .iter()
+    .map(|value| file_name(value))
+    .collect::<Vec<_>>();
+  file_names.sort();
+  file_names
+}
+
+#[test]
+fn detects_editions_as_duplicates() {
+  block_on(async {
+    let albums = TestAlbums::new(AlbumNameNormalization::IgnoreEditions).await;
+    albums.interactor.put(souvlaki()).await.unwrap();
+    albums.interactor.put(souvlaki_deluxe()).await.unwrap();
+    albums.interactor.put(pygmalion()).await.unwrap();
+
+    assert_eq!(
+      albums.duplicate_of(SOUVLAKI_DELUXE).await,
+      Some(file_name(SOUVLAKI))
+    );
+    assert_eq!(
+      albums.duplicates(SOUVLAKI).await,
+      file_names(&[SOUVLAKI_DELUXE])
+    );
+    assert_eq!(albums.duplicate_of(SOUVLAKI).await, None);
+    assert_eq!(albums.duplicate_of(PYGMALION).await, None);
+  });
+}
+
+#[test]
+fn keeps_editions_apart_without_ignoring_editions() {
+  block_on(async {
+    let albums = TestAlbums::new(AlbumNameNormalization::Ascii).await;
+    albums.interactor.put(souvlaki()).await.unwrap();
+    albums.interactor.put(souvlaki_deluxe()).await.unwrap();
+
+    assert_eq!(albums.duplicate_of(SOUVLAKI_DELUXE).await, None);
+    assert!(albums.duplicates(SOUVLAKI).await.is_empty());
+  });
+}
+
+#[test]
+fn keeps_manual_duplicates_across_reparses() {
+  block_on(async {
+    let albums = TestAlbums::new(AlbumNameNormalization::Ascii).await;
+    albums.interactor.put(souvlaki()).await.unwrap();
+    albums.interactor.put(pygmalion()).await.unwrap();
+    albums
+      .interactor
+      .mark_duplicate(&file_name(PYGMALION), &file_name(SOUVLAKI))
+      .await
+      .unwrap();
+    assert_eq!(
+      albums.duplicate_of(PYGMALION).await,
+      Some(file_name(SOUVLAKI))
+    );
+
+    let mut reparsed = pygmalion();
+    reparsed.rating_count = 500;
+    albums.interactor.put(reparsed).await.unwrap();
+    albums.interactor.put(souvlaki()).await.unwrap();
+
+    assert_eq!(
+      albums.duplicate_of(PYGMALION).await,
+      Some(file_name(SOUVLAKI))
+    );
+    assert_eq!(albums.duplicates(SOUVLAKI).await, file_names(&[PYGMALION]));
+  });
+}
+
+#[test]
+fn does_not_redetect_unmarked_duplicates() {
+  block_on(async {
+    let albums = TestAlbums::new(AlbumNameNormalization::IgnoreEditions).await;
+    albums.interactor.put(souvlaki()).await.unwrap();
+    albums.interactor.put(souvlaki_deluxe()).await.unwrap();
+    albums
+      .interactor
+      .unmark_duplicate(&file_name(SOUVLAKI_DELUXE))
+      .await
+      .unwrap();
+    assert_eq!(albums.duplicate_of(SOUVLAKI_DELUXE).await, None);
+    assert!(albums.duplicates(SOUVLAKI).await.is_empty());
+
+    albums.interactor.put(souvlaki_deluxe()).await.unwrap();
+    albums.interactor.put(souvlaki()).await.unwrap();
+
+    assert_eq!(albums.duplicate_of(SOUVLAKI_DELUXE).await, None);
+    assert!(albums.duplicates(SOUVLAKI).await.is_empty());
+    assert!(albums
+      .interactor
+      .unmark_duplicate(&file_name(SOUVLAKI_DELUXE))
+      .await
+      .is_err());
+  });
+}
+
+#[test]
+fn merges_existing_duplicates() {
+  block_on(async {
+    let albums = TestAlbums::new(AlbumNameNormalization::IgnoreEditions).await;
+    albums.interactor.put(souvlaki()).await.unwrap();
+    albums.interactor.put(souvlaki_deluxe()).await.unwrap();
+    albums.interactor.put(pygmalion()).await.unwrap();
+
+    albums
+      .interactor
+      .merge_duplicates(&file_name(PYGMALION), vec![file_name(SOUVLAKI)])
+      .await
+      .unwrap();
+
+    assert_eq!(albums.duplicate_of(PYGMALION).await, None);
+    assert_eq!(
+      albums.duplicates(PYGMALION).await,
+      file_names(&[SOUVLAKI, SOUVLAKI_DELUXE])
+    );
+    assert_eq!(
+      albums.duplicate_of(SOUVLAKI).await,
+      Some(file_name(PYGMALION))
+    );
+    assert_eq!(
+      albums.duplicate_of(SOUVLAKI_DELUXE).await,
+      Some(file_name(PYGMALION))
+    );
+    assert!(albums.duplicates(SOUVLAKI).await.is_empty());
+  });
+}
+
+#[test]
+fn rejects_merging_missing_albums() {
+  block_on(async {
+    let albums = TestAlbums::new(AlbumNameNormalization::Ascii).await;
+    albums.interactor.put(souvlaki()).await.unwrap();
+    assert!(albums
+      .interactor
+      .merge_duplicates(&file_name(SOUVLAKI), vec![file_name(PYGMALION)])
+      .await
+      .is_err());
+  });
+}
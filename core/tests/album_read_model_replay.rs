@@ -0,0 +1,141 @@
+mod common;
+
+use common::{block_on, test_settings, test_sqlite_connection, unconnected_redis_connection_pool};
+use core::{
+  albums::{
+    album_event_subscribers::build_album_read_model_event_subscriber,
+    album_read_model::AlbumReadModel, album_repository::AlbumRepository,
+    album_search_index::build_album_search_index, sqlite_album_repository::SqliteAlbumRepository,
+  },
+  events::{
+    event::{Event, EventPayloadBuilder, Stream},
+    event_publisher::EventPublisher,
+    event_subscriber::EventSubscriber,
+    event_subscriber_repository::{EventSubscriberRepository, EventSubscriberStatus},
+  },
+  files::file_metadata::file_name::FileName,
+  parser::parsed_file_data::{ParsedAlbum, ParsedFileData},
+};
+use std::sync::Arc;
+use ulid::Ulid;
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+fn parsed_album(name: &str) -> ParsedAlbum {
+  serde_json::from_value(serde_json::json!({
+    "name": name,
+    "rating": 3.9,
+    "rating_count": 100,
+    "artists": [{ "name": "Slowdive", "file_name": "artist/slowdive" }],
+    "primary_genres": ["Shoegaze"],
+    "secondary_genres": [],
+    "descriptors": [],
+    "tracks": [],
+    "release_date": "1993-05-17",
+  }))
+  .unwrap()
+}
+
+async fn publish_parsed(publisher: &EventPublisher, file_name: &FileName, name: &str) {
+  publisher
+    .publish(
+      Stream::Parser,
+      EventPayloadBuilder::default()
+        .event(Event::FileParsed {
+          file_id: Ulid::new(),
+          file_name: file_name.clone(),
+          data: ParsedFileData::Album(parsed_album(name)),
+          parser_version: 1,
+        })
+        .build()
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+}
+
+async fn publish_deleted(publisher: &EventPublisher, file_name: &FileName) {
+  publisher
+    .publish(
+      Stream::File,
+      EventPayloadBuilder::default()
+        .event(Event::FileDeleted {
+          file_id: Ulid::new(),
+          file_name: file_name.clone(),
+        })
+        .build()
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+}
+
+async fn drain(subscriber: &EventSubscriber) {
+  while let Some(cursor) = subscriber.poll(subscriber.batch_size).await.unwrap() {
+    subscriber.set_cursor(&cursor).await.unwrap();
+  }
+}
+
+#[test]
+fn replays_album_deletions() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let redis_connection_pool = unconnected_redis_connection_pool();
+    let subscriber = build_album_read_model_event_subscriber(
+      Arc::clone(&redis_connection_pool),
+      Arc::clone(&sqlite_connection),
+      Arc::clone(&settings),
+    )
+    .unwrap();
+    let publisher = EventPublisher::new(Arc::clone(&settings), Arc::clone(&sqlite_connection));
+    let album_repository = SqliteAlbumRepository::new(Arc::clone(&sqlite_connection));
+    let album_search_index = build_album_search_index(
+      &settings,
+      Arc::clone(&redis_connection_pool),
+      Arc::clone(&sqlite_connection),
+    );
+
+    let deleted = file_name("release/album/slowdive/souvlaki");
+    let kept = file_name("release/album/slowdive/pygmalion");
+    let orphan = file_name("release/album/slowdive/just-for-a-day");
+    publish_parsed(&publisher, &deleted, "Souvlaki").await;
+    publish_parsed(&publisher, &kept, "Pygmalion").await;
+    publish_deleted(&publisher, &deleted).await;
+    drain(&subscriber).await;
+    assert!(album_repository.find(&deleted).await.unwrap().is_none());
+    assert!(album_repository.find(&kept).await.unwrap().is_some());
+
+    // An index document without an event, e.g. left behind before deletions were projected.
+    album_search_index
+      .put(AlbumReadModel::from_parsed_album(
+        &orphan,
+        parsed_album("Just for a Day"),
+      ))
+      .await
+      .unwrap();
+
+    EventSubscriberRepository::new(Arc::clone(&sqlite_connection))
+      .set_status(
+        "update_album_read_models",
+        EventSubscriberStatus::ReplayRequested,
+      )
+      .await
+      .unwrap();
+    assert!(subscriber.prepare_replay().await.unwrap());
+    assert_eq!(subscriber.get_cursor().await.unwrap(), "0");
+    drain(&subscriber).await;
+    subscriber.complete_replay().await.unwrap();
+
+    assert!(album_repository.find(&deleted).await.unwrap().is_none());
+    assert!(album_repository.find(&kept).await.unwrap().is_some());
+    assert_eq!(
+      album_search_index.get_file_names().await.unwrap(),
+      vec![kept]
+    );
+    assert!(!subscriber.prepare_replay().await.unwrap());
+  });
+}
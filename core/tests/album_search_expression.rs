@@ -0,0 +1,102 @@
+use core::albums::album_search_expression::{
+  AlbumSearchExpression, AlbumSearchNumericField, AlbumSearchTagField, NumericBound,
+};
+
+fn tag(field: AlbumSearchTagField, value: &str) -> AlbumSearchExpression {
+  AlbumSearchExpression::Tag {
+    field,
+    value: value.to_string(),
+  }
+}
+
+fn range(
+  field: AlbumSearchNumericField,
+  min: Option<(f64, bool)>,
+  max: Option<(f64, bool)>,
+) -> AlbumSearchExpression {
+  let bound = |(value, inclusive)| NumericBound { value, inclusive };
+  AlbumSearchExpression::Range {
+    field,
+    min: min.map(bound),
+    max: max.map(bound),
+  }
+}
+
+fn error_position(expression: &str) -> usize {
+  AlbumSearchExpression::parse(expression)
+    .unwrap_err()
+    .position
+}
+
+#[test]
+fn parses_boolean_expressions() {
+  assert_eq!(
+    AlbumSearchExpression::parse(
+      "genre:\"Shoegaze\" AND (descriptor:melancholic OR descriptor:atmospheric) year:1990..1999 rating>3.5"
+    )
+    .unwrap(),
+    AlbumSearchExpression::And(vec![
+      tag(AlbumSearchTagField::Genre, "Shoegaze"),
+      AlbumSearchExpression::Or(vec![
+        tag(AlbumSearchTagField::Descriptor, "melancholic"),
+        tag(AlbumSearchTagField::Descriptor, "atmospheric"),
+      ]),
+      range(
+        AlbumSearchNumericField::Year,
+        Some((1990.0, true)),
+        Some((1999.0, true))
+      ),
+      range(AlbumSearchNumericField::Rating, Some((3.5, false)), None),
+    ])
+  );
+}
+
+#[test]
+fn and_binds_tighter_than_or() {
+  assert_eq!(
+    AlbumSearchExpression::parse("loveless OR genre:noise -language:English").unwrap(),
+    AlbumSearchExpression::Or(vec![
+      AlbumSearchExpression::Text("loveless".to_string()),
+      AlbumSearchExpression::And(vec![
+        tag(AlbumSearchTagField::Genre, "noise"),
+        AlbumSearchExpression::Not(Box::new(tag(AlbumSearchTagField::Language, "English"))),
+      ]),
+    ])
+  );
+}
+
+#[test]
+fn parses_numeric_comparisons() {
+  assert_eq!(
+    AlbumSearchExpression::parse("rating_count>=1000 NOT year:..1979 duration<3600").unwrap(),
+    AlbumSearchExpression::And(vec![
+      range(
+        AlbumSearchNumericField::RatingCount,
+        Some((1000.0, true)),
+        None
+      ),
+      AlbumSearchExpression::Not(Box::new(range(
+        AlbumSearchNumericField::Year,
+        None,
+        Some((1979.0, true))
+      ))),
+      range(
+        AlbumSearchNumericField::DurationSeconds,
+        None,
+        Some((3600.0, false))
+      ),
+    ])
+  );
+}
+
+#[test]
+fn reports_syntax_error_positions() {
+  assert_eq!(error_position(""), 1);
+  assert_eq!(error_position("(genre:rock"), 12);
+  assert_eq!(error_position("genre:rock)"), 11);
+  assert_eq!(error_position("colour:blue"), 1);
+  assert_eq!(error_position("genre:rock rating>high"), 19);
+  assert_eq!(error_position("genre:rock OR"), 14);
+  assert_eq!(error_position("genre>rock"), 1);
+  assert_eq!(error_position("label:\"Creation"), 7);
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use chrono::NaiveDateTime;
+use common::{block_on, test_settings, test_sqlite_connection};
+use core::{
+  files::file_metadata::file_name::FileName,
+  lookup::{
+    album_search_lookup::{AlbumSearchLookup, AlbumSearchLookupQuery},
+    album_search_lookup_repository::AlbumSearchLookupRepository,
+  },
+  parser::parsed_file_data::{ParsedAlbumSearchResult, ParsedArtistReference},
+};
+use std::sync::Arc;
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+fn album_crawling(query: &AlbumSearchLookupQuery, album_file_name: &str) -> AlbumSearchLookup {
+  AlbumSearchLookup::AlbumCrawling {
+    query: query.clone(),
+    last_updated_at: NaiveDateTime::parse_from_str("2024-03-01 12:00:00", "%Y-%m-%d %H:%M:%S")
+      .unwrap(),
+    album_search_file_name: query.file_name(),
+    parsed_album_search_result: ParsedAlbumSearchResult {
+      name: query.album_name().to_string(),
+      file_name: file_name(album_file_name),
+      artists: vec![ParsedArtistReference {
+        name: query.artist_name().to_string(),
+        file_name: file_name("artist/slowdive"),
+      }],
+    },
+    file_processing_correlation_id: "lookup:album_search:1".to_string(),
+  }
+}
+
+#[test]
+fn stores_album_search_lookups() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let repository = AlbumSearchLookupRepository::new(sqlite_connection);
+    let souvlaki = AlbumSearchLookupQuery::new("Souvlaki".to_string(), "Slowdive".to_string());
+    let pygmalion = AlbumSearchLookupQuery::new("Pygmalion".to_string(), "Slowdive".to_string());
+    let missing = AlbumSearchLookupQuery::new("Just for a Day".to_string(), "Slowdive".to_string());
+
+    assert!(repository.find(&souvlaki).await.unwrap().is_none());
+    repository
+      .put(&AlbumSearchLookup::new(souvlaki.clone()))
+      .await
+      .unwrap();
+    repository
+      .put(&AlbumSearchLookup::new(pygmalion.clone()))
+      .await
+      .unwrap();
+    repository
+      .put(&album_crawling(
+        &souvlaki,
+        "release/album/slowdive/souvlaki",
+      ))
+      .await
+      .unwrap();
+
+    let lookup = repository.get(&souvlaki).await.unwrap();
+    assert_eq!(lookup.status_string(), "album_crawling");
+    assert_eq!(lookup.query(), &souvlaki);
+
+    let lookups = repository
+      .find_many(vec![&pygmalion, &missing, &souvlaki])
+      .await
+      .unwrap();
+    assert_eq!(
+      lookups
+        .iter()
+        .map(|lookup| lookup.as_ref().map(|lookup| lookup.status_string()))
+        .collect::<Vec<_>>(),
+      vec![
+        Some("started".to_string()),
+        None,
+        Some("album_crawling".to_string())
+      ]
+    );
+
+    let lookups = repository
+      .find_many_by_album_file_name(&file_name("release/album/slowdive/souvlaki"))
+      .await
+      .unwrap();
+    assert_eq!(lookups.len(), 1);
+    assert_eq!(lookups[0].query(), &souvlaki);
+
+    let mut statuses = repository
+      .aggregate_statuses()
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|status| (status.status, status.count))
+      .collect::<Vec<_>>();
+    statuses.sort();
+    assert_eq!(
+      statuses,
+      vec![
+        ("album_crawling".to_string(), 1),
+        ("started".to_string(), 1)
+      ]
+    );
+
+    repository.delete(&souvlaki).await.unwrap();
+    assert!(repository.find(&souvlaki).await.unwrap().is_none());
+    assert!(repository
+      .find_many_by_album_file_name(&file_name("release/album/slowdive/souvlaki"))
+      .await
+      .unwrap()
+      .is_empty());
+  });
+}
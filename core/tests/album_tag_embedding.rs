@@ -0,0 +1,87 @@
+use chrono::NaiveDate;
+use core::albums::album_tag_embedding::{
+  AlbumTagEmbeddingModel, AlbumTagEmbeddingTraining, AlbumTags,
+};
+
+fn tags(genres: &[&str], descriptors: &[&str]) -> AlbumTags {
+  AlbumTags {
+    primary_genres: genres.iter().map(|genre| genre.to_string()).collect(),
+    descriptors: descriptors
+      .iter()
+      .map(|descriptor| descriptor.to_string())
+      .collect(),
+    ..Default::default()
+  }
+}
+
+fn corpus() -> Vec<AlbumTags> {
+  let mut albums = vec![];
+  for _ in 0..10 {
+    albums.push(tags(&["Shoegaze", "Noise Pop"], &["noisy", "ethereal"]));
+    albums.push(tags(
+      &["Shoegaze", "Dream Pop"],
+      &["ethereal", "melancholic"],
+    ));
+    albums.push(tags(&["Hard Bop"], &["improvisation", "energetic"]));
+    albums.push(tags(&["Hard Bop", "Soul Jazz"], &["energetic", "playful"]));
+  }
+  albums
+}
+
+fn training() -> AlbumTagEmbeddingTraining {
+  AlbumTagEmbeddingTraining {
+    dimensions: 4,
+    max_tags: 100,
+    min_tag_count: 2,
+  }
+}
+
+fn similarity(a: &[f32], b: &[f32]) -> f32 {
+  a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+#[test]
+fn weights_album_tags() {
+  let album = AlbumTags {
+    primary_genres: vec!["Shoegaze".to_string()],
+    secondary_genres: vec!["Shoegaze".to_string(), "Dream Pop".to_string()],
+    release_date: NaiveDate::from_ymd_opt(1991, 11, 4),
+    ..Default::default()
+  };
+  assert_eq!(
+    album.weighted_tags(),
+    vec![
+      ("decade:1990".to_string(), 1.0),
+      ("genre:dream pop".to_string(), 0.5),
+      ("genre:shoegaze".to_string(), 1.0),
+    ]
+  );
+}
+
+#[test]
+fn embeds_albums_with_shared_tags_closer() {
+  let model = AlbumTagEmbeddingModel::train(&corpus(), &training()).unwrap();
+  let shoegaze = model
+    .embed(&tags(&["Shoegaze"], &["noisy", "ethereal"]))
+    .unwrap();
+  let dream_pop = model
+    .embed(&tags(&["Dream Pop"], &["ethereal", "melancholic"]))
+    .unwrap();
+  let hard_bop = model.embed(&tags(&["Hard Bop"], &["energetic"])).unwrap();
+  assert_eq!(shoegaze.len(), 4);
+  assert!(similarity(&shoegaze, &dream_pop) > similarity(&shoegaze, &hard_bop));
+}
+
+#[test]
+fn trains_deterministically() {
+  assert_eq!(
+    AlbumTagEmbeddingModel::train(&corpus(), &training()).unwrap(),
+    AlbumTagEmbeddingModel::train(&corpus(), &training()).unwrap()
+  );
+}
+
+#[test]
+fn skips_albums_without_known_tags() {
+  let model = AlbumTagEmbeddingModel::train(&corpus(), &training()).unwrap();
+  assert_eq!(model.embed(&tags(&["Polka"], &[])), None);
+}
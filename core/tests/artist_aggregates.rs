@@ -0,0 +1,73 @@
+use core::{
+  albums::album_read_model::{AlbumReadModel, AlbumReadModelArtist, AlbumReadModelCredit},
+  artists::artist_aggregates::ArtistAggregates,
+  files::file_metadata::file_name::FileName,
+};
+
+fn file_name(value: &str) -> FileName {
+  FileName(value.to_string())
+}
+
+fn credit(artist: &str, roles: &[&str]) -> AlbumReadModelCredit {
+  AlbumReadModelCredit {
+    artist: AlbumReadModelArtist {
+      name: artist.to_string(),
+      file_name: file_name(&format!("artist/{}", artist)),
+    },
+    roles: roles.iter().map(|role| role.to_string()).collect(),
+  }
+}
+
+fn album(name: &str, rating: f32, rating_count: u32, genres: &[&str]) -> AlbumReadModel {
+  AlbumReadModel {
+    name: name.to_string(),
+    file_name: file_name(&format!("release/album/artist/{}", name)),
+    rating,
+    rating_count,
+    primary_genres: genres.iter().map(|genre| genre.to_string()).collect(),
+    ..Default::default()
+  }
+}
+
+#[test]
+fn aggregates_artist_albums() {
+  let artist = file_name("artist/a");
+  let mut first = album("first", 3.5, 100, &["Rock", "Post-Punk"]);
+  first.credits = vec![credit("b", &["Producer"]), credit("a", &["Vocals"])];
+  let mut second = album("second", 4.0, 10, &["Rock"]);
+  second.credits = vec![credit("b", &["Mixing"]), credit("c", &["Drums"])];
+  let unrated = album("unrated", 0.0, 0, &["Rock"]);
+  let mut reissue = album("reissue", 1.0, 5, &["Rock"]);
+  reissue.duplicate_of = Some(first.file_name.clone());
+
+  let aggregates = ArtistAggregates::from_albums(&artist, &[first, second, unrated, reissue], 10);
+
+  assert_eq!(aggregates.album_count, 3);
+  assert_eq!(aggregates.average_rating, Some(3.75));
+  assert_eq!(aggregates.top_genres[0].name, "Rock");
+  assert_eq!(aggregates.top_genres[0].count, 3);
+  assert_eq!(aggregates.collaborators.len(), 2);
+  assert_eq!(aggregates.collaborators[0].artist.name, "b");
+  assert_eq!(aggregates.collaborators[0].album_count, 2);
+  assert_eq!(
+    aggregates.collaborators[0].roles,
+    vec!["Mixing", "Producer"]
+  );
+}
+
+#[test]
+fn limits_aggregated_items() {
+  let albums = vec![
+    album("first", 3.0, 1, &["Rock", "Jazz"]),
+    album("second", 3.0, 1, &["Jazz", "Ambient"]),
+  ];
+  let aggregates = ArtistAggregates::from_albums(&file_name("artist/a"), &albums, 2);
+  assert_eq!(
+    aggregates
+      .top_genres
+      .iter()
+      .map(|genre| genre.name.as_str())
+      .collect::<Vec<_>>(),
+    vec!["Jazz", "Ambient"]
+  );
+}
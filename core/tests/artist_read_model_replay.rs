@@ -0,0 +1,132 @@
+mod common;
+
+use common::{block_on, test_settings, test_sqlite_connection, unconnected_redis_connection_pool};
+use core::{
+  artists::{
+    artist_event_subscribers::build_artist_event_subscribers, artist_repository::ArtistRepository,
+  },
+  events::{
+    event::{Event, EventPayloadBuilder, Stream},
+    event_publisher::EventPublisher,
+    event_subscriber::EventSubscriber,
+    event_subscriber_repository::{EventSubscriberRepository, EventSubscriberStatus},
+  },
+  files::file_metadata::file_name::FileName,
+  parser::parsed_file_data::{ParsedArtist, ParsedFileData},
+};
+use std::sync::Arc;
+use ulid::Ulid;
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+fn parsed_artist(name: &str) -> ParsedArtist {
+  serde_json::from_value(serde_json::json!({
+    "name": name,
+    "albums": [],
+    "country": "United Kingdom",
+    "genres": ["Shoegaze"],
+  }))
+  .unwrap()
+}
+
+async fn publish_parsed(publisher: &EventPublisher, file_name: &FileName, name: &str) {
+  publisher
+    .publish(
+      Stream::Parser,
+      EventPayloadBuilder::default()
+        .event(Event::FileParsed {
+          file_id: Ulid::new(),
+          file_name: file_name.clone(),
+          data: ParsedFileData::Artist(parsed_artist(name)),
+          parser_version: 1,
+        })
+        .build()
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+}
+
+async fn publish_deleted(publisher: &EventPublisher, file_name: &FileName) {
+  publisher
+    .publish(
+      Stream::File,
+      EventPayloadBuilder::default()
+        .event(Event::FileDeleted {
+          file_id: Ulid::new(),
+          file_name: file_name.clone(),
+        })
+        .build()
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+}
+
+/**
+ * The country of an artist, which is cleared when its file is deleted.
+ */
+async fn country(artist_repository: &ArtistRepository, file_name: &FileName) -> Option<String> {
+  artist_repository
+    .find(file_name)
+    .await
+    .unwrap()
+    .and_then(|artist| artist.country)
+}
+
+async fn drain(subscriber: &EventSubscriber) {
+  while let Some(cursor) = subscriber.poll(subscriber.batch_size).await.unwrap() {
+    subscriber.set_cursor(&cursor).await.unwrap();
+  }
+}
+
+#[test]
+fn replays_artist_deletions() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let subscribers = build_artist_event_subscribers(
+      unconnected_redis_connection_pool(),
+      Arc::clone(&sqlite_connection),
+      Arc::clone(&settings),
+    )
+    .unwrap();
+    assert_eq!(subscribers.len(), 1);
+    let subscriber = &subscribers[0];
+    let publisher = EventPublisher::new(Arc::clone(&settings), Arc::clone(&sqlite_connection));
+    let artist_repository = ArtistRepository::new(Arc::clone(&sqlite_connection));
+
+    let deleted = file_name("artist/slowdive");
+    let kept = file_name("artist/mojave-3");
+    publish_parsed(&publisher, &deleted, "Slowdive").await;
+    publish_parsed(&publisher, &kept, "Mojave 3").await;
+    publish_deleted(&publisher, &deleted).await;
+    drain(subscriber).await;
+    assert_eq!(country(&artist_repository, &deleted).await, None);
+    assert_eq!(
+      country(&artist_repository, &kept).await.as_deref(),
+      Some("United Kingdom")
+    );
+
+    EventSubscriberRepository::new(Arc::clone(&sqlite_connection))
+      .set_status(
+        "update_artist_read_models",
+        EventSubscriberStatus::ReplayRequested,
+      )
+      .await
+      .unwrap();
+    assert!(subscriber.prepare_replay().await.unwrap());
+    assert_eq!(subscriber.get_cursor().await.unwrap(), "0");
+    drain(subscriber).await;
+    subscriber.complete_replay().await.unwrap();
+
+    assert_eq!(country(&artist_repository, &deleted).await, None);
+    assert_eq!(
+      country(&artist_repository, &kept).await.as_deref(),
+      Some("United Kingdom")
+    );
+  });
+}
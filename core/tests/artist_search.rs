@@ -0,0 +1,75 @@
+mod common;
+
+use common::{block_on, test_settings, test_sqlite_connection};
+use core::{
+  albums::album_search_index::SearchPagination,
+  artists::{
+    artist_read_model::{ArtistReadModel, ArtistReadModelReference},
+    artist_repository::{ArtistRepository, DEFAULT_ARTIST_SEARCH_LIMIT},
+  },
+  files::file_metadata::file_name::FileName,
+};
+use std::sync::Arc;
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+fn artist(index: usize) -> ArtistReadModel {
+  ArtistReadModel {
+    file_name: file_name(&format!("artist/slowdive-{:02}", index)),
+    name: format!("Slowdive {:02}", index),
+    country: Some("United Kingdom".to_string()),
+    genres: vec!["Shoegaze".to_string(), "Dream Pop".to_string()],
+    members: vec![ArtistReadModelReference {
+      file_name: file_name("artist/rachel-goswell"),
+      name: "Rachel Goswell".to_string(),
+    }],
+    ..Default::default()
+  }
+}
+
+#[test]
+fn searches_artists_with_details_in_pages() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let artist_repository = ArtistRepository::new(sqlite_connection);
+    let artist_count = DEFAULT_ARTIST_SEARCH_LIMIT + 5;
+    for index in 0..artist_count {
+      artist_repository.put(artist(index)).await.unwrap();
+    }
+
+    let result = artist_repository.search("slowdive", None).await.unwrap();
+    assert_eq!(result.total, artist_count);
+    assert_eq!(result.artists.len(), DEFAULT_ARTIST_SEARCH_LIMIT);
+    assert_eq!(result.artists[0].name, "Slowdive 00");
+    for artist in &result.artists {
+      assert_eq!(artist.country.as_deref(), Some("United Kingdom"));
+      assert_eq!(artist.genres.len(), 2);
+      assert_eq!(artist.members.len(), 1);
+    }
+
+    let result = artist_repository
+      .search(
+        "SLOWDIVE",
+        Some(&SearchPagination {
+          offset: Some(DEFAULT_ARTIST_SEARCH_LIMIT),
+          limit: Some(10),
+        }),
+      )
+      .await
+      .unwrap();
+    assert_eq!(
+      result
+        .artists
+        .iter()
+        .map(|artist| artist.name.as_str())
+        .collect::<Vec<_>>(),
+      (DEFAULT_ARTIST_SEARCH_LIMIT..artist_count)
+        .map(|index| format!("Slowdive {:02}", index))
+        .collect::<Vec<_>>()
+    );
+  });
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use common::{block_on, test_settings, test_sqlite_connection, unconnected_redis_connection_pool};
+use core::{
+  charts::{
+    chart_event_subscribers::build_chart_event_subscribers, chart_repository::ChartRepository,
+  },
+  events::{
+    event::{Event, EventPayloadBuilder, Stream},
+    event_publisher::EventPublisher,
+    event_subscriber::EventSubscriber,
+    event_subscriber_repository::{EventSubscriberRepository, EventSubscriberStatus},
+  },
+  files::file_metadata::file_name::FileName,
+  parser::parsed_file_data::{ParsedChart, ParsedFileData},
+};
+use std::sync::Arc;
+use ulid::Ulid;
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+fn parsed_chart(page_number: u32, album: &str) -> ParsedChart {
+  serde_json::from_value(serde_json::json!({
+    "albums": [{
+      "file_name": format!("release/album/slowdive/{}", album),
+      "name": album,
+      "rating": 3.9,
+      "rating_count": 100,
+      "artists": [{ "name": "Slowdive", "file_name": "artist/slowdive" }],
+      "primary_genres": ["Shoegaze"],
+      "secondary_genres": [],
+      "descriptors": [],
+      "release_date": null,
+      "position": page_number,
+    }],
+    "page_number": page_number,
+    "total_pages": 2,
+  }))
+  .unwrap()
+}
+
+async fn publish_parsed(publisher: &EventPublisher, file_name: &FileName, chart: ParsedChart) {
+  publisher
+    .publish(
+      Stream::Parser,
+      EventPayloadBuilder::default()
+        .event(Event::FileParsed {
+          file_id: Ulid::new(),
+          file_name: file_name.clone(),
+          data: ParsedFileData::Chart(chart),
+          parser_version: 1,
+        })
+        .build()
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+}
+
+async fn publish_deleted(publisher: &EventPublisher, file_name: &FileName) {
+  publisher
+    .publish(
+      Stream::File,
+      EventPayloadBuilder::default()
+        .event(Event::FileDeleted {
+          file_id: Ulid::new(),
+          file_name: file_name.clone(),
+        })
+        .build()
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+}
+
+async fn drain(subscriber: &EventSubscriber) {
+  while let Some(cursor) = subscriber.poll(subscriber.batch_size).await.unwrap() {
+    subscriber.set_cursor(&cursor).await.unwrap();
+  }
+}
+
+async fn chart_albums(chart_repository: &ChartRepository, file_name: &FileName) -> Vec<String> {
+  chart_repository
+    .get(file_name)
+    .await
+    .unwrap()
+    .albums
+    .into_iter()
+    .map(|album| album.name)
+    .collect()
+}
+
+#[test]
+fn replays_chart_page_deletions() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let subscribers = build_chart_event_subscribers(
+      unconnected_redis_connection_pool(),
+      Arc::clone(&sqlite_connection),
+      Arc::clone(&settings),
+    )
+    .unwrap();
+    assert_eq!(subscribers.len(), 1);
+    let subscriber = &subscribers[0];
+    let publisher = EventPublisher::new(Arc::clone(&settings), Arc::clone(&sqlite_connection));
+    let chart_repository = ChartRepository::new(Arc::clone(&sqlite_connection));
+
+    let first_page = file_name("charts/top/album/1993");
+    let second_page = file_name("charts/top/album/1993/2");
+    publish_parsed(&publisher, &first_page, parsed_chart(1, "souvlaki")).await;
+    publish_parsed(&publisher, &second_page, parsed_chart(2, "pygmalion")).await;
+    publish_deleted(&publisher, &second_page).await;
+    drain(subscriber).await;
+    assert_eq!(
+      chart_albums(&chart_repository, &first_page).await,
+      vec!["souvlaki"]
+    );
+
+    EventSubscriberRepository::new(Arc::clone(&sqlite_connection))
+      .set_status(
+        "update_chart_read_models",
+        EventSubscriberStatus::ReplayRequested,
+      )
+      .await
+      .unwrap();
+    assert!(subscriber.prepare_replay().await.unwrap());
+    assert_eq!(subscriber.get_cursor().await.unwrap(), "0");
+    drain(subscriber).await;
+    subscriber.complete_replay().await.unwrap();
+
+    assert_eq!(
+      chart_albums(&chart_repository, &first_page).await,
+      vec!["souvlaki"]
+    );
+  });
+}
@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+
+use core::{
+  settings::{AlbumSearchBackend, AlbumSearchSettings, Settings, SqliteSettings},
+  sqlite::SqliteConnection,
+};
+use rustis::{bb8::Pool, client::PooledClientManager};
+use std::{future::Future, sync::Arc};
+use tempfile::TempDir;
+
+/**
+ * Settings backed by a fresh SQLite database in a temporary directory, with the SQLite album
+ * search backend. The directory is removed when the returned guard is dropped, so it must be kept
+ * alive for as long as the database is used.
+ */
+pub fn test_settings() -> (Settings, TempDir) {
+  let dir = tempfile::Builder::new()
+    .prefix("lute-test-")
+    .tempdir()
+    .unwrap();
+  let settings = Settings {
+    sqlite: SqliteSettings {
+      dir: dir.path().to_string_lossy().to_string(),
+    },
+    album_search: AlbumSearchSettings {
+      backend: AlbumSearchBackend::Sqlite,
+    },
+    ..Default::default()
+  };
+  (settings, dir)
+}
+
+/**
+ * Runs a test future on a current thread runtime. `#[tokio::test]` can't be used, as its expansion
+ * refers to `::core`, which this crate's name shadows.
+ */
+pub fn block_on<F: Future>(future: F) -> F::Output {
+  tokio::runtime::Builder::new_current_thread()
+    .enable_all()
+    .build()
+    .unwrap()
+    .block_on(future)
+}
+
+pub async fn test_sqlite_connection(settings: Arc<Settings>) -> Arc<SqliteConnection> {
+  Arc::new(SqliteConnection::new(settings).await.unwrap())
+}
+
+/**
+ * A Redis pool that never connects, for components that only use Redis with the Redis backends.
+ */
+pub fn unconnected_redis_connection_pool() -> Arc<Pool<PooledClientManager>> {
+  Arc::new(
+    Pool::builder().build_unchecked(PooledClientManager::new("redis://127.0.0.1:6379").unwrap()),
+  )
+}
@@ -0,0 +1,64 @@
+use candle_core::{Device, Tensor};
+use core::albums::{
+  album_search_index::EMBEDDING_DIMENSIONS,
+  embedding_provider::{pool_embedding, LocalEmbeddingModelDimensions},
+};
+
+fn assert_close(actual: f32, expected: f32) {
+  assert!(
+    (actual - expected).abs() < 1e-6,
+    "{} is not close to {}",
+    actual,
+    expected
+  );
+}
+
+#[test]
+fn mean_pools_and_pads_embeddings() {
+  let token_embeddings = Tensor::new(&[[[1f32, 2., 2.], [3., 6., 2.]]], &Device::Cpu).unwrap();
+  let embedding = pool_embedding(&token_embeddings).unwrap();
+
+  assert_eq!(embedding.len(), EMBEDDING_DIMENSIONS);
+  let norm = 24f32.sqrt();
+  assert_close(embedding[0], 2.0 / norm);
+  assert_close(embedding[1], 4.0 / norm);
+  assert_close(embedding[2], 2.0 / norm);
+  assert!(embedding[3..].iter().all(|value| *value == 0.0));
+  assert_close(
+    embedding.iter().map(|value| value * value).sum::<f32>(),
+    1.0,
+  );
+}
+
+#[test]
+fn reads_model_dimensions() {
+  let dimensions = LocalEmbeddingModelDimensions::from_config(
+    r#"{"hidden_size": 384, "max_position_embeddings": 512, "num_hidden_layers": 6}"#,
+  )
+  .unwrap();
+  assert_eq!(dimensions.hidden_size, 384);
+  assert_eq!(dimensions.max_position_embeddings, 512);
+  assert!(LocalEmbeddingModelDimensions::from_config(&format!(
+    r#"{{"hidden_size": {}, "max_position_embeddings": 512}}"#,
+    EMBEDDING_DIMENSIONS
+  ))
+  .is_ok());
+}
+
+#[test]
+fn rejects_models_wider_than_the_index() {
+  let error = LocalEmbeddingModelDimensions::from_config(&format!(
+    r#"{{"hidden_size": {}, "max_position_embeddings": 512}}"#,
+    EMBEDDING_DIMENSIONS + 1
+  ))
+  .err()
+  .unwrap();
+  assert_eq!(
+    error.to_string(),
+    format!(
+      "Local embedding model has {} dimensions, more than the supported {}",
+      EMBEDDING_DIMENSIONS + 1,
+      EMBEDDING_DIMENSIONS
+    )
+  );
+}
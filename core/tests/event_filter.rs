@@ -0,0 +1,146 @@
+mod common;
+
+use common::{block_on, test_settings, test_sqlite_connection};
+use core::{
+  events::{
+    event::{Event, EventKind, EventPayload, EventPayloadBuilder, Stream},
+    event_filter::EventFilter,
+    event_publisher::EventPublisher,
+    event_service::next_event_stream_reply,
+    event_subscriber_repository::EventSubscriberRepository,
+  },
+  files::file_metadata::{file_name::FileName, page_type::PageType},
+};
+use std::sync::Arc;
+use ulid::Ulid;
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+fn saved(file_name_value: &str, correlation_id: Option<&str>) -> EventPayload {
+  EventPayloadBuilder::default()
+    .event(Event::FileSaved {
+      file_id: Ulid::new(),
+      file_name: file_name(file_name_value),
+    })
+    .correlation_id(correlation_id.map(|id| id.to_string()))
+    .build()
+    .unwrap()
+}
+
+fn deleted(file_name_value: &str) -> EventPayload {
+  EventPayloadBuilder::default()
+    .event(Event::FileDeleted {
+      file_id: Ulid::new(),
+      file_name: file_name(file_name_value),
+    })
+    .build()
+    .unwrap()
+}
+
+#[test]
+fn matches_everything_without_criteria() {
+  let filter = EventFilter::default();
+  assert!(filter.matches(&saved("release/album/slowdive/souvlaki", None)));
+  assert!(filter.matches(&deleted("artist/slowdive")));
+}
+
+#[test]
+fn filters_by_event_kind() {
+  let filter = EventFilter {
+    event_kinds: vec![EventKind::FileDeleted],
+    ..Default::default()
+  };
+  assert!(filter.matches(&deleted("release/album/slowdive/souvlaki")));
+  assert!(!filter.matches(&saved("release/album/slowdive/souvlaki", None)));
+}
+
+#[test]
+fn filters_by_page_type() {
+  let filter = EventFilter {
+    page_types: vec![PageType::Artist, PageType::Chart],
+    ..Default::default()
+  };
+  assert!(filter.matches(&saved("artist/slowdive", None)));
+  assert!(filter.matches(&saved("charts/top/album/1993", None)));
+  assert!(!filter.matches(&saved("release/album/slowdive/souvlaki", None)));
+}
+
+#[test]
+fn filters_by_correlation_id_prefix() {
+  let filter = EventFilter {
+    correlation_id_prefix: Some("crawl:".to_string()),
+    ..Default::default()
+  };
+  assert!(filter.matches(&saved("artist/slowdive", Some("crawl:42"))));
+  assert!(!filter.matches(&saved("artist/slowdive", Some("import:42"))));
+  // Events without a correlation id can't match a prefix
+  assert!(!filter.matches(&saved("artist/slowdive", None)));
+}
+
+#[test]
+fn requires_every_dimension_to_match() {
+  let filter = EventFilter {
+    event_kinds: vec![EventKind::FileSaved],
+    page_types: vec![PageType::Album],
+    correlation_id_prefix: Some("crawl:".to_string()),
+  };
+  assert!(filter.matches(&saved("release/album/slowdive/souvlaki", Some("crawl:1"))));
+  assert!(!filter.matches(&saved("artist/slowdive", Some("crawl:1"))));
+  assert!(!filter.matches(&saved("release/album/slowdive/souvlaki", Some("import:1"))));
+  assert!(!filter.matches(&deleted("release/album/slowdive/souvlaki")));
+}
+
+#[test]
+fn advances_the_cursor_past_filtered_out_events() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let publisher = EventPublisher::new(Arc::clone(&settings), Arc::clone(&sqlite_connection));
+    let repository = EventSubscriberRepository::new(Arc::clone(&sqlite_connection));
+    for payload in [
+      saved("artist/slowdive", None),
+      saved("artist/mojave-3", None),
+      saved("charts/top/album/1993", None),
+    ] {
+      publisher.publish(Stream::File, payload).await.unwrap();
+    }
+    let filter = EventFilter {
+      event_kinds: vec![EventKind::FileDeleted],
+      ..Default::default()
+    };
+
+    // Every batch is filtered out, so nothing is returned but the cursor moves to the tail
+    let reply = next_event_stream_reply(
+      &repository,
+      &Stream::File,
+      "bolt_connector",
+      Some(&filter),
+      2,
+    )
+    .await
+    .unwrap();
+    assert!(reply.is_none());
+    let tail = repository.get_cursor("bolt_connector").await.unwrap();
+    assert_ne!(tail, "0");
+
+    publisher
+      .publish(Stream::File, deleted("artist/slowdive"))
+      .await
+      .unwrap();
+    let reply = next_event_stream_reply(
+      &repository,
+      &Stream::File,
+      "bolt_connector",
+      Some(&filter),
+      2,
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(reply.items.len(), 1);
+    assert!(reply.cursor.parse::<u64>().unwrap() > tail.parse::<u64>().unwrap());
+  });
+}
@@ -0,0 +1,193 @@
+use core::events::{
+  event::{Event, EventPayload},
+  event_upcaster::{EVENT_SCHEMA_VERSION, EVENT_UPCASTER_REGISTRY},
+};
+use core::parser::{parsed_file_data::ParsedFileData, parser_error::ParserError};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use ulid::Ulid;
+
+const FILE_ID: u128 = 2166807543054932046157362451605194450;
+
+/**
+ * A version 1 payload, as stored before albums had languages, credits and cover images.
+ */
+const V1_FILE_PARSED_ALBUM: &str = r#"{"type":"FileParsed","data":{"file_id":2166807543054932046157362451605194450,"file_name":"release/album/slowdive/souvlaki","data":{"type":"Album","data":{"name":"Souvlaki","rating":3.9,"rating_count":100,"artists":[{"name":"Slowdive","file_name":"artist/slowdive"}],"primary_genres":["Shoegaze"],"secondary_genres":["Dream Pop"],"descriptors":["ethereal"],"tracks":[],"release_date":"1993-05-17"}}}}"#;
+
+/**
+ * A version 2 payload, as stored before parsers were versioned.
+ */
+const V2_FILE_PARSED_ALBUM: &str = r#"{"type":"FileParsed","data":{"file_id":2166807543054932046157362451605194450,"file_name":"release/album/slowdive/souvlaki","data":{"type":"Album","data":{"name":"Souvlaki","rating":3.9,"rating_count":100,"artists":[{"name":"Slowdive","file_name":"artist/slowdive"}],"primary_genres":["Shoegaze"],"secondary_genres":["Dream Pop"],"descriptors":["ethereal"],"tracks":[],"release_date":"1993-05-17","languages":["English"],"credits":[],"cover_image_url":null}}}}"#;
+
+/**
+ * A version 3 payload, as stored when charts were a bare list of albums.
+ */
+const V3_FILE_PARSED_CHART: &str = r#"{"type":"FileParsed","data":{"file_id":2166807543054932046157362451605194450,"file_name":"charts/top/album/1993","data":{"type":"Chart","data":[{"file_name":"release/album/slowdive/souvlaki","name":"Souvlaki","rating":3.9,"rating_count":100,"artists":[{"name":"Slowdive","file_name":"artist/slowdive"}],"primary_genres":["Shoegaze"],"secondary_genres":[],"descriptors":[],"release_date":"1993-05-17"}]},"parser_version":1}}"#;
+
+/**
+ * A version 4 payload, as stored when parser errors were free-form messages.
+ */
+const V4_FILE_PARSE_FAILED: &str = r#"{"type":"FileParseFailed","data":{"file_id":2166807543054932046157362451605194450,"file_name":"release/album/slowdive/souvlaki","error":"No element found for selector: .album_title"}}"#;
+
+fn upcast(payload: &str, schema_version: u32) -> Value {
+  EVENT_UPCASTER_REGISTRY
+    .upcast(serde_json::from_str(payload).unwrap(), schema_version)
+    .unwrap()
+}
+
+#[test]
+fn upcasts_v1_album_defaults() {
+  let value = upcast(V1_FILE_PARSED_ALBUM, 1);
+  let album = &value["data"]["data"]["data"];
+  assert_eq!(album["languages"], json!([]));
+  assert_eq!(album["credits"], json!([]));
+  assert_eq!(album["cover_image_url"], Value::Null);
+
+  match EVENT_UPCASTER_REGISTRY
+    .deserialize(V1_FILE_PARSED_ALBUM, 1)
+    .unwrap()
+  {
+    Event::FileParsed {
+      file_id,
+      data: ParsedFileData::Album(album),
+      parser_version,
+      ..
+    } => {
+      assert_eq!(file_id, Ulid(FILE_ID));
+      assert_eq!(album.name, "Souvlaki");
+      assert!(album.languages.is_empty());
+      assert_eq!(parser_version, 1);
+    }
+    event => panic!("Unexpected event: {:?}", event),
+  }
+}
+
+#[test]
+fn upcasts_v2_parser_version() {
+  let value = upcast(V2_FILE_PARSED_ALBUM, 2);
+  assert_eq!(value["data"]["parser_version"], json!(1));
+  assert_eq!(
+    value["data"]["data"]["data"]["languages"],
+    json!(["English"])
+  );
+
+  match EVENT_UPCASTER_REGISTRY
+    .deserialize(V2_FILE_PARSED_ALBUM, 2)
+    .unwrap()
+  {
+    Event::FileParsed {
+      file_id,
+      parser_version,
+      ..
+    } => {
+      assert_eq!(file_id, Ulid(FILE_ID));
+      assert_eq!(parser_version, 1);
+    }
+    event => panic!("Unexpected event: {:?}", event),
+  }
+}
+
+#[test]
+fn upcasts_v3_parsed_chart() {
+  let value = upcast(V3_FILE_PARSED_CHART, 3);
+  let chart = &value["data"]["data"]["data"];
+  assert_eq!(chart["page_number"], json!(1));
+  assert_eq!(chart["total_pages"], Value::Null);
+  assert_eq!(chart["albums"][0]["name"], json!("Souvlaki"));
+
+  match EVENT_UPCASTER_REGISTRY
+    .deserialize(V3_FILE_PARSED_CHART, 3)
+    .unwrap()
+  {
+    Event::FileParsed {
+      file_id,
+      data: ParsedFileData::Chart(chart),
+      ..
+    } => {
+      assert_eq!(file_id, Ulid(FILE_ID));
+      assert_eq!(chart.albums.len(), 1);
+      assert_eq!(chart.page_number, 1);
+      assert_eq!(chart.total_pages, None);
+    }
+    event => panic!("Unexpected event: {:?}", event),
+  }
+}
+
+#[test]
+fn upcasts_v4_parser_error() {
+  let value = upcast(V4_FILE_PARSE_FAILED, 4);
+  assert_eq!(
+    value["data"]["error"],
+    json!({
+      "code": "unknown",
+      "message": "No element found for selector: .album_title",
+    })
+  );
+
+  match EVENT_UPCASTER_REGISTRY
+    .deserialize(V4_FILE_PARSE_FAILED, 4)
+    .unwrap()
+  {
+    Event::FileParseFailed { file_id, error, .. } => {
+      assert_eq!(file_id, Ulid(FILE_ID));
+      assert_eq!(
+        error,
+        ParserError::Unknown {
+          message: "No element found for selector: .album_title".to_string()
+        }
+      );
+    }
+    event => panic!("Unexpected event: {:?}", event),
+  }
+}
+
+#[test]
+fn keeps_current_payloads() {
+  let event = Event::FileParseFailed {
+    file_id: Ulid(FILE_ID),
+    file_name: "release/album/slowdive/souvlaki"
+      .to_string()
+      .try_into()
+      .unwrap(),
+    error: ParserError::element_not_found(".album_title"),
+  };
+  let payload = serde_json::to_string(&event).unwrap();
+  assert_eq!(
+    upcast(&payload, EVENT_SCHEMA_VERSION),
+    serde_json::from_str::<Value>(&payload).unwrap()
+  );
+  match EVENT_UPCASTER_REGISTRY
+    .deserialize(&payload, EVENT_SCHEMA_VERSION)
+    .unwrap()
+  {
+    Event::FileParseFailed { file_id, error, .. } => {
+      assert_eq!(file_id, Ulid(FILE_ID));
+      assert_eq!(error, ParserError::element_not_found(".album_title"));
+    }
+    event => panic!("Unexpected event: {:?}", event),
+  }
+}
+
+#[test]
+fn rejects_newer_payloads() {
+  assert!(EVENT_UPCASTER_REGISTRY
+    .deserialize(V4_FILE_PARSE_FAILED, EVENT_SCHEMA_VERSION + 1)
+    .is_err());
+}
+
+#[test]
+fn upcasts_unversioned_stream_payloads() {
+  let payload = HashMap::from([("event".to_string(), V1_FILE_PARSED_ALBUM.to_string())]);
+  let payload = EventPayload::try_from(&payload).unwrap();
+  match payload.event {
+    Event::FileParsed {
+      file_id,
+      parser_version,
+      ..
+    } => {
+      assert_eq!(file_id, Ulid(FILE_ID));
+      assert_eq!(parser_version, 1);
+    }
+    event => panic!("Unexpected event: {:?}", event),
+  }
+}
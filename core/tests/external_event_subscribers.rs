@@ -0,0 +1,75 @@
+mod common;
+
+use common::{block_on, test_settings, test_sqlite_connection};
+use core::{
+  events::{
+    event::Stream, event_service::EventService,
+    event_subscriber_repository::EventSubscriberRepository,
+  },
+  proto::{self, event_service_server::EventService as _},
+};
+use std::{collections::HashSet, sync::Arc};
+use tonic::{Code, Request};
+
+#[test]
+fn registers_subscribers_with_existing_cursors() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let repository = EventSubscriberRepository::new(sqlite_connection);
+
+    // Connectors kept a cursor before they registered as external subscribers.
+    repository.set_cursor("bolt_connector", "42").await.unwrap();
+    repository
+      .register_external_subscriber(
+        "bolt_connector",
+        vec![Stream::Parser],
+        Some("graph".to_string()),
+        None,
+      )
+      .await
+      .unwrap();
+
+    assert_eq!(repository.get_cursor("bolt_connector").await.unwrap(), "42");
+    assert_eq!(
+      repository
+        .get_external_subscriber_streams("bolt_connector")
+        .await
+        .unwrap(),
+      Some(vec![Stream::Parser])
+    );
+  });
+}
+
+fn register_request(subscriber_id: &str) -> Request<proto::RegisterSubscriberRequest> {
+  Request::new(proto::RegisterSubscriberRequest {
+    subscriber_id: subscriber_id.to_string(),
+    stream_ids: vec![Stream::Parser.tag()],
+    owner: None,
+    description: None,
+  })
+}
+
+#[test]
+fn rejects_registrations_with_internal_subscriber_ids() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let service = EventService::new(
+      sqlite_connection,
+      HashSet::from(["update_album_read_models".to_string()]),
+    );
+
+    let status = service
+      .register_subscriber(register_request("update_album_read_models"))
+      .await
+      .unwrap_err();
+    assert_eq!(status.code(), Code::AlreadyExists);
+    assert!(service
+      .register_subscriber(register_request("bolt_connector"))
+      .await
+      .is_ok());
+  });
+}
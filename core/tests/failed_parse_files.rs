@@ -0,0 +1,115 @@
+mod common;
+
+use chrono::NaiveDateTime;
+use common::{block_on, test_settings, test_sqlite_connection};
+use core::{
+  files::file_metadata::{file_name::FileName, page_type::PageType},
+  parser::{
+    failed_parse_files_repository::{FailedParseFile, FailedParseFilesRepository},
+    parser_error::ParserError,
+  },
+};
+use std::sync::Arc;
+
+fn failed_parse_file(file_name: &str, error: ParserError) -> FailedParseFile {
+  FailedParseFile {
+    file_name: FileName::try_from(file_name.to_string()).unwrap(),
+    error,
+    last_attempted_at: NaiveDateTime::parse_from_str("2024-03-01 12:00:00", "%Y-%m-%d %H:%M:%S")
+      .unwrap(),
+  }
+}
+
+#[test]
+fn stores_and_aggregates_failed_parse_files() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let repository = FailedParseFilesRepository::new(sqlite_connection);
+    let missing_cover = ParserError::element_not_found(".page_release_art_frame").in_field("cover");
+    for failed_parse_file in [
+      failed_parse_file("release/album/slowdive/souvlaki", missing_cover.clone()),
+      failed_parse_file("release/album/slowdive/pygmalion", missing_cover.clone()),
+      failed_parse_file(
+        "artist/slowdive",
+        ParserError::invalid_value("n/a", Some(".artist_info")).in_field("formed_date"),
+      ),
+    ] {
+      repository.put(failed_parse_file).await.unwrap();
+    }
+
+    let failed_parse_files = repository
+      .find_many(Some("element_not_found:cover"))
+      .await
+      .unwrap();
+    assert_eq!(failed_parse_files.len(), 2);
+    assert_eq!(failed_parse_files[0].error, missing_cover);
+    assert_eq!(
+      failed_parse_files[0].last_attempted_at.to_string(),
+      "2024-03-01 12:00:00"
+    );
+    assert_eq!(repository.find_many(None).await.unwrap().len(), 3);
+
+    let aggregates = repository.aggregate_errors(None).await.unwrap();
+    assert_eq!(aggregates.len(), 2);
+    assert_eq!(aggregates[0].error, "element_not_found:cover");
+    assert_eq!(aggregates[0].code, "element_not_found");
+    assert_eq!(aggregates[0].field.as_deref(), Some("cover"));
+    assert_eq!(aggregates[0].count, 2);
+
+    let aggregates = repository
+      .aggregate_errors(Some(PageType::Artist))
+      .await
+      .unwrap();
+    assert_eq!(aggregates.len(), 1);
+    assert_eq!(aggregates[0].error, "invalid_value:formed_date");
+    assert_eq!(aggregates[0].count, 1);
+
+    repository
+      .remove(&FileName::try_from("release/album/slowdive/souvlaki".to_string()).unwrap())
+      .await
+      .unwrap();
+    let aggregates = repository
+      .aggregate_errors(Some(PageType::Album))
+      .await
+      .unwrap();
+    assert_eq!(aggregates[0].count, 1);
+  });
+}
+
+#[test]
+fn replaces_the_error_of_a_file_that_failed_again() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let repository = FailedParseFilesRepository::new(sqlite_connection);
+    repository
+      .put(failed_parse_file(
+        "artist/slowdive",
+        ParserError::no_results(".artist_info"),
+      ))
+      .await
+      .unwrap();
+    repository
+      .put(failed_parse_file(
+        "artist/slowdive",
+        ParserError::element_not_found(".artist_name_hdr").in_field("name"),
+      ))
+      .await
+      .unwrap();
+
+    let failed_parse_files = repository.find_many(None).await.unwrap();
+    assert_eq!(failed_parse_files.len(), 1);
+    assert_eq!(
+      failed_parse_files[0].error.aggregation_key(),
+      "element_not_found:name"
+    );
+    assert!(repository
+      .find_many(Some("no_results"))
+      .await
+      .unwrap()
+      .is_empty());
+  });
+}
@@ -0,0 +1,57 @@
+use core::files::file_metadata::file_name::FileName;
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+#[test]
+fn reads_a_single_year_chart_as_its_first_page() {
+  let chart = file_name("charts/top/album/2014");
+  assert_eq!(chart.chart_page_number(), Some(1));
+  assert_eq!(chart.chart_first_page(), Some(chart.clone()));
+}
+
+#[test]
+fn reads_the_page_number_after_a_single_year() {
+  let chart = file_name("charts/top/album/2014/2");
+  assert_eq!(chart.chart_page_number(), Some(2));
+  assert_eq!(
+    chart.chart_first_page(),
+    Some(file_name("charts/top/album/2014"))
+  );
+}
+
+#[test]
+fn reads_the_page_number_after_filters() {
+  let chart = file_name("charts/top/album/2010-2019/g:shoegaze/3");
+  assert_eq!(chart.chart_page_number(), Some(3));
+  assert_eq!(
+    chart.chart_first_page(),
+    Some(file_name("charts/top/album/2010-2019/g:shoegaze"))
+  );
+  assert_eq!(
+    file_name("charts/top/album/2010-2019/g:shoegaze").chart_page_number(),
+    Some(1)
+  );
+}
+
+#[test]
+fn builds_chart_page_file_names() {
+  let chart = file_name("charts/top/album/2014/2");
+  assert_eq!(
+    chart.chart_page(1).unwrap(),
+    file_name("charts/top/album/2014")
+  );
+  assert_eq!(
+    chart.chart_page(3).unwrap(),
+    file_name("charts/top/album/2014/3")
+  );
+}
+
+#[test]
+fn has_no_chart_page_outside_charts() {
+  let album = file_name("release/album/slowdive/souvlaki");
+  assert_eq!(album.chart_page_number(), None);
+  assert_eq!(album.chart_first_page(), None);
+  assert!(album.chart_page(2).is_err());
+}
@@ -0,0 +1,57 @@
+use core::genres::genre_hierarchy::GenreHierarchy;
+
+fn hierarchy() -> GenreHierarchy {
+  GenreHierarchy::from_json(
+    r#"[
+      { "name": "Post-Punk", "parents": ["Rock"] },
+      { "name": "Gothic Rock", "parents": ["Post-Punk", "Rock"] },
+      { "name": "Shoegaze", "parents": ["Noise Pop", "Dream Pop"] }
+    ]"#,
+  )
+  .unwrap()
+}
+
+fn genres(names: &[&str]) -> Vec<String> {
+  names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn expands_genres_to_descendants() {
+  assert_eq!(
+    hierarchy().with_descendants(&genres(&["Rock", "Jazz"])),
+    genres(&["Rock", "Post-Punk", "Gothic Rock", "Jazz"])
+  );
+}
+
+#[test]
+fn expands_genres_to_ancestors() {
+  assert_eq!(
+    hierarchy().with_ancestors(&genres(&["Gothic Rock", "Shoegaze"])),
+    genres(&[
+      "Gothic Rock",
+      "Post-Punk",
+      "Rock",
+      "Shoegaze",
+      "Noise Pop",
+      "Dream Pop"
+    ])
+  );
+}
+
+#[test]
+fn terminates_on_cycles() {
+  let hierarchy = GenreHierarchy::from_json(
+    r#"[{ "name": "A", "parents": ["B"] }, { "name": "B", "parents": ["A"] }]"#,
+  )
+  .unwrap();
+  assert_eq!(
+    hierarchy.with_descendants(&genres(&["A"])),
+    genres(&["A", "B"])
+  );
+}
+
+#[test]
+fn rejects_invalid_imports() {
+  assert!(GenreHierarchy::from_json("{}").is_err());
+  assert!(GenreHierarchy::from_json(r#"[{ "name": " ", "parents": [] }]"#).is_err());
+}
@@ -0,0 +1,64 @@
+use core::helpers::hmac::hmac_sha256;
+use data_encoding::HEXLOWER;
+
+/**
+ * HMAC-SHA-256 test cases from RFC 4231, section 4.
+ */
+fn assert_hmac(key: &[u8], message: &[u8], expected: &str) {
+  assert_eq!(HEXLOWER.encode(&hmac_sha256(key, message)), expected);
+}
+
+#[test]
+fn test_case_1() {
+  assert_hmac(
+    &[0x0b; 20],
+    b"Hi There",
+    "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+  );
+}
+
+#[test]
+fn test_case_2() {
+  assert_hmac(
+    b"Jefe",
+    b"what do ya want for nothing?",
+    "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+  );
+}
+
+#[test]
+fn test_case_3() {
+  assert_hmac(
+    &[0xaa; 20],
+    &[0xdd; 50],
+    "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+  );
+}
+
+#[test]
+fn test_case_4() {
+  let key = (0x01..=0x19).collect::<Vec<u8>>();
+  assert_hmac(
+    &key,
+    &[0xcd; 50],
+    "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+  );
+}
+
+#[test]
+fn test_case_6_key_larger_than_block() {
+  assert_hmac(
+    &[0xaa; 131],
+    b"Test Using Larger Than Block-Size Key - Hash Key First",
+    "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+  );
+}
+
+#[test]
+fn test_case_7_key_and_message_larger_than_block() {
+  assert_hmac(
+    &[0xaa; 131],
+    b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+    "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+  );
+}
@@ -0,0 +1,247 @@
+mod common;
+
+use common::{block_on, test_settings, test_sqlite_connection, unconnected_redis_connection_pool};
+use core::{
+  events::{
+    event::{Event, EventPayloadBuilder, Stream},
+    event_publisher::EventPublisher,
+  },
+  files::file_metadata::{file_name::FileName, page_type::PageType},
+  parser::{
+    parsed_file_data::{ParsedArtist, ParsedFileData},
+    parsed_file_version_repository::ParsedFileVersionRepository,
+    parser::parser_version,
+    parser_event_subscribers::build_parser_event_subscribers,
+  },
+};
+use std::{sync::Arc, time::Duration};
+use tempfile::TempDir;
+use ulid::Ulid;
+
+const CLAIM_TTL: Duration = Duration::from_secs(60 * 60);
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+async fn repository() -> (ParsedFileVersionRepository, TempDir) {
+  let (settings, dir) = test_settings();
+  let repository =
+    ParsedFileVersionRepository::new(test_sqlite_connection(Arc::new(settings)).await);
+  (repository, dir)
+}
+
+fn outdated_album_version() -> u32 {
+  parser_version(&PageType::Album) - 1
+}
+
+#[test]
+fn claims_files_parsed_by_older_parsers() {
+  block_on(async {
+    let (repository, _dir) = repository().await;
+    let outdated = file_name("release/album/slowdive/souvlaki");
+    let current = file_name("release/album/slowdive/pygmalion");
+    repository
+      .put_parsed(&outdated, outdated_album_version())
+      .await
+      .unwrap();
+    repository
+      .put_parsed(&current, parser_version(&PageType::Album))
+      .await
+      .unwrap();
+
+    assert_eq!(
+      repository.claim_outdated(10, CLAIM_TTL).await.unwrap(),
+      vec![outdated]
+    );
+  });
+}
+
+#[test]
+fn does_not_claim_enqueued_files_again() {
+  block_on(async {
+    let (repository, _dir) = repository().await;
+    let outdated = file_name("release/album/slowdive/souvlaki");
+    repository
+      .put_parsed(&outdated, outdated_album_version())
+      .await
+      .unwrap();
+
+    assert_eq!(
+      repository.claim_outdated(10, CLAIM_TTL).await.unwrap(),
+      vec![outdated.clone()]
+    );
+    // The re-parse has not been processed yet.
+    assert!(repository
+      .claim_outdated(10, CLAIM_TTL)
+      .await
+      .unwrap()
+      .is_empty());
+    // Claims of re-parses that never completed expire.
+    assert_eq!(
+      repository
+        .claim_outdated(10, Duration::from_secs(0))
+        .await
+        .unwrap(),
+      vec![outdated.clone()]
+    );
+
+    repository
+      .put_parsed(&outdated, parser_version(&PageType::Album))
+      .await
+      .unwrap();
+    assert!(repository
+      .claim_outdated(10, Duration::from_secs(0))
+      .await
+      .unwrap()
+      .is_empty());
+  });
+}
+
+#[test]
+fn does_not_claim_files_that_failed_with_the_current_parser() {
+  block_on(async {
+    let (repository, _dir) = repository().await;
+    let failed = file_name("release/album/slowdive/souvlaki");
+    repository
+      .put_parsed(&failed, outdated_album_version())
+      .await
+      .unwrap();
+    repository
+      .put_failed(&failed, parser_version(&PageType::Album))
+      .await
+      .unwrap();
+
+    assert!(repository
+      .claim_outdated(10, Duration::from_secs(0))
+      .await
+      .unwrap()
+      .is_empty());
+  });
+}
+
+#[test]
+fn claims_at_most_the_limit() {
+  block_on(async {
+    let (repository, _dir) = repository().await;
+    for name in ["souvlaki", "pygmalion", "just-for-a-day"] {
+      repository
+        .put_parsed(
+          &file_name(&format!("release/album/slowdive/{}", name)),
+          outdated_album_version(),
+        )
+        .await
+        .unwrap();
+    }
+
+    assert_eq!(
+      repository.claim_outdated(2, CLAIM_TTL).await.unwrap().len(),
+      2
+    );
+    assert_eq!(
+      repository.claim_outdated(2, CLAIM_TTL).await.unwrap().len(),
+      1
+    );
+    assert!(repository
+      .claim_outdated(2, CLAIM_TTL)
+      .await
+      .unwrap()
+      .is_empty());
+  });
+}
+
+#[test]
+fn forgets_deleted_files() {
+  block_on(async {
+    let (repository, _dir) = repository().await;
+    let deleted = file_name("release/album/slowdive/souvlaki");
+    repository
+      .put_parsed(&deleted, outdated_album_version())
+      .await
+      .unwrap();
+    repository.delete(&deleted).await.unwrap();
+
+    assert!(repository
+      .claim_outdated(10, CLAIM_TTL)
+      .await
+      .unwrap()
+      .is_empty());
+  });
+}
+
+async fn publish(publisher: &EventPublisher, stream: Stream, event: Event) {
+  publisher
+    .publish(
+      stream,
+      EventPayloadBuilder::default().event(event).build().unwrap(),
+    )
+    .await
+    .unwrap();
+}
+
+#[test]
+fn applies_deletions_after_the_parses_they_follow() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let subscriber = build_parser_event_subscribers(
+      unconnected_redis_connection_pool(),
+      Arc::clone(&sqlite_connection),
+      Arc::clone(&settings),
+    )
+    .unwrap()
+    .into_iter()
+    .find(|subscriber| subscriber.id == "update_parsed_file_versions")
+    .unwrap();
+    let publisher = EventPublisher::new(Arc::clone(&settings), Arc::clone(&sqlite_connection));
+    let repository = ParsedFileVersionRepository::new(Arc::clone(&sqlite_connection));
+
+    let deleted = file_name("artist/slowdive");
+    let kept = file_name("artist/mojave-3");
+    for file_name in [&deleted, &kept] {
+      publish(
+        &publisher,
+        Stream::Parser,
+        Event::FileParsed {
+          file_id: Ulid::new(),
+          file_name: file_name.clone(),
+          data: ParsedFileData::Artist(ParsedArtist {
+            name: "Slowdive".to_string(),
+            albums: vec![],
+            formed_date: None,
+            disbanded_date: None,
+            location: None,
+            country: None,
+            genres: vec![],
+            members: vec![],
+            member_of: vec![],
+            related_artists: vec![],
+          }),
+          parser_version: parser_version(&PageType::Artist) - 1,
+        },
+      )
+      .await;
+    }
+    publish(
+      &publisher,
+      Stream::File,
+      Event::FileDeleted {
+        file_id: Ulid::new(),
+        file_name: deleted.clone(),
+      },
+    )
+    .await;
+
+    // A new or replayed subscriber starts from the beginning of both streams.
+    assert_eq!(subscriber.get_cursor().await.unwrap(), "0");
+    while let Some(cursor) = subscriber.poll(subscriber.batch_size).await.unwrap() {
+      subscriber.set_cursor(&cursor).await.unwrap();
+    }
+
+    assert_eq!(
+      repository.claim_outdated(10, CLAIM_TTL).await.unwrap(),
+      vec![kept]
+    );
+  });
+}
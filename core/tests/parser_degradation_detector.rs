@@ -0,0 +1,166 @@
+mod common;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use common::{block_on, test_settings, test_sqlite_connection};
+use core::{
+  files::file_metadata::page_type::PageType,
+  parser::{
+    parser_degradation_detector::detect_degradations,
+    parser_health_repository::{ParseOutcomeCount, ParserHealthRepository},
+  },
+  settings::ParserDegradationSettings,
+};
+use std::sync::Arc;
+
+const SELECTOR_ERROR: &str = "element_not_found:.album_title";
+
+fn settings() -> ParserDegradationSettings {
+  ParserDegradationSettings {
+    window_seconds: 3600,
+    baseline_seconds: 7 * 24 * 3600,
+    min_samples: 20,
+    failure_rate_increase_percent: 30,
+    check_interval_seconds: 60,
+  }
+}
+
+fn detected_at() -> NaiveDateTime {
+  NaiveDate::from_ymd_opt(2023, 10, 1)
+    .unwrap()
+    .and_hms_opt(12, 0, 0)
+    .unwrap()
+}
+
+/**
+ * Outcome counts of album pages with the given number of successes and selector failures.
+ */
+fn album_outcomes(successes: u32, failures: u32) -> Vec<ParseOutcomeCount> {
+  let mut counts = vec![];
+  if successes > 0 {
+    counts.push(ParseOutcomeCount {
+      page_type: PageType::Album,
+      error: None,
+      count: successes,
+    });
+  }
+  if failures > 0 {
+    counts.push(ParseOutcomeCount {
+      page_type: PageType::Album,
+      error: Some(SELECTOR_ERROR.to_string()),
+      count: failures,
+    });
+  }
+  counts
+}
+
+#[test]
+fn ignores_windows_below_min_samples() {
+  let degradations = detect_degradations(
+    album_outcomes(0, 19),
+    album_outcomes(100, 0),
+    &settings(),
+    detected_at(),
+  );
+  assert!(degradations.is_empty());
+}
+
+#[test]
+fn ignores_increases_below_threshold() {
+  let degradations = detect_degradations(
+    album_outcomes(75, 25),
+    album_outcomes(100, 0),
+    &settings(),
+    detected_at(),
+  );
+  assert!(degradations.is_empty());
+}
+
+#[test]
+fn detects_spikes_per_page_type_and_error() {
+  let mut degradations = detect_degradations(
+    album_outcomes(10, 30),
+    album_outcomes(95, 5),
+    &settings(),
+    detected_at(),
+  );
+  degradations.sort_by_key(|degradation| degradation.error.clone());
+  assert_eq!(degradations.len(), 2);
+
+  assert_eq!(degradations[0].page_type, PageType::Album);
+  assert_eq!(degradations[0].error, None);
+  assert_eq!(degradations[0].failure_rate, 0.75);
+  assert_eq!(degradations[0].baseline_failure_rate, 0.05);
+  assert_eq!(degradations[0].sample_count, 40);
+  assert_eq!(degradations[0].detected_at, detected_at());
+
+  assert_eq!(degradations[1].error, Some(SELECTOR_ERROR.to_string()));
+  assert_eq!(degradations[1].failure_rate, 0.75);
+}
+
+#[test]
+fn detects_spikes_without_baseline() {
+  let degradations = detect_degradations(album_outcomes(0, 20), vec![], &settings(), detected_at());
+  assert_eq!(degradations.len(), 2);
+  assert!(degradations
+    .iter()
+    .all(|degradation| degradation.baseline_failure_rate == 0.0));
+}
+
+#[test]
+fn ignores_recovered_windows() {
+  // The baseline still contains the spike, but the window is back to normal.
+  let degradations = detect_degradations(
+    album_outcomes(98, 2),
+    album_outcomes(70, 30),
+    &settings(),
+    detected_at(),
+  );
+  assert!(degradations.is_empty());
+}
+
+#[test]
+fn records_each_degradation_once_until_cleared() {
+  block_on(async {
+    let (sqlite_settings, _dir) = test_settings();
+    let sqlite_connection = test_sqlite_connection(Arc::new(sqlite_settings)).await;
+    let repository = ParserHealthRepository::new(sqlite_connection);
+    let baseline_start = detected_at() - Duration::days(1);
+    let window_start = detected_at() - Duration::hours(1);
+    for _ in 0..40 {
+      repository
+        .record_outcome(&PageType::Album, None, baseline_start)
+        .await
+        .unwrap();
+    }
+    for _ in 0..20 {
+      repository
+        .record_outcome(
+          &PageType::Album,
+          Some(SELECTOR_ERROR.to_string()),
+          window_start,
+        )
+        .await
+        .unwrap();
+    }
+
+    let window = repository
+      .get_outcome_counts(window_start, detected_at())
+      .await
+      .unwrap();
+    let baseline = repository
+      .get_outcome_counts(baseline_start, window_start)
+      .await
+      .unwrap();
+    let degradations = detect_degradations(window, baseline, &settings(), detected_at());
+    assert_eq!(degradations.len(), 2);
+    for degradation in &degradations {
+      assert!(repository.put_degradation(degradation).await.unwrap());
+      assert!(!repository.put_degradation(degradation).await.unwrap());
+    }
+    assert_eq!(repository.get_degradations().await.unwrap().len(), 2);
+
+    repository.clear_degradations().await.unwrap();
+    assert!(repository.get_degradations().await.unwrap().is_empty());
+    assert!(repository.put_degradation(&degradations[0]).await.unwrap());
+  });
+}
@@ -0,0 +1,122 @@
+use core::parser::parser_error::{split_aggregation_key, ParserError, PARSER_ERROR_CODES};
+use serde_json::json;
+
+fn errors() -> Vec<ParserError> {
+  vec![
+    ParserError::InvalidHtml {
+      message: "Unexpected end of input".to_string(),
+    },
+    ParserError::element_not_found(".album_title"),
+    ParserError::element_not_found(".album_title").in_field("name"),
+    ParserError::invalid_value("n/a", Some(".avg_rating")),
+    ParserError::invalid_value("n/a", None).in_field("rating"),
+    ParserError::no_results(".infobox"),
+    ParserError::Unknown {
+      message: "Something went wrong".to_string(),
+    },
+  ]
+}
+
+#[test]
+fn includes_field_in_aggregation_key() {
+  assert_eq!(
+    ParserError::element_not_found(".album_title").aggregation_key(),
+    "element_not_found"
+  );
+  assert_eq!(
+    ParserError::element_not_found(".album_title")
+      .in_field("name")
+      .aggregation_key(),
+    "element_not_found:name"
+  );
+  assert_eq!(
+    ParserError::invalid_value("n/a", None)
+      .in_field("rating")
+      .in_field("album")
+      .aggregation_key(),
+    "invalid_value:rating"
+  );
+}
+
+#[test]
+fn round_trips_aggregation_keys() {
+  for error in errors() {
+    assert!(PARSER_ERROR_CODES.contains(&error.code()));
+    assert_eq!(
+      split_aggregation_key(&error.aggregation_key()),
+      (
+        error.code().to_string(),
+        error.field().map(|field| field.to_string())
+      )
+    );
+  }
+}
+
+#[test]
+fn splits_legacy_aggregation_keys_as_unknown() {
+  assert_eq!(
+    split_aggregation_key("No element found for selector: .album_title"),
+    ("unknown".to_string(), None)
+  );
+  assert_eq!(
+    split_aggregation_key("Failed to parse rating"),
+    ("unknown".to_string(), None)
+  );
+}
+
+#[test]
+fn serializes_with_code_tag() {
+  assert_eq!(
+    serde_json::to_value(ParserError::element_not_found(".album_title").in_field("name")).unwrap(),
+    json!({
+      "code": "element_not_found",
+      "field": "name",
+      "selector": ".album_title",
+    })
+  );
+  assert_eq!(
+    serde_json::to_value(ParserError::invalid_value("n/a", None)).unwrap(),
+    json!({
+      "code": "invalid_value",
+      "field": null,
+      "selector": null,
+      "value": "n/a",
+    })
+  );
+  assert_eq!(
+    serde_json::to_value(ParserError::no_results(".infobox")).unwrap(),
+    json!({
+      "code": "no_results",
+      "selector": ".infobox",
+    })
+  );
+}
+
+#[test]
+fn round_trips_wire_format() {
+  for error in errors() {
+    let value = serde_json::to_value(&error).unwrap();
+    assert_eq!(value["code"], json!(error.code()));
+    assert_eq!(serde_json::from_value::<ParserError>(value).unwrap(), error);
+  }
+}
+
+#[test]
+fn deserializes_upcast_legacy_errors() {
+  let error = serde_json::from_value::<ParserError>(json!({
+    "code": "unknown",
+    "message": "No element found for selector: .album_title",
+  }))
+  .unwrap();
+  assert_eq!(
+    error,
+    ParserError::Unknown {
+      message: "No element found for selector: .album_title".to_string()
+    }
+  );
+  assert_eq!(error.aggregation_key(), "unknown");
+  assert_eq!(
+    error.to_string(),
+    "No element found for selector: .album_title"
+  );
+}
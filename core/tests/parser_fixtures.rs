@@ -0,0 +1,65 @@
+use core::parser::parser_fixtures::{list_parser_fixtures, PAGE_TYPES};
+
+fn first_difference(expected: &str, actual: &str) -> String {
+  let line = expected
+    .lines()
+    .zip(actual.lines())
+    .position(|(expected, actual)| expected != actual)
+    .unwrap_or(expected.lines().count().min(actual.lines().count()));
+  format!(
+    "line {}:\n  expected: {}\n  actual:   {}",
+    line + 1,
+    expected.lines().nth(line).unwrap_or("<end of snapshot>"),
+    actual.lines().nth(line).unwrap_or("<end of output>")
+  )
+}
+
+/**
+ * Each page type needs a few pages, so that layout variants are covered.
+ */
+const MIN_FIXTURES_PER_PAGE_TYPE: usize = 3;
+
+#[test]
+fn every_page_type_has_fixtures() {
+  let fixtures = list_parser_fixtures().unwrap();
+  for page_type in PAGE_TYPES {
+    let count = fixtures
+      .iter()
+      .filter(|fixture| fixture.page_type == page_type)
+      .count();
+    assert!(
+      count >= MIN_FIXTURES_PER_PAGE_TYPE,
+      "Only {} parser fixtures for page type {}",
+      count,
+      page_type.to_string()
+    );
+  }
+}
+
+/**
+ * Parser output must match the checked in snapshots. Run `cargo run --bin
+ * refresh_parser_fixtures` to accept intended changes, and review the snapshot diff.
+ */
+#[test]
+fn parser_output_matches_snapshots() {
+  let failures = list_parser_fixtures()
+    .unwrap()
+    .into_iter()
+    .filter_map(|fixture| {
+      let result = fixture
+        .render()
+        .and_then(|actual| fixture.read_snapshot().map(|expected| (expected, actual)));
+      match result {
+        Ok((expected, actual)) if expected == actual => None,
+        Ok((expected, actual)) => Some(format!(
+          "{} differs from its snapshot at {}",
+          fixture.page_path.display(),
+          first_difference(&expected, &actual)
+        )),
+        Err(e) => Some(e.to_string()),
+      }
+    })
+    .collect::<Vec<String>>();
+
+  assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
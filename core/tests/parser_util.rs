@@ -0,0 +1,92 @@
+use chrono::NaiveDate;
+use core::parser::util::{
+  expand_role_tracks, parse_chart_positions, parse_duration_seconds, parse_release_date,
+};
+
+fn positions(values: &[&str]) -> Vec<String> {
+  values.iter().map(|value| value.to_string()).collect()
+}
+
+#[test]
+fn parses_release_dates() {
+  assert_eq!(
+    parse_release_date("1993".to_string()).unwrap(),
+    NaiveDate::from_ymd_opt(1993, 1, 1).unwrap()
+  );
+  assert_eq!(
+    parse_release_date("May 1993".to_string()).unwrap(),
+    NaiveDate::from_ymd_opt(1993, 5, 1).unwrap()
+  );
+  assert_eq!(
+    parse_release_date(" 17 May 1993 ".to_string()).unwrap(),
+    NaiveDate::from_ymd_opt(1993, 5, 17).unwrap()
+  );
+  assert!(parse_release_date("".to_string()).is_err());
+}
+
+#[test]
+fn parses_durations() {
+  assert_eq!(parse_duration_seconds("4:05").unwrap(), 245);
+  assert_eq!(parse_duration_seconds(" 1:02:03 ").unwrap(), 3723);
+  assert_eq!(parse_duration_seconds("0:59").unwrap(), 59);
+}
+
+#[test]
+fn rejects_invalid_durations() {
+  assert!(parse_duration_seconds("").is_err());
+  assert!(parse_duration_seconds("245").is_err());
+  assert!(parse_duration_seconds("4:xx").is_err());
+  assert!(parse_duration_seconds("1:2:3:4").is_err());
+}
+
+#[test]
+fn parses_chart_positions() {
+  assert_eq!(
+    parse_chart_positions("#3 for 1997, #120 overall"),
+    vec![("1997".to_string(), 3), ("overall".to_string(), 120)]
+  );
+  assert_eq!(
+    parse_chart_positions("#1,204 overall"),
+    vec![("overall".to_string(), 1204)]
+  );
+}
+
+#[test]
+fn skips_malformed_chart_positions() {
+  assert!(parse_chart_positions("").is_empty());
+  assert!(parse_chart_positions("overall").is_empty());
+  assert_eq!(
+    parse_chart_positions("#x for 1997, #5, #2 for the 1990s"),
+    vec![("the 1990s".to_string(), 2)]
+  );
+}
+
+#[test]
+fn expands_role_track_ranges() {
+  let track_positions = positions(&["1", "2", "3", "4", "5"]);
+  assert_eq!(
+    expand_role_tracks("1-3, 5", &track_positions),
+    positions(&["1", "2", "3", "5"])
+  );
+  assert_eq!(expand_role_tracks("4", &track_positions), positions(&["4"]));
+}
+
+#[test]
+fn expands_role_track_ranges_across_sides() {
+  let track_positions = positions(&["A1", "A2", "A3", "B1", "B2"]);
+  assert_eq!(
+    expand_role_tracks("A2 to B1, B2", &track_positions),
+    positions(&["A2", "A3", "B1", "B2"])
+  );
+}
+
+#[test]
+fn skips_unknown_and_reversed_role_tracks() {
+  let track_positions = positions(&["1", "2", "3"]);
+  assert!(expand_role_tracks("3-1", &track_positions).is_empty());
+  assert!(expand_role_tracks("7", &track_positions).is_empty());
+  assert_eq!(
+    expand_role_tracks("2, 9-10", &track_positions),
+    positions(&["2"])
+  );
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use common::{block_on, test_settings, test_sqlite_connection};
+use core::{
+  files::file_metadata::file_name::FileName,
+  lookup::album_search_lookup::AlbumSearchLookupQuery,
+  profile::{
+    profile::ProfileId, profile_repository::ProfileRepository,
+    spotify_import_repository::SpotifyImportRepository,
+  },
+};
+use std::sync::Arc;
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+fn profile_id(value: &str) -> ProfileId {
+  ProfileId::try_from(value.to_string()).unwrap()
+}
+
+#[test]
+fn stores_profiles_and_their_albums() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let repository = ProfileRepository::new(sqlite_connection);
+    let id = profile_id("shoegaze");
+    let souvlaki = file_name("release/album/slowdive/souvlaki");
+
+    repository
+      .insert(id.clone(), "Shoegaze".to_string())
+      .await
+      .unwrap();
+    assert!(repository
+      .insert(id.clone(), "Shoegaze".to_string())
+      .await
+      .is_err());
+    assert!(repository.exists(&id).await.unwrap());
+    assert!(!repository
+      .is_album_on_profile(&id, &souvlaki)
+      .await
+      .unwrap());
+
+    let (profile, new_addition) = repository
+      .put_album_on_profile(&id, &souvlaki, 2)
+      .await
+      .unwrap();
+    assert!(new_addition);
+    assert_eq!(profile.albums.get(&souvlaki), Some(&2));
+    let (profile, new_addition) = repository
+      .put_album_on_profile(&id, &souvlaki, 5)
+      .await
+      .unwrap();
+    assert!(!new_addition);
+    assert_eq!(profile.albums.get(&souvlaki), Some(&5));
+
+    repository
+      .insert(profile_id("dream-pop"), "Dream Pop".to_string())
+      .await
+      .unwrap();
+    let profiles = repository.get_all().await.unwrap();
+    assert_eq!(profiles.len(), 2);
+    assert_eq!(profiles[0].id, profile_id("dream-pop"));
+    assert!(profiles[0].albums.is_empty());
+    assert_eq!(profiles[1].albums.len(), 1);
+
+    repository
+      .remove_album_from_profile(&id, &souvlaki)
+      .await
+      .unwrap();
+    assert!(repository.get(&id).await.unwrap().albums.is_empty());
+
+    repository.delete(&id).await.unwrap();
+    assert!(repository.find(&id).await.unwrap().is_none());
+    assert!(repository.delete(&id).await.is_err());
+    assert!(repository
+      .put_album_on_profile(&id, &souvlaki, 1)
+      .await
+      .is_err());
+  });
+}
+
+#[test]
+fn stores_spotify_import_lookup_subscriptions() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let settings = Arc::new(settings);
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let repository = SpotifyImportRepository::new(sqlite_connection);
+    let souvlaki = AlbumSearchLookupQuery::new("Souvlaki".to_string(), "Slowdive".to_string());
+    let pygmalion = AlbumSearchLookupQuery::new("Pygmalion".to_string(), "Slowdive".to_string());
+    let shoegaze = profile_id("shoegaze");
+    let dream_pop = profile_id("dream-pop");
+
+    repository
+      .put_subscription(&souvlaki, &shoegaze, 1)
+      .await
+      .unwrap();
+    repository
+      .put_subscription(&souvlaki, &shoegaze, 3)
+      .await
+      .unwrap();
+    repository
+      .put_subscription(&souvlaki, &dream_pop, 1)
+      .await
+      .unwrap();
+    repository
+      .put_subscription(&pygmalion, &shoegaze, 2)
+      .await
+      .unwrap();
+
+    let subscriptions = repository
+      .find_subscriptions_by_query(&souvlaki)
+      .await
+      .unwrap();
+    assert_eq!(subscriptions.len(), 2);
+    let subscription = subscriptions
+      .iter()
+      .find(|subscription| subscription.profile_id == shoegaze)
+      .unwrap();
+    assert_eq!(subscription.factor, 3);
+    assert_eq!(subscription.album_search_lookup_query, souvlaki);
+    assert_eq!(
+      subscription.album_search_lookup_encoded_query,
+      souvlaki.to_encoded_string()
+    );
+
+    assert_eq!(
+      repository
+        .find_subscriptions_by_profile_id(&shoegaze)
+        .await
+        .unwrap()
+        .len(),
+      2
+    );
+
+    repository
+      .delete_subscription(&shoegaze, &souvlaki)
+      .await
+      .unwrap();
+    let subscriptions = repository
+      .find_subscriptions_by_profile_id(&shoegaze)
+      .await
+      .unwrap();
+    assert_eq!(subscriptions.len(), 1);
+    assert_eq!(subscriptions[0].album_search_lookup_query, pygmalion);
+  });
+}
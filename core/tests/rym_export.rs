@@ -0,0 +1,111 @@
+use core::{
+  files::file_metadata::file_name::FileName,
+  lookup::album_search_lookup::AlbumSearchLookupQuery,
+  profile::rym_export::{parse_csv_records, parse_rym_export, RymExportEntry, RymExportTarget},
+};
+
+fn file_name_entry(file_name: &str, rating: Option<u32>) -> RymExportEntry {
+  RymExportEntry {
+    target: RymExportTarget::FileName(FileName::try_from(file_name.to_string()).unwrap()),
+    rating,
+  }
+}
+
+fn lookup_entry(album_name: &str, artist_name: &str, rating: Option<u32>) -> RymExportEntry {
+  RymExportEntry {
+    target: RymExportTarget::Lookup(AlbumSearchLookupQuery::new(
+      album_name.to_string(),
+      artist_name.to_string(),
+    )),
+    rating,
+  }
+}
+
+#[test]
+fn splits_csv_records() {
+  assert_eq!(
+    parse_csv_records("a,b,c\r\n1,,3\n\n"),
+    vec![
+      vec!["a".to_string(), "b".to_string(), "c".to_string()],
+      vec!["1".to_string(), "".to_string(), "3".to_string()],
+    ]
+  );
+}
+
+#[test]
+fn parses_quoted_csv_fields() {
+  assert_eq!(
+    parse_csv_records("\"Hello, World\",\"Say \"\"hi\"\"\",\"Line one\nLine two\"\nlast"),
+    vec![
+      vec![
+        "Hello, World".to_string(),
+        "Say \"hi\"".to_string(),
+        "Line one\nLine two".to_string(),
+      ],
+      vec!["last".to_string()],
+    ]
+  );
+}
+
+#[test]
+fn strips_byte_order_mark() {
+  assert_eq!(
+    parse_csv_records("\u{feff}Title\nOK Computer"),
+    vec![vec!["Title".to_string()], vec!["OK Computer".to_string()]]
+  );
+}
+
+#[test]
+fn parses_plain_export() {
+  let content = "\u{feff}RYM Album, First Name,Last Name,First Name localized, Last Name localized,Title,Release_Date,Rating,Ownership,Purchase Date,Media Type,Review\r\n\
+\"1234\",\"\",\"Radiohead\",\"\",\"\",\"OK Computer\",\"1997\",\"10\",\"\",\"\",\"\",\"\"\r\n\
+\"5678\",\"Thom\",\"Yorke\",\"\",\"\",\"The Eraser, Deluxe\",\"2006\",\"0\",\"\",\"\",\"\",\"A \"\"quoted\"\"\nreview\"\r\n";
+  assert_eq!(
+    parse_rym_export(content).unwrap(),
+    vec![
+      lookup_entry("OK Computer", "Radiohead", Some(10)),
+      lookup_entry("The Eraser, Deluxe", "Thom Yorke", None),
+    ]
+  );
+}
+
+#[test]
+fn parses_export_with_header_aliases() {
+  let content = "Release URL,Artist Name,Album Name,Rating\n\
+https://rateyourmusic.com/release/album/radiohead/ok-computer,Radiohead,OK Computer,8\n\
+,Portishead,Dummy,\n\
+not a url,Massive Attack,Mezzanine,11\n";
+  assert_eq!(
+    parse_rym_export(content).unwrap(),
+    vec![
+      file_name_entry("release/album/radiohead/ok-computer", Some(8)),
+      lookup_entry("Dummy", "Portishead", None),
+      lookup_entry("Mezzanine", "Massive Attack", None),
+    ]
+  );
+}
+
+#[test]
+fn skips_entries_without_artist_or_title() {
+  let content = "Artist,Title,Rating\nRadiohead,,7\n,OK Computer,7\n";
+  assert_eq!(parse_rym_export(content).unwrap(), vec![]);
+}
+
+#[test]
+fn rejects_malformed_exports() {
+  assert!(parse_rym_export("").is_err());
+  assert!(parse_rym_export("\u{feff}\n\n").is_err());
+  assert!(parse_rym_export("Artist,Rating\nRadiohead,10\n").is_err());
+}
+
+#[test]
+fn maps_ratings_to_factors() {
+  let factors = (1..=10)
+    .map(|rating| file_name_entry("release/album/radiohead/ok-computer", Some(rating)).factor())
+    .collect::<Vec<u32>>();
+  assert_eq!(factors, vec![0, 0, 0, 0, 0, 1, 2, 3, 4, 5]);
+  assert_eq!(
+    file_name_entry("release/album/radiohead/ok-computer", None).factor(),
+    1
+  );
+}
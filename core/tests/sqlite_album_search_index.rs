@@ -0,0 +1,433 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{block_on, test_settings, test_sqlite_connection};
+use core::{
+  albums::{
+    album_read_model::{AlbumReadModel, AlbumReadModelArtist, AlbumReadModelLabel},
+    album_repository::AlbumRepository,
+    album_search_index::{
+      AlbumEmbedding, AlbumEmbeddingSimilarirtySearchQuery, AlbumSearchIndex, AlbumSearchQuery,
+      AlbumSearchQueryBuilder, AlbumSearchSort, AlbumSearchSortKey, SearchPagination,
+    },
+    sqlite_album_repository::SqliteAlbumRepository,
+    sqlite_album_search_index::SqliteAlbumSearchIndex,
+  },
+  files::file_metadata::file_name::FileName,
+};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const EMBEDDING_KEY: &str = "test";
+
+fn file_name(value: &str) -> FileName {
+  FileName::try_from(value.to_string()).unwrap()
+}
+
+fn album(
+  file_name_value: &str,
+  name: &str,
+  artist: (&str, &str),
+  primary_genre: &str,
+  release_date: (i32, u32, u32),
+  rating: f32,
+) -> AlbumReadModel {
+  AlbumReadModel {
+    name: name.to_string(),
+    file_name: file_name(file_name_value),
+    rating,
+    rating_count: 100,
+    artists: vec![AlbumReadModelArtist {
+      name: artist.0.to_string(),
+      file_name: file_name(artist.1),
+    }],
+    primary_genres: vec![primary_genre.to_string()],
+    release_date: NaiveDate::from_ymd_opt(release_date.0, release_date.1, release_date.2),
+    release_type: Some("album".to_string()),
+    labels: vec![AlbumReadModelLabel {
+      name: "Creation".to_string(),
+      catalog_number: None,
+    }],
+    ..Default::default()
+  }
+}
+
+fn albums() -> Vec<AlbumReadModel> {
+  vec![
+    album(
+      "release/album/slowdive/souvlaki",
+      "Souvlaki",
+      ("Slowdive", "artist/slowdive"),
+      "Shoegaze",
+      (1993, 5, 17),
+      3.9,
+    ),
+    album(
+      "release/album/my-bloody-valentine/loveless",
+      "Loveless",
+      ("My Bloody Valentine", "artist/my-bloody-valentine"),
+      "Shoegaze",
+      (1991, 11, 4),
+      4.2,
+    ),
+    album(
+      "release/album/portishead/dummy",
+      "Dummy",
+      ("Portishead", "artist/portishead"),
+      "Trip Hop",
+      (1994, 8, 22),
+      3.8,
+    ),
+  ]
+}
+
+async fn test_index() -> (SqliteAlbumSearchIndex, TempDir) {
+  let (settings, dir) = test_settings();
+  let sqlite_connection = test_sqlite_connection(Arc::new(settings)).await;
+  let album_repository = SqliteAlbumRepository::new(Arc::clone(&sqlite_connection));
+  let album_search_index = SqliteAlbumSearchIndex::new(sqlite_connection);
+  for album in albums() {
+    album_repository.put(album.clone()).await.unwrap();
+    album_search_index.put(album).await.unwrap();
+  }
+  (album_search_index, dir)
+}
+
+async fn search(index: &SqliteAlbumSearchIndex, query: AlbumSearchQuery) -> Vec<String> {
+  index
+    .search(&query, None)
+    .await
+    .unwrap()
+    .albums
+    .into_iter()
+    .map(|album| album.name)
+    .collect()
+}
+
+fn sorted(mut names: Vec<String>) -> Vec<String> {
+  names.sort();
+  names
+}
+
+fn embedding(file_name_value: &str, embedding: Vec<f32>) -> AlbumEmbedding {
+  AlbumEmbedding {
+    file_name: file_name(file_name_value),
+    key: EMBEDDING_KEY.to_string(),
+    embedding,
+  }
+}
+
+#[test]
+fn searches_album_and_artist_names() {
+  block_on(async {
+    let (index, _dir) = test_index().await;
+    assert_eq!(
+      search(
+        &index,
+        AlbumSearchQueryBuilder::default()
+          .text("souvlaki".to_string())
+          .build()
+          .unwrap()
+      )
+      .await,
+      vec!["Souvlaki"]
+    );
+    assert_eq!(
+      search(
+        &index,
+        AlbumSearchQueryBuilder::default()
+          .text("bloody valentine".to_string())
+          .build()
+          .unwrap()
+      )
+      .await,
+      vec!["Loveless"]
+    );
+    assert!(search(
+      &index,
+      AlbumSearchQueryBuilder::default()
+        .text("nevermind".to_string())
+        .build()
+        .unwrap()
+    )
+    .await
+    .is_empty());
+  });
+}
+
+#[test]
+fn includes_and_excludes_tags() {
+  block_on(async {
+    let (index, _dir) = test_index().await;
+    assert_eq!(
+      sorted(
+        search(
+          &index,
+          AlbumSearchQueryBuilder::default()
+            .include_primary_genres(vec!["Shoegaze".to_string()])
+            .build()
+            .unwrap()
+        )
+        .await
+      ),
+      vec!["Loveless", "Souvlaki"]
+    );
+    assert_eq!(
+      search(
+        &index,
+        AlbumSearchQueryBuilder::default()
+          .exclude_primary_genres(vec!["Shoegaze".to_string()])
+          .build()
+          .unwrap()
+      )
+      .await,
+      vec!["Dummy"]
+    );
+    assert_eq!(
+      search(
+        &index,
+        AlbumSearchQueryBuilder::default()
+          .include_primary_genres(vec!["Shoegaze".to_string()])
+          .exclude_artists(vec!["artist/slowdive".to_string()])
+          .include_labels(vec!["Creation".to_string()])
+          .build()
+          .unwrap()
+      )
+      .await,
+      vec!["Loveless"]
+    );
+  });
+}
+
+#[test]
+fn filters_ranges() {
+  block_on(async {
+    let (index, _dir) = test_index().await;
+    assert_eq!(
+      sorted(
+        search(
+          &index,
+          AlbumSearchQueryBuilder::default()
+            .min_release_year(1992)
+            .build()
+            .unwrap()
+        )
+        .await
+      ),
+      vec!["Dummy", "Souvlaki"]
+    );
+    assert_eq!(
+      search(
+        &index,
+        AlbumSearchQueryBuilder::default()
+          .min_release_year(1993)
+          .max_release_year(1993)
+          .build()
+          .unwrap()
+      )
+      .await,
+      vec!["Souvlaki"]
+    );
+    assert_eq!(
+      search(
+        &index,
+        AlbumSearchQueryBuilder::default()
+          .min_rating(4.0)
+          .build()
+          .unwrap()
+      )
+      .await,
+      vec!["Loveless"]
+    );
+  });
+}
+
+#[test]
+fn sorts_and_paginates() {
+  block_on(async {
+    let (index, _dir) = test_index().await;
+    let by_release_date = AlbumSearchQueryBuilder::default()
+      .sort(AlbumSearchSort {
+        key: AlbumSearchSortKey::ReleaseDate,
+        descending: true,
+      })
+      .build()
+      .unwrap();
+    assert_eq!(
+      search(&index, by_release_date).await,
+      vec!["Dummy", "Souvlaki", "Loveless"]
+    );
+    assert_eq!(
+      search(
+        &index,
+        AlbumSearchQueryBuilder::default()
+          .sort(AlbumSearchSort {
+            key: AlbumSearchSortKey::Rating,
+            descending: false,
+          })
+          .build()
+          .unwrap()
+      )
+      .await,
+      vec!["Dummy", "Souvlaki", "Loveless"]
+    );
+
+    let result = index
+      .search(
+        &AlbumSearchQueryBuilder::default()
+          .sort(AlbumSearchSort {
+            key: AlbumSearchSortKey::Name,
+            descending: false,
+          })
+          .build()
+          .unwrap(),
+        Some(&SearchPagination {
+          offset: Some(1),
+          limit: Some(1),
+        }),
+      )
+      .await
+      .unwrap();
+    assert_eq!(result.total, 3);
+    assert_eq!(
+      result
+        .albums
+        .into_iter()
+        .map(|album| album.name)
+        .collect::<Vec<String>>(),
+      vec!["Loveless"]
+    );
+  });
+}
+
+#[test]
+fn searches_similar_embeddings() {
+  block_on(async {
+    let (index, _dir) = test_index().await;
+    index
+      .put_embedding(&embedding(
+        "release/album/slowdive/souvlaki",
+        vec![1.0, 0.0, 0.0],
+      ))
+      .await
+      .unwrap();
+    index
+      .put_embedding(&embedding(
+        "release/album/my-bloody-valentine/loveless",
+        vec![0.8, 0.6, 0.0],
+      ))
+      .await
+      .unwrap();
+    index
+      .put_embedding(&embedding(
+        "release/album/portishead/dummy",
+        vec![0.0, 0.0, 1.0],
+      ))
+      .await
+      .unwrap();
+
+    let results = index
+      .embedding_similarity_search(&AlbumEmbeddingSimilarirtySearchQuery {
+        embedding: vec![1.0, 0.0, 0.0],
+        embedding_key: EMBEDDING_KEY.to_string(),
+        filters: AlbumSearchQuery::default(),
+        limit: 2,
+      })
+      .await
+      .unwrap();
+    assert_eq!(
+      results
+        .iter()
+        .map(|(album, _)| album.name.as_str())
+        .collect::<Vec<&str>>(),
+      vec!["Souvlaki", "Loveless"]
+    );
+    assert!(results[0].1.abs() < 1e-6);
+    assert!((results[1].1 - 0.2).abs() < 1e-6);
+
+    let results = index
+      .embedding_similarity_search(&AlbumEmbeddingSimilarirtySearchQuery {
+        embedding: vec![1.0, 0.0, 0.0],
+        embedding_key: EMBEDDING_KEY.to_string(),
+        filters: AlbumSearchQueryBuilder::default()
+          .exclude_primary_genres(vec!["Shoegaze".to_string()])
+          .build()
+          .unwrap(),
+        limit: 2,
+      })
+      .await
+      .unwrap();
+    assert_eq!(
+      results
+        .iter()
+        .map(|(album, _)| album.name.as_str())
+        .collect::<Vec<&str>>(),
+      vec!["Dummy"]
+    );
+  });
+}
+
+#[test]
+fn reindexes_albums_missing_from_the_index() {
+  block_on(async {
+    let (settings, _dir) = test_settings();
+    let sqlite_connection = test_sqlite_connection(Arc::new(settings)).await;
+    let album_repository = SqliteAlbumRepository::new(Arc::clone(&sqlite_connection));
+    let index = SqliteAlbumSearchIndex::new(sqlite_connection);
+    for album in albums() {
+      album_repository.put(album).await.unwrap();
+    }
+    let query = || {
+      AlbumSearchQueryBuilder::default()
+        .text("souvlaki".to_string())
+        .build()
+        .unwrap()
+    };
+    assert!(search(&index, query()).await.is_empty());
+
+    assert!(index.setup_index().await.unwrap());
+    index.reindex().await.unwrap();
+    assert!(!index.setup_index().await.unwrap());
+    assert_eq!(search(&index, query()).await, vec!["Souvlaki"]);
+  });
+}
+
+#[test]
+fn backfills_embeddings_from_another_index() {
+  block_on(async {
+    let (source, _source_dir) = test_index().await;
+    source
+      .put_embedding(&embedding(
+        "release/album/slowdive/souvlaki",
+        vec![1.0, 0.0, 0.0],
+      ))
+      .await
+      .unwrap();
+    source
+      .put_embedding(&embedding(
+        "release/album/portishead/dummy",
+        vec![0.0, 1.0, 0.0],
+      ))
+      .await
+      .unwrap();
+    let (index, _dir) = test_index().await;
+    assert!(index.get_embedding_keys().await.unwrap().is_empty());
+
+    index.backfill_embeddings(&source).await.unwrap();
+    assert_eq!(
+      index.get_embedding_keys().await.unwrap(),
+      vec![EMBEDDING_KEY.to_string()]
+    );
+    let backfilled = index
+      .find_embedding(&file_name("release/album/portishead/dummy"), EMBEDDING_KEY)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(backfilled.embedding, vec![0.0, 1.0, 0.0]);
+    assert!(index
+      .get_embeddings(&file_name("release/album/my-bloody-valentine/loveless"))
+      .await
+      .unwrap()
+      .is_empty());
+  });
+}
@@ -0,0 +1,263 @@
+mod common;
+
+use common::{block_on, test_settings, test_sqlite_connection, unconnected_redis_connection_pool};
+use core::{
+  events::{
+    event::{Event, EventPayloadBuilder, Stream},
+    event_publisher::EventPublisher,
+  },
+  files::file_metadata::file_name::FileName,
+  helpers::hmac::hmac_sha256,
+  settings::{Settings, WebhookSettings},
+  webhooks::{
+    webhook_event_subscribers::{build_webhook_event_subscriber, retry_failed_webhook_deliveries},
+    webhook_repository::WebhookRepository,
+  },
+};
+use data_encoding::HEXLOWER;
+use std::{
+  collections::{HashMap, VecDeque},
+  io::{BufRead, BufReader, Read, Write},
+  net::TcpListener,
+  sync::{Arc, Mutex},
+  thread,
+};
+use tempfile::TempDir;
+use ulid::Ulid;
+
+#[derive(Debug, Clone)]
+struct ReceivedRequest {
+  headers: HashMap<String, String>,
+  body: String,
+}
+
+/**
+ * A webhook receiver that answers with the queued statuses, then with 200.
+ */
+struct WebhookReceiver {
+  url: String,
+  requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+}
+
+impl WebhookReceiver {
+  fn start(statuses: Vec<u16>) -> Self {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(vec![]));
+    let received = Arc::clone(&requests);
+    let mut statuses = VecDeque::from(statuses);
+    thread::spawn(move || {
+      for stream in listener.incoming() {
+        let mut stream = stream.unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut headers = HashMap::new();
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        loop {
+          line.clear();
+          reader.read_line(&mut line).unwrap();
+          match line.trim_end().split_once(": ") {
+            Some((name, value)) => {
+              headers.insert(name.to_lowercase(), value.to_string());
+            }
+            None => break,
+          }
+        }
+        let length = headers
+          .get("content-length")
+          .map(|length| length.parse::<usize>().unwrap())
+          .unwrap_or(0);
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        received.lock().unwrap().push(ReceivedRequest {
+          headers,
+          body: String::from_utf8(body).unwrap(),
+        });
+        let status = statuses.pop_front().unwrap_or(200);
+        write!(
+          stream,
+          "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+          status
+        )
+        .unwrap();
+      }
+    });
+    Self { url, requests }
+  }
+
+  fn requests(&self) -> Vec<ReceivedRequest> {
+    self.requests.lock().unwrap().clone()
+  }
+}
+
+fn settings() -> (Arc<Settings>, TempDir) {
+  let (settings, dir) = test_settings();
+  let settings = Settings {
+    webhook: WebhookSettings {
+      max_retries: 1,
+      timeout_seconds: 5,
+      retry_interval_seconds: 0,
+    },
+    ..settings
+  };
+  (Arc::new(settings), dir)
+}
+
+async fn publish_event(publisher: &EventPublisher) {
+  publisher
+    .publish(
+      Stream::File,
+      EventPayloadBuilder::default()
+        .event(Event::FileDeleted {
+          file_id: Ulid::new(),
+          file_name: FileName::try_from("release/album/slowdive/souvlaki".to_string()).unwrap(),
+        })
+        .build()
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+}
+
+fn assert_signed(request: &ReceivedRequest, secret: &str) {
+  let timestamp = request.headers.get("x-lute-timestamp").unwrap();
+  assert!(timestamp.parse::<i64>().is_ok());
+  assert_eq!(
+    request.headers.get("x-lute-signature"),
+    Some(&format!(
+      "sha256={}",
+      HEXLOWER.encode(&hmac_sha256(
+        secret.as_bytes(),
+        format!("{}.{}", timestamp, request.body).as_bytes()
+      ))
+    ))
+  );
+}
+
+#[test]
+fn retries_and_signs_deliveries() {
+  block_on(async {
+    let receiver = WebhookReceiver::start(vec![500]);
+    let (settings, _dir) = settings();
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let webhook_repository = WebhookRepository::new(Arc::clone(&sqlite_connection));
+    webhook_repository
+      .create("hook", &receiver.url, Some("secret".to_string()), &[])
+      .await
+      .unwrap();
+    let webhook = webhook_repository.find("hook").await.unwrap().unwrap();
+    let subscriber = build_webhook_event_subscriber(
+      &webhook,
+      unconnected_redis_connection_pool(),
+      Arc::clone(&sqlite_connection),
+      Arc::clone(&settings),
+    )
+    .unwrap();
+    publish_event(&EventPublisher::new(
+      Arc::clone(&settings),
+      Arc::clone(&sqlite_connection),
+    ))
+    .await;
+
+    assert!(subscriber
+      .poll(subscriber.batch_size)
+      .await
+      .unwrap()
+      .is_some());
+
+    let requests = receiver.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].body, requests[1].body);
+    assert_eq!(
+      requests[1].headers.get("x-lute-webhook-id"),
+      Some(&"hook".to_string())
+    );
+    assert_signed(&requests[1], "secret");
+    let body = serde_json::from_str::<serde_json::Value>(&requests[1].body).unwrap();
+    let file_id = body["payload"]["event"]["data"]["file_id"]
+      .as_str()
+      .unwrap();
+    assert!(Ulid::from_string(file_id).is_ok());
+    let webhook = webhook_repository.find("hook").await.unwrap().unwrap();
+    assert!(webhook.last_delivered_at.is_some());
+    assert_eq!(webhook.last_error, None);
+    assert!(webhook_repository
+      .find_failed_deliveries("hook")
+      .await
+      .unwrap()
+      .is_empty());
+  });
+}
+
+#[test]
+fn keeps_failed_deliveries_for_later() {
+  block_on(async {
+    let receiver = WebhookReceiver::start(vec![500, 503]);
+    let (settings, _dir) = settings();
+    let sqlite_connection = test_sqlite_connection(Arc::clone(&settings)).await;
+    let webhook_repository = WebhookRepository::new(Arc::clone(&sqlite_connection));
+    webhook_repository
+      .create("hook", &receiver.url, Some("secret".to_string()), &[])
+      .await
+      .unwrap();
+    let webhook = webhook_repository.find("hook").await.unwrap().unwrap();
+    let subscriber = build_webhook_event_subscriber(
+      &webhook,
+      unconnected_redis_connection_pool(),
+      Arc::clone(&sqlite_connection),
+      Arc::clone(&settings),
+    )
+    .unwrap();
+    publish_event(&EventPublisher::new(
+      Arc::clone(&settings),
+      Arc::clone(&sqlite_connection),
+    ))
+    .await;
+
+    // The cursor moves past the event, which is kept as a failed delivery.
+    assert!(subscriber
+      .poll(subscriber.batch_size)
+      .await
+      .unwrap()
+      .is_some());
+    assert_eq!(receiver.requests().len(), 2);
+    let failed_deliveries = webhook_repository
+      .find_failed_deliveries("hook")
+      .await
+      .unwrap();
+    assert_eq!(failed_deliveries.len(), 1);
+    assert_eq!(failed_deliveries[0].attempts, 1);
+    assert_eq!(failed_deliveries[0].body, receiver.requests()[0].body);
+    assert!(webhook_repository
+      .find("hook")
+      .await
+      .unwrap()
+      .unwrap()
+      .last_error
+      .is_some());
+
+    let client = reqwest::Client::new();
+    retry_failed_webhook_deliveries(&client, &webhook_repository, &[webhook], &settings)
+      .await
+      .unwrap();
+
+    let requests = receiver.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[2].body, requests[0].body);
+    assert_signed(&requests[2], "secret");
+    assert!(webhook_repository
+      .find_failed_deliveries("hook")
+      .await
+      .unwrap()
+      .is_empty());
+    assert_eq!(
+      webhook_repository
+        .find("hook")
+        .await
+        .unwrap()
+        .unwrap()
+        .last_error,
+      None
+    );
+  });
+}